@@ -0,0 +1,90 @@
+//! Decoding of RTU frames, 10k responses buffered back to back.
+//!
+//! The allocations of one pass are counted and printed before the timings.
+//!
+//! ```text
+//! cargo bench --bench decode
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use tokio_util::codec::{Decoder, Encoder};
+
+use easy_modbus::codec::{RtuClientCodec, RtuServerCodec};
+use easy_modbus::Frame;
+
+const FRAMES: usize = 10_000;
+
+/// The system allocator, counting allocations and reallocations
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// `FRAMES` responses reading registers, then writing a coil, encoded one after the other
+fn responses() -> BytesMut {
+    let frame = Frame::rtu();
+    let mut codec = RtuServerCodec::default();
+    let mut buf = BytesMut::new();
+    for i in 0..FRAMES {
+        let response = match i % 2 {
+            0 => frame.read_holding_register_response(0x01, vec![0x00, 0x2A, 0x01, 0x02]),
+            _ => frame.write_single_coil_response(0x01, 0x00BF, 0xFF00),
+        };
+        codec.encode(response, &mut buf).unwrap();
+    }
+    buf
+}
+
+/// Decode every response of `buf`
+fn decode(buf: &mut BytesMut) {
+    let mut codec = RtuClientCodec::default();
+    while let Some(response) = codec.decode(buf).unwrap() {
+        criterion::black_box(response);
+    }
+}
+
+fn decode_rtu(c: &mut Criterion) {
+    let buf = responses();
+
+    let mut pass = buf.clone();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    decode(&mut pass);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "rtu/decode: {} allocations, {:.2} per frame",
+        allocations,
+        allocations as f64 / FRAMES as f64
+    );
+
+    let mut group = c.benchmark_group("rtu");
+    group.throughput(Throughput::Elements(FRAMES as u64));
+    group.bench_function("decode", |b| {
+        b.iter_batched_ref(|| buf.clone(), decode, BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, decode_rtu);
+criterion_main!(benches);
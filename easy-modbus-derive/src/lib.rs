@@ -0,0 +1,268 @@
+//! Derive macros of the register conversion traits of
+//! [easy-modbus](https://docs.rs/easy-modbus).
+//!
+//! Used through the `derive` feature of easy-modbus, which re-exports them from
+//! `easy_modbus::data`:
+//!
+//! ```
+//! use easy_modbus::data::{FromRegisters, ToRegisters, WordOrder};
+//!
+//! #[derive(Debug, PartialEq, FromRegisters, ToRegisters)]
+//! struct Drive {
+//!     speed: u16,
+//!     current: f32,
+//!     #[modbus(word_order = "CDAB")]
+//!     energy: u32,
+//!     #[modbus(skip)]
+//!     label: String,
+//! }
+//!
+//! assert_eq!(<Drive as FromRegisters>::REGISTER_COUNT, 5);
+//! let registers = [0x0064, 0x3FC0, 0x0000, 0x5678, 0x1234];
+//! let drive = Drive::from_registers(&registers, WordOrder::BigEndian).unwrap();
+//! assert_eq!(drive.current, 1.5);
+//! assert_eq!(drive.energy, 0x1234_5678);
+//! assert_eq!(drive.to_registers(WordOrder::BigEndian), registers);
+//! ```
+//!
+//! Fields are laid out in declaration order, each taking the registers of its type. Every field
+//! type has to implement the trait derived, a field of another type is a compile error.
+//!
+//! Field attributes:
+//!
+//! - `#[modbus(skip)]` leaves the field out of the registers, it is set to its
+//!   [`Default`] when read
+//! - `#[modbus(word_order = "...")]` converts the field in this order, whichever order the
+//!   struct is converted in. Either a variant name of `WordOrder`, such as `"LittleEndian"`, or
+//!   one of `"ABCD"`, `"CDAB"`, `"BADC"` and `"DCBA"`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Index, LitStr, Member, Result};
+
+/// Derive `FromRegisters`, reading the fields from consecutive registers
+///
+/// A field of a type without register conversion doesn't compile:
+///
+/// ```compile_fail
+/// use easy_modbus::data::FromRegisters;
+///
+/// #[derive(FromRegisters)]
+/// struct Named {
+///     name: String,
+/// }
+/// ```
+///
+/// Neither does an unknown word order:
+///
+/// ```compile_fail
+/// use easy_modbus::data::FromRegisters;
+///
+/// #[derive(FromRegisters)]
+/// struct Counter {
+///     #[modbus(word_order = "middle")]
+///     count: u32,
+/// }
+/// ```
+#[proc_macro_derive(FromRegisters, attributes(modbus))]
+pub fn derive_from_registers(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_registers(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ToRegisters`, writing the fields to consecutive registers
+#[proc_macro_derive(ToRegisters, attributes(modbus))]
+pub fn derive_to_registers(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_registers(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// A field of the struct derived for
+struct Field {
+    member: Member,
+    ty: syn::Type,
+    skip: bool,
+    /// Path of the `WordOrder` constant the field is converted in, if not the struct's
+    order: Option<TokenStream2>,
+}
+
+fn from_registers(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (fields, named) = fields(input)?;
+    let register_count = register_count(&fields, quote!(FromRegisters));
+
+    let reads = fields.iter().enumerate().map(|(i, field)| {
+        let var = format_ident!("field_{}", i);
+        let ty = &field.ty;
+        if field.skip {
+            return quote_spanned!(ty.span()=> let #var: #ty = ::core::default::Default::default(););
+        }
+        let order = field.order.clone().unwrap_or(quote!(order));
+        quote_spanned! {ty.span()=>
+            let count = <#ty as ::easy_modbus::data::FromRegisters>::REGISTER_COUNT;
+            let #var = <#ty as ::easy_modbus::data::FromRegisters>::from_registers(
+                &registers[offset..offset + count],
+                #order,
+            )?;
+            offset += count;
+        }
+    });
+    let vars = (0..fields.len()).map(|i| format_ident!("field_{}", i));
+    let members = fields.iter().map(|field| &field.member);
+    let value = match named {
+        Some(true) => quote!(#name { #(#members: #vars),* }),
+        Some(false) => quote!(#name ( #(#vars),* )),
+        None => quote!(#name),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::easy_modbus::data::FromRegisters for #name #ty_generics
+        #where_clause
+        {
+            const REGISTER_COUNT: usize = #register_count;
+
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn from_registers(
+                registers: &[u16],
+                order: ::easy_modbus::data::WordOrder,
+            ) -> ::std::io::Result<Self> {
+                let expected = <Self as ::easy_modbus::data::FromRegisters>::REGISTER_COUNT;
+                if registers.len() != expected {
+                    return Err(::easy_modbus::data::ConversionError::LengthMismatch {
+                        expected,
+                        actual: registers.len(),
+                    }
+                    .into());
+                }
+                let mut offset = 0;
+                #(#reads)*
+                Ok(#value)
+            }
+        }
+    })
+}
+
+fn to_registers(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (fields, _) = fields(input)?;
+    let register_count = register_count(&fields, quote!(ToRegisters));
+
+    let writes = fields.iter().filter(|field| !field.skip).map(|field| {
+        let member = &field.member;
+        let ty = &field.ty;
+        let order = field.order.clone().unwrap_or(quote!(order));
+        quote_spanned! {ty.span()=>
+            registers.extend(<#ty as ::easy_modbus::data::ToRegisters>::to_registers(
+                &self.#member,
+                #order,
+            ));
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::easy_modbus::data::ToRegisters for #name #ty_generics
+        #where_clause
+        {
+            const REGISTER_COUNT: usize = #register_count;
+
+            #[allow(unused_variables)]
+            fn to_registers(&self, order: ::easy_modbus::data::WordOrder) -> Vec<u16> {
+                let mut registers = Vec::with_capacity(
+                    <Self as ::easy_modbus::data::ToRegisters>::REGISTER_COUNT,
+                );
+                #(#writes)*
+                registers
+            }
+        }
+    })
+}
+
+/// Sum of the register counts of the fields not skipped
+fn register_count(fields: &[Field], tr: TokenStream2) -> TokenStream2 {
+    let counts = fields.iter().filter(|field| !field.skip).map(|field| {
+        let ty = &field.ty;
+        quote_spanned!(ty.span()=> <#ty as ::easy_modbus::data::#tr>::REGISTER_COUNT)
+    });
+    quote!(0 #(+ #counts)*)
+}
+
+/// The fields of a struct, and whether they are named, `None` for a unit struct
+fn fields(input: &DeriveInput) -> Result<(Vec<Field>, Option<bool>)> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "register conversions can only be derived for structs",
+            ))
+        }
+    };
+    let named = match data.fields {
+        Fields::Named(_) => Some(true),
+        Fields::Unnamed(_) => Some(false),
+        Fields::Unit => None,
+    };
+    let fields = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            let mut parsed = Field {
+                member,
+                ty: field.ty.clone(),
+                skip: false,
+                order: None,
+            };
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("modbus"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("skip") {
+                        parsed.skip = true;
+                        Ok(())
+                    } else if meta.path.is_ident("word_order") {
+                        let order: LitStr = meta.value()?.parse()?;
+                        parsed.order = Some(word_order(&order)?);
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `skip` or `word_order = \"...\"`"))
+                    }
+                })?;
+            }
+            Ok(parsed)
+        })
+        .collect::<Result<Vec<Field>>>()?;
+    Ok((fields, named))
+}
+
+/// The `WordOrder` named by a `word_order` attribute
+fn word_order(order: &LitStr) -> Result<TokenStream2> {
+    let variant = match order.value().as_str() {
+        "BigEndian" | "ABCD" => "BigEndian",
+        "LittleEndian" | "CDAB" => "LittleEndian",
+        "BigEndianByteSwap" | "BADC" => "BigEndianByteSwap",
+        "LittleEndianByteSwap" | "DCBA" => "LittleEndianByteSwap",
+        other => {
+            return Err(Error::new(
+                order.span(),
+                format!("unknown word order `{}`", other),
+            ))
+        }
+    };
+    let variant = format_ident!("{}", variant);
+    Ok(quote!(::easy_modbus::data::WordOrder::#variant))
+}
@@ -0,0 +1,110 @@
+use futures::{SinkExt, StreamExt};
+
+use easy_modbus::client::{read_as, write_as, Retry};
+use easy_modbus::data::{Bitfield16, FromRegisters, ToRegisters, WordOrder};
+use easy_modbus::server::{dispatch, DataStore};
+use easy_modbus::{testing, Frame};
+
+#[derive(Clone, Debug, PartialEq, FromRegisters, ToRegisters)]
+struct Drive {
+    speed: u16,
+    current: f32,
+    setpoints: [u16; 3],
+    #[modbus(word_order = "LittleEndian")]
+    energy: u32,
+    #[modbus(skip)]
+    label: String,
+}
+
+#[derive(Debug, PartialEq, FromRegisters, ToRegisters)]
+struct Pair(i16, #[modbus(word_order = "BADC")] u32);
+
+fn drive() -> Drive {
+    Drive {
+        speed: 1500,
+        current: 12.5,
+        setpoints: [10, 20, 30],
+        energy: 0x0001_0002,
+        label: String::new(),
+    }
+}
+
+#[test]
+fn layout_test() {
+    assert_eq!(<Drive as FromRegisters>::REGISTER_COUNT, 8);
+    assert_eq!(<Drive as ToRegisters>::REGISTER_COUNT, 8);
+    let registers = drive().to_registers(WordOrder::BigEndian);
+    assert_eq!(
+        registers,
+        vec![1500, 0x4148, 0x0000, 10, 20, 30, 0x0002, 0x0001]
+    );
+    // The word order of the struct, but for the field with its own
+    let registers = drive().to_registers(WordOrder::LittleEndian);
+    assert_eq!(&registers[1..3], &[0x0000, 0x4148]);
+    assert_eq!(&registers[6..], &[0x0002, 0x0001]);
+}
+
+#[test]
+fn round_trip_test() {
+    let mut labeled = drive();
+    labeled.label = "left out".to_string();
+    for order in [WordOrder::BigEndian, WordOrder::DCBA] {
+        let registers = labeled.to_registers(order);
+        assert_eq!(Drive::from_registers(&registers, order).unwrap(), drive());
+        assert!(Drive::from_registers(&registers[1..], order).is_err());
+    }
+
+    let pair = Pair(-2, 0x1234_5678);
+    let registers = pair.to_registers(WordOrder::BigEndian);
+    assert_eq!(registers, vec![0xFFFE, 0x3412, 0x7856]);
+    assert_eq!(
+        Pair::from_registers(&registers, WordOrder::BigEndian).unwrap(),
+        pair
+    );
+}
+
+#[tokio::test]
+async fn read_test() {
+    let store = DataStore::default().with_holding_registers(0x0100, 8);
+    let (mut client, mut server) = testing::pair();
+    tokio::spawn(async move {
+        while let Some(request) = server.next().await {
+            let response = dispatch(&store, &request.unwrap()).unwrap();
+            server.send(response).await.unwrap();
+        }
+    });
+
+    let (frame, order, retry) = (Frame::tcp(), WordOrder::BigEndian, Retry::default());
+    write_as(&mut client, &frame, 0x01, 0x0100, &drive(), order, retry)
+        .await
+        .unwrap();
+    let read: Drive = read_as(&mut client, &frame, 0x01, 0x0100, order, retry)
+        .await
+        .unwrap();
+    assert_eq!(read, drive());
+    let read: Pair = read_as(&mut client, &frame, 0x01, 0x0101, order, retry)
+        .await
+        .unwrap();
+    assert_eq!(read, Pair(0x4148, 0x0000_0A00));
+}
+
+#[derive(Debug, PartialEq, FromRegisters, ToRegisters)]
+struct Pump {
+    status: Bitfield16,
+    flow: f32,
+}
+
+#[test]
+fn bitfield_test() {
+    const RUNNING: u8 = 3;
+    const FAULT: u8 = 15;
+
+    let pump = Pump::from_registers(&[0x8008, 0x3FC0, 0x0000], WordOrder::BigEndian).unwrap();
+    assert!(pump.status.bit(RUNNING) && pump.status.bit(FAULT));
+    assert!(!pump.status.bit(0));
+    assert_eq!(pump.flow, 1.5);
+    assert_eq!(
+        pump.to_registers(WordOrder::BigEndian),
+        vec![0x8008, 0x3FC0, 0x0000]
+    );
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use futures::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_serial::SerialStream;
+use tokio_util::codec::Framed;
+
+use easy_modbus::codec::{RtuClientCodec, TcpServerCodec};
+
+/// The serial line, shared by every TCP connection
+type Bus = Arc<Mutex<Framed<SerialStream, RtuClientCodec>>>;
+
+/// Forward the requests of one TCP master to the RTU bus
+async fn forward(stream: TcpStream, bus: Bus) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut transport = Framed::new(stream, TcpServerCodec::default());
+    while let Some(request) = transport.next().await {
+        let request = request?;
+        let tid = request.head().get_tid();
+        let broadcast = request.head().get_uid() == 0;
+
+        let response = {
+            let mut bus = bus.lock().await;
+            bus.send(request.into_rtu()).await?;
+            // Unit id 0 is a broadcast on the bus, which no slave answers
+            if broadcast {
+                continue;
+            }
+            match bus.next().await {
+                Some(response) => response?,
+                None => return Ok(()),
+            }
+        };
+        transport.send(response.into_tcp(tid)).await?;
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let tty_path = "/dev/ttyUSB0";
+    let rate = 9600;
+
+    let port = SerialStream::open(&tokio_serial::new(tty_path, rate))?;
+    let bus: Bus = Arc::new(Mutex::new(Framed::new(port, RtuClientCodec::default())));
+
+    let addr = "127.0.0.1:502".to_string();
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Bridging {} to {}", addr, tty_path);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let bus = bus.clone();
+        tokio::spawn(async move {
+            if let Err(e) = forward(stream, bus).await {
+                println!("{}: {}", peer, e);
+            }
+        });
+    }
+}
@@ -14,7 +14,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let serial_builder = tokio_serial::new(tty_path, rate);
     let port = SerialStream::open(&serial_builder).unwrap();
 
-    let mut transport = Framed::new(port, RtuClientCodec);
+    let mut transport = Framed::new(port, RtuClientCodec::default());
 
     let frame = Frame::rtu();
     let request = frame.read_multiple_holding_registers_request(slave, 0x00, 0x02);
@@ -27,9 +27,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Response:\t{}", response);
                 match response {
                     Response::ReadMultipleHoldingRegisters(_, res) => {
-                        let a = res.get_values();
-                        let h = ((a[0] as u16 * 256) + a[1] as u16) as f64 / 10.0;
-                        let t = ((a[2] as u16 * 256) + a[3] as u16) as f64 / 10.0;
+                        let h = res.get_scaled(0, 0.1)?;
+                        let t = res.get_scaled(1, 0.1)?;
                         println!("h {} t {}", h, t);
                         return Ok(())
                     }
@@ -11,12 +11,12 @@ use easy_modbus::{Frame, codec::TcpClientCodec};
 async fn main() -> Result<(), Box<dyn Error>> {
     let addr = "127.0.0.1:502".to_string();
     let stream = TcpStream::connect(&addr).await?;
-    let mut transport = Framed::new(stream, TcpClientCodec);
+    let mut transport = Framed::new(stream, TcpClientCodec::default());
     let frame = Frame::tcp();
     let request = frame.read_coils_request(0x01, 0x02, 0x08);
     println!("{}", request);
     transport.send(request).await?;
-    while let Some(response) = transport.next().await {
+    if let Some(response) = transport.next().await {
         return match response {
             Ok(response) => {
                 println!("{}", response);
@@ -1,41 +1,41 @@
 use std::error::Error;
+use std::sync::Mutex;
 
-use futures::SinkExt;
-use tokio::net::{TcpListener, TcpStream};
-use tokio_stream::StreamExt;
-use tokio_util::codec::Framed;
+use easy_modbus::server::{Service, TcpServer};
+use easy_modbus::Exception;
 
-use easy_modbus::{Frame, codec::TcpServerCodec};
+/// Eight coils, starting at address 0
+struct Coils(Mutex<[bool; 8]>);
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let addr = "127.0.0.1:502".to_string();
-    let server = TcpListener::bind(&addr).await?;
-    println!("Listening on: {}", addr);
+impl Service for Coils {
+    fn read_coils(&self, _unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        let coils = self.0.lock().unwrap();
+        let range = address as usize..address as usize + count as usize;
+        coils
+            .get(range)
+            .map(|coils| coils.to_vec())
+            .ok_or(Exception::IllegalDataAddress)
+    }
 
-    loop {
-        let (stream, _) = server.accept().await?;
-        tokio::spawn(async move {
-            if let Err(e) = process(stream).await {
-                println!("failed to  process connection; error = {}", e);
-            }
-        });
+    fn write_single_coil(&self, _unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+        let mut coils = self.0.lock().unwrap();
+        let coil = coils
+            .get_mut(address as usize)
+            .ok_or(Exception::IllegalDataAddress)?;
+        *coil = value;
+        Ok(())
     }
 }
 
-async fn process(stream: TcpStream) -> Result<(), Box<dyn Error>> {
-    let mut transport = Framed::new(stream, TcpServerCodec);
-    let frame = Frame::tcp();
-    while let Some(request) = transport.next().await {
-        match request {
-            Ok(request) => {
-                println!("load request --- {:?}", request);
-                let response = frame.read_coils_response(0x01, vec![0x00, 0x01]);
-                println!("send response --- {:?}", response);
-                transport.send(response).await?;
-            }
-            Err(e) => return Err(e.into()),
-        }
-    }
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let server = TcpServer::bind("127.0.0.1:502").await?;
+    println!("Listening on: {}", server.local_addr());
+    let handle = server.handle();
+    tokio::spawn(async move {
+        let _ = tokio::signal::ctrl_c().await;
+        handle.shutdown();
+    });
+    server.serve(Coils(Mutex::new([false; 8]))).await?;
     Ok(())
 }
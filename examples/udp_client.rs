@@ -0,0 +1,30 @@
+use std::error::Error;
+
+use bytes::BytesMut;
+use tokio::net::UdpSocket;
+use tokio_util::codec::Encoder;
+
+use easy_modbus::{Frame, codec::{decode_one_tcp_response, TcpClientCodec, MAX_FRAME}};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let addr = "127.0.0.1:502".to_string();
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(&addr).await?;
+
+    let frame = Frame::tcp();
+    let request = frame.read_coils_request(0x01, 0x02, 0x08);
+    println!("{}", request);
+    let mut buf = BytesMut::new();
+    TcpClientCodec::default().encode(request, &mut buf)?;
+    socket.send(&buf).await?;
+
+    // Every datagram carries one whole frame
+    let mut datagram = [0u8; MAX_FRAME];
+    let len = socket.recv(&mut datagram).await?;
+    match decode_one_tcp_response(&datagram[..len])? {
+        Some((response, _)) => println!("{}", response),
+        None => println!("Truncated datagram of {} bytes", len),
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+//! Feed arbitrary bytes to every decoder, none of them may panic.
+//!
+//! Run with `cargo +nightly fuzz run decode` from the repository root.
+
+#![no_main]
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+
+use easy_modbus::codec::{
+    decode_rtu_request, decode_rtu_response, decode_tcp_request, decode_tcp_response,
+};
+
+fuzz_target!(|data: &[u8]| {
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(_)) = decode_tcp_request(&mut src) {}
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(_)) = decode_tcp_response(&mut src) {}
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(_)) = decode_rtu_request(&mut src) {}
+    let mut src = BytesMut::from(data);
+    while let Ok(Some(_)) = decode_rtu_response(&mut src) {}
+});
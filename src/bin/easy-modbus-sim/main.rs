@@ -0,0 +1,172 @@
+//! A Modbus server simulator.
+//!
+//! Serves the register map of a TOML file over Modbus TCP, and over Modbus RTU on a serial
+//! port if the map has a `[rtu]` section, until interrupted. Every request answered is logged
+//! to stdout. See [`map`] for the register map.
+//!
+//! The options inject faults into the responses, for testing the robustness of clients:
+//! `--drop-every N` drops every `N`th response, `--delay MS` sends every response `MS`
+//! milliseconds late and `--corrupt-crc-every N` sends every `N`th RTU response with a wrong
+//! CRC.
+//!
+//! ```text
+//! cargo run --features sim --bin easy-modbus-sim -- --drop-every 10 register-map.toml
+//! ```
+
+use std::io::Result;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio_serial::SerialStream;
+
+use easy_modbus::server::{ChaosService, Faults, MetricsSink, Outcome, RtuServer, TcpServer};
+use easy_modbus::Function;
+
+use map::RegisterMap;
+
+mod map;
+
+const USAGE: &str = "Usage: easy-modbus-sim [--drop-every N] [--delay MS] \
+                     [--corrupt-crc-every N] <register-map.toml>";
+
+/// The command line
+#[derive(Debug, PartialEq)]
+struct Args {
+    path: String,
+    faults: Faults,
+}
+
+impl Args {
+    /// Parse the arguments following the program name
+    fn parse<I: IntoIterator<Item = String>>(args: I) -> std::result::Result<Args, String> {
+        let mut path = None;
+        let mut faults = Faults::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                let value = args.next().ok_or(format!("{} needs a value", name))?;
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid value for {}: {:?}", name, value))
+            };
+            match arg.as_str() {
+                "--drop-every" => faults = faults.with_drop_every(value(&arg)?),
+                "--delay" => faults = faults.with_delay(Duration::from_millis(value(&arg)?)),
+                "--corrupt-crc-every" => faults = faults.with_corrupt_crc_every(value(&arg)?),
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ if path.is_none() => path = Some(arg),
+                _ => return Err(format!("Unexpected argument {:?}", arg)),
+            }
+        }
+        let path = path.ok_or("No register map given")?;
+        Ok(Args { path, faults })
+    }
+}
+
+/// Prints every request answered
+struct Log;
+
+impl MetricsSink for Log {
+    fn record(&self, unit: u8, function: &Function, outcome: Outcome, elapsed: Duration) {
+        println!(
+            "unit {:#04X} {:?}: {:?} in {:?}",
+            unit, function, outcome, elapsed
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("easy-modbus-sim: {}\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("easy-modbus-sim: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<()> {
+    let map = RegisterMap::load(&args.path)?;
+    let store = map.store()?;
+    let chaos = Arc::new(ChaosService::new(store).with_faults(args.faults));
+    let log = Arc::new(Log);
+
+    let server = TcpServer::bind(map.listen.as_str()).await?;
+    let server = server.with_metrics(log.clone()).with_chaos(chaos.clone());
+    println!("Serving Modbus TCP on {}", server.local_addr());
+    let handle = server.handle();
+    let tcp = tokio::spawn(server.serve(chaos.clone()));
+
+    if let Some(rtu) = &map.rtu {
+        let port = SerialStream::open(&tokio_serial::new(&rtu.path, rtu.baud_rate))?;
+        let server = RtuServer::new(port, rtu.unit, chaos.clone())
+            .with_chaos(chaos)
+            .with_metrics(log);
+        println!("Serving Modbus RTU unit {:#04X} on {}", rtu.unit, rtu.path);
+        let path = rtu.path.clone();
+        tokio::spawn(async move {
+            if let Err(e) = server.serve().await {
+                eprintln!("Stopped serving {}: {}", path, e);
+            }
+        });
+    }
+
+    tokio::signal::ctrl_c().await?;
+    handle.shutdown();
+    tcp.await?
+}
+
+#[cfg(test)]
+mod main_test {
+    use std::time::Duration;
+
+    use easy_modbus::server::Faults;
+
+    use super::Args;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_test() {
+        let args = parse(&["map.toml"]).unwrap();
+        assert_eq!(args.path, "map.toml");
+        assert_eq!(args.faults, Faults::default());
+
+        let args = parse(&[
+            "--drop-every",
+            "10",
+            "map.toml",
+            "--delay",
+            "250",
+            "--corrupt-crc-every",
+            "3",
+        ])
+        .unwrap();
+        let faults = Faults::default()
+            .with_drop_every(10)
+            .with_delay(Duration::from_millis(250))
+            .with_corrupt_crc_every(3);
+        assert_eq!(args.faults, faults);
+
+        for args in [
+            &[][..],
+            &["--drop-every", "map.toml"],
+            &["--delay"],
+            &["--wrong", "1", "map.toml"],
+            &["map.toml", "other.toml"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+}
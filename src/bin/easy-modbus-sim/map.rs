@@ -0,0 +1,340 @@
+//! Register map of the simulator, read from a TOML file.
+//!
+//! ```toml
+//! [tcp]
+//! listen = "0.0.0.0:502"
+//!
+//! [rtu]
+//! port = "/dev/ttyUSB0"
+//! baud_rate = 19200
+//! unit = 1
+//!
+//! [coils]
+//! base = 0
+//! count = 16
+//! values = [true, false, true]
+//! read_only = ["0..4"]
+//!
+//! [holding_registers]
+//! base = 0x0100
+//! count = 8
+//! values = [1450, 0x0002]
+//! ```
+//!
+//! A space is declared by its own section, `coils`, `discrete_inputs`, `holding_registers` or
+//! `input_registers`. `values` sets the first addresses of the space, the others start at
+//! zero. `read_only` lists ranges of coils or holding registers a master may not write, from
+//! the first address to the one after the last.
+
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use easy_modbus::server::DataStore;
+use easy_modbus::AddressRange;
+
+/// The Modbus TCP address served by default
+pub const DEFAULT_LISTEN: &str = "0.0.0.0:502";
+
+/// A register map definition
+#[derive(Debug, Default, PartialEq)]
+pub struct RegisterMap {
+    /// Address the Modbus TCP server listens on
+    pub listen: String,
+
+    /// Serial port a Modbus RTU server is also served on
+    pub rtu: Option<RtuPort>,
+
+    pub coils: Option<Space>,
+    pub discrete_inputs: Option<Space>,
+    pub holding_registers: Option<Space>,
+    pub input_registers: Option<Space>,
+}
+
+/// A serial port served with Modbus RTU
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RtuPort {
+    #[serde(rename = "port")]
+    pub path: String,
+    #[serde(default = "default_baud_rate")]
+    pub baud_rate: u32,
+    pub unit: u8,
+}
+
+/// The addresses of one space
+#[derive(Debug, PartialEq)]
+pub struct Space {
+    pub base: u16,
+    pub count: usize,
+
+    /// Initial values of the first addresses, 0 or 1 for bits
+    pub values: Vec<u16>,
+
+    /// Ranges a master may not write
+    pub read_only: Vec<AddressRange>,
+}
+
+impl RegisterMap {
+    /// Read the register map in the file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<RegisterMap> {
+        RegisterMap::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Read a register map from the text of its file
+    pub fn parse(text: &str) -> Result<RegisterMap> {
+        let file: File = toml::from_str(text).map_err(|e| invalid(e.to_string()))?;
+        Ok(RegisterMap {
+            listen: file
+                .tcp
+                .and_then(|tcp| tcp.listen)
+                .unwrap_or_else(|| DEFAULT_LISTEN.to_string()),
+            rtu: file.rtu,
+            coils: space(file.coils, "coils", true)?,
+            discrete_inputs: space(file.discrete_inputs, "discrete_inputs", false)?,
+            holding_registers: space(file.holding_registers, "holding_registers", true)?,
+            input_registers: space(file.input_registers, "input_registers", false)?,
+        })
+    }
+
+    /// A store holding the spaces of the map, with their initial values
+    pub fn store(&self) -> Result<DataStore> {
+        let mut store = DataStore::default();
+        if let Some(space) = &self.coils {
+            store = store.with_coils(space.base, space.count);
+            store
+                .set_coils(space.base, &bits(&space.values))
+                .map_err(overflow)?;
+            for range in &space.read_only {
+                store.protect_coils(*range);
+            }
+        }
+        if let Some(space) = &self.discrete_inputs {
+            store = store.with_discrete_inputs(space.base, space.count);
+            store
+                .set_discrete_inputs(space.base, &bits(&space.values))
+                .map_err(overflow)?;
+        }
+        if let Some(space) = &self.holding_registers {
+            store = store.with_holding_registers(space.base, space.count);
+            store
+                .set_holding_registers(space.base, &space.values)
+                .map_err(overflow)?;
+            for range in &space.read_only {
+                store.protect(*range);
+            }
+        }
+        if let Some(space) = &self.input_registers {
+            store = store.with_input_registers(space.base, space.count);
+            store
+                .set_input_registers(space.base, &space.values)
+                .map_err(overflow)?;
+        }
+        Ok(store)
+    }
+}
+
+/// The sections of a register map file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    tcp: Option<TcpSection>,
+    rtu: Option<RtuPort>,
+    coils: Option<SpaceSection>,
+    discrete_inputs: Option<SpaceSection>,
+    holding_registers: Option<SpaceSection>,
+    input_registers: Option<SpaceSection>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TcpSection {
+    listen: Option<String>,
+}
+
+/// A space as written in its section, before it is checked
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpaceSection {
+    base: u16,
+    count: usize,
+    #[serde(default)]
+    values: Vec<Initial>,
+    read_only: Option<Vec<String>>,
+}
+
+/// The initial value of an address, a bit as a boolean or an integer
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Initial {
+    Bit(bool),
+    Register(u16),
+}
+
+/// The space declared by the section `name`, checked
+fn space(section: Option<SpaceSection>, name: &str, writable: bool) -> Result<Option<Space>> {
+    let Some(section) = section else {
+        return Ok(None);
+    };
+    if section.base as usize + section.count > 0x10000 {
+        return Err(invalid(format!("[{}] goes past address 0xFFFF", name)));
+    }
+    if section.values.len() > section.count {
+        return Err(invalid(format!(
+            "{}.values has more values than count",
+            name
+        )));
+    }
+    let read_only = match section.read_only {
+        Some(_) if !writable => {
+            return Err(invalid(format!("[{}] is always read-only", name)));
+        }
+        Some(ranges) => ranges
+            .iter()
+            .map(|range| parse_range(range))
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    let values = section
+        .values
+        .into_iter()
+        .map(|value| match value {
+            Initial::Bit(value) => value as u16,
+            Initial::Register(value) => value,
+        })
+        .collect();
+    Ok(Some(Space {
+        base: section.base,
+        count: section.count,
+        values,
+        read_only,
+    }))
+}
+
+/// Parse `start..end`, `end` excluded
+fn parse_range(range: &str) -> Result<AddressRange> {
+    let invalid_range = || invalid(format!("Invalid range {:?}, expected start..end", range));
+    let (start, end) = range.split_once("..").ok_or_else(invalid_range)?;
+    let start = parse_address(start.trim()).ok_or_else(invalid_range)?;
+    let end = parse_address(end.trim()).ok_or_else(invalid_range)?;
+    let count = end.checked_sub(start).ok_or_else(invalid_range)?;
+    let start = u16::try_from(start).map_err(|_| invalid_range())?;
+    let count = u16::try_from(count).map_err(|_| invalid_range())?;
+    AddressRange::new(start, count)
+}
+
+/// Parse a decimal or `0x` hexadecimal address, the one after the last included
+fn parse_address(word: &str) -> Option<u32> {
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+fn default_baud_rate() -> u32 {
+    9600
+}
+
+fn bits(values: &[u16]) -> Vec<bool> {
+    values.iter().map(|value| *value != 0).collect()
+}
+
+fn overflow<E: std::fmt::Debug>(error: E) -> Error {
+    invalid(format!("Initial values don't fit: {:?}", error))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod map_test {
+    use easy_modbus::AddressRange;
+
+    use super::{RegisterMap, RtuPort, Space, DEFAULT_LISTEN};
+
+    #[test]
+    fn parse_test() {
+        let text = r#"
+            # A drive
+            [tcp]
+            listen = "127.0.0.1:5020"
+
+            [rtu]
+            port = "/dev/ttyUSB0"  # The RS485 adapter
+            unit = 0x0B
+
+            [coils]
+            base = 0
+            count = 16
+            values = [true, false, 1]
+            read_only = ["0..4", "0x0A..0x0C"]
+
+            [input_registers]
+            base = 0x0100
+            count = 4
+            values = [
+                215,
+                0xFFFF,
+            ]
+        "#;
+        let map = RegisterMap::parse(text).unwrap();
+        assert_eq!(map.listen, "127.0.0.1:5020");
+        let rtu = RtuPort {
+            path: "/dev/ttyUSB0".to_string(),
+            baud_rate: 9600,
+            unit: 0x0B,
+        };
+        assert_eq!(map.rtu, Some(rtu));
+        let coils = Space {
+            base: 0,
+            count: 16,
+            values: vec![1, 0, 1],
+            read_only: vec![
+                AddressRange::new(0x0000, 4).unwrap(),
+                AddressRange::new(0x000A, 2).unwrap(),
+            ],
+        };
+        assert_eq!(map.coils, Some(coils));
+        assert_eq!(map.holding_registers, None);
+
+        let store = map.store().unwrap();
+        assert_eq!(
+            store.get_coils(0x0000, 4).unwrap(),
+            [true, false, true, false]
+        );
+        let registers = store.get_input_registers(0x0100, 4).unwrap();
+        assert_eq!(registers, [215, 0xFFFF, 0, 0]);
+        assert!(store.get_holding_registers(0x0000, 1).is_err());
+    }
+
+    #[test]
+    fn default_test() {
+        let map = RegisterMap::parse("[holding_registers]\nbase = 0\ncount = 2").unwrap();
+        assert_eq!(map.listen, DEFAULT_LISTEN);
+        assert_eq!(map.rtu, None);
+        let store = map.store().unwrap();
+        assert_eq!(store.get_holding_registers(0x0000, 2).unwrap(), [0, 0]);
+    }
+
+    #[test]
+    fn invalid_test() {
+        for text in [
+            "base = 0",
+            "[coils]\nbase = 0",
+            "[coils]\nbase = 0\ncount = 2\nvalues = [1, 1, 1]",
+            "[coils]\nbase = 0xFFFF\ncount = 2",
+            "[discrete_inputs]\nbase = 0\ncount = 2\nread_only = [\"0..1\"]",
+            "[holding_registers]\nbase = 0\ncount = 2\nread_only = [\"1..1\"]",
+            "[holding_registers]\nbase = 0\ncount = 2\nvalues = [70000]",
+            "[holding_registers]\nbase = 0\ncount = 2\nspeed = 3",
+            "[registers]\nbase = 0",
+            "[tcp]\nlisten = 502",
+            "[tcp]\nlisten = \"0.0.0.0:502",
+            "[coils]\nvalues = [1, 2",
+        ] {
+            assert!(RegisterMap::parse(text).is_err(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,1138 @@
+//! Helpers for Modbus clients.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tokio::net::TcpStream;
+//! use tokio_util::codec::Framed;
+//!
+//! use easy_modbus::client::{send_with_retry, Retry};
+//! use easy_modbus::codec::TcpClientCodec;
+//! use easy_modbus::{Frame, ModbusError};
+//!
+//! #[tokio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let stream = TcpStream::connect("127.0.0.1:502").await?;
+//!     let mut transport = Framed::new(stream, TcpClientCodec::default());
+//!     let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0008);
+//!     match send_with_retry(&mut transport, request, Retry::default()).await {
+//!         Ok(response) => println!("{}", response),
+//!         Err(e) => match ModbusError::from(e) {
+//!             ModbusError::Exception { exception, .. } => println!("Answered {:?}", exception),
+//!             e => return Err(e.into()),
+//!         },
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::io::{Error, ErrorKind, Result};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use futures::{future, SinkExt, Stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::data::{ConversionError, FromRegisters, ToRegisters, WordOrder};
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::{pack_registers, Exception, Frame, Head, Version};
+use crate::ModbusError;
+
+/// When [`send_with_retry`] sends a request again
+///
+/// Defaults to 3 attempts, 100 ms apart.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use easy_modbus::client::Retry;
+///
+/// let retry = Retry::default().with_attempts(10).with_delay(Duration::from_secs(1));
+/// assert_eq!(retry.get_attempts(), 10);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Retry {
+    pub(crate) attempts: u32,
+    pub(crate) delay: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            attempts: 3,
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl Retry {
+    /// Send a request at most `attempts` times, at least once
+    pub fn with_attempts(mut self, attempts: u32) -> Retry {
+        self.attempts = attempts.max(1);
+        self
+    }
+
+    /// Wait `delay` before sending a request again
+    pub fn with_delay(mut self, delay: Duration) -> Retry {
+        self.delay = delay;
+        self
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn get_delay(&self) -> Duration {
+        self.delay
+    }
+}
+
+/// Send `request` on `transport` and wait for its response, polling again while the server
+/// answers with a retriable exception
+///
+/// An exception response fails with [`ModbusError::Exception`], carried in the
+/// [`io::Error`](Error) and recovered with `ModbusError::from`. When it is retriable, see
+/// [`ModbusError::is_retriable`], the request is sent again after the delay of `retry`, and the
+/// last exception fails once the attempts are exhausted. Any other exception fails right away:
+/// a device answering [`Exception::IllegalDataAddress`](crate::Exception::IllegalDataAddress)
+/// is asked only once.
+///
+/// The responses to other requests are dropped on the way, see [`filter_tid`]. A call cancelled
+/// before its response, by a timeout say, leaves the response on the transport for the next
+/// call to drop. Over TCP it never answers another request; over RTU it answers a request for
+/// the same unit and function.
+///
+/// Fails with [`ErrorKind::UnexpectedEof`] if the transport is closed before a response.
+pub async fn send_with_retry<T, C>(
+    transport: &mut Framed<T, C>,
+    request: Request,
+    retry: Retry,
+) -> Result<Response>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let head = request.head().clone();
+    let mut attempt = 1;
+    loop {
+        transport.send(request.clone()).await?;
+        let response = match Box::pin(filter_tid(&mut *transport, &head)).next().await {
+            Some(response) => response?,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "Transport closed before a response",
+                ))
+            }
+        };
+        let error = match &response {
+            Response::Exception(head, body) => ModbusError::Exception {
+                function: head.function,
+                exception: *body.get_exception(),
+            },
+            _ => return Ok(response),
+        };
+        if !error.is_retriable() || attempt >= retry.attempts {
+            return Err(error.into());
+        }
+        attempt += 1;
+        tokio::time::sleep(retry.delay).await;
+    }
+}
+
+/// Send a request of the custom function `code` with the body `data` on `transport`, and wait
+/// for its response
+///
+/// For functions this crate doesn't model, vendor commands say. The request is built by
+/// `frame`, see [`Frame::custom_request`], and sent with [`send_with_retry`]: an exception
+/// response fails with [`ModbusError::Exception`]. The response is a
+/// [`Response::Custom`], with the function code in its head and the body following it as is.
+/// The codec of `transport` only decodes it once `code` is registered with it, see
+/// [`TcpClientCodec::with_custom_function`](crate::codec::TcpClientCodec).
+///
+/// Panics for a code of a modeled function, or of an exception.
+pub async fn send_raw<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    code: u8,
+    data: &[u8],
+    retry: Retry,
+) -> Result<Response>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let request = frame.custom_request(unit_id, code, data.to_vec());
+    match send_with_retry(transport, request, retry).await? {
+        response @ Response::Custom(..) => Ok(response),
+        response => Err(response.wrong_variant(&["Custom"]).into()),
+    }
+}
+
+/// Write `values` from `write_address` and read `read_count` registers from `read_address` in
+/// one [`Function::ReadWriteMultipleRegisters`](crate::Function) request, and wait for the
+/// registers read
+///
+/// The write is done before the read, so an overlapping read range returns the values just
+/// written. The request is built by `frame`, see
+/// [`Frame::try_read_write_multiple_registers_request`], and sent with [`send_with_retry`]: an
+/// exception response fails with [`ModbusError::Exception`].
+///
+/// Fails with [`ErrorKind::InvalidInput`] when either range is empty, longer than the 0x17 limits
+/// or past `0xFFFF`, without sending anything.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{SinkExt, StreamExt};
+/// use easy_modbus::client::{read_write_registers, Retry};
+/// use easy_modbus::server::{dispatch, DataStore};
+/// use easy_modbus::{testing, Frame};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let store = DataStore::default().with_holding_registers(0x0000, 8);
+///     let (mut client, mut server) = testing::pair();
+///     tokio::spawn(async move {
+///         let request = server.next().await.unwrap().unwrap();
+///         server.send(dispatch(&store, &request).unwrap()).await.unwrap();
+///     });
+///
+///     let frame = Frame::tcp();
+///     let retry = Retry::default();
+///     let read = read_write_registers(&mut client, &frame, 0x01, 0x0000, 2, 0x0001, &[7], retry)
+///         .await
+///         .unwrap();
+///     assert_eq!(read, vec![0, 7]);
+/// }
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub async fn read_write_registers<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    read_address: u16,
+    read_count: u16,
+    write_address: u16,
+    values: &[u16],
+    retry: Retry,
+) -> Result<Vec<u16>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let request = frame.try_read_write_multiple_registers_request(
+        unit_id,
+        read_address,
+        read_count,
+        write_address,
+        pack_registers(values),
+    )?;
+    match send_with_retry(transport, request, retry).await? {
+        Response::ReadWriteMultipleRegisters(_, body) => Ok(body.to_vec_u16()),
+        response => Err(response
+            .wrong_variant(&["ReadWriteMultipleRegisters"])
+            .into()),
+    }
+}
+
+/// Read a `T` from the holding registers starting at `address`
+///
+/// Exactly [`T::REGISTER_COUNT`](FromRegisters::REGISTER_COUNT) registers are read, in one
+/// request built by `frame`, see [`Frame::try_read_multiple_holding_registers_request`], and sent
+/// with [`send_with_retry`]: an exception response fails with [`ModbusError::Exception`]. The
+/// registers are converted with `order`, see [`FromRegisters`].
+///
+/// Fails with [`ErrorKind::InvalidInput`] when `T` spans more registers than one read returns,
+/// without sending anything, and with [`ErrorKind::InvalidData`] when the response holds another
+/// number of registers.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{SinkExt, StreamExt};
+/// use easy_modbus::client::{read_as, Retry};
+/// use easy_modbus::data::WordOrder;
+/// use easy_modbus::server::{dispatch, DataStore};
+/// use easy_modbus::{testing, Frame};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let store = DataStore::default().with_holding_registers(0x0000, 8);
+///     store.set_holding_value(0x0002, &1234.5f32, WordOrder::BigEndian).unwrap();
+///     let (mut client, mut server) = testing::pair();
+///     tokio::spawn(async move {
+///         let request = server.next().await.unwrap().unwrap();
+///         server.send(dispatch(&store, &request).unwrap()).await.unwrap();
+///     });
+///
+///     let frame = Frame::tcp();
+///     let order = WordOrder::BigEndian;
+///     let value: f32 = read_as(&mut client, &frame, 0x01, 0x0002, order, Retry::default())
+///         .await
+///         .unwrap();
+///     assert_eq!(value, 1234.5);
+/// }
+/// ```
+pub async fn read_as<V, T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    order: WordOrder,
+    retry: Retry,
+) -> Result<V>
+where
+    V: FromRegisters,
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let count = u16::try_from(V::REGISTER_COUNT).unwrap_or(u16::MAX);
+    let request = frame.try_read_multiple_holding_registers_request(unit_id, address, count)?;
+    let registers = match send_with_retry(transport, request, retry).await? {
+        Response::ReadMultipleHoldingRegisters(_, body) => body.to_vec_u16(),
+        response => {
+            return Err(response
+                .wrong_variant(&["ReadMultipleHoldingRegisters"])
+                .into())
+        }
+    };
+    V::from_registers(&registers, order)
+}
+
+/// Write `value` to the holding registers starting at `address`
+///
+/// The [`T::REGISTER_COUNT`](ToRegisters::REGISTER_COUNT) registers of `value`, converted with
+/// `order`, are written in one request built by `frame`, see
+/// [`Frame::try_write_multiple_holding_registers_request`], and sent with [`send_with_retry`]:
+/// an exception response fails with [`ModbusError::Exception`].
+///
+/// Fails with [`ErrorKind::InvalidInput`] when `value` spans more registers than one write
+/// takes, without sending anything.
+pub async fn write_as<V, T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    value: &V,
+    order: WordOrder,
+    retry: Retry,
+) -> Result<()>
+where
+    V: ToRegisters + ?Sized,
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let values = pack_registers(&value.to_registers(order));
+    let request = frame.try_write_multiple_holding_registers_request(unit_id, address, values)?;
+    match send_with_retry(transport, request, retry).await? {
+        Response::WriteMultipleHoldingRegisters(..) => Ok(()),
+        response => Err(response
+            .wrong_variant(&["WriteMultipleHoldingRegisters"])
+            .into()),
+    }
+}
+
+/// How [`mask_write_register_or_fallback`] modified the register
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaskWritePath {
+    /// With one mask write register request (Function Code: 0x16)
+    Native,
+    /// With a read of the register (Function Code: 0x03), then a write of the new value
+    /// (Function Code: 0x06), the device answering the mask write with
+    /// [`Exception::IllegalFunction`](crate::Exception::IllegalFunction)
+    ReadModifyWrite,
+}
+
+/// Set the holding register at `address` to `(current & and_mask) | (or_mask & !and_mask)`
+///
+/// The request is built by `frame`, see [`Frame::mask_write_register_request`], and sent with
+/// [`send_with_retry`]: an exception response fails with [`ModbusError::Exception`]. The
+/// function isn't modeled, the codec of `transport` only decodes its response once 0x16 is
+/// registered with it as a [`BodyLength::Fixed(6)`](crate::codec::BodyLength).
+///
+/// Many devices don't implement the function, see [`mask_write_register_or_fallback`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tokio::net::TcpStream;
+/// use tokio_util::codec::Framed;
+///
+/// use easy_modbus::client::{mask_write_register, Retry};
+/// use easy_modbus::codec::{BodyLength, TcpClientCodec};
+/// use easy_modbus::Frame;
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let stream = TcpStream::connect("127.0.0.1:502").await?;
+///     let codec = TcpClientCodec::default().with_custom_function(0x16, BodyLength::Fixed(6));
+///     let mut transport = Framed::new(stream, codec);
+///     let frame = Frame::tcp();
+///     mask_write_register(&mut transport, &frame, 0x01, 0x0004, 0x00F2, 0x0025, Retry::default())
+///         .await
+/// }
+/// ```
+pub async fn mask_write_register<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    and_mask: u16,
+    or_mask: u16,
+    retry: Retry,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let request = frame.mask_write_register_request(unit_id, address, and_mask, or_mask);
+    match send_with_retry(transport, request, retry).await? {
+        Response::Custom(..) => Ok(()),
+        response => Err(response.wrong_variant(&["Custom"]).into()),
+    }
+}
+
+/// Set the `bits` of the holding register at `address`, keeping the others
+///
+/// A [`mask_write_register`] with an `and_mask` of `!bits` and an `or_mask` of `bits`.
+pub async fn set_register_bits<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    bits: u16,
+    retry: Retry,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    mask_write_register(transport, frame, unit_id, address, !bits, bits, retry).await
+}
+
+/// Clear the `bits` of the holding register at `address`, keeping the others
+///
+/// A [`mask_write_register`] with an `and_mask` of `!bits` and an `or_mask` of `0x0000`.
+pub async fn clear_register_bits<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    bits: u16,
+    retry: Retry,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    mask_write_register(transport, frame, unit_id, address, !bits, 0x0000, retry).await
+}
+
+/// Like [`mask_write_register`], falling back to a read-modify-write when the device doesn't
+/// implement the mask write
+///
+/// When the device answers the mask write with
+/// [`Exception::IllegalFunction`](crate::Exception::IllegalFunction), the register is read
+/// (Function Code: 0x03), masked here, and the new value written (Function Code: 0x06), each with
+/// [`send_with_retry`]. Returns the path taken.
+///
+/// The fallback is not atomic: a write of the register by another master, or by the device
+/// itself, between the read and the write is overwritten. Only opt in for registers nothing else
+/// writes.
+pub async fn mask_write_register_or_fallback<T, C>(
+    transport: &mut Framed<T, C>,
+    frame: &Frame,
+    unit_id: u8,
+    address: u16,
+    and_mask: u16,
+    or_mask: u16,
+    retry: Retry,
+) -> Result<MaskWritePath>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let native =
+        mask_write_register(transport, frame, unit_id, address, and_mask, or_mask, retry).await;
+    match native.map_err(ModbusError::from) {
+        Ok(()) => return Ok(MaskWritePath::Native),
+        Err(ModbusError::Exception {
+            exception: Exception::IllegalFunction,
+            ..
+        }) => {}
+        Err(e) => return Err(e.into()),
+    }
+    let request = frame.read_multiple_holding_registers_request(unit_id, address, 1);
+    let current = match send_with_retry(transport, request, retry).await? {
+        Response::ReadMultipleHoldingRegisters(_, body) => match body.to_vec_u16()[..] {
+            [current] => current,
+            ref registers => {
+                return Err(ConversionError::LengthMismatch {
+                    expected: 1,
+                    actual: registers.len(),
+                }
+                .into())
+            }
+        },
+        response => {
+            return Err(response
+                .wrong_variant(&["ReadMultipleHoldingRegisters"])
+                .into())
+        }
+    };
+    let value = (current & and_mask) | (or_mask & !and_mask);
+    let request = frame.write_single_holding_register_request(unit_id, address, value);
+    match send_with_retry(transport, request, retry).await? {
+        Response::WriteSingleHoldingRegister(..) => Ok(MaskWritePath::ReadModifyWrite),
+        response => Err(response
+            .wrong_variant(&["WriteSingleHoldingRegister"])
+            .into()),
+    }
+}
+
+/// Send `request` on `transport` and wait for its response, with the time it took
+///
+/// The round trip is measured from before the request is sent until the response answering it
+/// is decoded, the responses to other requests are dropped on the way, see [`filter_tid`].
+///
+/// Fails with [`ErrorKind::UnexpectedEof`] if the transport is closed before a response.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{SinkExt, StreamExt};
+/// use easy_modbus::client::send_timed;
+/// use easy_modbus::server::{dispatch, DataStore};
+/// use easy_modbus::{testing, Frame};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let store = DataStore::default().with_coils(0x0000, 8);
+///     let (mut client, mut server) = testing::pair();
+///     tokio::spawn(async move {
+///         let request = server.next().await.unwrap().unwrap();
+///         server.send(dispatch(&store, &request).unwrap()).await.unwrap();
+///     });
+///
+///     let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0008);
+///     let (response, elapsed) = send_timed(&mut client, request).await.unwrap();
+///     println!("{} in {:?}", response, elapsed);
+/// }
+/// ```
+pub async fn send_timed<T, C>(
+    transport: &mut Framed<T, C>,
+    request: Request,
+) -> Result<(Response, Duration)>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Encoder<Request, Error = Error> + Decoder<Item = Response, Error = Error>,
+{
+    let head = request.head().clone();
+    let start = Instant::now();
+    transport.send(request).await?;
+    let mut responses = Box::pin(filter_tid(transport, &head));
+    match responses.next().await {
+        Some(response) => Ok((response?, start.elapsed())),
+        None => Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "Transport closed before a response",
+        )),
+    }
+}
+
+/// The responses of `responses` answering the request with head `request`, dropping the others
+///
+/// Over TCP a response answers the request with the same transaction identifier. RTU frames
+/// carry none, a response then answers the request for the same unit and function. Errors are
+/// passed through. With the `tracing` feature enabled, every response dropped emits a `debug!`
+/// event.
+///
+/// # Examples
+///
+/// ```
+/// use futures::{SinkExt, StreamExt};
+/// use easy_modbus::client::filter_tid;
+/// use easy_modbus::{testing, Frame};
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let (mut client, mut server) = testing::pair();
+///     let (client_frame, server_frame) = (Frame::tcp(), Frame::tcp());
+///     let _first = client_frame.read_coils_request(0x01, 0x0000, 0x0001);
+///     let second = client_frame.read_coils_request(0x01, 0x0000, 0x0001);
+///     // Answering both, with the same transaction identifiers
+///     server.send(server_frame.read_coils_response(0x01, vec![0x00])).await.unwrap();
+///     server.send(server_frame.read_coils_response(0x01, vec![0x01])).await.unwrap();
+///
+///     let mut responses = Box::pin(filter_tid(&mut client, second.head()));
+///     let response = responses.next().await.unwrap().unwrap();
+///     assert_eq!(response.head().get_tid(), second.head().get_tid());
+/// }
+/// ```
+pub fn filter_tid<'a, S>(responses: S, request: &Head) -> impl Stream<Item = Result<Response>> + 'a
+where
+    S: Stream<Item = Result<Response>> + 'a,
+{
+    let request = request.clone();
+    responses.filter(move |response| {
+        let keep = match response {
+            Ok(response) => answers(&request, response.head()),
+            Err(_) => true,
+        };
+        if let (false, Ok(response)) = (keep, response) {
+            trace_dropped(response.head());
+        }
+        future::ready(keep)
+    })
+}
+
+/// Whether a response with head `response` answers the request with head `request`
+fn answers(request: &Head, response: &Head) -> bool {
+    match request.version {
+        Version::Tcp => response.tid == request.tid,
+        Version::Rtu => response.uid == request.uid && response.function == request.function,
+    }
+}
+
+/// Emit a `debug!` event for a response not answering the request waited for
+#[cfg(feature = "tracing")]
+fn trace_dropped(head: &Head) {
+    tracing::debug!(
+        tid = head.tid,
+        uid = head.uid,
+        function = ?head.function,
+        "modbus response dropped"
+    );
+}
+
+/// No-op without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+fn trace_dropped(_head: &Head) {}
+
+#[cfg(test)]
+mod client_test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio::io::DuplexStream;
+    use tokio_util::codec::Framed;
+
+    use crate::client::{send_with_retry, Retry};
+    use crate::codec::{
+        BodyLength, RtuClientCodec, RtuServerCodec, TcpClientCodec, TcpServerCodec,
+    };
+    use crate::frame::{Exception, Function};
+    use crate::server::{dispatch, serve_rtu, DataStore, Service};
+    use crate::{Frame, ModbusError, Request};
+
+    /// Answers the first `pending` reads with `exception` before answering them
+    struct Slow {
+        pending: usize,
+        exception: Exception,
+        reads: AtomicUsize,
+    }
+
+    impl Service for Slow {
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            match self.reads.fetch_add(1, Ordering::SeqCst) < self.pending {
+                true => Err(self.exception),
+                false => Ok(vec![0x0102; count as usize]),
+            }
+        }
+    }
+
+    fn slow(pending: usize, exception: Exception) -> Arc<Slow> {
+        Arc::new(Slow {
+            pending,
+            exception,
+            reads: AtomicUsize::new(0),
+        })
+    }
+
+    /// A RTU client transport to `service`
+    fn connect<S: Service + Send + 'static>(service: S) -> Framed<DuplexStream, RtuClientCodec> {
+        let (client, server) = tokio::io::duplex(64);
+        let transport = Framed::new(server, RtuServerCodec::default());
+        tokio::spawn(serve_rtu(transport, service));
+        Framed::new(client, RtuClientCodec::default())
+    }
+
+    #[tokio::test]
+    async fn retry_test() {
+        let frame = Frame::rtu();
+        let retry = Retry::default().with_delay(Duration::from_millis(10));
+        for exception in [
+            Exception::Acknowledge,
+            Exception::GatewayTargetDeviceFailedToRespond,
+        ] {
+            let service = slow(2, exception);
+            let mut transport = connect(service.clone());
+            let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+            let response = send_with_retry(&mut transport, request, retry)
+                .await
+                .unwrap();
+            assert_eq!(
+                response,
+                frame.read_holding_register_response(0x01, vec![0x01, 0x02])
+            );
+            assert_eq!(service.reads.load(Ordering::SeqCst), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn exhausted_test() {
+        let service = slow(5, Exception::Acknowledge);
+        let mut transport = connect(service.clone());
+        let frame = Frame::rtu();
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        let retry = Retry::default().with_attempts(2).with_delay(Duration::ZERO);
+        let error = send_with_retry(&mut transport, request, retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::ReadMultipleHoldingRegisters,
+                exception: Exception::Acknowledge,
+            }
+        ));
+        assert_eq!(service.reads.load(Ordering::SeqCst), 2);
+
+        // Other exceptions are never retried
+        let request = frame.read_coils_request(0x01, 0x0000, 0x0001);
+        let error = send_with_retry(&mut transport, request, retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::ReadCoils,
+                exception: Exception::IllegalFunction,
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn not_retriable_test() {
+        let service = slow(5, Exception::IllegalDataAddress);
+        let mut transport = connect(service.clone());
+        let frame = Frame::rtu();
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        let retry = Retry::default().with_attempts(5).with_delay(Duration::ZERO);
+        let error = send_with_retry(&mut transport, request, retry)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AddrNotAvailable);
+        assert!(!ModbusError::from(error).is_retriable());
+        assert_eq!(service.reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn cancel_test() {
+        use futures::{SinkExt, StreamExt};
+
+        use crate::testing;
+
+        let store = DataStore::default().with_holding_registers(0x0000, 2);
+        store.set_holding_registers(0x0000, &[10, 11]).unwrap();
+        let (mut client, mut server) = testing::pair();
+        let (answer, answered) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let late = server.next().await.unwrap().unwrap();
+            answered.await.unwrap();
+            server.send(dispatch(&store, &late).unwrap()).await.unwrap();
+            let request = server.next().await.unwrap().unwrap();
+            server
+                .send(dispatch(&store, &request).unwrap())
+                .await
+                .unwrap();
+        });
+
+        let frame = Frame::tcp();
+        let retry = Retry::default();
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        let sent = send_with_retry(&mut client, request, retry);
+        let timeout = tokio::time::timeout(Duration::from_millis(20), sent).await;
+        assert!(timeout.is_err());
+        answer.send(()).unwrap();
+
+        // The late response to the cancelled request is not taken for this one
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0001, 0x0001);
+        let response = send_with_retry(&mut client, request, retry).await.unwrap();
+        assert_eq!(response.into_registers().unwrap(), vec![11]);
+    }
+
+    #[tokio::test]
+    async fn send_raw_test() {
+        use futures::{SinkExt, StreamExt};
+
+        use crate::client::send_raw;
+        use crate::codec::{BodyLength, TcpClientCodec, TcpServerCodec};
+
+        let (client, server) = tokio::io::duplex(256);
+        let codec = TcpClientCodec::default().with_custom_function(0x41, BodyLength::Fixed(2));
+        let mut client = Framed::new(client, codec);
+        let codec = TcpServerCodec::default().with_custom_function(0x41, BodyLength::Fixed(2));
+        let mut server = Framed::new(server, codec);
+        tokio::spawn(async move {
+            let frame = Frame::tcp();
+            let request = server.next().await.unwrap().unwrap();
+            assert_eq!(request.pdu_bytes()[..], [0x41, 0xA5, 0x5A]);
+            server
+                .send(frame.custom_response(0x01, 0x41, vec![0x12, 0x34]))
+                .await
+                .unwrap();
+            server.next().await.unwrap().unwrap();
+            let exception = Exception::IllegalDataValue;
+            let response = frame.exception_response(0x01, Function::Custom(0x41), exception);
+            server.send(response).await.unwrap();
+        });
+
+        let frame = Frame::tcp();
+        let retry = Retry::default();
+        let response = send_raw(&mut client, &frame, 0x01, 0x41, &[0xA5, 0x5A], retry)
+            .await
+            .unwrap();
+        assert_eq!(response.head().get_function(), Function::Custom(0x41));
+        assert_eq!(response.pdu_bytes()[..], [0x41, 0x12, 0x34]);
+
+        let error = send_raw(&mut client, &frame, 0x01, 0x41, &[0xA5, 0x5A], retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::Custom(0x41),
+                exception: Exception::IllegalDataValue,
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn read_write_registers_test() {
+        use crate::client::read_write_registers;
+        use crate::server::DataStore;
+
+        let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 8));
+        let mut transport = connect(store.clone());
+        let frame = Frame::rtu();
+        let retry = Retry::default();
+        store.set_holding_registers(0x0000, &[0x1234]).unwrap();
+        let read =
+            read_write_registers(&mut transport, &frame, 1, 0x0000, 3, 0x0002, &[5, 6], retry)
+                .await
+                .unwrap();
+        assert_eq!(read, vec![0x1234, 0x0000, 0x0005]);
+        assert_eq!(store.get_holding_registers(0x0002, 2).unwrap(), vec![5, 6]);
+
+        let error = read_write_registers(&mut transport, &frame, 1, 0x0006, 4, 0x0000, &[1], retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::ReadWriteMultipleRegisters,
+                exception: Exception::IllegalDataAddress,
+            }
+        ));
+        let registers = store.get_holding_registers(0x0000, 1).unwrap();
+        assert_eq!(registers, vec![0x1234]);
+
+        // Out of the 0x17 limits, nothing is sent
+        let error =
+            read_write_registers(&mut transport, &frame, 1, 0x0000, 126, 0x0000, &[1], retry)
+                .await
+                .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[tokio::test]
+    async fn read_as_test() {
+        use crate::client::{read_as, write_as};
+        use crate::data::WordOrder;
+        use crate::server::DataStore;
+
+        let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 8));
+        let mut transport = connect(store.clone());
+        let frame = Frame::rtu();
+        let (order, retry) = (WordOrder::LittleEndian, Retry::default());
+        write_as(&mut transport, &frame, 1, 0x0001, &-2i32, order, retry)
+            .await
+            .unwrap();
+        let registers = store.get_holding_registers(0x0000, 4).unwrap();
+        assert_eq!(registers, vec![0x0000, 0xFFFE, 0xFFFF, 0x0000]);
+        let value: i32 = read_as(&mut transport, &frame, 1, 0x0001, order, retry)
+            .await
+            .unwrap();
+        assert_eq!(value, -2);
+        let value: [u16; 3] = read_as(&mut transport, &frame, 1, 0x0000, order, retry)
+            .await
+            .unwrap();
+        assert_eq!(value, [0x0000, 0xFFFE, 0xFFFF]);
+
+        let error = read_as::<f64, _, _>(&mut transport, &frame, 1, 0x0006, order, retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::ReadMultipleHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            }
+        ));
+
+        // More registers than one request carries, nothing is sent
+        let error = read_as::<[u16; 126], _, _>(&mut transport, &frame, 1, 0x0000, order, retry)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        let values = [0u16; 124];
+        let error = write_as(&mut transport, &frame, 1, 0x0000, &values, order, retry)
+            .await
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    /// A TCP client transport to `store`, answering mask writes when `native`, or with
+    /// `IllegalFunction` as a device not implementing them
+    fn mask_device(store: Arc<DataStore>, native: bool) -> Framed<DuplexStream, TcpClientCodec> {
+        use futures::{SinkExt, StreamExt};
+
+        let (client, server) = tokio::io::duplex(256);
+        let codec = TcpClientCodec::default().with_custom_function(0x16, BodyLength::Fixed(6));
+        let client = Framed::new(client, codec);
+        let codec = TcpServerCodec::default().with_custom_function(0x16, BodyLength::Fixed(6));
+        let mut server = Framed::new(server, codec);
+        tokio::spawn(async move {
+            let frame = Frame::tcp();
+            while let Some(request) = server.next().await {
+                let request = request.unwrap();
+                let response = match &request {
+                    Request::Custom(_, body) if native => {
+                        let data = body.get_data();
+                        let field = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+                        let (address, and_mask, or_mask) = (field(0), field(2), field(4));
+                        match store.get_holding_registers(address, 1) {
+                            Ok(current) => {
+                                let value = (current[0] & and_mask) | (or_mask & !and_mask);
+                                store.set_holding_registers(address, &[value]).unwrap();
+                                frame.custom_response(0x01, 0x16, data.to_vec())
+                            }
+                            Err(e) => frame.exception_response(0x01, Function::Custom(0x16), e),
+                        }
+                    }
+                    request => dispatch(&*store, request).unwrap(),
+                };
+                server.send(response).await.unwrap();
+            }
+        });
+        client
+    }
+
+    #[tokio::test]
+    async fn mask_write_register_test() {
+        use crate::client::{clear_register_bits, mask_write_register, set_register_bits};
+
+        let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 8));
+        let mut transport = mask_device(store.clone(), true);
+        let (frame, retry) = (Frame::tcp(), Retry::default());
+        store.set_holding_registers(0x0004, &[0x0012]).unwrap();
+        mask_write_register(&mut transport, &frame, 1, 0x0004, 0x00F2, 0x0025, retry)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_holding_registers(0x0004, 1).unwrap(),
+            vec![0x0017]
+        );
+        set_register_bits(&mut transport, &frame, 1, 0x0004, 0x8100, retry)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_holding_registers(0x0004, 1).unwrap(),
+            vec![0x8117]
+        );
+        clear_register_bits(&mut transport, &frame, 1, 0x0004, 0x0107, retry)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_holding_registers(0x0004, 1).unwrap(),
+            vec![0x8010]
+        );
+
+        // No fallback unless opted in
+        let mut transport = mask_device(store.clone(), false);
+        let error = set_register_bits(&mut transport, &frame, 1, 0x0004, 0x0001, retry)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Exception {
+                function: Function::Custom(0x16),
+                exception: Exception::IllegalFunction,
+            }
+        ));
+        assert_eq!(
+            store.get_holding_registers(0x0004, 1).unwrap(),
+            vec![0x8010]
+        );
+    }
+
+    #[tokio::test]
+    async fn mask_write_fallback_test() {
+        use crate::client::{mask_write_register_or_fallback, MaskWritePath};
+
+        let (frame, retry) = (Frame::tcp(), Retry::default());
+        for (native, path) in [
+            (true, MaskWritePath::Native),
+            (false, MaskWritePath::ReadModifyWrite),
+        ] {
+            let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 8));
+            let mut transport = mask_device(store.clone(), native);
+            store.set_holding_registers(0x0004, &[0x0012]).unwrap();
+            let taken = mask_write_register_or_fallback(
+                &mut transport,
+                &frame,
+                1,
+                0x0004,
+                0x00F2,
+                0x0025,
+                retry,
+            )
+            .await
+            .unwrap();
+            assert_eq!(taken, path);
+            assert_eq!(
+                store.get_holding_registers(0x0004, 1).unwrap(),
+                vec![0x0017]
+            );
+
+            // The exceptions of either path still fail
+            let error = mask_write_register_or_fallback(
+                &mut transport,
+                &frame,
+                1,
+                0x0010,
+                0x00F2,
+                0x0025,
+                retry,
+            )
+            .await
+            .unwrap_err();
+            assert!(matches!(
+                ModbusError::from(error),
+                ModbusError::Exception {
+                    exception: Exception::IllegalDataAddress,
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn filter_tid_test() {
+        use futures::{SinkExt, StreamExt};
+
+        use crate::client::filter_tid;
+        use crate::server::{dispatch, DataStore};
+        use crate::testing;
+
+        let store = DataStore::default().with_holding_registers(0x0000, 3);
+        store.set_holding_registers(0x0000, &[10, 20, 30]).unwrap();
+        let (mut client, mut server) = testing::pair();
+        let frame = Frame::tcp();
+        let requests: Vec<_> = (0..3)
+            .map(|i| frame.read_multiple_holding_registers_request(0x01, i, 0x0001))
+            .collect();
+        for request in &requests {
+            client.send(request.clone()).await.unwrap();
+            let request = server.next().await.unwrap().unwrap();
+            server
+                .send(dispatch(&store, &request).unwrap())
+                .await
+                .unwrap();
+        }
+        drop(server);
+
+        let responses: Vec<_> = filter_tid(&mut client, requests[1].head()).collect().await;
+        assert_eq!(responses.len(), 1);
+        let response = responses[0].as_ref().unwrap();
+        assert_eq!(response.head().get_tid(), requests[1].head().get_tid());
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 20]);
+        assert_eq!(response.pdu_bytes(), expected.pdu_bytes());
+    }
+
+    #[tokio::test]
+    async fn send_timed_test() {
+        use futures::{SinkExt, StreamExt};
+
+        use crate::client::send_timed;
+        use crate::server::{dispatch, DataStore};
+        use crate::testing;
+
+        let store = DataStore::default().with_coils(0x0000, 8);
+        let (mut client, mut server) = testing::pair();
+        let delay = Duration::from_millis(50);
+        tokio::spawn(async move {
+            let request = server.next().await.unwrap().unwrap();
+            // A stale response first, then the answer, late
+            let stale = Frame::tcp().read_coils_response(0x01, vec![0xFF]);
+            server.send(stale).await.unwrap();
+            tokio::time::sleep(delay).await;
+            server
+                .send(dispatch(&store, &request).unwrap())
+                .await
+                .unwrap();
+        });
+
+        let frame = Frame::tcp();
+        frame.read_coils_request(0x01, 0x0000, 0x0008);
+        let request = frame.read_coils_request(0x01, 0x0000, 0x0008);
+        let (response, elapsed) = send_timed(&mut client, request.clone()).await.unwrap();
+        assert_eq!(response.head().get_tid(), request.head().get_tid());
+        assert!(elapsed >= delay, "{:?}", elapsed);
+        assert!(elapsed < delay + Duration::from_secs(1), "{:?}", elapsed);
+
+        let request = frame.read_coils_request(0x01, 0x0000, 0x0008);
+        assert!(send_timed(&mut client, request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn filter_rtu_test() {
+        use futures::{SinkExt, StreamExt};
+
+        use crate::client::filter_tid;
+
+        let (client, server) = tokio::io::duplex(256);
+        let mut client = Framed::new(client, RtuClientCodec::default());
+        let mut server = Framed::new(server, RtuServerCodec::default());
+        let frame = Frame::rtu();
+        let request = frame.read_coils_request(0x02, 0x0000, 0x0001);
+        let responses = [
+            frame.read_coils_response(0x01, vec![0x00]),
+            frame.read_discrete_response(0x02, vec![0x00]),
+            frame.read_coils_response(0x02, vec![0x01]),
+            frame.exception_response(0x02, Function::ReadCoils, Exception::SlaveDeviceBusy),
+        ];
+        for response in &responses {
+            server.send(response.clone()).await.unwrap();
+        }
+        drop(server);
+
+        let filtered: Vec<_> = filter_tid(&mut client, request.head()).collect().await;
+        let filtered: Vec<_> = filtered.into_iter().map(|r| r.unwrap()).collect();
+        assert_eq!(filtered, responses[2..]);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::limits::{MAX_RTU_ADU_LEN, MAX_TCP_ADU_LEN};
+
+/// Largest Modbus TCP frame, 7 bytes MBAP header and a 253 bytes PDU
+pub const MAX_FRAME: usize = MAX_TCP_ADU_LEN;
+
+/// Codec configuration
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::codec::{CodecConfig, TcpServerCodec};
+/// let config = CodecConfig::default().strict(true).max_frame(128);
+/// let codec = TcpServerCodec::new(config);
+/// assert!(codec.get_config().is_strict());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CodecConfig {
+    /// Reject frames deviating from the specification
+    ///
+    /// A protocol identifier other than [`CodecConfig::pid`], or a byte count which does not
+    /// agree with the MBAP length, is an error instead of being ignored.
+    pub(crate) strict: bool,
+
+    /// Largest frame the decoder accepts, in bytes, [`MAX_FRAME`] by default and
+    /// [`MAX_RTU_ADU_LEN`] for the RTU codecs
+    pub(crate) max_frame: usize,
+
+    /// MBAP protocol identifier of TCP frames, 0 for Modbus
+    pub(crate) pid: u16,
+}
+
+impl Default for CodecConfig {
+    fn default() -> Self {
+        CodecConfig {
+            strict: false,
+            max_frame: MAX_FRAME,
+            pid: 0,
+        }
+    }
+}
+
+impl CodecConfig {
+    /// The default configuration of the RTU codecs, accepting frames up to
+    /// [`MAX_RTU_ADU_LEN`] bytes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::codec::{CodecConfig, RtuServerCodec};
+    /// use easy_modbus::limits::MAX_RTU_ADU_LEN;
+    /// assert_eq!(CodecConfig::rtu().get_max_frame(), MAX_RTU_ADU_LEN);
+    /// assert_eq!(RtuServerCodec::default().get_config(), &CodecConfig::rtu());
+    /// ```
+    pub fn rtu() -> Self {
+        CodecConfig {
+            max_frame: MAX_RTU_ADU_LEN,
+            ..CodecConfig::default()
+        }
+    }
+
+    /// Set whether frames deviating from the specification are rejected
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Set the largest frame the decoder accepts, in bytes
+    pub fn max_frame(mut self, max_frame: usize) -> Self {
+        self.max_frame = max_frame;
+        self
+    }
+
+    /// Set the MBAP protocol identifier of TCP frames
+    ///
+    /// Encoded TCP frames whose head has the default identifier 0 carry `pid`, a response
+    /// echoing the identifier of its request keeps it. A strict decoder rejects frames
+    /// carrying another one.
+    /// Some vendors tunnel Modbus in a protocol of their own with a non-zero identifier.
+    pub fn pid(mut self, pid: u16) -> Self {
+        self.pid = pid;
+        self
+    }
+
+    /// Whether frames deviating from the specification are rejected
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// The largest frame the decoder accepts, in bytes
+    pub fn get_max_frame(&self) -> usize {
+        self.max_frame
+    }
+
+    /// The MBAP protocol identifier of TCP frames
+    pub fn get_pid(&self) -> u16 {
+        self.pid
+    }
+}
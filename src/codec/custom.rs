@@ -0,0 +1,80 @@
+/// Length of the body of a custom function, the bytes between the function code and the CRC
+///
+/// A Modbus TCP frame carries its length in its MBAP header, a RTU frame has to be measured.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::codec::BodyLength;
+/// assert_eq!(BodyLength::Fixed(3).of(&[]), Some(3));
+/// // A byte count at offset 2, counting 4 bytes
+/// assert_eq!(BodyLength::ByteCount(2).of(&[0x00, 0x01, 0x04]), Some(7));
+/// assert_eq!(BodyLength::ByteCount(2).of(&[0x00, 0x01]), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BodyLength {
+    /// Always this many bytes
+    Fixed(usize),
+
+    /// A byte count at this offset of the body, the body ending with the bytes it counts
+    ByteCount(usize),
+}
+
+impl BodyLength {
+    /// Length of the body starting with `body`, `None` until it holds the byte count
+    pub fn of(self, body: &[u8]) -> Option<usize> {
+        match self {
+            BodyLength::Fixed(len) => Some(len),
+            BodyLength::ByteCount(offset) => {
+                let bytes_num = *body.get(offset)?;
+                Some(offset + 1 + bytes_num as usize)
+            }
+        }
+    }
+}
+
+/// Rule giving the body length of the function `code`, `None` for a function it doesn't know
+///
+/// Only asked about codes which are neither modeled functions nor registered custom functions.
+pub type BodyLengthFn = fn(code: u8) -> Option<BodyLength>;
+
+/// Custom functions a codec decodes, by code
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CustomFunctions {
+    functions: Vec<(u8, BodyLength)>,
+
+    /// Rule for the codes not registered
+    fallback: Option<BodyLengthFn>,
+}
+
+impl CustomFunctions {
+    pub(crate) fn insert(&mut self, code: u8, length: BodyLength) {
+        self.functions.retain(|&(registered, _)| registered != code);
+        self.functions.push((code, length));
+    }
+
+    pub(crate) fn set_fallback(&mut self, fallback: BodyLengthFn) {
+        self.fallback = Some(fallback);
+    }
+
+    /// Body length of the custom function `code`, registered or given by the fallback rule
+    pub(crate) fn get(&self, code: u8) -> Option<BodyLength> {
+        let registered = self
+            .functions
+            .iter()
+            .find(|&&(registered, _)| registered == code)
+            .map(|&(_, length)| length);
+        match (registered, self.fallback) {
+            (Some(length), _) => Some(length),
+            // Exception codes, and codes of no function at all, are never custom
+            (None, Some(fallback)) if code > 0x00 && code < 0x80 => fallback(code),
+            (None, _) => None,
+        }
+    }
+
+    /// Length of the body of the custom function `code` starting with `body`, see
+    /// [`BodyLength::of`]
+    pub(crate) fn body_length(&self, code: u8, body: &[u8]) -> Option<usize> {
+        self.get(code)?.of(body)
+    }
+}
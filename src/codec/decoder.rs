@@ -3,7 +3,8 @@ use std::io::{Error, ErrorKind::InvalidData, Result};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use tokio_util::codec::Decoder;
 
-use crate::codec::{RtuClientCodec, RtuServerCodec};
+use crate::codec::{trace_frame, CodecConfig, RtuClientCodec, RtuServerCodec};
+use crate::codec::custom::CustomFunctions;
 use crate::frame::{
     Exception,
     Function,
@@ -12,6 +13,7 @@ use crate::frame::{
 use crate::frame::request::*;
 use crate::frame::response::*;
 use crate::util::crc;
+use crate::ModbusError;
 
 use super::{TcpClientCodec, TcpServerCodec};
 
@@ -20,53 +22,86 @@ impl Decoder for RtuClientCodec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Response>> {
-        if src.len() < 2 {
+        if self.skip_leading_nulls {
+            while src.first() == Some(&0x00) && !self.may_start_response(src) {
+                src.advance(1);
+            }
+        }
+        if src.len() < MIN_RTU_FRAME {
             return Ok(None);
         }
 
-        let mut data_bytes = BytesMut::new();
-
-        let head_bytes = src.copy_to_bytes(2);
-        data_bytes.put_slice(&(head_bytes.to_vec()));
-        let mut head = Head::rtu_try_from(head_bytes)?;
-
-        let len: usize = {
-            if head.is_exception {
-                1
-            } else {
-                match head.function {
-                    Function::ReadCoils
-                    | Function::ReadDiscreteInputs
-                    | Function::ReadMultipleHoldingRegisters
-                    | Function::ReadInputRegisters => {
-                        src.get(0).map_or(0, |&bytes_num| bytes_num as usize + 1)
-                    }
-                    Function::WriteSingleCoil
-                    | Function::WriteSingleHoldingRegister
-                    | Function::WriteMultipleCoils
-                    | Function::WriteMultipleHoldingRegisters => 4,
-                }
-            }
+        let mut head = Head::rtu_try_from(&src[..2], &self.custom)?;
+        let len = match self.body_length(&head, src) {
+            Some(len) => len,
+            None => return Ok(None),
         };
 
-        if src.len() < len + 2 {
+        check_frame_length(&self.config, len + MIN_RTU_FRAME)?;
+        if src.len() < len + MIN_RTU_FRAME {
             return Ok(None);
         }
 
         head.body_length(len as u16);
 
-        let body_bytes = src.copy_to_bytes(len);
-        data_bytes.put_slice(&(body_bytes.to_vec()));
-        let response = get_response(body_bytes, head);
-
+        let mut frame = src.split_to(len + 2);
         let crc = src.get_u16();
-        if crc::check(&(data_bytes.to_vec()), crc) {
+        if crc::check(&frame, crc) {
+            let body_bytes = frame.split_off(2).freeze();
+            trace_frame("decode", &head, len + MIN_RTU_FRAME);
+            let response = get_response(body_bytes, head)?;
+            self.observer.decoded_response(&response);
             return Ok(Some(response));
         }
-        return Err(Error::new(
-            InvalidData,
-            format!("Invalid crc code: 0x{:0>2X}", crc),
-        ));
+        self.observer.crc_error();
+        let expected = crc::compute(&frame);
+        Err(ModbusError::Crc { expected, got: crc }.into())
+    }
+}
+
+impl RtuClientCodec {
+    /// Length of the body of the response starting `src` with `head`, `None` until it is
+    /// buffered far enough to tell
+    fn body_length(&self, head: &Head, src: &[u8]) -> Option<usize> {
+        if head.is_exception {
+            return Some(1);
+        }
+        match head.function {
+            Function::ReadCoils
+            | Function::ReadDiscreteInputs
+            | Function::ReadMultipleHoldingRegisters
+            | Function::ReadInputRegisters
+            | Function::ReadWriteMultipleRegisters => {
+                src.get(2).map(|&bytes_num| bytes_num as usize + 1)
+            }
+            Function::WriteSingleCoil
+            | Function::WriteSingleHoldingRegister
+            | Function::WriteMultipleCoils
+            | Function::WriteMultipleHoldingRegisters => Some(4),
+            Function::Custom(code) => self.custom.body_length(code, &src[2..]),
+        }
+    }
+
+    /// Whether `src` may start with a response, its head parsing and its CRC checking once it is
+    /// buffered whole
+    ///
+    /// A broadcast response, of unit 0, may, so only a `0x00` which can't start one is skipped.
+    fn may_start_response(&self, src: &[u8]) -> bool {
+        if src.len() < 2 {
+            return true;
+        }
+        let head = match Head::rtu_try_from(&src[..2], &self.custom) {
+            Ok(head) => head,
+            Err(_) => return false,
+        };
+        let len = match self.body_length(&head, src) {
+            Some(len) => len,
+            None => return true,
+        };
+        match src.get(len + 2..len + MIN_RTU_FRAME) {
+            Some(crc) => crc::check(&src[..len + 2], u16::from_be_bytes([crc[0], crc[1]])),
+            None => true,
+        }
     }
 }
 
@@ -75,14 +110,23 @@ impl Decoder for RtuServerCodec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Request>> {
-        if src.len() < 2 {
+        loop {
+            match self.decode_request(src)? {
+                Some(request) if !self.accepts(request.head().uid) => continue,
+                request => return Ok(request),
+            }
+        }
+    }
+}
+
+impl RtuServerCodec {
+    /// Decode the next request, whichever unit it is addressed to
+    fn decode_request(&mut self, src: &mut BytesMut) -> Result<Option<Request>> {
+        if src.len() < MIN_RTU_FRAME {
             return Ok(None);
         }
 
-        let mut data_bytes = BytesMut::new();
-        let head_bytes = src.copy_to_bytes(2);
-        data_bytes.put_slice(&(head_bytes.to_vec()));
-        let mut head = Head::rtu_try_from(head_bytes)?;
+        let mut head = Head::rtu_try_from(&src[..2], &self.custom)?;
 
         let len: usize = {
             match head.function {
@@ -93,26 +137,39 @@ impl Decoder for RtuServerCodec {
                 | Function::WriteSingleCoil
                 | Function::WriteSingleHoldingRegister => 4,
                 Function::WriteMultipleCoils | Function::WriteMultipleHoldingRegisters => {
-                    src.get(4).map_or(0, |&bytes_num| bytes_num as usize + 5)
+                    match src.get(6) {
+                        Some(&bytes_num) => bytes_num as usize + 5,
+                        None => return Ok(None),
+                    }
                 }
+                Function::ReadWriteMultipleRegisters => match src.get(10) {
+                    Some(&bytes_num) => bytes_num as usize + 9,
+                    None => return Ok(None),
+                },
+                Function::Custom(code) => match self.custom.body_length(code, &src[2..]) {
+                    Some(len) => len,
+                    None => return Ok(None),
+                },
             }
         };
-        if src.len() < len + 2 {
+        check_frame_length(&self.config, len + MIN_RTU_FRAME)?;
+        if src.len() < len + MIN_RTU_FRAME {
             return Ok(None);
         }
 
         head.body_length(len as u16);
-        let body_bytes = src.copy_to_bytes(len);
-        data_bytes.put_slice(&(body_bytes.to_vec()));
-        let request = get_request(body_bytes, head);
+        let mut frame = src.split_to(len + 2);
         let crc = src.get_u16();
-        if crc::check(&(data_bytes.to_vec()), crc) {
+        if crc::check(&frame, crc) {
+            let body_bytes = frame.split_off(2).freeze();
+            trace_frame("decode", &head, len + MIN_RTU_FRAME);
+            let request = get_request(body_bytes, head)?;
+            self.observer.decoded_request(&request);
             return Ok(Some(request));
         }
-        return Err(Error::new(
-            InvalidData,
-            format!("Invalid crc code: 0x{:0>2X}", crc),
-        ));
+        self.observer.crc_error();
+        let expected = crc::compute(&frame);
+        Err(ModbusError::Crc { expected, got: crc }.into())
     }
 }
 
@@ -121,12 +178,24 @@ impl Decoder for TcpClientCodec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Response>> {
-        if src.len() < 4 {
-            return Ok(None);
-        }
-        let head = Head::tcp_try_from(src.copy_to_bytes(8))?;
+        let mut head = match peek_tcp_head(&self.config, &self.custom, src)? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        src.advance(8);
         let len = head.length as usize - 2;
-        let response = get_response(src.copy_to_bytes(len), head);
+        let mut body = src.split_to(len);
+        if self.no_byte_count && response_byte_count_offset(&head).is_some() {
+            body = insert_byte_count(body)?;
+            head.length += 1;
+        }
+        let body = body.freeze();
+        if self.config.strict {
+            check_byte_count(&body, response_byte_count_offset(&head))?;
+        }
+        trace_frame("decode", &head, len + 8);
+        let response = get_response(body, head)?;
+        self.observer.decoded_response(&response);
         Ok(Some(response))
     }
 }
@@ -136,18 +205,242 @@ impl Decoder for TcpServerCodec {
     type Error = Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Request>> {
-        if src.len() < 8 {
-            return Ok(None);
-        }
-        let head = Head::tcp_try_from(src.copy_to_bytes(8))?;
+        let head = match peek_tcp_head(&self.config, &self.custom, src)? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        src.advance(8);
         let len = head.length as usize - 2;
-        let request = get_request(src.copy_to_bytes(len), head);
+        let body = src.split_to(len).freeze();
+        if self.config.strict {
+            check_byte_count(&body, request_byte_count_offset(&head))?;
+        }
+        trace_frame("decode", &head, len + 8);
+        let request = get_request(body, head)?;
+        self.observer.decoded_request(&request);
         Ok(Some(request))
     }
 }
 
-fn get_request(src: Bytes, head: Head) -> Request {
+impl RtuClientCodec {
+    /// Decode a read response whose payload size is known at compile time
+    ///
+    /// The register or coil bytes are copied into a stack array. An exception response, a
+    /// response of another function or a payload of a different size is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use easy_modbus::codec::RtuClientCodec;
+    /// let mut codec = RtuClientCodec::default();
+    /// let mut src = BytesMut::from(&[0x0B, 0x04, 0x02, 0x10, 0x2F, 0x6D, 0x2D][..]);
+    /// let values = codec.decode_into::<2>(&mut src).unwrap();
+    /// assert_eq!(values, Some([0x10, 0x2F]));
+    /// ```
+    pub fn decode_into<const N: usize>(&mut self, src: &mut BytesMut) -> Result<Option<[u8; N]>> {
+        match self.decode(src)? {
+            Some(response) => Ok(Some(values_into(response)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl TcpClientCodec {
+    /// Decode a read response whose payload size is known at compile time
+    ///
+    /// The register or coil bytes are copied into a stack array. An exception response, a
+    /// response of another function or a payload of a different size is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use easy_modbus::codec::TcpClientCodec;
+    /// let mut codec = TcpClientCodec::default();
+    /// let mut src = BytesMut::from(
+    ///     &[0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x04, 0x02, 0x10, 0x2F][..],
+    /// );
+    /// let values = codec.decode_into::<2>(&mut src).unwrap();
+    /// assert_eq!(values, Some([0x10, 0x2F]));
+    /// ```
+    pub fn decode_into<const N: usize>(&mut self, src: &mut BytesMut) -> Result<Option<[u8; N]>> {
+        match self.decode(src)? {
+            Some(response) => Ok(Some(values_into(response)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+fn values_into<const N: usize>(response: Response) -> Result<[u8; N]> {
+    let values = match &response {
+        Response::ReadCoils(_, body) => body.get_values(),
+        Response::ReadDiscreteInputs(_, body) => body.get_values(),
+        Response::ReadMultipleHoldingRegisters(_, body) => body.get_values(),
+        Response::ReadInputRegisters(_, body) => body.get_values(),
+        Response::ReadWriteMultipleRegisters(_, body) => body.get_values(),
+        Response::Exception(_, body) => {
+            let exception = body.get_exception();
+            return Err(Error::new(
+                exception.as_error_kind(),
+                format!("Exception response: {:?}", exception),
+            ));
+        }
+        _ => {
+            return Err(Error::new(
+                InvalidData,
+                "Response does not carry read values",
+            ));
+        }
+    };
+    values[..].try_into().map_err(|_| {
+        Error::new(
+            InvalidData,
+            format!("Expected {} value bytes, got {}", N, values.len()),
+        )
+    })
+}
+
+/// Length of the shortest RTU frame, a unit id, a function code and the CRC
+///
+/// Every function adds its body, the RTU decoders wait for `body + MIN_RTU_FRAME` bytes
+/// before reading the CRC.
+const MIN_RTU_FRAME: usize = 4;
+
+/// Reject frames longer than the configured limit
+fn check_frame_length(config: &CodecConfig, len: usize) -> Result<()> {
+    if len > config.max_frame {
+        return Err(Error::new(
+            InvalidData,
+            format!("Frame length {} exceeds limit {}", len, config.max_frame),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse the head of a TCP frame, once the whole frame is buffered
+///
+/// Nothing is consumed, so a partial frame is decoded again when more bytes arrive.
+fn peek_tcp_head(
+    config: &CodecConfig,
+    custom: &CustomFunctions,
+    src: &BytesMut,
+) -> Result<Option<Head>> {
+    if src.len() < 8 {
+        return Ok(None);
+    }
+    let head = Head::tcp_try_from(&src[..8], custom)?;
+    check_tcp_head(config, &head)?;
+    if src.len() < head.length as usize + 6 {
+        return Ok(None);
+    }
+    Ok(Some(head))
+}
+
+fn check_tcp_head(config: &CodecConfig, head: &Head) -> Result<()> {
+    if head.length < 2 {
+        return Err(Error::new(
+            InvalidData,
+            format!("Invalid length: {}", head.length),
+        ));
+    }
+    check_frame_length(config, head.length as usize + 6)?;
+    if config.strict && head.pid != config.pid {
+        return Err(Error::new(
+            InvalidData,
+            format!("Invalid protocol identifier: 0x{:0>4X}", head.pid),
+        ));
+    }
+    Ok(())
+}
+
+/// Put the byte count a conformant read response would carry in front of `values`
+fn insert_byte_count(values: BytesMut) -> Result<BytesMut> {
+    let bytes_num = u8::try_from(values.len()).map_err(|_| {
+        Error::new(
+            InvalidData,
+            format!("Too many values for a byte count: {}", values.len()),
+        )
+    })?;
+    let mut body = BytesMut::with_capacity(values.len() + 1);
+    body.put_u8(bytes_num);
+    body.extend_from_slice(&values);
+    Ok(body)
+}
+
+/// Check the byte count at `offset` against the number of bytes following it
+fn check_byte_count(body: &[u8], offset: Option<usize>) -> Result<()> {
+    if let Some(offset) = offset {
+        let expected = body.get(offset).map(|&bytes_num| offset + 1 + bytes_num as usize);
+        if expected != Some(body.len()) {
+            return Err(Error::new(
+                InvalidData,
+                format!("Byte count does not match body length {}", body.len()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn request_byte_count_offset(head: &Head) -> Option<usize> {
+    match head.function {
+        Function::WriteMultipleCoils | Function::WriteMultipleHoldingRegisters => Some(4),
+        Function::ReadWriteMultipleRegisters => Some(8),
+        _ => None,
+    }
+}
+
+fn response_byte_count_offset(head: &Head) -> Option<usize> {
+    if head.is_exception {
+        return None;
+    }
+    match head.function {
+        Function::ReadCoils
+        | Function::ReadDiscreteInputs
+        | Function::ReadMultipleHoldingRegisters
+        | Function::ReadInputRegisters
+        | Function::ReadWriteMultipleRegisters => Some(0),
+        _ => None,
+    }
+}
+
+/// Reject bodies too short for the fields of their function
+fn check_body_length(body: &[u8], min: usize) -> Result<()> {
+    if body.len() < min {
+        return Err(ModbusError::Incomplete.into());
+    }
+    Ok(())
+}
+
+/// Length of the fixed fields of a request body
+fn request_body_length(head: &Head) -> usize {
+    match head.function {
+        Function::WriteMultipleCoils | Function::WriteMultipleHoldingRegisters => 5,
+        Function::ReadWriteMultipleRegisters => 9,
+        Function::Custom(_) => 0,
+        _ => 4,
+    }
+}
+
+/// Length of the fixed fields of a response body
+fn response_body_length(head: &Head) -> usize {
+    if head.is_exception {
+        return 1;
+    }
     match head.function {
+        Function::ReadCoils
+        | Function::ReadDiscreteInputs
+        | Function::ReadMultipleHoldingRegisters
+        | Function::ReadInputRegisters
+        | Function::ReadWriteMultipleRegisters => 1,
+        Function::Custom(_) => 0,
+        _ => 4,
+    }
+}
+
+fn get_request(src: Bytes, head: Head) -> Result<Request> {
+    check_body_length(&src, request_body_length(&head))?;
+    let request = match head.function {
         Function::ReadCoils => Request::ReadCoils(head, ReadCoilsRequest::from(src)),
         Function::ReadDiscreteInputs => {
             Request::ReadDiscreteInputs(head, ReadDiscreteInputsRequest::from(src))
@@ -172,15 +465,21 @@ fn get_request(src: Bytes, head: Head) -> Request {
             head,
             WriteMultipleHoldingRegistersRequest::from(src),
         ),
-    }
+        Function::ReadWriteMultipleRegisters => {
+            Request::ReadWriteMultipleRegisters(head, ReadWriteMultipleRegistersRequest::from(src))
+        }
+        Function::Custom(_) => Request::Custom(head, CustomRequest { data: src }),
+    };
+    Ok(request)
 }
 
-fn get_response(src: Bytes, head: Head) -> Response {
+fn get_response(src: Bytes, head: Head) -> Result<Response> {
+    check_body_length(&src, response_body_length(&head))?;
     if head.is_exception {
-        return Response::Exception(head, ExceptionResponse::from(src));
+        return Ok(Response::Exception(head, ExceptionResponse::try_from(src)?));
     }
 
-    match head.function {
+    let response = match head.function {
         Function::ReadCoils => Response::ReadCoils(head, ReadCoilsResponse::from(src)),
         Function::ReadDiscreteInputs => {
             Response::ReadDiscreteInputs(head, ReadDiscreteInputsResponse::from(src))
@@ -206,7 +505,13 @@ fn get_response(src: Bytes, head: Head) -> Response {
             head,
             WriteMultipleHoldingRegistersResponse::from(src),
         ),
-    }
+        Function::ReadWriteMultipleRegisters => Response::ReadWriteMultipleRegisters(
+            head,
+            ReadWriteMultipleRegistersResponse::from(src),
+        ),
+        Function::Custom(_) => Response::Custom(head, CustomResponse { data: src }),
+    };
+    Ok(response)
 }
 
 impl From<Bytes> for ReadCoilsRequest {
@@ -269,7 +574,7 @@ impl From<Bytes> for WriteMultipleCoilsRequest {
             first_address: buf.get_u16(),
             coils_number: buf.get_u16(),
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
         }
     }
 }
@@ -280,7 +585,20 @@ impl From<Bytes> for WriteMultipleHoldingRegistersRequest {
             first_address: buf.get_u16(),
             registers_number: buf.get_u16(),
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
+        }
+    }
+}
+
+impl From<Bytes> for ReadWriteMultipleRegistersRequest {
+    fn from(mut buf: Bytes) -> Self {
+        ReadWriteMultipleRegistersRequest {
+            read_first_address: buf.get_u16(),
+            read_registers_number: buf.get_u16(),
+            write_first_address: buf.get_u16(),
+            write_registers_number: buf.get_u16(),
+            bytes_number: buf.get_u8(),
+            values: buf,
         }
     }
 }
@@ -289,7 +607,7 @@ impl From<Bytes> for ReadCoilsResponse {
     fn from(mut buf: Bytes) -> Self {
         ReadCoilsResponse {
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
         }
     }
 }
@@ -298,7 +616,7 @@ impl From<Bytes> for ReadDiscreteInputsResponse {
     fn from(mut buf: Bytes) -> Self {
         ReadDiscreteInputsResponse {
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
         }
     }
 }
@@ -307,7 +625,7 @@ impl From<Bytes> for ReadMultipleHoldingRegistersResponse {
     fn from(mut buf: Bytes) -> Self {
         ReadMultipleHoldingRegistersResponse {
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
         }
     }
 }
@@ -316,7 +634,7 @@ impl From<Bytes> for ReadInputRegistersResponse {
     fn from(mut buf: Bytes) -> Self {
         ReadInputRegistersResponse {
             bytes_number: buf.get_u8(),
-            values: buf.to_vec(),
+            values: buf,
         }
     }
 }
@@ -357,21 +675,33 @@ impl From<Bytes> for WriteMultipleHoldingRegistersResponse {
     }
 }
 
-impl From<Bytes> for ExceptionResponse {
+impl From<Bytes> for ReadWriteMultipleRegistersResponse {
     fn from(mut buf: Bytes) -> Self {
-        ExceptionResponse {
-            exception: Exception::try_from(buf.get_u8()).unwrap(),
+        ReadWriteMultipleRegistersResponse {
+            bytes_number: buf.get_u8(),
+            values: buf,
         }
     }
 }
 
+impl TryFrom<Bytes> for ExceptionResponse {
+    type Error = Error;
+
+    fn try_from(buf: Bytes) -> Result<Self> {
+        let code = buf.first().copied().ok_or(ModbusError::Incomplete)?;
+        Ok(ExceptionResponse {
+            exception: Exception::try_from(code)?,
+        })
+    }
+}
+
 impl Head {
-    fn tcp_try_from(mut buf: Bytes) -> Result<Self> {
+    fn tcp_try_from(mut buf: &[u8], custom: &CustomFunctions) -> Result<Self> {
         let tid = buf.get_u16();
         let pid = buf.get_u16();
         let length = buf.get_u16();
         let uid = buf.get_u8();
-        let (function, is_exception) = get_function(buf.get_u8())?;
+        let (function, is_exception) = get_function(buf.get_u8(), custom)?;
         Ok(Head {
             tid,
             pid,
@@ -383,9 +713,9 @@ impl Head {
         })
     }
 
-    fn rtu_try_from(mut buf: Bytes) -> Result<Self> {
+    fn rtu_try_from(mut buf: &[u8], custom: &CustomFunctions) -> Result<Self> {
         let uid = buf.get_u8();
-        let (function, is_exception) = get_function(buf.get_u8())?;
+        let (function, is_exception) = get_function(buf.get_u8(), custom)?;
         Ok(Head {
             tid: 0,
             pid: 0,
@@ -403,12 +733,7 @@ impl TryFrom<u8> for Exception {
 
     fn try_from(value: u8) -> Result<Self> {
         match Exception::from_code(value) {
-            None => {
-                return Err(Error::new(
-                    InvalidData,
-                    format!("Invalid Exception code: 0x{:0>2X}", value),
-                ));
-            }
+            None => Err(ModbusError::UnknownException(value).into()),
             Some(exception) => Ok(exception),
         }
     }
@@ -417,45 +742,99 @@ impl TryFrom<u8> for Exception {
 impl TryFrom<u8> for Function {
     type Error = Error;
     fn try_from(value: u8) -> Result<Self> {
-        let func = match value {
-            0x01 => Function::ReadCoils,
-            0x02 => Function::ReadDiscreteInputs,
-            0x03 => Function::ReadMultipleHoldingRegisters,
-            0x04 => Function::ReadInputRegisters,
-            0x05 => Function::WriteSingleCoil,
-            0x06 => Function::WriteSingleHoldingRegister,
-            0x0F => Function::WriteMultipleCoils,
-            0x10 => Function::WriteMultipleHoldingRegisters,
-            _ => {
-                return Err(Error::new(
-                    Exception::IllegalFunction.as_error_kind(),
-                    format!("Invalid function code: 0x{:0>2X}", value),
-                ));
-            }
-        };
-        Ok(func)
+        match Function::from_code(value) {
+            None => Err(ModbusError::UnknownFunction(value).into()),
+            Some(function) => Ok(function),
+        }
     }
 }
 
-fn get_function(function_code: u8) -> Result<(Function, bool)> {
-    let function: Function;
+fn get_function(function_code: u8, custom: &CustomFunctions) -> Result<(Function, bool)> {
+    let mut code = function_code;
     let mut is_exception = false;
-    if function_code <= 0x80 {
-        function = Function::try_from(function_code)?;
-    } else {
-        function = Function::try_from(function_code - 0x80)?;
+    if function_code > 0x80 {
+        code = function_code - 0x80;
         is_exception = true;
     }
+    let function = match Function::try_from(code) {
+        Err(_) if custom.get(code).is_some() => Function::Custom(code),
+        function => function?,
+    };
     Ok((function, is_exception))
 }
 
+/// Decode a TCP request from the front of `src`
+///
+/// A free function for fuzzing and one-off decoding, the same as
+/// [`TcpServerCodec::default()`](TcpServerCodec) decoding a frame. Malformed input is an
+/// error, never a panic. `Ok(None)` means more bytes are needed.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use easy_modbus::codec::decode_tcp_request;
+/// let mut src = BytesMut::from(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x0B, 0x01][..]);
+/// assert!(decode_tcp_request(&mut src).unwrap().is_none());
+/// src.extend_from_slice(&[0x00, 0x1D, 0x00, 0x1F]);
+/// assert!(decode_tcp_request(&mut src).unwrap().is_some());
+/// ```
+pub fn decode_tcp_request(src: &mut BytesMut) -> Result<Option<Request>> {
+    TcpServerCodec::default().decode(src)
+}
+
+/// Decode a TCP response from the front of `src`
+///
+/// The same as [`TcpClientCodec::default()`](TcpClientCodec) decoding a frame, see
+/// [`decode_tcp_request`].
+pub fn decode_tcp_response(src: &mut BytesMut) -> Result<Option<Response>> {
+    TcpClientCodec::default().decode(src)
+}
+
+/// Decode a TCP response from the front of `buf`, with the number of bytes it took
+///
+/// For datagram transports, where a whole frame is received at once and no `Framed` buffers
+/// it. The bytes after the frame are left alone, see [`decode_tcp_response`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::codec::decode_one_tcp_response;
+/// let datagram = [0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x0B, 0x01, 0x01, 0x05, 0xFF];
+/// let (response, consumed) = decode_one_tcp_response(&datagram).unwrap().unwrap();
+/// assert_eq!(consumed, 10);
+/// assert_eq!(response.head().get_uid(), 0x0B);
+/// ```
+pub fn decode_one_tcp_response(buf: &[u8]) -> Result<Option<(Response, usize)>> {
+    let mut src = BytesMut::from(buf);
+    let response = decode_tcp_response(&mut src)?;
+    Ok(response.map(|response| (response, buf.len() - src.len())))
+}
+
+/// Decode a RTU request from the front of `src`
+///
+/// The same as [`RtuServerCodec::default()`](RtuServerCodec) decoding a frame, see
+/// [`decode_tcp_request`].
+pub fn decode_rtu_request(src: &mut BytesMut) -> Result<Option<Request>> {
+    RtuServerCodec::default().decode(src)
+}
+
+/// Decode a RTU response from the front of `src`
+///
+/// The same as [`RtuClientCodec::default()`](RtuClientCodec) decoding a frame, see
+/// [`decode_tcp_request`].
+pub fn decode_rtu_response(src: &mut BytesMut) -> Result<Option<Response>> {
+    RtuClientCodec::default().decode(src)
+}
+
 #[cfg(test)]
 mod rtu_client_decoder_test {
     use bytes::BytesMut;
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
-    use crate::codec::RtuClientCodec;
+    use crate::codec::{BodyLength, CodecConfig, RtuClientCodec, RtuServerCodec};
     use crate::frame::{Exception, Function};
+    use crate::limits::MAX_RTU_ADU_LEN;
     use crate::Frame;
 
     #[test]
@@ -469,6 +848,23 @@ mod rtu_client_decoder_test {
         assert_eq!(response_l, response_r);
     }
 
+    #[test]
+    fn short_frame_test() {
+        let mut codec = RtuClientCodec::default()
+            .with_custom_function(0x41, BodyLength::Fixed(0));
+        for v in [&[0x0B, 0x41, 0x00][..], &[0x0B, 0x81, 0x02], &[0x0B, 0x99, 0x00]] {
+            let mut buf = BytesMut::from(v);
+            assert!(codec.decode(&mut buf).unwrap().is_none());
+            assert_eq!(&buf[..], v);
+        }
+
+        let response_r = Frame::rtu().custom_response(0x0B, 0x41, vec![]);
+        let mut buf = BytesMut::new();
+        RtuServerCodec::default().encode(response_r.clone(), &mut buf).unwrap();
+        assert_eq!(buf.len(), 4);
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), response_r);
+    }
+
     #[test]
     fn read_discrete_inputs_response_test() {
         let mut codec = RtuClientCodec::default();
@@ -560,16 +956,198 @@ mod rtu_client_decoder_test {
             frame.exception_response(0x0A, Function::ReadCoils, Exception::IllegalDataAddress);
         assert_eq!(response_l, response_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_response_test() {
+        let mut codec = RtuClientCodec::default();
+        let v: Vec<u8> = vec![
+            0x11, 0x17, 0x0C, 0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01, 0x00, 0x03, 0x00, 0x0D, 0x00,
+            0xFF, 0x0D, 0x75,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        let frame = Frame::rtu();
+        let response_r = frame.read_write_multiple_registers_response(
+            0x11,
+            vec![0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01, 0x00, 0x03, 0x00, 0x0D, 0x00, 0xFF],
+        );
+        assert_eq!(response_l, response_r);
+    }
+
+    #[test]
+    fn max_frame_test() {
+        let mut codec = RtuClientCodec::default().max_frame(8);
+        let v: Vec<u8> = vec![0x0B, 0x04, 0x02, 0x10, 0x2F, 0x6D, 0x2D];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let mut codec = RtuClientCodec::default().max_frame(6);
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).is_err());
+
+        let codec = RtuClientCodec::default();
+        assert_eq!(codec.get_config().get_max_frame(), MAX_RTU_ADU_LEN);
+        let codec = RtuClientCodec::new(CodecConfig::default().strict(true));
+        assert_eq!(codec.get_config().get_max_frame(), MAX_RTU_ADU_LEN);
+        assert!(codec.get_config().is_strict());
+        let codec = RtuClientCodec::new(CodecConfig::default().max_frame(300));
+        assert_eq!(codec.get_config().get_max_frame(), 300);
+    }
+
+    #[test]
+    fn decode_into_test() {
+        let mut codec = RtuClientCodec::default();
+        let v: Vec<u8> = vec![0x0B, 0x03, 0x04, 0x02, 0x2B, 0x00, 0x64, 0x20, 0x68];
+        let mut buf = BytesMut::from(&v[..]);
+        let values = codec.decode_into::<4>(&mut buf).unwrap();
+        assert_eq!(values, Some([0x02, 0x2B, 0x00, 0x64]));
+
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode_into::<2>(&mut buf).is_err());
+
+        let v: Vec<u8> = vec![0x0A, 0x81, 0x02, 0xB0, 0x53];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode_into::<4>(&mut buf).is_err());
+    }
+
+    #[test]
+    fn skip_leading_nulls_test() {
+        let v: Vec<u8> = vec![0x00, 0x00, 0x0B, 0x05, 0x00, 0xBF, 0x00, 0x00, 0xFC, 0x84];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(RtuClientCodec::default().decode(&mut buf).is_err());
+
+        let mut codec = RtuClientCodec::default().skip_leading_nulls();
+        assert!(codec.skips_leading_nulls());
+        let mut buf = BytesMut::from(&v[..1]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&v[1..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        let response_r = Frame::rtu().write_single_coil_response(0x0B, 0x00BF, 0x0000);
+        assert_eq!(response_l, response_r);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn skip_leading_nulls_broadcast_test() {
+        let frame = Frame::rtu();
+        let mut codec = RtuClientCodec::default().skip_leading_nulls();
+        let mut server = RtuServerCodec::default();
+
+        // A frame of unit 0 is kept
+        let response = frame.write_single_coil_response(0x00, 0x00BF, 0xFF00);
+        let mut buf = BytesMut::new();
+        server.encode(response.clone(), &mut buf).unwrap();
+        assert_eq!(buf[0], 0x00);
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), response);
+
+        // A null in front of unit 1, whose id is also the code of read coils
+        let response = frame.read_holding_register_response(0x01, vec![0x00, 0x2A]);
+        let mut buf = BytesMut::from(&[0x00][..]);
+        server.encode(response.clone(), &mut buf).unwrap();
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), response);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn custom_response_test() {
+        let frame = Frame::rtu();
+        let response_r = frame.custom_response(0x0B, 0x41, vec![0x02, 0xAA, 0xBB]);
+        let mut v = BytesMut::new();
+        RtuServerCodec::default().encode(response_r.clone(), &mut v).unwrap();
+        assert_eq!(&v[..5], &[0x0B, 0x41, 0x02, 0xAA, 0xBB]);
+
+        let mut codec = RtuClientCodec::default()
+            .with_custom_function(0x41, BodyLength::ByteCount(0));
+        let mut buf = BytesMut::from(&v[..2]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&v[2..6]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&v[6..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(response_l, response_r);
+        assert!(buf.is_empty());
+
+        let exception =
+            frame.exception_response(0x0B, Function::Custom(0x41), Exception::SlaveDeviceBusy);
+        let mut buf = BytesMut::new();
+        RtuServerCodec::default().encode(exception.clone(), &mut buf).unwrap();
+        assert_eq!(buf[1], 0xC1);
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), exception);
+
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(RtuClientCodec::default().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn body_lengths_test() {
+        let frame = Frame::rtu();
+        let response_r = frame.custom_response(0x0B, 0x65, vec![0x01, 0x02, 0x03, 0x04]);
+        let mut v = BytesMut::new();
+        RtuServerCodec::default().encode(response_r.clone(), &mut v).unwrap();
+
+        let mut codec = RtuClientCodec::default()
+            .with_custom_function(0x41, BodyLength::ByteCount(0))
+            .with_body_lengths(|code| match code {
+                0x41 | 0x65 => Some(BodyLength::Fixed(4)),
+                _ => None,
+            });
+        let mut buf = BytesMut::from(&v[..5]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(&v[5..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(response_l, response_r);
+        assert!(buf.is_empty());
+
+        // The registered length wins over the rule
+        let response_r = frame.custom_response(0x0B, 0x41, vec![0x02, 0xAA, 0xBB]);
+        let mut buf = BytesMut::new();
+        RtuServerCodec::default().encode(response_r.clone(), &mut buf).unwrap();
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), response_r);
+
+        // Codes the rule doesn't know stay unsupported
+        let response_r = frame.custom_response(0x0B, 0x66, vec![0x01, 0x02, 0x03, 0x04]);
+        let mut buf = BytesMut::new();
+        RtuServerCodec::default().encode(response_r, &mut buf).unwrap();
+        assert!(codec.decode(&mut buf).is_err());
+    }
 }
 
 #[cfg(test)]
 mod tcp_client_decoder_test {
     use bytes::BytesMut;
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
-    use crate::{codec::TcpClientCodec, Frame};
+    use crate::{codec::TcpClientCodec, Frame, Response};
+    use crate::codec::{decode_one_tcp_response, TcpServerCodec, MAX_FRAME};
     use crate::frame::{Exception, Function};
 
+    #[test]
+    fn bools_round_trip_test() {
+        let mut server = TcpServerCodec::default();
+        let mut client = TcpClientCodec::default();
+        let frame = Frame::tcp();
+        for len in [1usize, 7, 8, 9, 19, 2000] {
+            let bits: Vec<bool> = (0..len).map(|i| i % 3 == 0 || i % 7 == 1).collect();
+            let mut buf = BytesMut::new();
+            let response = frame.read_coils_response_from_bools(0x01, &bits);
+            server.encode(response, &mut buf).unwrap();
+            let response = frame.read_discrete_response_from_bools(0x01, &bits);
+            server.encode(response, &mut buf).unwrap();
+
+            match client.decode(&mut buf).unwrap().unwrap() {
+                Response::ReadCoils(_, body) => {
+                    assert_eq!(*body.get_bytes_number() as usize, len.div_ceil(8));
+                    assert_eq!(body.coils(len), bits);
+                }
+                response => panic!("unexpected response {:?}", response),
+            }
+            match client.decode(&mut buf).unwrap().unwrap() {
+                Response::ReadDiscreteInputs(_, body) => assert_eq!(body.inputs(len), bits),
+                response => panic!("unexpected response {:?}", response),
+            }
+        }
+    }
+
     #[test]
     fn read_coils_response_test() {
         let mut codec = TcpClientCodec::default();
@@ -583,6 +1161,34 @@ mod tcp_client_decoder_test {
         assert_eq!(response_l, response_r);
     }
 
+    #[test]
+    fn decode_one_test() {
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x01, 0x02, 0x00, 0x01, 0xDE, 0xAD, 0xBE,
+        ];
+        let (response, consumed) = decode_one_tcp_response(&v).unwrap().unwrap();
+        assert_eq!(consumed, 11);
+        let frame = Frame::tcp();
+        assert_eq!(response, frame.read_coils_response(0x01, vec![0x00, 0x01]));
+        assert!(decode_one_tcp_response(&v[..8]).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn datagram_test() {
+        let client = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let mut buf = BytesMut::new();
+        let response = Frame::tcp().read_coils_response(0x01, vec![0x00, 0x01]);
+        TcpServerCodec::default().encode(response.clone(), &mut buf).unwrap();
+        server.send_to(&buf, client.local_addr().unwrap()).await.unwrap();
+
+        let mut datagram = [0u8; MAX_FRAME];
+        let len = client.recv(&mut datagram).await.unwrap();
+        let (decoded, consumed) = decode_one_tcp_response(&datagram[..len]).unwrap().unwrap();
+        assert_eq!(decoded, response);
+        assert_eq!(consumed, len);
+    }
+
     #[test]
     fn read_discrete_inputs_response_test() {
         let mut codec = TcpClientCodec::default();
@@ -687,16 +1293,135 @@ mod tcp_client_decoder_test {
             frame.exception_response(0x0A, Function::ReadCoils, Exception::IllegalDataAddress);
         assert_eq!(response_l, response_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_response_test() {
+        let mut codec = TcpClientCodec::default();
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x09, 0x11, 0x17, 0x06, 0x00, 0xFE, 0x0A, 0xCD, 0x00,
+            0x01,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        let frame = Frame::tcp();
+        let response_r = frame
+            .read_write_multiple_registers_response(0x11, vec![0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01]);
+        assert_eq!(response_l, response_r);
+    }
+
+    #[test]
+    fn quirk_no_byte_count_test() {
+        let mut codec = TcpClientCodec::default().quirk_no_byte_count().strict();
+        assert!(codec.is_quirk_no_byte_count());
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x08, 0x01, 0x03, 0x02, 0x2B, 0x00, 0x00, 0x00, 0x64,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        let values = vec![0x02, 0x2B, 0x00, 0x00, 0x00, 0x64];
+        let response_r = Frame::tcp().read_holding_register_response(0x01, values);
+        assert_eq!(response_l, response_r);
+
+        // Responses without a byte count are decoded as usual
+        let v: Vec<u8> = vec![
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x06, 0x01, 0x06, 0x00, 0x01, 0x00, 0x03,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let response_l = codec.decode(&mut buf).unwrap().unwrap();
+        let frame = Frame::tcp();
+        frame.write_single_holding_register_response(0x01, 0x0001, 0x0003);
+        let response_r = frame.write_single_holding_register_response(0x01, 0x0001, 0x0003);
+        assert_eq!(response_l, response_r);
+
+        let v: Vec<u8> = vec![0x00, 0x03, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x02];
+        let mut buf = BytesMut::from(&v[..]);
+        let response = codec.decode(&mut buf).unwrap().unwrap();
+        assert!(matches!(response, Response::Exception(..)));
+    }
+
+    #[test]
+    fn decode_into_test() {
+        let mut codec = TcpClientCodec::default();
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x02, 0x2B, 0x00, 0x64,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let values = codec.decode_into::<4>(&mut buf).unwrap();
+        assert_eq!(values, Some([0x02, 0x2B, 0x00, 0x64]));
+
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode_into::<6>(&mut buf).is_err());
+
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x05, 0x00, 0xBF, 0x00, 0x00,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode_into::<4>(&mut buf).is_err());
+    }
+
 }
 
 #[cfg(test)]
 mod rtu_server_decoder_test {
     use bytes::BytesMut;
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
-    use crate::codec::RtuServerCodec;
+    use crate::codec::{RtuClientCodec, RtuServerCodec};
     use crate::frame::Frame;
 
+    #[test]
+    fn short_frame_test() {
+        let mut codec = RtuServerCodec::default();
+        let v = [0x0B, 0x05, 0x00];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(&buf[..], &v[..]);
+    }
+
+    #[test]
+    fn for_unit_test() {
+        let frame = Frame::rtu();
+        let requests = [
+            frame.read_coils_request(0x0A, 0x0000, 0x0001),
+            frame.read_coils_request(0x0B, 0x0000, 0x0002),
+            frame.write_single_coil_request(0x00, 0x0001, 0xFF00),
+            frame.read_coils_request(0x0C, 0x0000, 0x0003),
+        ];
+        let mut buf = BytesMut::new();
+        for request in requests.iter().cloned() {
+            RtuClientCodec::default().encode(request, &mut buf).unwrap();
+        }
+
+        let mut codec = RtuServerCodec::for_unit(0x0B);
+        let request = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(request, requests[1]);
+        assert!(!request.is_broadcast());
+        let request = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(request, requests[2]);
+        assert!(request.is_broadcast());
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn for_units_test() {
+        let frame = Frame::rtu();
+        let mut buf = BytesMut::new();
+        for unit_id in [0x0A, 0x0B, 0x0C] {
+            let request = frame.read_coils_request(unit_id, 0x0000, 0x0001);
+            RtuClientCodec::default().encode(request, &mut buf).unwrap();
+        }
+
+        let mut codec = RtuServerCodec::for_units([0x0A, 0x0C]);
+        assert_eq!(codec.get_units(), Some(&[0x0A, 0x0C][..]));
+        let mut units = Vec::new();
+        while let Some(request) = codec.decode(&mut buf).unwrap() {
+            units.push(request.head().uid);
+        }
+        assert_eq!(units, vec![0x0A, 0x0C]);
+        assert_eq!(RtuServerCodec::default().get_units(), None);
+    }
+
     #[test]
     fn read_coils_request_test() {
         let mut codec = RtuServerCodec::default();
@@ -793,15 +1518,36 @@ mod rtu_server_decoder_test {
         );
         assert_eq!(request_l, request_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_test() {
+        let mut codec = RtuServerCodec::default();
+        let v: Vec<u8> = vec![
+            0x11, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x03, 0x06, 0x00, 0xFF, 0x00,
+            0xFF, 0x00, 0xFF, 0x4B, 0x54,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let request_l = codec.decode(&mut buf).unwrap().unwrap();
+        let frame = Frame::rtu();
+        let request_r = frame.read_write_multiple_registers_request(
+            0x11,
+            0x0003,
+            0x0006,
+            0x000E,
+            vec![0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+        );
+        assert_eq!(request_l, request_r);
+    }
 }
 
 #[cfg(test)]
 mod tcp_server_decoder_test {
     use bytes::BytesMut;
-    use tokio_util::codec::Decoder;
+    use tokio_util::codec::{Decoder, Encoder};
 
-    use crate::codec::TcpServerCodec;
+    use crate::codec::{BodyLength, TcpClientCodec, TcpServerCodec};
     use crate::frame::Frame;
+    use crate::Request;
 
     #[test]
     fn read_coils_request_test() {
@@ -913,4 +1659,258 @@ mod tcp_server_decoder_test {
         );
         assert_eq!(request_l, request_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_test() {
+        let mut codec = TcpServerCodec::default();
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x11, 0x11, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E,
+            0x00, 0x03, 0x06, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        let request_l = codec.decode(&mut buf).unwrap().unwrap();
+        let frame = Frame::tcp();
+        let request_r = frame.read_write_multiple_registers_request(
+            0x11,
+            0x0003,
+            0x0006,
+            0x000E,
+            vec![0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+        );
+        assert_eq!(request_l, request_r);
+    }
+
+    #[test]
+    fn strict_test() {
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x01, 0x00, 0x06, 0x01, 0x01, 0x00, 0x02, 0x00, 0x08,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(TcpServerCodec::default().decode(&mut buf).unwrap().is_some());
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(TcpServerCodec::default().strict().decode(&mut buf).is_err());
+
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x0B, 0x01, 0x10, 0x00, 0x12, 0x00, 0x02, 0x05, 0x0B,
+            0x0A, 0xC1, 0x02,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(TcpServerCodec::default().strict().decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn pid_test() {
+        let mut client = TcpClientCodec::default().with_pid(0x1234).strict();
+        let mut server = TcpServerCodec::default().with_pid(0x1234).strict();
+
+        let request = Frame::tcp().read_coils_request(0x01, 0x0002, 0x0008);
+        let mut buf = BytesMut::new();
+        client.encode(request.clone(), &mut buf).unwrap();
+        assert_eq!(&buf[2..4], &[0x12, 0x34]);
+        let mut copy = buf.clone();
+        assert!(TcpServerCodec::default().strict().decode(&mut copy).is_err());
+        let request_l = server.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(request_l.head().tid, request.head().tid);
+        assert_eq!(request_l.head().pid, 0x1234);
+
+        let response = Frame::tcp().read_coils_response(0x01, vec![0xA5]);
+        server.encode(response, &mut buf).unwrap();
+        assert_eq!(&buf[2..4], &[0x12, 0x34]);
+        let response = client.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(response.head().pid, 0x1234);
+    }
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn pid_echo_test() {
+        use crate::server::{dispatch, DataStore};
+
+        // A lenient server echoes the identifier of the request, whatever its own
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x12, 0x34, 0x00, 0x06, 0x01, 0x01, 0x00, 0x00, 0x00, 0x08,
+        ];
+        let mut server = TcpServerCodec::default();
+        let mut buf = BytesMut::from(&v[..]);
+        let request = server.decode(&mut buf).unwrap().unwrap();
+        let store = DataStore::default().with_coils(0x0000, 8);
+        let response = dispatch(&store, &request).unwrap();
+        assert_eq!(response.head().pid, 0x1234);
+        server.encode(response, &mut buf).unwrap();
+        assert_eq!(&buf[2..4], &[0x12, 0x34]);
+    }
+
+    #[test]
+    fn pid_rtu_frame_test() {
+        // A RTU request keeps its CRC through a TCP codec configured with an identifier
+        let request = Frame::rtu().read_coils_request(0x0B, 0x1234, 0x0008);
+        let mut buf = BytesMut::new();
+        TcpClientCodec::default().with_pid(0x5678).encode(request, &mut buf).unwrap();
+        assert_eq!(&buf[..], &[0x0B, 0x01, 0x12, 0x34, 0x00, 0x08, 0x79, 0xD0]);
+    }
+
+    #[test]
+    fn max_frame_test() {
+        let mut codec = TcpServerCodec::default().max_frame(11);
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x01, 0x00, 0x02, 0x00, 0x08,
+        ];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).is_err());
+    }
+    #[test]
+    fn custom_request_test() {
+        let v: Vec<u8> = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x0B, 0x41, 0x01, 0x02, 0x03,
+        ];
+        let request_r = Frame::tcp().custom_request(0x0B, 0x41, vec![0x01, 0x02, 0x03]);
+        let mut buf = BytesMut::new();
+        TcpClientCodec::default().encode(request_r.clone(), &mut buf).unwrap();
+        assert_eq!(&buf[..], &v[..]);
+
+        let mut codec = TcpServerCodec::default().with_custom_function(0x41, BodyLength::Fixed(3));
+        let request_l = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(request_l, request_r);
+        match request_l {
+            Request::Custom(_, body) => assert_eq!(&body.get_data()[..], &[0x01, 0x02, 0x03]),
+            request => panic!("unexpected request {:?}", request),
+        }
+
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(TcpServerCodec::default().decode(&mut buf).is_err());
+    }
+}
+
+#[cfg(test)]
+mod fuzz_test {
+    use bytes::BytesMut;
+
+    use crate::codec::{
+        decode_rtu_request, decode_rtu_response, decode_tcp_request, decode_tcp_response,
+    };
+
+    /// Run every decoder over `input` until it needs more bytes or fails
+    fn decode_all(input: &[u8]) {
+        let mut src = BytesMut::from(input);
+        while let Ok(Some(_)) = decode_tcp_request(&mut src) {}
+        let mut src = BytesMut::from(input);
+        while let Ok(Some(_)) = decode_tcp_response(&mut src) {}
+        let mut src = BytesMut::from(input);
+        while let Ok(Some(_)) = decode_rtu_request(&mut src) {}
+        let mut src = BytesMut::from(input);
+        while let Ok(Some(_)) = decode_rtu_response(&mut src) {}
+    }
+
+    #[test]
+    fn regression_test() {
+        // TCP response head split before 8 bytes were buffered
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00]);
+        // TCP length field below 2
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x03]);
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x03]);
+        // TCP body shorter than the fields of its function
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x03]);
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x10, 0x00]);
+        // TCP exception response without code, or with an unknown code
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x01, 0x81]);
+        decode_all(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x81, 0x09]);
+        // RTU exception response with an unknown code
+        decode_all(&[0x0B, 0x81, 0x09, 0xA0, 0x54]);
+    }
+
+    #[test]
+    fn truncated_tcp_test() {
+        let frame = [0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x0B, 0x03, 0x00, 0x6F, 0x00];
+        let mut src = BytesMut::from(&frame[..]);
+        assert!(decode_tcp_request(&mut src).unwrap().is_none());
+        assert_eq!(src.len(), frame.len());
+
+        let mut src = BytesMut::from(&frame[..7]);
+        assert!(decode_tcp_response(&mut src).unwrap().is_none());
+        assert_eq!(src.len(), 7);
+    }
+
+    #[test]
+    fn truncated_write_multiple_test() {
+        let frames: [&[u8]; 3] = [
+            &[0x0B, 0x0F, 0x00, 0x1B, 0x00, 0x09, 0x02, 0x4D, 0x01, 0x6C, 0xA7],
+            &[
+                0x0B, 0x10, 0x00, 0x12, 0x00, 0x02, 0x04, 0x0B, 0x0A, 0xC1, 0x02, 0xA0, 0xD5,
+            ],
+            &[
+                0x11, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x03, 0x06, 0x00, 0xFF,
+                0x00, 0xFF, 0x00, 0xFF, 0x4B, 0x54,
+            ],
+        ];
+        for frame in frames {
+            for len in 0..frame.len() {
+                let mut src = BytesMut::from(&frame[..len]);
+                assert!(decode_rtu_request(&mut src).unwrap().is_none());
+                assert_eq!(src.len(), len);
+            }
+            let mut src = BytesMut::from(&frame[..frame.len() - 1]);
+            assert!(decode_rtu_request(&mut src).unwrap().is_none());
+            src.extend_from_slice(&frame[frame.len() - 1..]);
+            assert!(decode_rtu_request(&mut src).unwrap().is_some());
+            assert!(src.is_empty());
+
+            // The same PDU behind an MBAP head, without the CRC
+            let pdu = &frame[1..frame.len() - 2];
+            let mut tcp = vec![0x00, 0x01, 0x00, 0x00, 0x00, pdu.len() as u8 + 1, frame[0]];
+            tcp.extend_from_slice(pdu);
+            let mut src = BytesMut::from(&tcp[..tcp.len() - 1]);
+            assert!(decode_tcp_request(&mut src).unwrap().is_none());
+            assert_eq!(src.len(), tcp.len() - 1);
+            src.extend_from_slice(&tcp[tcp.len() - 1..]);
+            assert!(decode_tcp_request(&mut src).unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn truncated_rtu_response_test() {
+        let frame = [0x0B, 0x03, 0x04, 0x00, 0x06, 0x00, 0x05, 0x70, 0x31];
+        for len in 0..frame.len() {
+            let mut src = BytesMut::from(&frame[..len]);
+            assert!(decode_rtu_response(&mut src).unwrap().is_none());
+            assert_eq!(src.len(), len);
+        }
+        let mut src = BytesMut::from(&frame[..]);
+        assert!(decode_rtu_response(&mut src).unwrap().is_some());
+    }
+
+    #[test]
+    fn invalid_exception_test() {
+        let mut src = BytesMut::from(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x81, 0x09][..]);
+        assert!(decode_tcp_response(&mut src).is_err());
+        let mut src = BytesMut::from(&[0x0B, 0x81, 0x09, 0xA0, 0x54][..]);
+        assert!(decode_rtu_response(&mut src).is_err());
+    }
+
+    #[test]
+    fn random_input_test() {
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        let frames: [&[u8]; 4] = [
+            &[0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x0B, 0x03, 0x00, 0x6F, 0x00, 0x03],
+            &[0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x0B, 0x04, 0x02, 0x10, 0x2F],
+            &[0x0B, 0x0F, 0x00, 0x1B, 0x00, 0x09, 0x02, 0x4D, 0x01, 0x6C, 0xA7],
+            &[0x0B, 0x04, 0x02, 0x10, 0x2F, 0x6D, 0x2D],
+        ];
+        for _ in 0..20_000 {
+            let mut input = frames[next() as usize % frames.len()].to_vec();
+            for _ in 0..next() % 4 {
+                let index = next() as usize % input.len();
+                input[index] = next() as u8;
+            }
+            input.truncate(next() as usize % (input.len() + 1));
+            decode_all(&input);
+
+            let input: Vec<u8> = (0..next() % 32).map(|_| next() as u8).collect();
+            decode_all(&input);
+        }
+    }
 }
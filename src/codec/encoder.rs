@@ -3,9 +3,10 @@ use std::io::{Error, Result};
 use bytes::BytesMut;
 use tokio_util::codec::Encoder;
 
-use crate::codec::{RtuClientCodec, RtuServerCodec, TcpClientCodec};
+use crate::codec::{trace_frame, RtuClientCodec, RtuServerCodec, TcpClientCodec};
 use crate::frame::request::*;
 use crate::frame::response::*;
+use crate::frame::{Head, Version};
 use crate::frame::response::Response;
 
 use super::TcpServerCodec;
@@ -18,7 +19,10 @@ impl Encoder<Request> for RtuClientCodec {
         item: Request,
         dst: &mut BytesMut,
     ) -> std::result::Result<(), Self::Error> {
-        request_to_bytesmut(item, dst);
+        let start = dst.len();
+        request_to_bytesmut(&item, dst);
+        trace_frame("encode", item.head(), dst.len() - start);
+        self.observer.encoded_request(&item);
         Ok(())
     }
 }
@@ -31,7 +35,10 @@ impl Encoder<Response> for RtuServerCodec {
         item: Response,
         dst: &mut BytesMut,
     ) -> std::result::Result<(), Self::Error> {
-        response_to_bytesmut(item, dst);
+        let start = dst.len();
+        response_to_bytesmut(&item, dst);
+        trace_frame("encode", item.head(), dst.len() - start);
+        self.observer.encoded_response(&item);
         Ok(())
     }
 }
@@ -40,7 +47,11 @@ impl Encoder<Request> for TcpClientCodec {
     type Error = Error;
 
     fn encode(&mut self, item: Request, dst: &mut BytesMut) -> Result<()> {
-        request_to_bytesmut(item, dst);
+        let start = dst.len();
+        request_to_bytesmut(&item, dst);
+        set_pid(dst, start, item.head(), self.config.pid);
+        trace_frame("encode", item.head(), dst.len() - start);
+        self.observer.encoded_request(&item);
         Ok(())
     }
 }
@@ -49,11 +60,25 @@ impl Encoder<Response> for TcpServerCodec {
     type Error = Error;
 
     fn encode(&mut self, item: Response, dst: &mut BytesMut) -> Result<()> {
-        response_to_bytesmut(item, dst);
+        let start = dst.len();
+        response_to_bytesmut(&item, dst);
+        set_pid(dst, start, item.head(), self.config.pid);
+        trace_frame("encode", item.head(), dst.len() - start);
+        self.observer.encoded_response(&item);
         Ok(())
     }
 }
 
+/// Overwrite the protocol identifier of the TCP frame encoded at `start` with head `head`
+///
+/// Only a TCP head still carrying the default identifier gets the one of the codec: a RTU frame
+/// has no identifier, and a response echoing the identifier of its request keeps it.
+fn set_pid(dst: &mut BytesMut, start: usize, head: &Head, pid: u16) {
+    if head.version == Version::Tcp && head.pid == 0 {
+        dst[start + 2..start + 4].copy_from_slice(&pid.to_be_bytes());
+    }
+}
+
 #[cfg(test)]
 mod rtu_client_encoder_test {
     use bytes::BytesMut;
@@ -173,6 +198,28 @@ mod rtu_client_encoder_test {
         ];
         assert_eq!(vec_l, vec_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_request_test() {
+        let mut codec = RtuClientCodec::default();
+        let frame = Frame::rtu();
+        let request = frame.read_write_multiple_registers_request(
+            0x11,
+            0x0003,
+            0x0006,
+            0x000E,
+            vec![0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+        );
+        let mut dst = BytesMut::new();
+        let res = codec.encode(request, &mut dst);
+        assert!(res.is_ok());
+        let vec_l = dst.to_vec();
+        let vec_r = vec![
+            0x11, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E, 0x00, 0x03, 0x06, 0x00, 0xFF, 0x00,
+            0xFF, 0x00, 0xFF, 0x4B, 0x54,
+        ];
+        assert_eq!(vec_l, vec_r);
+    }
 }
 
 #[cfg(test)]
@@ -305,6 +352,28 @@ mod tcp_client_decoder_test {
         ];
         assert_eq!(vec_l, vec_r);
     }
+
+    #[test]
+    fn read_write_multiple_registers_request_test() {
+        let mut codec = TcpClientCodec::default();
+        let frame = Frame::tcp();
+        let request = frame.read_write_multiple_registers_request(
+            0x11,
+            0x0003,
+            0x0006,
+            0x000E,
+            vec![0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+        );
+        let mut dst = BytesMut::new();
+        let res = codec.encode(request, &mut dst);
+        assert!(res.is_ok());
+        let vec_l = dst.to_vec();
+        let vec_r = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x11, 0x11, 0x17, 0x00, 0x03, 0x00, 0x06, 0x00, 0x0E,
+            0x00, 0x03, 0x06, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF,
+        ];
+        assert_eq!(vec_l, vec_r);
+    }
 }
 
 #[cfg(test)]
@@ -441,6 +510,23 @@ mod tcp_server_decoder_test {
         assert_eq!(vec_l, vec_r);
     }
 
+    #[test]
+    fn read_write_multiple_registers_response_test() {
+        let mut codec = TcpServerCodec::default();
+        let frame = Frame::tcp();
+        let response = frame
+            .read_write_multiple_registers_response(0x11, vec![0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01]);
+        let mut dst = BytesMut::new();
+        let res = codec.encode(response, &mut dst);
+        assert!(res.is_ok());
+        let vec_l = dst.to_vec();
+        let vec_r = vec![
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x09, 0x11, 0x17, 0x06, 0x00, 0xFE, 0x0A, 0xCD, 0x00,
+            0x01,
+        ];
+        assert_eq!(vec_l, vec_r);
+    }
+
     #[test]
     fn exception_response_test() {
         let mut codec = TcpServerCodec::default();
@@ -571,6 +657,25 @@ mod rtu_server_decoder_test {
         assert_eq!(vec_l, vec_r);
     }
 
+    #[test]
+    fn read_write_multiple_registers_response_test() {
+        let mut codec = RtuServerCodec::default();
+        let frame = Frame::rtu();
+        let response = frame.read_write_multiple_registers_response(
+            0x11,
+            vec![0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01, 0x00, 0x03, 0x00, 0x0D, 0x00, 0xFF],
+        );
+        let mut dst = BytesMut::new();
+        let res = codec.encode(response, &mut dst);
+        assert!(res.is_ok());
+        let vec_l = dst.to_vec();
+        let vec_r = vec![
+            0x11, 0x17, 0x0C, 0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01, 0x00, 0x03, 0x00, 0x0D, 0x00,
+            0xFF, 0x0D, 0x75,
+        ];
+        assert_eq!(vec_l, vec_r);
+    }
+
     #[test]
     fn exception_response_test() {
         let mut codec = RtuServerCodec::default();
@@ -584,4 +689,50 @@ mod rtu_server_decoder_test {
         let vec_r = vec![0x0A, 0x81, 0x02, 0xB0, 0x53];
         assert_eq!(vec_l, vec_r);
     }
+
+    #[test]
+    fn two_frames_test() {
+        use tokio_util::codec::Decoder;
+
+        use crate::codec::RtuClientCodec;
+
+        let mut codec = RtuServerCodec::default();
+        let frame = Frame::rtu();
+        let first = frame.read_input_register_response(0x0B, vec![0x10, 0x2F]);
+        let second = frame.write_single_coil_response(0x0B, 0x00BF, 0xFF00);
+        let mut dst = BytesMut::new();
+        codec.encode(first.clone(), &mut dst).unwrap();
+        codec.encode(second.clone(), &mut dst).unwrap();
+
+        // Either frame carries the CRC of its own bytes only
+        let mut codec = RtuClientCodec::default();
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(first));
+        assert_eq!(codec.decode(&mut dst).unwrap(), Some(second));
+        assert!(dst.is_empty());
+    }
+
+    #[test]
+    fn register_response_from_u16_test() {
+        let mut codec = RtuServerCodec::default();
+        let frame = Frame::rtu();
+        let registers = [0x022B, 0x0000, 0x0064];
+        let bytes = vec![0x02, 0x2B, 0x00, 0x00, 0x00, 0x64];
+        let pairs = [
+            (
+                frame.read_holding_registers_response_from_u16(0x0B, &registers),
+                frame.read_holding_register_response(0x0B, bytes.clone()),
+            ),
+            (
+                frame.read_input_registers_response_from_u16(0x0B, &registers),
+                frame.read_input_register_response(0x0B, bytes),
+            ),
+        ];
+        for (from_u16, from_bytes) in pairs {
+            let (mut dst_l, mut dst_r) = (BytesMut::new(), BytesMut::new());
+            codec.encode(from_u16, &mut dst_l).unwrap();
+            codec.encode(from_bytes, &mut dst_r).unwrap();
+            assert_eq!(dst_l, dst_r);
+            assert_eq!(&dst_l[2..9], &[0x06, 0x02, 0x2B, 0x00, 0x00, 0x00, 0x64]);
+        }
+    }
 }
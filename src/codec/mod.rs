@@ -1,20 +1,485 @@
 //! Codec based [tokio-util](https://docs.rs/tokio-util/latest/tokio_util/codec/index.html)
+//!
+//! With the `tracing` feature enabled, every encoded and decoded frame emits a `trace!` event
+//! with the fields `direction` (`"encode"` or `"decode"`), `version`, `tid`, `uid`, `function`
+//! and `length` (frame size in bytes).
+//!
+//! # Validation
+//!
+//! The decoders check the framing of a request, not its quantities: a request for zero coils,
+//! or for more registers than the specification allows, decodes like any other. Rejecting it
+//! with [`Exception::IllegalDataValue`](crate::Exception::IllegalDataValue) is up to the
+//! caller, as [`server::dispatch`](crate::server::dispatch) does. On the client side, the
+//! `try_` builders of [`Frame`](crate::Frame) refuse to build such requests.
+//!
+//! # Datagrams
+//!
+//! Modbus/UDP carries the MBAP frames of Modbus/TCP, one whole frame per datagram. The TCP
+//! decoders make no assumption about the stream the bytes come from: a received datagram fed
+//! to [`TcpClientCodec`] or [`TcpServerCodec`] decodes to its frame, and
+//! [`decode_one_tcp_response`] decodes it straight from the receive buffer. See
+//! `examples/udp_client.rs`.
+//!
+//! # Pairing
+//!
+//! Each codec handles one side of the connection: [`TcpClientCodec`] encodes requests and
+//! decodes responses, [`TcpServerCodec`] decodes requests and encodes responses, and likewise
+//! for RTU. A fake server in a test is framed with the server codec, like a real one, and a
+//! client framed with the client codec talks to it, as [`testing::pair`](crate::testing::pair)
+//! sets up:
+//!
+//! ```
+//! use futures::{SinkExt, StreamExt};
+//! use tokio_util::codec::Framed;
+//! use easy_modbus::codec::{TcpClientCodec, TcpServerCodec};
+//! use easy_modbus::{Frame, Request};
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let (client, server) = tokio::io::duplex(256);
+//!     let mut client = Framed::new(client, TcpClientCodec::default());
+//!     let mut server = Framed::new(server, TcpServerCodec::default());
+//!
+//!     client.send(Frame::tcp().read_coils_request(0x01, 0x0000, 0x0002)).await.unwrap();
+//!     let request = server.next().await.unwrap().unwrap();
+//!     assert!(matches!(request, Request::ReadCoils(_, _)));
+//!     let response = Frame::tcp().read_coils_response(0x01, vec![0x01]);
+//!     server.send(response.clone()).await.unwrap();
+//!     assert_eq!(client.next().await.unwrap().unwrap(), response);
+//! }
+//! ```
 
+use std::sync::Arc;
+
+pub use config::{CodecConfig, MAX_FRAME};
+pub use custom::{BodyLength, BodyLengthFn};
+pub use decoder::{
+    decode_one_tcp_response, decode_rtu_request, decode_rtu_response, decode_tcp_request,
+    decode_tcp_response,
+};
+pub use observer::FrameObserver;
+
+use crate::frame::Head;
+use custom::CustomFunctions;
+use observer::Observer;
+
+mod config;
+mod custom;
 mod decoder;
 mod encoder;
+mod observer;
 
 /// Mutual convert TCP Client frames and buffers.
 #[derive(Debug, Default)]
-pub struct TcpClientCodec;
+pub struct TcpClientCodec {
+    config: CodecConfig,
+    observer: Observer,
+    custom: CustomFunctions,
+    /// Read responses carry no byte count, see [`TcpClientCodec::quirk_no_byte_count`]
+    no_byte_count: bool,
+}
 
 /// Mutual convert TCP Server frames and buffers.
 #[derive(Debug, Default)]
-pub struct TcpServerCodec;
+pub struct TcpServerCodec {
+    config: CodecConfig,
+    observer: Observer,
+    custom: CustomFunctions,
+}
 
 /// Mutual convert RTU Client frames and buffers.
-#[derive(Debug, Default)]
-pub struct RtuClientCodec;
+///
+/// Frames are limited to [`MAX_RTU_ADU_LEN`](crate::limits::MAX_RTU_ADU_LEN) bytes by default,
+/// see [`CodecConfig::rtu`].
+#[derive(Debug)]
+pub struct RtuClientCodec {
+    config: CodecConfig,
+    observer: Observer,
+    custom: CustomFunctions,
+    /// Discard `0x00` bytes in front of a response
+    skip_leading_nulls: bool,
+}
 
 /// Mutual convert RTU Server frames and buffers.
-#[derive(Debug, Default)]
-pub struct RtuServerCodec;
+///
+/// Frames are limited to [`MAX_RTU_ADU_LEN`](crate::limits::MAX_RTU_ADU_LEN) bytes by default,
+/// see [`CodecConfig::rtu`].
+#[derive(Debug)]
+pub struct RtuServerCodec {
+    config: CodecConfig,
+    observer: Observer,
+    custom: CustomFunctions,
+    /// Unit ids requests are delivered for, all of them when `None`
+    units: Option<Vec<u8>>,
+}
+
+impl Default for RtuClientCodec {
+    fn default() -> Self {
+        RtuClientCodec {
+            config: CodecConfig::rtu(),
+            observer: Observer::default(),
+            custom: CustomFunctions::default(),
+            skip_leading_nulls: false,
+        }
+    }
+}
+
+impl Default for RtuServerCodec {
+    fn default() -> Self {
+        RtuServerCodec {
+            config: CodecConfig::rtu(),
+            observer: Observer::default(),
+            custom: CustomFunctions::default(),
+            units: None,
+        }
+    }
+}
+
+macro_rules! impl_config {
+    ($($codec:ident),*) => {$(
+        impl $codec {
+            /// Create a codec with the given configuration
+            ///
+            /// A `max_frame` left at [`MAX_FRAME`], the default of [`CodecConfig`], becomes the
+            /// default of the codec, [`MAX_RTU_ADU_LEN`](crate::limits::MAX_RTU_ADU_LEN) for the
+            /// RTU codecs. Set a larger one on the codec, see `max_frame`.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::{CodecConfig, ", stringify!($codec), "};")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::new(CodecConfig::default());")]
+            #[doc = concat!("let default = ", stringify!($codec), "::default();")]
+            /// assert_eq!(codec.get_config(), default.get_config());
+            /// ```
+            pub fn new(config: CodecConfig) -> $codec {
+                let default = $codec::default();
+                let max_frame = match config.max_frame {
+                    MAX_FRAME => default.config.max_frame,
+                    max_frame => max_frame,
+                };
+                $codec {
+                    config: CodecConfig { max_frame, ..config },
+                    ..default
+                }
+            }
+
+            /// Reject frames deviating from the specification
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::", stringify!($codec), ";")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::default().strict();")]
+            /// assert!(codec.get_config().is_strict());
+            /// ```
+            pub fn strict(mut self) -> $codec {
+                self.config.strict = true;
+                self
+            }
+
+            /// Set the largest frame the decoder accepts, in bytes
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::", stringify!($codec), ";")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::default().max_frame(64);")]
+            /// assert_eq!(codec.get_config().get_max_frame(), 64);
+            /// ```
+            pub fn max_frame(mut self, max_frame: usize) -> $codec {
+                self.config.max_frame = max_frame;
+                self
+            }
+
+            /// Report encoded and decoded frames to `observer`
+            pub fn observer(mut self, observer: Arc<dyn FrameObserver>) -> $codec {
+                self.observer = Observer::new(observer);
+                self
+            }
+
+            /// Decode frames of the custom function `code`, whose bodies are `length` long
+            ///
+            /// They decode to the `Custom` variant with the body as is. Exception responses to
+            /// it decode to [`Response::Exception`](crate::Response::Exception). Panics for a
+            /// code of a modeled function, or of an exception.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::{BodyLength, ", stringify!($codec), "};")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::default()")]
+            ///     .with_custom_function(0x41, BodyLength::Fixed(2))
+            ///     .with_custom_function(0x42, BodyLength::ByteCount(0));
+            /// ```
+            pub fn with_custom_function(mut self, code: u8, length: BodyLength) -> $codec {
+                crate::frame::custom_function(code);
+                self.custom.insert(code, length);
+                self
+            }
+
+            /// Decode frames of the functions `lengths` gives a body length for, as custom
+            /// functions
+            ///
+            /// For proxying functions not known ahead of time. Modeled functions and the ones
+            /// registered with `with_custom_function` keep their lengths, `lengths` is only
+            /// asked about the other codes below `0x80`.
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::{BodyLength, ", stringify!($codec), "};")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::default()")]
+            ///     .with_body_lengths(|code| match code {
+            ///         0x41..=0x48 => Some(BodyLength::ByteCount(0)),
+            ///         0x65 => Some(BodyLength::Fixed(4)),
+            ///         _ => None,
+            ///     });
+            /// ```
+            pub fn with_body_lengths(mut self, lengths: BodyLengthFn) -> $codec {
+                self.custom.set_fallback(lengths);
+                self
+            }
+
+            /// The configuration of the codec
+            ///
+            /// Its `max_frame` is [`MAX_FRAME`] for the TCP codecs and
+            /// [`MAX_RTU_ADU_LEN`](crate::limits::MAX_RTU_ADU_LEN) for the RTU codecs, unless
+            /// set, see [`CodecConfig::rtu`].
+            pub fn get_config(&self) -> &CodecConfig {
+                &self.config
+            }
+        }
+    )*};
+}
+
+impl_config!(TcpClientCodec, TcpServerCodec, RtuClientCodec, RtuServerCodec);
+
+macro_rules! impl_pid {
+    ($($codec:ident),*) => {$(
+        impl $codec {
+            /// Set the MBAP protocol identifier of encoded and expected frames
+            ///
+            /// See [`CodecConfig::pid`].
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use easy_modbus::codec::", stringify!($codec), ";")]
+            #[doc = concat!("let codec = ", stringify!($codec), "::default().with_pid(0x1234);")]
+            /// assert_eq!(codec.get_config().get_pid(), 0x1234);
+            /// ```
+            pub fn with_pid(mut self, pid: u16) -> $codec {
+                self.config.pid = pid;
+                self
+            }
+        }
+    )*};
+}
+
+impl_pid!(TcpClientCodec, TcpServerCodec);
+
+impl TcpClientCodec {
+    /// Decode read responses missing their byte count
+    ///
+    /// A quirk for non-conformant devices whose read responses go straight from the function
+    /// code to the values. The length of the values is taken from the MBAP length field
+    /// instead, and the decoded response carries the byte count a conformant device would
+    /// have sent. Only use it for such a device, a conformant response would be decoded with
+    /// its byte count as the first value byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    /// use easy_modbus::Frame;
+    /// use easy_modbus::codec::TcpClientCodec;
+    ///
+    /// let mut codec = TcpClientCodec::default().quirk_no_byte_count();
+    /// let v = [0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x01, 0x03, 0x12, 0x34];
+    /// let response = codec.decode(&mut BytesMut::from(&v[..])).unwrap().unwrap();
+    /// let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x12, 0x34]);
+    /// assert_eq!(response, expected);
+    /// ```
+    pub fn quirk_no_byte_count(mut self) -> TcpClientCodec {
+        self.no_byte_count = true;
+        self
+    }
+
+    /// Whether read responses are decoded without a byte count
+    pub fn is_quirk_no_byte_count(&self) -> bool {
+        self.no_byte_count
+    }
+}
+
+impl RtuClientCodec {
+    /// Discard `0x00` bytes in front of a response before framing it
+    ///
+    /// Some USB-serial adapters inject a spurious `0x00` when the line comes up, which would be
+    /// taken as the unit id of the response. A leading `0x00` is only dropped when it can't
+    /// start a response of unit 0: the head following it doesn't parse, or the frame doesn't
+    /// pass its CRC check once buffered whole. A frame of unit 0, a response to a broadcast
+    /// request on a bus shared with another master say, still decodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    /// use easy_modbus::codec::RtuClientCodec;
+    ///
+    /// let mut codec = RtuClientCodec::default().skip_leading_nulls();
+    /// let v = [0x00, 0x0B, 0x05, 0x00, 0xBF, 0x00, 0x00, 0xFC, 0x84];
+    /// let mut src = BytesMut::from(&v[..]);
+    /// assert!(codec.decode(&mut src).unwrap().is_some());
+    /// ```
+    pub fn skip_leading_nulls(mut self) -> RtuClientCodec {
+        self.skip_leading_nulls = true;
+        self
+    }
+
+    /// Whether `0x00` bytes in front of a response are discarded
+    pub fn skips_leading_nulls(&self) -> bool {
+        self.skip_leading_nulls
+    }
+}
+
+impl RtuServerCodec {
+    /// Create a codec only delivering requests addressed to `unit_id`
+    ///
+    /// Requests for other units are consumed from the buffer and dropped, so a server sharing a
+    /// bus never answers them. Broadcast requests (unit id 0) are still delivered, see
+    /// [`Request::is_broadcast`](crate::Request::is_broadcast).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use tokio_util::codec::Decoder;
+    /// use easy_modbus::codec::RtuServerCodec;
+    ///
+    /// let mut codec = RtuServerCodec::for_unit(0x0B);
+    /// let mut src = BytesMut::from(&[0x0A, 0x01, 0x00, 0x00, 0x00, 0x01, 0xFC, 0xB1][..]);
+    /// assert!(codec.decode(&mut src).unwrap().is_none());
+    /// assert!(src.is_empty());
+    /// ```
+    pub fn for_unit(unit_id: u8) -> RtuServerCodec {
+        RtuServerCodec::for_units([unit_id])
+    }
+
+    /// Create a codec only delivering requests addressed to one of `unit_ids`
+    ///
+    /// Like [`RtuServerCodec::for_unit`], for a server answering as several units.
+    pub fn for_units(unit_ids: impl IntoIterator<Item = u8>) -> RtuServerCodec {
+        RtuServerCodec {
+            units: Some(unit_ids.into_iter().collect()),
+            ..RtuServerCodec::default()
+        }
+    }
+
+    /// Unit ids requests are delivered for, `None` for every unit
+    pub fn get_units(&self) -> Option<&[u8]> {
+        self.units.as_deref()
+    }
+
+    /// Whether a request for `unit_id` is delivered
+    pub(crate) fn accepts(&self, unit_id: u8) -> bool {
+        match &self.units {
+            Some(units) => unit_id == 0 || units.contains(&unit_id),
+            None => true,
+        }
+    }
+}
+
+/// Emit a `trace!` event for a frame
+#[cfg(feature = "tracing")]
+pub(crate) fn trace_frame(direction: &'static str, head: &Head, length: usize) {
+    tracing::trace!(
+        direction,
+        version = ?head.version,
+        tid = head.tid,
+        uid = head.uid,
+        function = ?head.function,
+        length,
+        "modbus frame"
+    );
+}
+
+/// No-op without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn trace_frame(_direction: &'static str, _head: &Head, _length: usize) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod trace_test {
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+
+    use bytes::BytesMut;
+    use tokio_util::codec::Decoder;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use crate::codec::TcpServerCodec;
+
+    /// Collects the fields of every event
+    #[derive(Clone, Default)]
+    struct Collector(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Visit for Collector {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            let value = format!("{:?}", value);
+            self.0.lock().unwrap().push((field.name().to_string(), value));
+        }
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn decode_event_test() {
+        let collector = Collector::default();
+        let fields = collector.0.clone();
+        tracing::subscriber::with_default(collector, || {
+            let v: Vec<u8> = vec![
+                0x00, 0x07, 0x00, 0x00, 0x00, 0x06, 0x01, 0x01, 0x00, 0x02, 0x00, 0x08,
+            ];
+            let mut buf = BytesMut::from(&v[..]);
+            TcpServerCodec::default().decode(&mut buf).unwrap().unwrap();
+        });
+        let fields = fields.lock().unwrap();
+        let get = |name: &str| {
+            fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(get("direction"), Some("\"decode\""));
+        assert_eq!(get("version"), Some("Tcp"));
+        assert_eq!(get("tid"), Some("7"));
+        assert_eq!(get("uid"), Some("1"));
+        assert_eq!(get("function"), Some("ReadCoils"));
+        assert_eq!(get("length"), Some("12"));
+    }
+}
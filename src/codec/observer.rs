@@ -0,0 +1,185 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::{Exception, Function};
+
+/// Callbacks for frames passing through a codec
+///
+/// Plug in counters of a metrics backend. Every callback defaults to doing nothing.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use bytes::BytesMut;
+/// use tokio_util::codec::Decoder;
+/// use easy_modbus::codec::{FrameObserver, RtuClientCodec};
+///
+/// #[derive(Default)]
+/// struct CrcErrors(AtomicUsize);
+///
+/// impl FrameObserver for CrcErrors {
+///     fn on_crc_error(&self) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let errors = Arc::new(CrcErrors::default());
+/// let mut codec = RtuClientCodec::default().observer(errors.clone());
+/// let mut src = BytesMut::from(&[0x0B, 0x04, 0x02, 0x10, 0x2F, 0x00, 0x00][..]);
+/// assert!(codec.decode(&mut src).is_err());
+/// assert_eq!(errors.0.load(Ordering::Relaxed), 1);
+/// ```
+pub trait FrameObserver: Send + Sync {
+    /// A frame of `function` was decoded
+    fn on_decode_ok(&self, function: &Function) {
+        let _ = function;
+    }
+
+    /// A frame of `function` was encoded
+    fn on_encode_ok(&self, function: &Function) {
+        let _ = function;
+    }
+
+    /// A RTU frame failed its CRC check
+    fn on_crc_error(&self) {}
+
+    /// An exception response was decoded or encoded
+    fn on_exception(&self, exception: &Exception) {
+        let _ = exception;
+    }
+}
+
+/// Optional observer held by a codec
+#[derive(Clone, Default)]
+pub(crate) struct Observer(Option<Arc<dyn FrameObserver>>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "Observer(Some(..))"),
+            None => write!(f, "Observer(None)"),
+        }
+    }
+}
+
+impl Observer {
+    pub(crate) fn new(observer: Arc<dyn FrameObserver>) -> Observer {
+        Observer(Some(observer))
+    }
+
+    pub(crate) fn crc_error(&self) {
+        if let Some(observer) = &self.0 {
+            observer.on_crc_error();
+        }
+    }
+
+    pub(crate) fn decoded_request(&self, request: &Request) {
+        if let Some(observer) = &self.0 {
+            observer.on_decode_ok(&request.head().function);
+        }
+    }
+
+    pub(crate) fn encoded_request(&self, request: &Request) {
+        if let Some(observer) = &self.0 {
+            observer.on_encode_ok(&request.head().function);
+        }
+    }
+
+    pub(crate) fn decoded_response(&self, response: &Response) {
+        if let Some(observer) = &self.0 {
+            observer.on_decode_ok(&response.head().function);
+            if let Response::Exception(_, body) = response {
+                observer.on_exception(body.get_exception());
+            }
+        }
+    }
+
+    pub(crate) fn encoded_response(&self, response: &Response) {
+        if let Some(observer) = &self.0 {
+            observer.on_encode_ok(&response.head().function);
+            if let Response::Exception(_, body) = response {
+                observer.on_exception(body.get_exception());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod observer_test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use crate::codec::{FrameObserver, RtuClientCodec, RtuServerCodec};
+    use crate::frame::{Exception, Function};
+    use crate::Frame;
+
+    #[derive(Default)]
+    struct Counter {
+        decoded: AtomicUsize,
+        encoded: AtomicUsize,
+        crc_errors: AtomicUsize,
+        exceptions: AtomicUsize,
+    }
+
+    impl FrameObserver for Counter {
+        fn on_decode_ok(&self, _function: &Function) {
+            self.decoded.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_encode_ok(&self, _function: &Function) {
+            self.encoded.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_crc_error(&self) {
+            self.crc_errors.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn on_exception(&self, _exception: &Exception) {
+            self.exceptions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn decode_test() {
+        let counter = Arc::new(Counter::default());
+        let mut codec = RtuClientCodec::default().observer(counter.clone());
+
+        let v: Vec<u8> = vec![0x0B, 0x04, 0x02, 0x10, 0x2F, 0x6D, 0x2D];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        let v: Vec<u8> = vec![0x0B, 0x04, 0x02, 0x10, 0x2F, 0x6D, 0x2E];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).is_err());
+
+        let v: Vec<u8> = vec![0x0A, 0x81, 0x02, 0xB0, 0x53];
+        let mut buf = BytesMut::from(&v[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        assert_eq!(counter.decoded.load(Ordering::Relaxed), 2);
+        assert_eq!(counter.crc_errors.load(Ordering::Relaxed), 1);
+        assert_eq!(counter.exceptions.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn encode_test() {
+        let counter = Arc::new(Counter::default());
+        let mut codec = RtuServerCodec::default().observer(counter.clone());
+        let frame = Frame::rtu();
+        let response =
+            frame.exception_response(0x0A, Function::ReadCoils, Exception::IllegalDataAddress);
+        let mut dst = BytesMut::new();
+        codec.encode(response, &mut dst).unwrap();
+        assert_eq!(counter.encoded.load(Ordering::Relaxed), 1);
+        assert_eq!(counter.exceptions.load(Ordering::Relaxed), 1);
+    }
+}
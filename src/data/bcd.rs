@@ -0,0 +1,169 @@
+use super::register::words;
+use super::{ConversionError, WordOrder};
+
+/// Most registers of a BCD value, 16 digits fitting a `u64`
+const MAX_BCD_REGISTERS: usize = 4;
+
+/// The value of a register holding four BCD digits, `0x1234` being 1234
+///
+/// Returns [`ConversionError::InvalidBcdDigit`] for a nibble above 9.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::bcd_to_u16;
+/// assert_eq!(bcd_to_u16(0x1234).unwrap(), 1234);
+/// assert_eq!(bcd_to_u16(0x0009).unwrap(), 9);
+/// assert!(bcd_to_u16(0x12A4).is_err());
+/// ```
+pub fn bcd_to_u16(register: u16) -> Result<u16, ConversionError> {
+    let mut value = 0;
+    for shift in [12, 8, 4, 0] {
+        value = value * 10 + digit(register, shift)?;
+    }
+    Ok(value)
+}
+
+/// The register holding `value` as four BCD digits
+///
+/// Returns [`ConversionError::ValueOutOfRange`] for a value above 9999.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::u16_to_bcd;
+/// assert_eq!(u16_to_bcd(1234).unwrap(), 0x1234);
+/// assert!(u16_to_bcd(10000).is_err());
+/// ```
+pub fn u16_to_bcd(value: u16) -> Result<u16, ConversionError> {
+    if value > 9999 {
+        return Err(ConversionError::ValueOutOfRange { min: 0, max: 9999 });
+    }
+    Ok(encode(value as u64, 1)[0])
+}
+
+/// The value of registers holding four BCD digits each, in the order they were read
+///
+/// `order` orders the registers as for any other value spanning several registers, with
+/// [`WordOrder::BigEndian`] the most significant digits come first. Up to four registers, 16
+/// digits, are read. Returns [`ConversionError::InvalidBcdDigit`] for a nibble above 9 and
+/// [`ConversionError::LengthMismatch`] for more than four registers.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{bcd_from_registers, WordOrder};
+/// let registers = [0x0012, 0x3456];
+/// assert_eq!(bcd_from_registers(&registers, WordOrder::BigEndian).unwrap(), 123456);
+/// assert_eq!(bcd_from_registers(&registers, WordOrder::LittleEndian).unwrap(), 34560012);
+/// ```
+pub fn bcd_from_registers(registers: &[u16], order: WordOrder) -> Result<u64, ConversionError> {
+    check_len(registers.len())?;
+    let mut value = 0;
+    for register in words(registers, order) {
+        value = value * 10_000 + bcd_to_u16(register)? as u64;
+    }
+    Ok(value)
+}
+
+/// The `len` registers holding `value` as four BCD digits each, in the order they are written
+///
+/// Returns [`ConversionError::LengthMismatch`] for more than four registers,
+/// [`ConversionError::ValueOutOfRange`] for a value with more digits than `len` registers hold.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{bcd_to_registers, WordOrder};
+/// let registers = bcd_to_registers(123456, 2, WordOrder::BigEndian).unwrap();
+/// assert_eq!(registers, vec![0x0012, 0x3456]);
+/// assert!(bcd_to_registers(123456, 1, WordOrder::BigEndian).is_err());
+/// ```
+pub fn bcd_to_registers(
+    value: u64,
+    len: usize,
+    order: WordOrder,
+) -> Result<Vec<u16>, ConversionError> {
+    check_len(len)?;
+    let max = 10_000i64.pow(len as u32) - 1;
+    if value > max as u64 {
+        return Err(ConversionError::ValueOutOfRange { min: 0, max });
+    }
+    Ok(words(&encode(value, len), order))
+}
+
+/// The BCD digit in the nibble of `register` at `shift`
+fn digit(register: u16, shift: u32) -> Result<u16, ConversionError> {
+    let digit = (register >> shift) & 0x0F;
+    if digit > 9 {
+        return Err(ConversionError::InvalidBcdDigit(register));
+    }
+    Ok(digit)
+}
+
+/// The `len` big-endian registers of a value known to fit them
+fn encode(mut value: u64, len: usize) -> Vec<u16> {
+    let mut registers = vec![0; len];
+    for register in registers.iter_mut().rev() {
+        let mut group = value % 10_000;
+        value /= 10_000;
+        for shift in [0, 4, 8, 12] {
+            *register |= ((group % 10) as u16) << shift;
+            group /= 10;
+        }
+    }
+    registers
+}
+
+fn check_len(len: usize) -> Result<(), ConversionError> {
+    if len > MAX_BCD_REGISTERS {
+        return Err(ConversionError::LengthMismatch {
+            expected: MAX_BCD_REGISTERS,
+            actual: len,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod bcd_test {
+    use crate::data::{bcd_from_registers, bcd_to_registers, bcd_to_u16, u16_to_bcd, WordOrder};
+
+    #[test]
+    fn register_test() {
+        for value in [0, 1, 9, 10, 99, 1234, 9000, 9999] {
+            assert_eq!(bcd_to_u16(u16_to_bcd(value).unwrap()).unwrap(), value);
+        }
+        assert_eq!(u16_to_bcd(905).unwrap(), 0x0905);
+        assert!(u16_to_bcd(10000).is_err());
+        for register in [0x000A, 0x00F0, 0x0B00, 0xC000, 0xFFFF] {
+            assert!(bcd_to_u16(register).is_err(), "{:04X}", register);
+        }
+    }
+
+    #[test]
+    fn registers_test() {
+        let registers = [0x9999, 0x0000, 0x1234, 0x5678];
+        let value = bcd_from_registers(&registers, WordOrder::BigEndian).unwrap();
+        assert_eq!(value, 9999_0000_1234_5678);
+        for order in [
+            WordOrder::ABCD,
+            WordOrder::CDAB,
+            WordOrder::BADC,
+            WordOrder::DCBA,
+        ] {
+            let registers = bcd_to_registers(value, 4, order).unwrap();
+            assert_eq!(bcd_from_registers(&registers, order).unwrap(), value);
+        }
+        assert_eq!(
+            bcd_to_registers(12, 2, WordOrder::LittleEndian).unwrap(),
+            vec![0x0012, 0x0000]
+        );
+        assert_eq!(bcd_from_registers(&[], WordOrder::BigEndian).unwrap(), 0);
+
+        assert!(bcd_from_registers(&[0x0000, 0x001A], WordOrder::BigEndian).is_err());
+        assert!(bcd_from_registers(&[0; 5], WordOrder::BigEndian).is_err());
+        assert!(bcd_to_registers(10_000, 1, WordOrder::BigEndian).is_err());
+        assert!(bcd_to_registers(0, 5, WordOrder::BigEndian).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::Result;
+
+use super::{FromRegisters, ToRegisters, WordOrder};
+
+/// A register whose bits each mean something, such as a status word
+///
+/// Bit 0 is the least significant. Name the bits with constants of their index, a
+/// `Bitfield16` converts like a `u16` and can be a field of a struct mapping a register block.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::Bitfield16;
+///
+/// const PUMP_RUNNING: u8 = 3;
+/// const ALARM: u8 = 15;
+///
+/// let mut status = Bitfield16::new(0x0008);
+/// assert!(status.bit(PUMP_RUNNING));
+/// assert!(!status.bit(ALARM));
+/// status.set_bit(ALARM, true);
+/// assert_eq!(status.get_value(), 0x8008);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bitfield16 {
+    pub(crate) value: u16,
+}
+
+impl Bitfield16 {
+    pub const fn new(value: u16) -> Bitfield16 {
+        Bitfield16 { value }
+    }
+
+    pub const fn get_value(&self) -> u16 {
+        self.value
+    }
+
+    /// Whether bit `n` is set
+    ///
+    /// Panics if `n` is above 15.
+    pub const fn bit(&self, n: u8) -> bool {
+        self.value & mask(n) != 0
+    }
+
+    /// Set or clear bit `n`
+    ///
+    /// Panics if `n` is above 15.
+    pub fn set_bit(&mut self, n: u8, on: bool) {
+        *self = self.with_bit(n, on);
+    }
+
+    /// The bitfield with bit `n` set or cleared, for building constants
+    ///
+    /// Panics if `n` is above 15.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::data::Bitfield16;
+    /// const START: Bitfield16 = Bitfield16::new(0).with_bit(0, true).with_bit(4, true);
+    /// assert_eq!(START.get_value(), 0x0011);
+    /// ```
+    pub const fn with_bit(self, n: u8, on: bool) -> Bitfield16 {
+        match on {
+            true => Bitfield16::new(self.value | mask(n)),
+            false => Bitfield16::new(self.value & !mask(n)),
+        }
+    }
+}
+
+/// The mask of bit `n` of a register, panicking past bit 15
+const fn mask(n: u8) -> u16 {
+    assert!(n < 16, "bit index past the 16 bits of a register");
+    1 << n
+}
+
+impl From<u16> for Bitfield16 {
+    fn from(value: u16) -> Self {
+        Bitfield16::new(value)
+    }
+}
+
+impl From<Bitfield16> for u16 {
+    fn from(bitfield: Bitfield16) -> Self {
+        bitfield.value
+    }
+}
+
+impl FromRegisters for Bitfield16 {
+    const REGISTER_COUNT: usize = 1;
+
+    fn from_registers(registers: &[u16], order: WordOrder) -> Result<Self> {
+        u16::from_registers(registers, order).map(Bitfield16::new)
+    }
+}
+
+impl ToRegisters for Bitfield16 {
+    const REGISTER_COUNT: usize = 1;
+
+    fn to_registers(&self, order: WordOrder) -> Vec<u16> {
+        self.value.to_registers(order)
+    }
+}
+
+#[cfg(test)]
+mod bitfield_test {
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use crate::codec::{TcpClientCodec, TcpServerCodec};
+    use crate::data::{Bitfield16, FromRegisters, ToRegisters, WordOrder};
+    use crate::{Frame, Request};
+
+    #[test]
+    fn bit_test() {
+        let mut bitfield = Bitfield16::new(0x8001);
+        assert!(bitfield.bit(0));
+        assert!(bitfield.bit(15));
+        assert!(!bitfield.bit(1) && !bitfield.bit(14));
+        bitfield.set_bit(0, false);
+        bitfield.set_bit(15, false);
+        assert_eq!(bitfield, Bitfield16::default());
+        bitfield.set_bit(15, true);
+        bitfield.set_bit(15, true);
+        assert_eq!(bitfield.get_value(), 0x8000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bit_past_end_test() {
+        Bitfield16::new(0xFFFF).bit(16);
+    }
+
+    #[test]
+    fn write_request_test() {
+        let status = Bitfield16::new(0).with_bit(0, true).with_bit(15, true);
+        let [register] = status.to_registers(WordOrder::BigEndian)[..] else {
+            panic!("a bitfield is one register");
+        };
+        let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0010, register);
+        let mut buf = BytesMut::new();
+        TcpClientCodec::default().encode(request, &mut buf).unwrap();
+        match TcpServerCodec::default().decode(&mut buf).unwrap().unwrap() {
+            Request::WriteSingleHoldingRegister(_, body) => {
+                let written = [*body.get_value()];
+                let read = Bitfield16::from_registers(&written, WordOrder::BigEndian).unwrap();
+                assert_eq!(read, status);
+                assert!(read.bit(0) && read.bit(15));
+            }
+            request => panic!("unexpected request {:?}", request),
+        }
+    }
+}
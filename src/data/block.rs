@@ -0,0 +1,63 @@
+/// Registers read from consecutive addresses, looked up by address
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::RegisterBlock;
+/// let block = RegisterBlock::new(0x0100, vec![0x000A, 0x000B, 0x000C]);
+/// assert_eq!(block.get(0x0102), Some(0x000C));
+/// assert_eq!(block.get(0x00FF), None);
+/// assert_eq!(block.get(0x0103), None);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RegisterBlock {
+    /// Address of the first register
+    pub(crate) start: u16,
+
+    /// Register values, the first one at `start`
+    pub(crate) values: Vec<u16>,
+}
+
+impl RegisterBlock {
+    pub fn new(start: u16, values: Vec<u16>) -> RegisterBlock {
+        RegisterBlock { start, values }
+    }
+
+    /// Value of the register at `address`, `None` outside the block
+    pub fn get(&self, address: u16) -> Option<u16> {
+        let index = address.checked_sub(self.start)?;
+        self.values.get(index as usize).copied()
+    }
+
+    pub fn get_start(&self) -> u16 {
+        self.start
+    }
+
+    pub fn get_values(&self) -> &[u16] {
+        &self.values
+    }
+
+    /// Pairs of address and value of every register, in address order
+    pub fn iter(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        (self.start..=u16::MAX).zip(self.values.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod block_test {
+    use crate::data::RegisterBlock;
+
+    #[test]
+    fn get_test() {
+        let block = RegisterBlock::new(0x0100, vec![0x1111, 0x2222, 0x3333, 0x4444]);
+        assert_eq!(block.get(0x0100), Some(0x1111));
+        assert_eq!(block.get(0x0102), Some(0x3333));
+        assert_eq!(block.get(0x0103), Some(0x4444));
+        assert_eq!(block.get(0x0104), None);
+        assert_eq!(block.get(0x0000), None);
+
+        let block = RegisterBlock::new(0xFFFF, vec![0x1234]);
+        assert_eq!(block.get(0xFFFF), Some(0x1234));
+        assert_eq!(block.iter().collect::<Vec<_>>(), vec![(0xFFFF, 0x1234)]);
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+use std::io;
+use std::io::ErrorKind;
+
+/// Cause of a failed conversion between registers and a value
+///
+/// The helpers of [`data`](super) converting single values fail with a `ConversionError`.
+/// [`FromRegisters`](super::FromRegisters) and the typed getters of read responses fail with
+/// [`std::io::Error`], which carries the `ConversionError`, recovered with
+/// `ConversionError::try_from`.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{bcd_to_u16, ConversionError, FromRegisters, WordOrder};
+/// assert_eq!(bcd_to_u16(0x12A4), Err(ConversionError::InvalidBcdDigit(0x12A4)));
+///
+/// let error = u32::from_registers(&[0x0001], WordOrder::BigEndian).unwrap_err();
+/// let expected = ConversionError::LengthMismatch { expected: 2, actual: 1 };
+/// assert_eq!(ConversionError::try_from(error).unwrap(), expected);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConversionError {
+    /// Registers of another count than the value occupies, or at most occupies
+    LengthMismatch { expected: usize, actual: usize },
+
+    /// A register holding a nibble above 9 where BCD digits were expected
+    InvalidBcdDigit(u16),
+
+    /// Bytes of a string which are not UTF-8, the first `valid_up_to` of them being valid
+    InvalidUtf8 { valid_up_to: usize },
+
+    /// A string to write with a character which is not ASCII at byte `position`
+    NotAscii { position: usize },
+
+    /// A string to write of `len` characters, where the registers hold `max`
+    StringTooLong { len: usize, max: usize },
+
+    /// A value to write outside the `min..=max` the registers hold
+    ValueOutOfRange { min: i64, max: i64 },
+
+    /// An offset past the `len` registers read, or past the 16 bits of a register
+    OffsetOutOfRange { offset: usize, len: usize },
+}
+
+impl ConversionError {
+    /// The [`ErrorKind`] of the [`io::Error`] carrying this error
+    ///
+    /// Registers which don't hold a value of the type are [`ErrorKind::InvalidData`], a value,
+    /// string or offset the registers cannot hold is [`ErrorKind::InvalidInput`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ConversionError::LengthMismatch { .. }
+            | ConversionError::InvalidBcdDigit(_)
+            | ConversionError::InvalidUtf8 { .. } => ErrorKind::InvalidData,
+            ConversionError::NotAscii { .. }
+            | ConversionError::StringTooLong { .. }
+            | ConversionError::ValueOutOfRange { .. }
+            | ConversionError::OffsetOutOfRange { .. } => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::LengthMismatch { expected, actual } => {
+                write!(f, "Expected {} registers, got {}", expected, actual)
+            }
+            ConversionError::InvalidBcdDigit(register) => {
+                write!(f, "0x{:0>4X} is not BCD", register)
+            }
+            ConversionError::InvalidUtf8 { valid_up_to } => {
+                write!(f, "Invalid UTF-8 after byte {}", valid_up_to)
+            }
+            ConversionError::NotAscii { position } => {
+                write!(f, "Not ASCII at byte {}", position)
+            }
+            ConversionError::StringTooLong { len, max } => {
+                write!(f, "{} characters do not fit {}", len, max)
+            }
+            ConversionError::ValueOutOfRange { min, max } => {
+                write!(f, "Value outside {}..={}", min, max)
+            }
+            ConversionError::OffsetOutOfRange { offset, len } => {
+                write!(f, "No offset {} in {}", offset, len)
+            }
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+impl From<ConversionError> for io::Error {
+    fn from(error: ConversionError) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+impl TryFrom<io::Error> for ConversionError {
+    type Error = io::Error;
+
+    /// The `ConversionError` carried by `error`, `error` itself for any other failure
+    fn try_from(error: io::Error) -> Result<Self, io::Error> {
+        error.downcast::<ConversionError>()
+    }
+}
+
+#[cfg(test)]
+mod conversion_error_test {
+    use std::io;
+
+    use crate::data::{
+        bcd_to_registers, scaled_to_register, u16_to_bcd, ConversionError, FromRegisters, WordOrder,
+    };
+
+    #[test]
+    fn variant_test() {
+        let error = u32::from_registers(&[0x0001], WordOrder::BigEndian).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Expected 2 registers, got 1");
+        assert_eq!(
+            ConversionError::try_from(error).unwrap(),
+            ConversionError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            }
+        );
+
+        let error = u16_to_bcd(10000).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::ValueOutOfRange { min: 0, max: 9999 }
+        );
+        let error = bcd_to_registers(123_456, 1, WordOrder::BigEndian).unwrap_err();
+        assert_eq!(
+            error,
+            ConversionError::ValueOutOfRange { min: 0, max: 9999 }
+        );
+        let error = bcd_to_registers(0, 5, WordOrder::BigEndian).unwrap_err();
+        assert!(matches!(
+            error,
+            ConversionError::LengthMismatch { actual: 5, .. }
+        ));
+        let error = scaled_to_register(f64::NAN, 0.1).unwrap_err();
+        assert_eq!(error.to_string(), "Value outside -32768..=32767");
+    }
+
+    #[test]
+    fn conversion_test() {
+        let error = io::Error::from(ConversionError::InvalidBcdDigit(0x00A0));
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "0x00A0 is not BCD");
+        let error = io::Error::from(ConversionError::NotAscii { position: 3 });
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(
+            ConversionError::try_from(error).unwrap(),
+            ConversionError::NotAscii { position: 3 }
+        );
+
+        let error = io::Error::new(io::ErrorKind::TimedOut, "no response");
+        let error = ConversionError::try_from(error).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(error.to_string(), "no response");
+    }
+}
@@ -0,0 +1,457 @@
+//! Conversions between register values and Rust types.
+//!
+//! # Examples
+//!
+//! ```
+//! use easy_modbus::data::{FromRegisters, ToRegisters, WordOrder};
+//! let registers = 0x1234_5678u32.to_registers(WordOrder::BigEndian);
+//! assert_eq!(registers, vec![0x1234, 0x5678]);
+//!
+//! let value = u32::from_registers(&registers, WordOrder::BigEndian).unwrap();
+//! assert_eq!(value, 0x1234_5678);
+//! ```
+//!
+//! With the `derive` feature enabled, [`FromRegisters`] and [`ToRegisters`] can be derived for
+//! a struct whose fields follow each other in the register map, see the `easy-modbus-derive`
+//! crate.
+
+pub use bcd::{bcd_from_registers, bcd_to_registers, bcd_to_u16, u16_to_bcd};
+pub use bitfield::Bitfield16;
+pub use block::RegisterBlock;
+pub use error::ConversionError;
+#[cfg(feature = "derive")]
+pub use easy_modbus_derive::{FromRegisters, ToRegisters};
+pub use register::{FromRegisters, ToRegisters};
+pub use string::{
+    string_from_registers, string_from_registers_with, string_to_registers, utf8_from_registers,
+    CharOrder, InvalidChar,
+};
+
+mod bcd;
+mod bitfield;
+mod block;
+mod error;
+mod register;
+mod string;
+
+/// Order of the 16-bit words of a value spanning several registers
+///
+/// Modbus only defines the byte order inside a single register (big-endian), so devices
+/// disagree about which register carries the most significant word, and some even swap the
+/// bytes of every register. There is no default, the order of a register map has to be stated
+/// explicitly.
+///
+/// The constants name the orders after the layout of a 32-bit value, each letter a byte of the
+/// value, `A` the most significant, in the order they appear in the registers:
+///
+/// | Variant                | Name   | `0x12345678` on the wire | Also known as        |
+/// |------------------------|--------|--------------------------|----------------------|
+/// | `BigEndian`            | `ABCD` | `12 34 56 78`            | big-endian, Motorola |
+/// | `LittleEndian`         | `CDAB` | `56 78 12 34`            | word swap            |
+/// | `BigEndianByteSwap`    | `BADC` | `34 12 78 56`            | byte swap            |
+/// | `LittleEndianByteSwap` | `DCBA` | `78 56 34 12`            | little-endian, Intel |
+///
+/// Siemens S7 and most devices following the specification to the letter use `ABCD`. Schneider
+/// Modicon PLCs and many energy meters put the low word first, `CDAB`. `DCBA` is the memory
+/// layout of little-endian CPUs copied to the registers as is, `BADC` is rare. The manual of
+/// the device has the final word, often under "word swap" or "byte swap" settings.
+///
+/// Every conversion of the crate takes a `WordOrder`: [`FromRegisters`] and [`ToRegisters`],
+/// the `_from_registers` and `_to_registers` functions, the typed getters of read responses and
+/// the typed views of [`DataStore`](crate::server::DataStore). A [`Frame`](crate::Frame) can
+/// remember the order of its register map, see
+/// [`Frame::with_word_order`](crate::Frame::with_word_order).
+///
+/// Wider values keep the same meaning across their full width: [`WordOrder::LittleEndian`]
+/// reverses all four words of a 64-bit value, not each of its 32-bit halves, so
+/// `0x0001_0002_0003_0004` is written `[0x0004, 0x0003, 0x0002, 0x0001]`.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{ToRegisters, WordOrder};
+/// assert_eq!(0x1234_5678u32.to_registers(WordOrder::CDAB), vec![0x5678, 0x1234]);
+/// assert_eq!(0x1234_5678u32.to_registers(WordOrder::BADC), vec![0x3412, 0x7856]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WordOrder {
+    /// Most significant word in the first register
+    BigEndian,
+
+    /// Least significant word in the first register
+    LittleEndian,
+
+    /// Most significant word in the first register, the bytes of every register swapped
+    BigEndianByteSwap,
+
+    /// Least significant word in the first register, the bytes of every register swapped
+    LittleEndianByteSwap,
+}
+
+impl WordOrder {
+    /// [`WordOrder::BigEndian`]
+    pub const ABCD: WordOrder = WordOrder::BigEndian;
+
+    /// [`WordOrder::LittleEndian`]
+    pub const CDAB: WordOrder = WordOrder::LittleEndian;
+
+    /// [`WordOrder::BigEndianByteSwap`]
+    pub const BADC: WordOrder = WordOrder::BigEndianByteSwap;
+
+    /// [`WordOrder::LittleEndianByteSwap`]
+    pub const DCBA: WordOrder = WordOrder::LittleEndianByteSwap;
+
+    /// Whether the least significant word comes first
+    pub(crate) fn is_little_endian(self) -> bool {
+        matches!(self, WordOrder::LittleEndian | WordOrder::LittleEndianByteSwap)
+    }
+
+    /// Whether the bytes of every register are swapped
+    pub(crate) fn is_byte_swapped(self) -> bool {
+        matches!(
+            self,
+            WordOrder::BigEndianByteSwap | WordOrder::LittleEndianByteSwap
+        )
+    }
+}
+
+/// The `f32` held by two registers, in the order they were read
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{f32_from_registers, WordOrder};
+/// assert_eq!(f32_from_registers(&[0x522B, 0x449A], WordOrder::CDAB), 1234.5678);
+/// ```
+pub fn f32_from_registers(registers: &[u16; 2], order: WordOrder) -> f32 {
+    from_registers(registers, order)
+}
+
+/// The two registers holding `value`, in the order they are written
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{f32_to_registers, WordOrder};
+/// assert_eq!(f32_to_registers(1234.5678, WordOrder::DCBA), [0x2B52, 0x9A44]);
+/// ```
+pub fn f32_to_registers(value: f32, order: WordOrder) -> [u16; 2] {
+    to_registers(&value, order)
+}
+
+/// The `u32` held by two registers, in the order they were read
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{u32_from_registers, WordOrder};
+/// assert_eq!(u32_from_registers(&[0x0001, 0x0002], WordOrder::BigEndian), 0x0001_0002);
+/// assert_eq!(u32_from_registers(&[0x0001, 0x0002], WordOrder::LittleEndian), 0x0002_0001);
+/// ```
+pub fn u32_from_registers(registers: &[u16; 2], order: WordOrder) -> u32 {
+    from_registers(registers, order)
+}
+
+/// The two registers holding `value`, in the order they are written
+pub fn u32_to_registers(value: u32, order: WordOrder) -> [u16; 2] {
+    to_registers(&value, order)
+}
+
+/// The `i32` held by two registers, in the order they were read
+pub fn i32_from_registers(registers: &[u16; 2], order: WordOrder) -> i32 {
+    from_registers(registers, order)
+}
+
+/// The two registers holding `value`, in the order they are written
+pub fn i32_to_registers(value: i32, order: WordOrder) -> [u16; 2] {
+    to_registers(&value, order)
+}
+
+/// The `f64` held by four registers, in the order they were read
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{f64_from_registers, WordOrder};
+/// let registers = [0x3958, 0xC8B4, 0x1CD6, 0x40C8];
+/// assert_eq!(f64_from_registers(&registers, WordOrder::LittleEndian), 12345.678);
+/// ```
+pub fn f64_from_registers(registers: &[u16; 4], order: WordOrder) -> f64 {
+    from_registers(registers, order)
+}
+
+/// The four registers holding `value`, in the order they are written
+pub fn f64_to_registers(value: f64, order: WordOrder) -> [u16; 4] {
+    to_registers(&value, order)
+}
+
+/// The `u64` held by four registers, in the order they were read
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{u64_from_registers, WordOrder};
+/// let registers = [0x0000, 0x0000, 0x0001, 0x0002];
+/// assert_eq!(u64_from_registers(&registers, WordOrder::BigEndian), 0x0001_0002);
+/// ```
+pub fn u64_from_registers(registers: &[u16; 4], order: WordOrder) -> u64 {
+    from_registers(registers, order)
+}
+
+/// The four registers holding `value`, in the order they are written
+pub fn u64_to_registers(value: u64, order: WordOrder) -> [u16; 4] {
+    to_registers(&value, order)
+}
+
+/// The `i64` held by four registers, in the order they were read
+pub fn i64_from_registers(registers: &[u16; 4], order: WordOrder) -> i64 {
+    from_registers(registers, order)
+}
+
+/// The four registers holding `value`, in the order they are written
+pub fn i64_to_registers(value: i64, order: WordOrder) -> [u16; 4] {
+    to_registers(&value, order)
+}
+
+/// The engineering value of a register holding a signed count of `scale`
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::scaled_from_register;
+/// // -12.5 °C in tenths of a degree
+/// assert_eq!(scaled_from_register(0xFF83, 0.1), -12.5);
+/// ```
+pub fn scaled_from_register(register: u16, scale: f64) -> f64 {
+    register as i16 as f64 * scale
+}
+
+/// The register holding `value` as a signed count of `scale`
+///
+/// The value is divided by the scale and rounded to the nearest integer, halves away from zero.
+/// Returns [`ConversionError::ValueOutOfRange`], with the range of an `i16`, if the count is
+/// not a number or outside that range.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::scaled_to_register;
+/// use easy_modbus::Frame;
+/// let register = scaled_to_register(-12.5, 0.1).unwrap();
+/// assert_eq!(register, 0xFF83);
+/// let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0010, register);
+/// assert!(scaled_to_register(4000.0, 0.1).is_err());
+/// ```
+pub fn scaled_to_register(value: f64, scale: f64) -> Result<u16, ConversionError> {
+    let count = (value / scale).round();
+    if !(i16::MIN as f64..=i16::MAX as f64).contains(&count) {
+        let (min, max) = (i16::MIN as i64, i16::MAX as i64);
+        return Err(ConversionError::ValueOutOfRange { min, max });
+    }
+    Ok(count as i16 as u16)
+}
+
+/// Read a value from exactly as many registers as it occupies
+fn from_registers<T: FromRegisters>(registers: &[u16], order: WordOrder) -> T {
+    T::from_registers(registers, order).expect("register count of the type")
+}
+
+/// Write a value to exactly as many registers as it occupies
+fn to_registers<T: ToRegisters, const N: usize>(value: &T, order: WordOrder) -> [u16; N] {
+    let registers = value.to_registers(order);
+    registers.try_into().expect("register count of the type")
+}
+
+#[cfg(test)]
+mod word_order_test {
+    use crate::data::{f32_from_registers, f32_to_registers, ToRegisters, WordOrder};
+
+    /// The registers of 1234.5678, `0x449A522B`, in every order
+    const ORDERS: [(WordOrder, [u16; 2]); 4] = [
+        (WordOrder::ABCD, [0x449A, 0x522B]),
+        (WordOrder::CDAB, [0x522B, 0x449A]),
+        (WordOrder::BADC, [0x9A44, 0x2B52]),
+        (WordOrder::DCBA, [0x2B52, 0x9A44]),
+    ];
+
+    #[test]
+    fn f32_test() {
+        assert_eq!(1234.5678f32.to_bits(), 0x449A_522B);
+        for (order, registers) in ORDERS {
+            assert_eq!(f32_from_registers(&registers, order), 1234.5678);
+            assert_eq!(f32_to_registers(1234.5678, order), registers);
+            assert_eq!(1234.5678f32.to_registers(order), registers);
+        }
+    }
+
+    #[test]
+    fn u32_test() {
+        use crate::data::{i32_from_registers, i32_to_registers};
+        use crate::data::{u32_from_registers, u32_to_registers};
+
+        let orders = [
+            (WordOrder::ABCD, [0x1234, 0x5678]),
+            (WordOrder::CDAB, [0x5678, 0x1234]),
+            (WordOrder::BADC, [0x3412, 0x7856]),
+            (WordOrder::DCBA, [0x7856, 0x3412]),
+        ];
+        for (order, registers) in orders {
+            assert_eq!(u32_from_registers(&registers, order), 0x1234_5678);
+            assert_eq!(u32_to_registers(0x1234_5678, order), registers);
+        }
+
+        assert_eq!(i32_from_registers(&[0xFFFF, 0xFFFE], WordOrder::BigEndian), -2);
+        assert_eq!(i32_from_registers(&[0xFFFE, 0xFFFF], WordOrder::LittleEndian), -2);
+        assert_eq!(i32_to_registers(i32::MIN, WordOrder::LittleEndian), [0x0000, 0x8000]);
+    }
+
+    #[test]
+    fn scaled_test() {
+        use crate::data::{scaled_from_register, scaled_to_register};
+
+        assert_eq!(scaled_from_register(0x00D7, 0.1), 21.5);
+        assert_eq!(scaled_from_register(0xFF9C, 0.1), -10.0);
+        assert_eq!(scaled_from_register(0x8000, 1.0), -32768.0);
+        assert_eq!(scaled_from_register(0x7FFF, 0.01), 327.67);
+
+        assert_eq!(scaled_to_register(21.5, 0.1).unwrap(), 0x00D7);
+        assert_eq!(scaled_to_register(-10.0, 0.1).unwrap(), 0xFF9C);
+        assert_eq!(scaled_to_register(-3276.8, 0.1).unwrap(), 0x8000);
+        // Halves away from zero
+        assert_eq!(scaled_to_register(0.25, 0.5).unwrap(), 0x0001);
+        assert_eq!(scaled_to_register(-0.25, 0.5).unwrap(), 0xFFFF);
+        assert_eq!(scaled_to_register(21.54, 0.1).unwrap(), 0x00D7);
+        assert!(scaled_to_register(-3276.9, 0.1).is_err());
+        assert!(scaled_to_register(3276.8, 0.1).is_err());
+        assert!(scaled_to_register(f64::NAN, 0.1).is_err());
+        assert!(scaled_to_register(1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn f64_test() {
+        use crate::data::{f64_from_registers, f64_to_registers};
+
+        // 12345.678 is 0x40C81CD6C8B43958
+        let orders = [
+            (WordOrder::ABCD, [0x40C8, 0x1CD6, 0xC8B4, 0x3958]),
+            (WordOrder::CDAB, [0x3958, 0xC8B4, 0x1CD6, 0x40C8]),
+            (WordOrder::BADC, [0xC840, 0xD61C, 0xB4C8, 0x5839]),
+            (WordOrder::DCBA, [0x5839, 0xB4C8, 0xD61C, 0xC840]),
+        ];
+        for (order, registers) in orders {
+            assert_eq!(f64_from_registers(&registers, order), 12345.678);
+            assert_eq!(f64_to_registers(12345.678, order), registers);
+        }
+    }
+
+    #[test]
+    fn u64_test() {
+        use crate::data::{i64_from_registers, i64_to_registers};
+        use crate::data::{u64_from_registers, u64_to_registers};
+
+        let orders = [
+            (WordOrder::ABCD, [0x0001, 0x0002, 0x0003, 0x0004]),
+            (WordOrder::CDAB, [0x0004, 0x0003, 0x0002, 0x0001]),
+            (WordOrder::BADC, [0x0100, 0x0200, 0x0300, 0x0400]),
+            (WordOrder::DCBA, [0x0400, 0x0300, 0x0200, 0x0100]),
+        ];
+        for (order, registers) in orders {
+            assert_eq!(u64_from_registers(&registers, order), 0x0001_0002_0003_0004);
+            assert_eq!(u64_to_registers(0x0001_0002_0003_0004, order), registers);
+        }
+
+        let registers = [0xFFFE, 0xFFFF, 0xFFFF, 0xFFFF];
+        assert_eq!(i64_from_registers(&registers, WordOrder::LittleEndian), -2);
+        assert_eq!(i64_to_registers(-2, WordOrder::LittleEndian), registers);
+        assert_eq!(i64_to_registers(i64::MIN, WordOrder::BigEndian), [0x8000, 0, 0, 0]);
+    }
+
+    #[test]
+    fn wide_test() {
+        let value = 0x0102_0304_0506_0708u64;
+        let swapped = value.to_registers(WordOrder::BigEndianByteSwap);
+        assert_eq!(swapped, vec![0x0201, 0x0403, 0x0605, 0x0807]);
+        let swapped = value.to_registers(WordOrder::LittleEndianByteSwap);
+        assert_eq!(swapped, vec![0x0807, 0x0605, 0x0403, 0x0201]);
+        assert_eq!(0x1234u16.to_registers(WordOrder::BADC), vec![0x3412]);
+    }
+}
+
+#[cfg(test)]
+mod layout_test {
+    use crate::data::{FromRegisters, ToRegisters, WordOrder};
+
+    /// Bytes on the wire of a value in `order`
+    fn wire<T: ToRegisters>(value: T, order: WordOrder) -> Vec<u8> {
+        value
+            .to_registers(order)
+            .iter()
+            .flat_map(|register| register.to_be_bytes())
+            .collect()
+    }
+
+    /// Value of bytes on the wire in `order`
+    fn value<T: FromRegisters>(bytes: &[u8], order: WordOrder) -> T {
+        let registers: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        T::from_registers(&registers, order).unwrap()
+    }
+
+    #[test]
+    fn f32_test() {
+        // 1234.5678 is 0x449A522B
+        let table: [(WordOrder, [u8; 4]); 4] = [
+            (WordOrder::BigEndian, [0x44, 0x9A, 0x52, 0x2B]),
+            (WordOrder::LittleEndian, [0x52, 0x2B, 0x44, 0x9A]),
+            (WordOrder::BigEndianByteSwap, [0x9A, 0x44, 0x2B, 0x52]),
+            (WordOrder::LittleEndianByteSwap, [0x2B, 0x52, 0x9A, 0x44]),
+        ];
+        for (order, bytes) in table {
+            assert_eq!(wire(1234.5678f32, order), bytes, "{:?}", order);
+            assert_eq!(value::<f32>(&bytes, order), 1234.5678, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn u32_test() {
+        let table: [(WordOrder, [u8; 4]); 4] = [
+            (WordOrder::ABCD, [0x12, 0x34, 0x56, 0x78]),
+            (WordOrder::CDAB, [0x56, 0x78, 0x12, 0x34]),
+            (WordOrder::BADC, [0x34, 0x12, 0x78, 0x56]),
+            (WordOrder::DCBA, [0x78, 0x56, 0x34, 0x12]),
+        ];
+        for (order, bytes) in table {
+            assert_eq!(wire(0x1234_5678u32, order), bytes, "{:?}", order);
+            assert_eq!(value::<u32>(&bytes, order), 0x1234_5678, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn f64_test() {
+        // 12345.678 is 0x40C81CD6C8B43958
+        let table: [(WordOrder, [u8; 8]); 4] = [
+            (
+                WordOrder::BigEndian,
+                [0x40, 0xC8, 0x1C, 0xD6, 0xC8, 0xB4, 0x39, 0x58],
+            ),
+            (
+                WordOrder::LittleEndian,
+                [0x39, 0x58, 0xC8, 0xB4, 0x1C, 0xD6, 0x40, 0xC8],
+            ),
+            (
+                WordOrder::BigEndianByteSwap,
+                [0xC8, 0x40, 0xD6, 0x1C, 0xB4, 0xC8, 0x58, 0x39],
+            ),
+            (
+                WordOrder::LittleEndianByteSwap,
+                [0x58, 0x39, 0xB4, 0xC8, 0xD6, 0x1C, 0xC8, 0x40],
+            ),
+        ];
+        for (order, bytes) in table {
+            assert_eq!(wire(12345.678f64, order), bytes, "{:?}", order);
+            assert_eq!(value::<f64>(&bytes, order), 12345.678, "{:?}", order);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::io::{Error, Result};
+use std::mem::size_of;
+
+use super::{ConversionError, WordOrder};
+
+/// Types which can be read from a block of registers
+///
+/// The extension point of every typed conversion of the crate, implemented for the integer and
+/// float types and for arrays of them. Implement it, and [`ToRegisters`], for a type of your
+/// register map, see [`Mapped`](crate::server::Mapped).
+///
+/// # Laws
+///
+/// For a type implementing both traits, with the same `REGISTER_COUNT`, and any word order:
+///
+/// - `from_registers(&value.to_registers(order), order)` is `Ok(value)`, bit for bit for floats
+/// - `to_registers(&from_registers(registers, order)?, order)` is `registers`
+/// - `registers` of any other length than `REGISTER_COUNT` is an error
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{FromRegisters, WordOrder};
+/// let value = f32::from_registers(&[0x5678, 0x1234], WordOrder::LittleEndian).unwrap();
+/// assert_eq!(value, f32::from_bits(0x1234_5678));
+/// ```
+pub trait FromRegisters: Sized {
+    /// Number of registers the value occupies
+    const REGISTER_COUNT: usize;
+
+    /// Convert exactly `REGISTER_COUNT` registers into a value
+    ///
+    /// * `registers` - Register values, in the order they were read
+    /// * `order` - Word order of the register map
+    fn from_registers(registers: &[u16], order: WordOrder) -> Result<Self>;
+}
+
+/// Types which can be written to a block of registers
+///
+/// `to_registers` returns exactly `REGISTER_COUNT` registers, see the laws of
+/// [`FromRegisters`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{ToRegisters, WordOrder};
+/// let registers = (-2i32).to_registers(WordOrder::BigEndian);
+/// assert_eq!(registers, vec![0xFFFF, 0xFFFE]);
+/// ```
+pub trait ToRegisters {
+    /// Number of registers the value occupies
+    const REGISTER_COUNT: usize;
+
+    /// Convert a value into `REGISTER_COUNT` registers, in the order they are written
+    fn to_registers(&self, order: WordOrder) -> Vec<u16>;
+}
+
+macro_rules! impl_registers {
+    ($($ty:ty),*) => {$(
+        impl FromRegisters for $ty {
+            const REGISTER_COUNT: usize = size_of::<$ty>() / 2;
+
+            fn from_registers(registers: &[u16], order: WordOrder) -> Result<Self> {
+                check_count(registers, <Self as FromRegisters>::REGISTER_COUNT)?;
+                let mut bytes = [0u8; size_of::<$ty>()];
+                for (chunk, register) in bytes.chunks_exact_mut(2).zip(words(registers, order)) {
+                    chunk.copy_from_slice(&register.to_be_bytes());
+                }
+                Ok(<$ty>::from_be_bytes(bytes))
+            }
+        }
+
+        impl ToRegisters for $ty {
+            const REGISTER_COUNT: usize = size_of::<$ty>() / 2;
+
+            fn to_registers(&self, order: WordOrder) -> Vec<u16> {
+                let registers: Vec<u16> = self
+                    .to_be_bytes()
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+                    .collect();
+                words(&registers, order)
+            }
+        }
+    )*};
+}
+
+impl_registers!(u16, i16, u32, i32, u64, i64, f32, f64);
+
+impl<T: FromRegisters, const N: usize> FromRegisters for [T; N] {
+    const REGISTER_COUNT: usize = T::REGISTER_COUNT * N;
+
+    fn from_registers(registers: &[u16], order: WordOrder) -> Result<Self> {
+        check_count(registers, <Self as FromRegisters>::REGISTER_COUNT)?;
+        let values = registers
+            .chunks_exact(T::REGISTER_COUNT)
+            .map(|chunk| T::from_registers(chunk, order))
+            .collect::<Result<Vec<T>>>()?;
+        match values.try_into() {
+            Ok(values) => Ok(values),
+            Err(_) => Err(count_error(registers, <Self as FromRegisters>::REGISTER_COUNT)),
+        }
+    }
+}
+
+impl<T: ToRegisters, const N: usize> ToRegisters for [T; N] {
+    const REGISTER_COUNT: usize = T::REGISTER_COUNT * N;
+
+    fn to_registers(&self, order: WordOrder) -> Vec<u16> {
+        self.iter().flat_map(|value| value.to_registers(order)).collect()
+    }
+}
+
+/// Put big-endian words into the requested word order, or back
+pub(crate) fn words(registers: &[u16], order: WordOrder) -> Vec<u16> {
+    let mut words = registers.to_vec();
+    if order.is_little_endian() {
+        words.reverse();
+    }
+    if order.is_byte_swapped() {
+        words.iter_mut().for_each(|word| *word = word.swap_bytes());
+    }
+    words
+}
+
+fn check_count(registers: &[u16], expected: usize) -> Result<()> {
+    if registers.len() != expected {
+        return Err(count_error(registers, expected));
+    }
+    Ok(())
+}
+
+fn count_error(registers: &[u16], expected: usize) -> Error {
+    ConversionError::LengthMismatch {
+        expected,
+        actual: registers.len(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod register_test {
+    use crate::data::{FromRegisters, ToRegisters, WordOrder};
+
+    #[test]
+    fn u16_test() {
+        assert_eq!(u16::from_registers(&[0xABCD], WordOrder::BigEndian).unwrap(), 0xABCD);
+        assert_eq!(0xABCDu16.to_registers(WordOrder::LittleEndian), vec![0xABCD]);
+        assert_eq!(i16::from_registers(&[0xFFFF], WordOrder::BigEndian).unwrap(), -1);
+    }
+
+    #[test]
+    fn word_order_test() {
+        let big = 0x0102_0304_0506_0708u64.to_registers(WordOrder::BigEndian);
+        assert_eq!(big, vec![0x0102, 0x0304, 0x0506, 0x0708]);
+        let little = 0x0102_0304_0506_0708u64.to_registers(WordOrder::LittleEndian);
+        assert_eq!(little, vec![0x0708, 0x0506, 0x0304, 0x0102]);
+        assert_eq!(
+            u64::from_registers(&little, WordOrder::LittleEndian).unwrap(),
+            0x0102_0304_0506_0708
+        );
+    }
+
+    #[test]
+    fn float_test() {
+        let registers = 1234.5f32.to_registers(WordOrder::BigEndian);
+        assert_eq!(registers, vec![0x449A, 0x5000]);
+        assert_eq!(f32::from_registers(&registers, WordOrder::BigEndian).unwrap(), 1234.5);
+
+        let registers = (-0.25f64).to_registers(WordOrder::LittleEndian);
+        assert_eq!(f64::from_registers(&registers, WordOrder::LittleEndian).unwrap(), -0.25);
+        assert_eq!(i64::from_registers(&[0xFFFF; 4], WordOrder::BigEndian).unwrap(), -1);
+    }
+
+    #[test]
+    fn array_test() {
+        let values = [0x0001_0002u32, 0x0003_0004];
+        let registers = values.to_registers(WordOrder::LittleEndian);
+        assert_eq!(registers, vec![0x0002, 0x0001, 0x0004, 0x0003]);
+        assert_eq!(<[u32; 2] as FromRegisters>::REGISTER_COUNT, 4);
+        let decoded = <[u32; 2]>::from_registers(&registers, WordOrder::LittleEndian).unwrap();
+        assert_eq!(decoded, values);
+    }
+
+    #[test]
+    fn register_count_test() {
+        assert!(u32::from_registers(&[0x0001], WordOrder::BigEndian).is_err());
+        assert!(u32::from_registers(&[0x0001, 0x0002, 0x0003], WordOrder::BigEndian).is_err());
+        assert!(<[u16; 3]>::from_registers(&[0x0001, 0x0002], WordOrder::BigEndian).is_err());
+    }
+
+    /// Check the laws of `FromRegisters` and `ToRegisters` for every type, on a few values
+    macro_rules! laws_test {
+        ($($name:ident: $ty:ty => [$($value:expr),*]),* $(,)?) => {$(
+            #[test]
+            fn $name() {
+                let orders = [
+                    WordOrder::BigEndian,
+                    WordOrder::LittleEndian,
+                    WordOrder::BigEndianByteSwap,
+                    WordOrder::LittleEndianByteSwap,
+                ];
+                let count = <$ty as FromRegisters>::REGISTER_COUNT;
+                assert_eq!(count, <$ty as ToRegisters>::REGISTER_COUNT);
+                for order in orders {
+                    for value in [$($value),*] {
+                        let value: $ty = value;
+                        let registers = value.to_registers(order);
+                        assert_eq!(registers.len(), count);
+                        let decoded = <$ty>::from_registers(&registers, order).unwrap();
+                        assert_eq!(decoded.to_registers(order), registers);
+
+                        let mut longer = registers.clone();
+                        longer.push(0x0000);
+                        assert!(<$ty>::from_registers(&longer, order).is_err());
+                        assert!(<$ty>::from_registers(&registers[1..], order).is_err());
+                    }
+                }
+            }
+        )*};
+    }
+
+    laws_test! {
+        u16_laws_test: u16 => [0, 1, 0xABCD, u16::MAX],
+        i16_laws_test: i16 => [0, -1, i16::MIN, i16::MAX],
+        u32_laws_test: u32 => [0, 0x1234_5678, u32::MAX],
+        i32_laws_test: i32 => [0, -2, i32::MIN, i32::MAX],
+        u64_laws_test: u64 => [0, 0x0102_0304_0506_0708, u64::MAX],
+        i64_laws_test: i64 => [0, -2, i64::MIN, i64::MAX],
+        f32_laws_test: f32 => [0.0, -0.0, 1234.5678, f32::MIN_POSITIVE, f32::INFINITY, f32::NAN],
+        f64_laws_test: f64 => [0.0, -0.25, 12345.678, f64::MAX, f64::NEG_INFINITY, f64::NAN],
+        array_laws_test: [u16; 3] => [[0, 0, 0], [1, 2, 3], [u16::MAX; 3]],
+        nested_array_laws_test: [[i32; 2]; 2] => [[[1, -1], [i32::MIN, i32::MAX]]],
+    }
+}
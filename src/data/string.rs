@@ -0,0 +1,206 @@
+use super::ConversionError;
+
+/// Order of the two characters of a string packed into a register
+///
+/// Most devices put the first character in the high byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CharOrder {
+    /// First character in the high byte
+    HighFirst,
+
+    /// First character in the low byte
+    LowFirst,
+}
+
+/// What decoding a string does with a byte which is not ASCII
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InvalidChar {
+    /// Put the character in its place
+    Replace(char),
+
+    /// Leave the byte out
+    Skip,
+}
+
+impl Default for InvalidChar {
+    /// [`char::REPLACEMENT_CHARACTER`] in place of every byte which is not ASCII
+    fn default() -> Self {
+        InvalidChar::Replace(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+/// The ASCII string packed two characters per register
+///
+/// The string ends at the first NUL, trailing spaces are stripped. Bytes which are not ASCII
+/// are replaced by [`char::REPLACEMENT_CHARACTER`], see [`string_from_registers_with`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{string_from_registers, CharOrder};
+/// let registers = [0x4D42, 0x2D31, 0x3220, 0x2020];
+/// assert_eq!(string_from_registers(&registers, CharOrder::HighFirst), "MB-12");
+/// let registers = [0x424D, 0x312D, 0x0032];
+/// assert_eq!(string_from_registers(&registers, CharOrder::LowFirst), "MB-12");
+/// ```
+pub fn string_from_registers(registers: &[u16], order: CharOrder) -> String {
+    string_from_registers_with(registers, order, InvalidChar::default())
+}
+
+/// The ASCII string packed two characters per register, `invalid` deciding about the bytes
+/// which are not ASCII
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{string_from_registers_with, CharOrder, InvalidChar};
+/// let registers = [0x41FF, 0x4200];
+/// let string = string_from_registers_with(&registers, CharOrder::HighFirst, InvalidChar::Skip);
+/// assert_eq!(string, "AB");
+/// ```
+pub fn string_from_registers_with(
+    registers: &[u16],
+    order: CharOrder,
+    invalid: InvalidChar,
+) -> String {
+    let mut string: String = registers
+        .iter()
+        .flat_map(|register| chars(*register, order))
+        .take_while(|byte| *byte != 0)
+        .filter_map(|byte| match (byte.is_ascii(), invalid) {
+            (true, _) => Some(byte as char),
+            (false, InvalidChar::Replace(c)) => Some(c),
+            (false, InvalidChar::Skip) => None,
+        })
+        .collect();
+    string.truncate(string.trim_end_matches(' ').len());
+    string
+}
+
+/// The UTF-8 string packed two bytes per register
+///
+/// Like [`string_from_registers`], for devices storing text in UTF-8: the string ends at the
+/// first NUL, trailing spaces are stripped. Returns [`ConversionError::InvalidUtf8`] for bytes
+/// which are not UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{utf8_from_registers, CharOrder, ConversionError};
+/// let registers = [0x4E20, 0x3DC2, 0xB020, 0x0000];
+/// assert_eq!(utf8_from_registers(&registers, CharOrder::HighFirst).unwrap(), "N =°");
+/// let error = utf8_from_registers(&[0x41FF], CharOrder::HighFirst).unwrap_err();
+/// assert_eq!(error, ConversionError::InvalidUtf8 { valid_up_to: 1 });
+/// ```
+pub fn utf8_from_registers(registers: &[u16], order: CharOrder) -> Result<String, ConversionError> {
+    let bytes = registers
+        .iter()
+        .flat_map(|register| chars(*register, order))
+        .take_while(|byte| *byte != 0)
+        .collect();
+    let mut string = String::from_utf8(bytes).map_err(|e| ConversionError::InvalidUtf8 {
+        valid_up_to: e.utf8_error().valid_up_to(),
+    })?;
+    string.truncate(string.trim_end_matches(' ').len());
+    Ok(string)
+}
+
+/// The `len` registers holding the ASCII string `string`, packed two characters per register
+///
+/// The string is padded with NUL up to `len` registers. Returns
+/// [`ConversionError::NotAscii`] for a string which is not ASCII,
+/// [`ConversionError::StringTooLong`] for one longer than `2 * len` characters.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::{string_to_registers, CharOrder};
+/// let registers = string_to_registers("MB-1", 3, CharOrder::HighFirst).unwrap();
+/// assert_eq!(registers, vec![0x4D42, 0x2D31, 0x0000]);
+/// assert!(string_to_registers("MB-12", 2, CharOrder::HighFirst).is_err());
+/// ```
+pub fn string_to_registers(
+    string: &str,
+    len: usize,
+    order: CharOrder,
+) -> Result<Vec<u16>, ConversionError> {
+    if let Some(position) = string.bytes().position(|byte| !byte.is_ascii()) {
+        return Err(ConversionError::NotAscii { position });
+    }
+    if string.len() > 2 * len {
+        let (len, max) = (string.len(), 2 * len);
+        return Err(ConversionError::StringTooLong { len, max });
+    }
+    let mut bytes = string.as_bytes().to_vec();
+    bytes.resize(2 * len, 0);
+    let registers = bytes
+        .chunks_exact(2)
+        .map(|pair| match order {
+            CharOrder::HighFirst => u16::from_be_bytes([pair[0], pair[1]]),
+            CharOrder::LowFirst => u16::from_le_bytes([pair[0], pair[1]]),
+        })
+        .collect();
+    Ok(registers)
+}
+
+/// The two characters of a register, in string order
+fn chars(register: u16, order: CharOrder) -> [u8; 2] {
+    match order {
+        CharOrder::HighFirst => register.to_be_bytes(),
+        CharOrder::LowFirst => register.to_le_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod string_test {
+    use crate::data::{string_from_registers, string_from_registers_with, string_to_registers};
+    use crate::data::{utf8_from_registers, CharOrder, ConversionError, InvalidChar};
+
+    #[test]
+    fn round_trip_test() {
+        for order in [CharOrder::HighFirst, CharOrder::LowFirst] {
+            for string in ["", "A", "AB", "SN 0042-X", "odd"] {
+                let registers = string_to_registers(string, 5, order).unwrap();
+                assert_eq!(registers.len(), 5);
+                assert_eq!(string_from_registers(&registers, order), string);
+            }
+        }
+        let registers = string_to_registers("ABC", 2, CharOrder::LowFirst).unwrap();
+        assert_eq!(registers, vec![0x4241, 0x0043]);
+    }
+
+    #[test]
+    fn decode_test() {
+        let order = CharOrder::HighFirst;
+        // Space padding, and whatever follows the NUL
+        assert_eq!(string_from_registers(&[0x4142, 0x2020], order), "AB");
+        assert_eq!(string_from_registers(&[0x4120, 0x4200], order), "A B");
+        assert_eq!(string_from_registers(&[0x4100, 0x4243], order), "A");
+        assert_eq!(string_from_registers(&[], order), "");
+
+        let registers = [0x41C3, 0xA942];
+        assert_eq!(string_from_registers(&registers, order), "A\u{FFFD}\u{FFFD}B");
+        let string = string_from_registers_with(&registers, order, InvalidChar::Replace('?'));
+        assert_eq!(string, "A??B");
+        let string = string_from_registers_with(&registers, order, InvalidChar::Skip);
+        assert_eq!(string, "AB");
+    }
+
+    #[test]
+    fn utf8_test() {
+        let order = CharOrder::LowFirst;
+        assert_eq!(utf8_from_registers(&[0xA9C3, 0x2020], order).unwrap(), "é");
+        assert_eq!(utf8_from_registers(&[0x0041, 0xA9C3], order).unwrap(), "A");
+        let error = utf8_from_registers(&[0x4241, 0x00C3], order).unwrap_err();
+        assert_eq!(error, ConversionError::InvalidUtf8 { valid_up_to: 2 });
+    }
+
+    #[test]
+    fn encode_error_test() {
+        let error = string_to_registers("Aé", 4, CharOrder::HighFirst).unwrap_err();
+        assert_eq!(error, ConversionError::NotAscii { position: 1 });
+        let error = string_to_registers("ABC", 1, CharOrder::HighFirst).unwrap_err();
+        assert_eq!(error, ConversionError::StringTooLong { len: 3, max: 2 });
+        assert!(string_to_registers("AB", 1, CharOrder::HighFirst).is_ok());
+    }
+}
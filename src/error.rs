@@ -0,0 +1,274 @@
+//! Structured decoding errors, exception responses, and [`WrongVariant`] of the conversions of
+//! a response.
+//!
+//! The codecs fail with [`std::io::Error`], as `tokio_util` codecs do. A decoding error
+//! carries a [`ModbusError`] telling its cause, recovered with `ModbusError::from`.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use tokio_util::codec::Decoder;
+//! use easy_modbus::ModbusError;
+//! use easy_modbus::codec::RtuClientCodec;
+//!
+//! let mut src = BytesMut::from(&[0x0B, 0x04, 0x02, 0x10, 0x2F, 0x00, 0x00][..]);
+//! let error = RtuClientCodec::default().decode(&mut src).unwrap_err();
+//! assert!(matches!(ModbusError::from(error), ModbusError::Crc { got: 0x0000, .. }));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+use std::io;
+use std::io::ErrorKind;
+
+use crate::frame::{Exception, Function};
+
+/// Cause of a decoding failure, or of a request answered with an exception
+#[derive(Debug)]
+pub enum ModbusError {
+    /// A RTU frame failed its CRC check, `got` is the CRC received
+    Crc { expected: u16, got: u16 },
+
+    /// The function code isn't supported
+    UnknownFunction(u8),
+
+    /// The exception code of an exception response isn't defined
+    UnknownException(u8),
+
+    /// The body of a frame is too short for the fields of its function
+    Incomplete,
+
+    /// The device answered a request for `function` with `exception`
+    Exception {
+        function: Function,
+        exception: Exception,
+    },
+
+    /// Any other failure
+    Io(io::Error),
+}
+
+impl ModbusError {
+    /// The [`ErrorKind`] of the [`io::Error`] carrying this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ModbusError::Crc { .. }
+            | ModbusError::UnknownException(_)
+            | ModbusError::Incomplete => ErrorKind::InvalidData,
+            ModbusError::UnknownFunction(_) => Exception::IllegalFunction.as_error_kind(),
+            ModbusError::Exception { exception, .. } => exception.as_error_kind(),
+            ModbusError::Io(error) => error.kind(),
+        }
+    }
+
+    /// Whether the request may succeed when sent again later
+    ///
+    /// True for an exception the device may not answer with next time, see
+    /// [`Exception::is_retriable`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Exception, Function, ModbusError};
+    /// let error = ModbusError::Exception {
+    ///     function: Function::ReadCoils,
+    ///     exception: Exception::SlaveDeviceBusy,
+    /// };
+    /// assert!(error.is_retriable());
+    /// assert!(!ModbusError::Incomplete.is_retriable());
+    /// ```
+    pub fn is_retriable(&self) -> bool {
+        matches!(self, ModbusError::Exception { exception, .. } if exception.is_retriable())
+    }
+}
+
+impl fmt::Display for ModbusError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ModbusError::Crc { expected, got } => write!(
+                f,
+                "Invalid crc code: 0x{:0>2X}, expected 0x{:0>2X}",
+                got, expected
+            ),
+            ModbusError::UnknownFunction(code) => {
+                write!(f, "Invalid function code: 0x{:0>2X}", code)
+            }
+            ModbusError::UnknownException(code) => {
+                write!(f, "Invalid Exception code: 0x{:0>2X}", code)
+            }
+            ModbusError::Incomplete => write!(f, "Incomplete frame"),
+            ModbusError::Exception {
+                function,
+                exception,
+            } => write!(
+                f,
+                "Exception on {:?}: {:?} (0x{:0>2X})",
+                function,
+                exception,
+                exception.to_code()
+            ),
+            ModbusError::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl Error for ModbusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ModbusError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ModbusError> for io::Error {
+    fn from(error: ModbusError) -> Self {
+        match error {
+            ModbusError::Io(error) => error,
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
+
+impl From<io::Error> for ModbusError {
+    fn from(error: io::Error) -> Self {
+        if !matches!(error.get_ref(), Some(inner) if inner.is::<ModbusError>()) {
+            return ModbusError::Io(error);
+        }
+        match error
+            .into_inner()
+            .map(|inner| inner.downcast::<ModbusError>())
+        {
+            Some(Ok(error)) => *error,
+            _ => unreachable!("checked to carry a ModbusError"),
+        }
+    }
+}
+
+/// A [`Response`](crate::Response) converted into a payload it doesn't carry
+///
+/// Names the variants the conversion accepts and the variant the response was.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::Frame;
+/// let response = Frame::tcp().write_single_coil_response(0x01, 0x0010, 0xFF00);
+/// let error = response.into_registers().unwrap_err();
+/// assert_eq!(error.get_actual(), "WriteSingleCoil");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WrongVariant {
+    /// Variants the conversion accepts
+    pub(crate) expected: &'static [&'static str],
+
+    /// Variant of the response
+    pub(crate) actual: &'static str,
+}
+
+impl WrongVariant {
+    pub fn get_expected(&self) -> &'static [&'static str] {
+        self.expected
+    }
+
+    pub fn get_actual(&self) -> &'static str {
+        self.actual
+    }
+}
+
+impl fmt::Display for WrongVariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Expected {} response, got {}",
+            self.expected.join(" or "),
+            self.actual
+        )
+    }
+}
+
+impl Error for WrongVariant {}
+
+impl From<WrongVariant> for io::Error {
+    fn from(error: WrongVariant) -> Self {
+        io::Error::new(ErrorKind::InvalidData, error)
+    }
+}
+
+#[cfg(test)]
+mod error_test {
+    use std::io;
+
+    use bytes::BytesMut;
+    use tokio_util::codec::Decoder;
+
+    use crate::codec::{RtuClientCodec, TcpClientCodec};
+    use crate::{Exception, Function, ModbusError};
+
+    #[test]
+    fn crc_test() {
+        let mut src = BytesMut::from(&[0x0B, 0x01, 0x04, 0xCD, 0x6B, 0xB2, 0x7F, 0x00, 0x00][..]);
+        let error = RtuClientCodec::default().decode(&mut src).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::Crc {
+                expected: 0x2BE1,
+                got: 0x0000
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_function_test() {
+        let v = [0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x01, 0x2B, 0x0E];
+        let error = TcpClientCodec::default()
+            .decode(&mut BytesMut::from(&v[..]))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Invalid function code: 0x2B");
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::UnknownFunction(0x2B)
+        ));
+    }
+
+    #[test]
+    fn conversion_test() {
+        let error = io::Error::new(io::ErrorKind::TimedOut, "no response");
+        let error = ModbusError::from(error);
+        assert!(matches!(&error, ModbusError::Io(e) if e.kind() == io::ErrorKind::TimedOut));
+        let error = io::Error::from(error);
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+
+        let error = io::Error::from(ModbusError::UnknownException(0x07));
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(matches!(
+            ModbusError::from(error),
+            ModbusError::UnknownException(0x07)
+        ));
+    }
+
+    #[test]
+    fn exception_test() {
+        let error = io::Error::from(ModbusError::Exception {
+            function: Function::ReadMultipleHoldingRegisters,
+            exception: Exception::IllegalDataAddress,
+        });
+        assert_eq!(error.kind(), io::ErrorKind::AddrNotAvailable);
+        assert_eq!(
+            error.to_string(),
+            "Exception on ReadMultipleHoldingRegisters: IllegalDataAddress (0x02)"
+        );
+        let error = ModbusError::from(error);
+        assert!(matches!(
+            error,
+            ModbusError::Exception {
+                function: Function::ReadMultipleHoldingRegisters,
+                exception: Exception::IllegalDataAddress,
+            }
+        ));
+        assert!(!error.is_retriable());
+    }
+}
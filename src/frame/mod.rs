@@ -1,12 +1,24 @@
 use std::collections::HashMap;
-use std::io::ErrorKind;
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
 use std::sync::Mutex;
 
+use bytes::BytesMut;
+
+use crate::data::{FromRegisters, WordOrder};
 use crate::frame::request::*;
 use crate::frame::response::*;
+use crate::util::bits;
+use crate::limits::{
+    MAX_READ_COILS, MAX_READ_DISCRETE_INPUTS, MAX_READ_REGISTERS, MAX_READ_WRITE_READ_REGISTERS,
+    MAX_READ_WRITE_WRITE_REGISTERS, MAX_WRITE_COILS, MAX_WRITE_REGISTERS,
+};
+
+pub use range::AddressRange;
 
 pub mod request;
 pub mod response;
+mod range;
 
 /// Modbus Frame
 #[derive(Debug)]
@@ -16,6 +28,9 @@ pub struct Frame {
 
     /// Tid Buffer
     tid_map: Mutex<HashMap<u8, u16>>,
+
+    /// Word order of the register map, for the conversions not given one
+    word_order: Option<WordOrder>,
 }
 
 impl Frame {
@@ -33,6 +48,7 @@ impl Frame {
         Frame {
             version: Version::Tcp,
             tid_map: Mutex::new(HashMap::new()),
+            word_order: None,
         }
     }
 
@@ -50,6 +66,83 @@ impl Frame {
         Frame {
             version: Version::Rtu,
             tid_map: Mutex::new(HashMap::new()),
+            word_order: None,
+        }
+    }
+
+    /// The frame with `order` as the word order of every conversion not given one
+    ///
+    /// For talking to devices of one register map. The word order also covers the byte order
+    /// inside the registers, see [`WordOrder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// use easy_modbus::data::WordOrder;
+    /// let frame = Frame::tcp().with_word_order(WordOrder::CDAB);
+    /// assert_eq!(frame.word_order(), Some(WordOrder::CDAB));
+    /// ```
+    pub fn with_word_order(mut self, order: WordOrder) -> Frame {
+        self.word_order = Some(order);
+        self
+    }
+
+    /// The word order set by [`with_word_order`](Self::with_word_order), none by default
+    pub fn word_order(&self) -> Option<WordOrder> {
+        self.word_order
+    }
+
+    /// The two registers of a register read response as one `u32`, in the word order of the
+    /// frame
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a frame without a word order or a response
+    /// not reading registers, [`ErrorKind::InvalidData`] unless exactly two registers were
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// use easy_modbus::data::WordOrder;
+    /// let frame = Frame::tcp().with_word_order(WordOrder::CDAB);
+    /// let response = frame.read_holding_register_response(0x01, vec![0x56, 0x78, 0x12, 0x34]);
+    /// assert_eq!(frame.as_u32(&response).unwrap(), 0x1234_5678);
+    /// assert!(Frame::tcp().as_u32(&response).is_err());
+    /// ```
+    pub fn as_u32(&self, response: &Response) -> Result<u32> {
+        self.only_value(response)
+    }
+
+    /// The two registers of a register read response as one `f32`, in the word order of the
+    /// frame
+    ///
+    /// See [`as_u32`](Self::as_u32).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// use easy_modbus::data::WordOrder;
+    /// let frame = Frame::tcp().with_word_order(WordOrder::CDAB);
+    /// let response = frame.read_input_register_response(0x01, vec![0x52, 0x2B, 0x44, 0x9A]);
+    /// assert_eq!(frame.as_f32(&response).unwrap(), 1234.5678);
+    /// ```
+    pub fn as_f32(&self, response: &Response) -> Result<f32> {
+        self.only_value(response)
+    }
+
+    /// The value held by all registers of a register read response, in the frame's word order
+    fn only_value<T: FromRegisters>(&self, response: &Response) -> Result<T> {
+        let order = self.word_order.ok_or_else(|| {
+            Error::new(ErrorKind::InvalidInput, "No word order set on the frame")
+        })?;
+        match response.register_values() {
+            Some(values) => only_value(values, order),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} does not read registers", response.describe()),
+            )),
         }
     }
 
@@ -72,6 +165,46 @@ impl Frame {
         Request::ReadCoils(head, request_body)
     }
 
+    /// Create a read coils request (Function Code: 0x01) for a range of coils
+    ///
+    /// * `unit_id` -  Server address
+    /// * `range` - Coils to read
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Frame};
+    /// let range = AddressRange::new(0x02, 0x08).unwrap();
+    /// let request = Frame::tcp().read_coils_in(0x01, range);
+    /// ```
+    pub fn read_coils_in(&self, unit_id: u8, range: AddressRange) -> Request {
+        self.read_coils_request(unit_id, range.start, range.count)
+    }
+
+    /// Create a read coils request (Function Code: 0x01), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `number` of zero or above
+    /// [`MAX_READ_COILS`](crate::limits::MAX_READ_COILS), or coils past address `0xFFFF`, all
+    /// of them answered with [`Exception::IllegalDataValue`] or
+    /// [`Exception::IllegalDataAddress`] by a conformant server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// assert!(Frame::tcp().try_read_coils_request(0x01, 0x02, 0x08).is_ok());
+    /// assert!(Frame::tcp().try_read_coils_request(0x01,  0x02, 0).is_err());
+    /// ```
+    pub fn try_read_coils_request(
+        &self,
+        unit_id: u8,
+        first_address: u16,
+        number: u16,
+    ) -> std::io::Result<Request> {
+        let range = checked_range(first_address, number, MAX_READ_COILS)?;
+        Ok(self.read_coils_in(unit_id, range))
+    }
+
     /// Create a read discrete Request (Function Code: 0x02)
     ///
     /// * `unit_id` -  Server address
@@ -91,6 +224,46 @@ impl Frame {
         Request::ReadDiscreteInputs(head, request_body)
     }
 
+    /// Create a read discrete Request (Function Code: 0x02) for a range of discrete inputs
+    ///
+    /// * `unit_id` -  Server address
+    /// * `range` - Discrete inputs to read
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Frame};
+    /// let range = AddressRange::new(0x007A, 0x001C).unwrap();
+    /// let request = Frame::tcp().read_discrete_in(0x0B, range);
+    /// ```
+    pub fn read_discrete_in(&self, unit_id: u8, range: AddressRange) -> Request {
+        self.read_discrete_request(unit_id, range.start, range.count)
+    }
+
+    /// Create a read discrete inputs request (Function Code: 0x02), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `number` of zero or above
+    /// [`MAX_READ_DISCRETE_INPUTS`](crate::limits::MAX_READ_DISCRETE_INPUTS), or discrete inputs
+    /// past address `0xFFFF`, all of them answered with [`Exception::IllegalDataValue`] or
+    /// [`Exception::IllegalDataAddress`] by a conformant server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// assert!(Frame::tcp().try_read_discrete_request(0x0B, 0x007A, 0x001C).is_ok());
+    /// assert!(Frame::tcp().try_read_discrete_request(0x0B,  0x007A, 0).is_err());
+    /// ```
+    pub fn try_read_discrete_request(
+        &self,
+        unit_id: u8,
+        first_address: u16,
+        number: u16,
+    ) -> std::io::Result<Request> {
+        let range = checked_range(first_address, number, MAX_READ_DISCRETE_INPUTS)?;
+        Ok(self.read_discrete_in(unit_id, range))
+    }
+
     /// Create a read multiple holding registers request (Function Code: 0x03)
     ///
     /// * `unit_id` -  Server address
@@ -115,6 +288,47 @@ impl Frame {
         Request::ReadMultipleHoldingRegisters(head, request_body)
     }
 
+    /// Create a read multiple holding registers request (Function Code: 0x03) for a range of
+    /// registers
+    ///
+    /// * `unit_id` -  Server address
+    /// * `range` - Registers to read
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Frame};
+    /// let range = AddressRange::new(0x006F, 0x0003).unwrap();
+    /// let request = Frame::tcp().read_multiple_holding_registers_in(0x0B, range);
+    /// ```
+    pub fn read_multiple_holding_registers_in(&self, unit_id: u8, range: AddressRange) -> Request {
+        self.read_multiple_holding_registers_request(unit_id, range.start, range.count)
+    }
+
+    /// Create a read registers request (Function Code: 0x03), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `number` of zero or above
+    /// [`MAX_READ_REGISTERS`](crate::limits::MAX_READ_REGISTERS), or registers past address
+    /// `0xFFFF`, all of them answered with [`Exception::IllegalDataValue`] or
+    /// [`Exception::IllegalDataAddress`] by a conformant server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// assert!(Frame::tcp().try_read_multiple_holding_registers_request(0x0B, 0x006F, 0x0003).is_ok());
+    /// assert!(Frame::tcp().try_read_multiple_holding_registers_request(0x0B,  0x006F, 0).is_err());
+    /// ```
+    pub fn try_read_multiple_holding_registers_request(
+        &self,
+        unit_id: u8,
+        first_address: u16,
+        number: u16,
+    ) -> std::io::Result<Request> {
+        let range = checked_range(first_address, number, MAX_READ_REGISTERS)?;
+        Ok(self.read_multiple_holding_registers_in(unit_id, range))
+    }
+
     /// Create a read input registers request (Function Code: 0x04)
     ///
     /// * `unit_id` -  Server address
@@ -139,6 +353,46 @@ impl Frame {
         Request::ReadInputRegisters(head, request_body)
     }
 
+    /// Create a read input registers request (Function Code: 0x04) for a range of registers
+    ///
+    /// * `unit_id` -  Server address
+    /// * `range` - Registers to read
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Frame};
+    /// let range = AddressRange::new(0x000A, 0x0001).unwrap();
+    /// let request = Frame::tcp().read_input_registers_in(0x0B, range);
+    /// ```
+    pub fn read_input_registers_in(&self, unit_id: u8, range: AddressRange) -> Request {
+        self.read_input_registers_request(unit_id, range.start, range.count)
+    }
+
+    /// Create a read registers request (Function Code: 0x04), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `number` of zero or above
+    /// [`MAX_READ_REGISTERS`](crate::limits::MAX_READ_REGISTERS), or registers past address
+    /// `0xFFFF`, all of them answered with [`Exception::IllegalDataValue`] or
+    /// [`Exception::IllegalDataAddress`] by a conformant server.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// assert!(Frame::tcp().try_read_input_registers_request(0x0B, 0x000A, 0x0001).is_ok());
+    /// assert!(Frame::tcp().try_read_input_registers_request(0x0B,  0x000A, 0).is_err());
+    /// ```
+    pub fn try_read_input_registers_request(
+        &self,
+        unit_id: u8,
+        first_address: u16,
+        number: u16,
+    ) -> std::io::Result<Request> {
+        let range = checked_range(first_address, number, MAX_READ_REGISTERS)?;
+        Ok(self.read_input_registers_in(unit_id, range))
+    }
+
     /// Create a write single coil request (Function Code: 0x05)
     ///
     /// * `unit_id` -  Server address
@@ -189,6 +443,11 @@ impl Frame {
     /// * `coils_number` - Number of coils to write
     /// * `values` - Coil values. Value of each coil is binary (0 for off, 1 for on).
     ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold. See
+    /// [`Frame::try_write_multiple_coils_request`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -213,12 +472,128 @@ impl Frame {
         Request::WriteMultipleCoils(head, request_body)
     }
 
+    /// Create a write multiple coils request (Function Code: 0x0F), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `coils_number` of zero or above
+    /// [`MAX_WRITE_COILS`](crate::limits::MAX_WRITE_COILS), coils past address `0xFFFF`, or
+    /// `values` of another length than the `coils_number` coils take.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let frame = Frame::tcp();
+    /// assert!(frame.try_write_multiple_coils_request(0x0B, 0x1B, 9, vec![0x4D, 0x01]).is_ok());
+    /// assert!(frame.try_write_multiple_coils_request(0x0B, 0x1B, 9, vec![0x4D]).is_err());
+    /// assert!(frame.try_write_multiple_coils_request(0x0B, 0x1B, 2048, vec![0xFF; 256]).is_err());
+    /// ```
+    pub fn try_write_multiple_coils_request(
+        &self,
+        unit_id: u8,
+        address: u16,
+        coils_number: u16,
+        values: Vec<u8>,
+    ) -> std::io::Result<Request> {
+        checked_range(address, coils_number, MAX_WRITE_COILS)?;
+        let bytes_number = (coils_number as usize).div_ceil(8);
+        if values.len() != bytes_number {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{} value bytes for {} coils, expected {}",
+                    values.len(),
+                    coils_number,
+                    bytes_number
+                ),
+            ));
+        }
+        Ok(self.write_multiple_coils_request(unit_id, address, coils_number, values))
+    }
+
+    /// Create a write multiple coils request from coil states (Function Code: 0x0F)
+    ///
+    /// * `unit_id` -  Server address
+    /// * `address` - Address of the first coil to write
+    /// * `coils` - Coil states, packed least significant bit first like
+    ///   [`util::bits::pack`](crate::util::bits::pack)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let coils = [true, false, true, true, false, false, true, false, true];
+    /// let request = Frame::tcp().write_multiple_coils_request_from_bools(0x0B, 0x001B, &coils);
+    /// let expected = Frame::tcp().write_multiple_coils_request(0x0B, 0x001B, 9, vec![0x4D, 0x01]);
+    /// assert_eq!(request, expected);
+    /// ```
+    pub fn write_multiple_coils_request_from_bools(
+        &self,
+        unit_id: u8,
+        address: u16,
+        coils: &[bool],
+    ) -> Request {
+        let coils_number = coils.len() as u16;
+        self.write_multiple_coils_request(unit_id, address, coils_number, bits::pack(coils))
+    }
+
+    /// Create the write multiple coils requests writing `coils`, as few as the limit allows
+    ///
+    /// A request writes at most [`MAX_WRITE_COILS`](crate::limits::MAX_WRITE_COILS) coils, so
+    /// longer slices are split into consecutive requests, each starting at the address of its
+    /// first coil. No request is created for an empty slice.
+    ///
+    /// * `unit_id` -  Server address
+    /// * `address` - Address of the first coil to write
+    /// * `coils` - Coil values, in address order
+    ///
+    /// # Panics
+    ///
+    /// If the coils extend past address `0xFFFF`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Request};
+    /// let requests = Frame::tcp().write_multiple_coils_chunked(0x0B, 0x0000, &[true; 2000]);
+    /// assert_eq!(requests.len(), 2);
+    /// if let Request::WriteMultipleCoils(_, body) = &requests[1] {
+    ///     assert_eq!(*body.first_address(), 1968);
+    ///     assert_eq!(*body.coils_number(), 32);
+    /// }
+    /// ```
+    pub fn write_multiple_coils_chunked(
+        &self,
+        unit_id: u8,
+        address: u16,
+        coils: &[bool],
+    ) -> Vec<Request> {
+        assert!(
+            address as usize + coils.len() <= 0x10000,
+            "{} coils from address {:#06X} extend past 0xFFFF",
+            coils.len(),
+            address
+        );
+        coils
+            .chunks(MAX_WRITE_COILS as usize)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let first_address = address + (i * MAX_WRITE_COILS as usize) as u16;
+                self.write_multiple_coils_request_from_bools(unit_id, first_address, chunk)
+            })
+            .collect()
+    }
+
     /// Create a write multiple coils request (Function Code: 0x10)
     ///
     /// * `unit_id` -  Server address
     /// * `address` - Address of first holding registers to write
     /// * `values` - New values of holding registers
     ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold. See
+    /// [`Frame::try_write_multiple_holding_registers_request`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -241,12 +616,135 @@ impl Frame {
         Request::WriteMultipleHoldingRegisters(head, request_body)
     }
 
+    /// Create a write multiple registers request (Function Code: 0x10), checking the quantity
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for `values` of odd length, of no register or of
+    /// more than [`MAX_WRITE_REGISTERS`](crate::limits::MAX_WRITE_REGISTERS), or registers past
+    /// address `0xFFFF`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let frame = Frame::tcp();
+    /// for (values, ok) in [(vec![0x0B, 0x0A], true), (vec![0x0B], false), (vec![0; 256], false)] {
+    ///     let request = frame.try_write_multiple_holding_registers_request(0x0B, 0x12, values);
+    ///     assert_eq!(request.is_ok(), ok);
+    /// }
+    /// ```
+    pub fn try_write_multiple_holding_registers_request(
+        &self,
+        unit_id: u8,
+        address: u16,
+        values: Vec<u8>,
+    ) -> std::io::Result<Request> {
+        if !values.len().is_multiple_of(2) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} value bytes are no whole registers", values.len()),
+            ));
+        }
+        let registers_number = u16::try_from(values.len() / 2).unwrap_or(u16::MAX);
+        checked_range(address, registers_number, MAX_WRITE_REGISTERS)?;
+        Ok(self.write_multiple_holding_registers_request(unit_id, address, values))
+    }
+
+    /// Create a read/write multiple registers request (Function Code: 0x17)
+    ///
+    /// The write is performed before the read.
+    ///
+    /// * `unit_id` -  Server address
+    /// * `read_address` - Address of first holding register to read
+    /// * `read_number` - Number of holding registers to read
+    /// * `write_address` - Address of first holding register to write
+    /// * `values` - New values of holding registers
+    ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::tcp().read_write_multiple_registers_request(
+    ///     0x11,
+    ///     0x0003,
+    ///     0x0006,
+    ///     0x000E,
+    ///     vec![0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF],
+    /// );
+    /// ```
+    pub fn read_write_multiple_registers_request(
+        &self,
+        unit_id: u8,
+        read_address: u16,
+        read_number: u16,
+        write_address: u16,
+        values: Vec<u8>,
+    ) -> Request {
+        let function = Function::ReadWriteMultipleRegisters;
+        let request_body =
+            ReadWriteMultipleRegistersRequest::new(read_address, read_number, write_address, values);
+        let head = self.head(unit_id, function, request_body.len(), false);
+        Request::ReadWriteMultipleRegisters(head, request_body)
+    }
+
+    /// Create a read/write multiple registers request (Function Code: 0x17), checking the
+    /// quantities
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `read_number` of zero or above
+    /// [`MAX_READ_WRITE_READ_REGISTERS`](crate::limits::MAX_READ_WRITE_READ_REGISTERS), for
+    /// `values` of odd length, of no register or of more than
+    /// [`MAX_READ_WRITE_WRITE_REGISTERS`](crate::limits::MAX_READ_WRITE_WRITE_REGISTERS), or for
+    /// registers past address `0xFFFF`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let frame = Frame::tcp();
+    /// let values = vec![0x00, 0xFF];
+    /// assert!(frame.try_read_write_multiple_registers_request(0x11, 3, 6, 14, values).is_ok());
+    /// let values = vec![0x00; 2 * 122];
+    /// assert!(frame.try_read_write_multiple_registers_request(0x11, 3, 6, 14, values).is_err());
+    /// ```
+    pub fn try_read_write_multiple_registers_request(
+        &self,
+        unit_id: u8,
+        read_address: u16,
+        read_number: u16,
+        write_address: u16,
+        values: Vec<u8>,
+    ) -> std::io::Result<Request> {
+        if !values.len().is_multiple_of(2) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} value bytes are no whole registers", values.len()),
+            ));
+        }
+        checked_range(read_address, read_number, MAX_READ_WRITE_READ_REGISTERS)?;
+        let write_number = u16::try_from(values.len() / 2).unwrap_or(u16::MAX);
+        checked_range(write_address, write_number, MAX_READ_WRITE_WRITE_REGISTERS)?;
+        Ok(self.read_write_multiple_registers_request(
+            unit_id,
+            read_address,
+            read_number,
+            write_address,
+            values,
+        ))
+    }
+
     /// Create a read coils response (Function Code: 0x01)
     ///
     /// * `unit_id` -  Server address
     /// * `values` - Coil input values, Values of each coil input is binary (0 for off, 1 for on).
-    /// First requested coil input is as least significant bit of first byte in reply. If number of
-    /// coils inputs is not a multiple of 8, most significant bits of last byte will be stuffed zeros.
+    ///   First requested coil input is as least significant bit of first byte in reply. If number of
+    ///   coils inputs is not a multiple of 8, most significant bits of last byte will be stuffed zeros.
+    ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
     ///
     /// # Examples
     ///
@@ -261,11 +759,36 @@ impl Frame {
         Response::ReadCoils(head, response_body)
     }
 
+    /// Create a read coils response from coil states (Function Code: 0x01)
+    ///
+    /// * `unit_id` - Server address
+    /// * `coils` - Coil states, packed with the first coil as the least significant bit of the
+    ///   first byte
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 2040 `coils`, packing into more bytes than a byte count holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_coils_response_from_bools(0x0B, &[true, false, true, true]);
+    /// assert_eq!(response, Frame::tcp().read_coils_response(0x0B, vec![0x0D]));
+    /// ```
+    pub fn read_coils_response_from_bools(&self, unit_id: u8, coils: &[bool]) -> Response {
+        self.read_coils_response(unit_id, bits::pack(coils))
+    }
+
     /// Create a read discrete response (Function Code: 0x02)
     ///
     /// * `unit_id` - Server address
     /// * `values` - Discrete input values
     ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
+    ///
     /// # Examples
     ///
     /// ```
@@ -279,11 +802,35 @@ impl Frame {
         Response::ReadDiscreteInputs(head, response_body)
     }
 
+    /// Create a read discrete response from input states (Function Code: 0x02)
+    ///
+    /// * `unit_id` - Server address
+    /// * `inputs` - Discrete input states, packed like [`Frame::read_coils_response_from_bools`]
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 2040 `inputs`, packing into more bytes than a byte count holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_discrete_response_from_bools(0x0B, &[false, true]);
+    /// assert_eq!(response, Frame::tcp().read_discrete_response(0x0B, vec![0x02]));
+    /// ```
+    pub fn read_discrete_response_from_bools(&self, unit_id: u8, inputs: &[bool]) -> Response {
+        self.read_discrete_response(unit_id, bits::pack(inputs))
+    }
+
     /// Create a read holding register response (Function Code: 0x03)
     ///
     /// * `unit_id` - Server address
     /// * `values` - Discrete input values
     ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
+    ///
     /// # Examples
     ///
     /// ```
@@ -297,24 +844,76 @@ impl Frame {
         Response::ReadMultipleHoldingRegisters(head, response_body)
     }
 
-    /// Create a read input register response (Function Code: 0x04)
+    /// Create a read holding register response from register values (Function Code: 0x03)
     ///
     /// * `unit_id` - Server address
-    /// * `values` - Register values
+    /// * `registers` - Register values, each serialized big-endian
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 127 `registers`, taking more bytes than a byte count holds.
     ///
     /// # Examples
     ///
     /// ```
     /// use easy_modbus::Frame;
-    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x10, 0x2F]);
+    /// let response = Frame::tcp().read_holding_registers_response_from_u16(0x01, &[0x0021, 0x0000]);
+    /// assert_eq!(response, Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x21, 0x00, 0x00]));
     /// ```
-    pub fn read_input_register_response(&self, unit_id: u8, values: Vec<u8>) -> Response {
-        let function = Function::ReadInputRegisters;
-        let response_body = ReadInputRegistersResponse::new(values);
+    pub fn read_holding_registers_response_from_u16(
+        &self,
+        unit_id: u8,
+        registers: &[u16],
+    ) -> Response {
+        self.read_holding_register_response(unit_id, pack_registers(registers))
+    }
+
+    /// Create a read input register response (Function Code: 0x04)
+    ///
+    /// * `unit_id` - Server address
+    /// * `values` - Register values
+    ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x10, 0x2F]);
+    /// ```
+    pub fn read_input_register_response(&self, unit_id: u8, values: Vec<u8>) -> Response {
+        let function = Function::ReadInputRegisters;
+        let response_body = ReadInputRegistersResponse::new(values);
         let head = self.head(unit_id, function, response_body.len(), false);
         Response::ReadInputRegisters(head, response_body)
     }
 
+    /// Create a read input register response from register values (Function Code: 0x04)
+    ///
+    /// * `unit_id` - Server address
+    /// * `registers` - Register values, each serialized big-endian
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 127 `registers`, taking more bytes than a byte count holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_input_registers_response_from_u16(0x01, &[0x102F]);
+    /// assert_eq!(response, Frame::tcp().read_input_register_response(0x01, vec![0x10, 0x2F]));
+    /// ```
+    pub fn read_input_registers_response_from_u16(
+        &self,
+        unit_id: u8,
+        registers: &[u16],
+    ) -> Response {
+        self.read_input_register_response(unit_id, pack_registers(registers))
+    }
+
     /// Create a write single coil response (Function Code: 0x05)
     ///
     /// * `unit_id` - Server address
@@ -405,6 +1004,142 @@ impl Frame {
         Response::WriteMultipleHoldingRegisters(head, response_body)
     }
 
+    /// Create a read/write multiple registers response (Function Code: 0x17)
+    ///
+    /// * `unit_id` - Server address
+    /// * `values` - Values of the holding registers read
+    ///
+    /// # Panics
+    ///
+    /// If `values` is longer than 255 bytes, which its byte count can't hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_write_multiple_registers_response(
+    ///     0x11,
+    ///     vec![0x00, 0xFE, 0x0A, 0xCD, 0x00, 0x01],
+    /// );
+    /// ```
+    pub fn read_write_multiple_registers_response(&self, unit_id: u8, values: Vec<u8>) -> Response {
+        let function = Function::ReadWriteMultipleRegisters;
+        let response_body = ReadWriteMultipleRegistersResponse::new(values);
+        let head = self.head(unit_id, function, response_body.len(), false);
+        Response::ReadWriteMultipleRegisters(head, response_body)
+    }
+
+    /// Create a request of a custom function
+    ///
+    /// * `unit_id` - Server address
+    /// * `code` - Function code, of a function this crate doesn't model
+    /// * `data` - Body following the function code, encoded as is
+    ///
+    /// Panics for a code of a modeled function, or of an exception. A codec only decodes the
+    /// custom functions registered with it, see
+    /// [`TcpServerCodec::with_custom_function`](crate::codec::TcpServerCodec).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Request};
+    /// let request = Frame::tcp().custom_request(0x0B, 0x41, vec![0x01, 0x02, 0x03]);
+    /// assert_eq!(request.head().get_length(), 5);
+    /// assert!(matches!(request, Request::Custom(_, _)));
+    /// ```
+    pub fn custom_request(&self, unit_id: u8, code: u8, data: Vec<u8>) -> Request {
+        let function = custom_function(code);
+        let request_body = CustomRequest::new(data);
+        let head = self.head(unit_id, function, request_body.len(), false);
+        Request::Custom(head, request_body)
+    }
+
+    /// Create a mask write register request (Function Code: 0x16)
+    ///
+    /// The server sets the register to `(current & and_mask) | (or_mask & !and_mask)`. The
+    /// function isn't modeled, the request is a [`Request::Custom`] and a server codec decodes
+    /// it once registered with [`BodyLength::Fixed(6)`](crate::codec::BodyLength).
+    ///
+    /// * `unit_id` -  Server address
+    /// * `address` - Address of the holding register to modify
+    /// * `and_mask` - Bits of the register kept
+    /// * `or_mask` - Bits set, among the ones not kept
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::tcp().mask_write_register_request(0x0B, 0x0004, 0x00F2, 0x0025);
+    /// assert_eq!(&request.pdu_bytes()[..], &[0x16, 0x00, 0x04, 0x00, 0xF2, 0x00, 0x25]);
+    /// ```
+    pub fn mask_write_register_request(
+        &self,
+        unit_id: u8,
+        address: u16,
+        and_mask: u16,
+        or_mask: u16,
+    ) -> Request {
+        let data = pack_registers(&[address, and_mask, or_mask]);
+        self.custom_request(unit_id, MASK_WRITE_REGISTER, data)
+    }
+
+    /// Create a mask write register request (Function Code: 0x16) setting or clearing one bit
+    ///
+    /// Every other bit of the register is kept: `and_mask` is `!(1 << bit)`, and `or_mask` is
+    /// `1 << bit` to set the bit or `0x0000` to clear it. See
+    /// [`Frame::mask_write_register_request`].
+    ///
+    /// * `unit_id` -  Server address
+    /// * `address` - Address of the holding register to modify
+    /// * `bit` - Index of the bit, 0 the least significant
+    /// * `on` - Set the bit, or clear it
+    ///
+    /// # Panics
+    ///
+    /// If `bit` is above 15.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::tcp().set_register_bit_request(0x0B, 0x0004, 3, true);
+    /// let expected = Frame::tcp().mask_write_register_request(0x0B, 0x0004, 0xFFF7, 0x0008);
+    /// assert_eq!(request, expected);
+    /// ```
+    pub fn set_register_bit_request(
+        &self,
+        unit_id: u8,
+        address: u16,
+        bit: u8,
+        on: bool,
+    ) -> Request {
+        assert!(bit < 16, "bit {} is past the 16 bits of a register", bit);
+        let mask = 1u16 << bit;
+        let or_mask = if on { mask } else { 0x0000 };
+        self.mask_write_register_request(unit_id, address, !mask, or_mask)
+    }
+
+    /// Create a response of a custom function
+    ///
+    /// * `unit_id` - Server address
+    /// * `code` - Function code, of a function this crate doesn't model
+    /// * `data` - Body following the function code, encoded as is
+    ///
+    /// Panics like [`Frame::custom_request`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::rtu().custom_response(0x0B, 0x41, vec![0x00]);
+    /// ```
+    pub fn custom_response(&self, unit_id: u8, code: u8, data: Vec<u8>) -> Response {
+        let function = custom_function(code);
+        let response_body = CustomResponse::new(data);
+        let head = self.head(unit_id, function, response_body.len(), false);
+        Response::Custom(head, response_body)
+    }
+
     /// Create a exception response
     ///
     /// * `unit_id` - Server address
@@ -432,6 +1167,39 @@ impl Frame {
         Response::Exception(head, response_body)
     }
 
+    /// Encode several requests back-to-back into one buffer
+    ///
+    /// Every request gets a fresh transaction identifier from this frame before it is encoded,
+    /// so pipelined requests can be told apart by their responses. The identifier is written
+    /// back into `items`, whose heads then match the frames sent, see
+    /// [`filter_tid`](crate::client::filter_tid). The whole batch can then be written to the
+    /// transport at once.
+    ///
+    /// * `items` - Requests to encode, in sending order
+    /// * `dst` - Buffer the encoded frames are appended to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use easy_modbus::Frame;
+    /// let frame = Frame::tcp();
+    /// let mut requests = vec![
+    ///     frame.read_coils_request(0x01, 0x0000, 0x0008),
+    ///     frame.read_input_registers_request(0x01, 0x0000, 0x0002),
+    /// ];
+    /// let mut buf = BytesMut::new();
+    /// frame.encode_batch(&mut requests, &mut buf);
+    /// assert_eq!(buf.len(), 24);
+    /// assert_eq!(requests[1].head().get_tid(), 4);
+    /// ```
+    pub fn encode_batch(&self, items: &mut [Request], dst: &mut BytesMut) {
+        for request in items {
+            let head = request.head_mut();
+            head.tid = self.get_tid(head.uid);
+            request_to_bytesmut(request, dst);
+        }
+    }
 
     /// Build modbus message head
     fn head(&self, uid: u8, function: Function, body_length: u16, is_exception: bool) -> Head {
@@ -453,14 +1221,8 @@ impl Frame {
 
         let mut map = self.tid_map.lock().unwrap();
         let value = match map.get(&unit_id) {
-            None => 1,
-            Some(v) => {
-                if v < &0xFFFF {
-                    v + 1
-                } else {
-                    1
-                }
-            }
+            Some(v) if v < &0xFFFF => v + 1,
+            _ => 1,
         };
         map.insert(unit_id, value);
         value
@@ -477,6 +1239,24 @@ pub enum Version {
     Rtu,
 }
 
+/// Modbus message head
+///
+/// The MBAP header for TCP, or the address and function code for RTU.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::{Exception, Frame, Function, Head, Request, Response, Version};
+/// let head = Head::new(0x0001, 0x0A, Function::ReadCoils, 4, Version::Tcp, false);
+///
+/// let frame = Frame::tcp();
+/// let request = frame.read_coils_request(0x0A, 0x0000, 0x0008);
+/// assert!(matches!(request, Request::ReadCoils(h, _) if h == head));
+///
+/// let response =
+///     frame.exception_response(0x0A, Function::ReadCoils, Exception::IllegalDataAddress);
+/// assert!(matches!(response, Response::Exception(..)));
+/// ```
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Head {
     /// Transaction Identifier
@@ -502,7 +1282,7 @@ pub struct Head {
 }
 
 /// Exception types
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Exception {
     /// Code 1
     ///
@@ -519,21 +1299,74 @@ pub enum Exception {
     /// Value is not accepted by server
     IllegalDataValue,
 
-    /// Code 5
+    /// Code 4
     ///
     /// Unrecoverable error occurred while server was attempting to perform requested action
     SlaveDeviceFailure,
 
-    /// Code 6
+    /// Code 5
     ///
     /// Server has accepted request and is processing it, but a long duration of time is required.
     /// This response is returned to prevent a timeout error from occurring in the client. client
     /// can next issue a Poll Program Complete message to determine whether processing is completed
     Acknowledge,
+
+    /// Code 6
+    ///
+    /// Server is engaged in processing a long-duration command, client should retry later
+    SlaveDeviceBusy,
+
+    /// Code 8
+    ///
+    /// Server detected a parity error in the memory while reading a record file
+    MemoryParityError,
+
+    /// Code 10
+    ///
+    /// Gateway was unable to allocate a path from the input port to the output port for the
+    /// target device
+    GatewayPathUnavailable,
+
+    /// Code 11
+    ///
+    /// Gateway did not get a response from the target device
+    GatewayTargetDeviceFailedToRespond,
 }
 
 impl Exception {
-    pub(crate) fn to_code(&self) -> u8 {
+    /// Whether the request may succeed when sent again later
+    ///
+    /// True for [`Exception::Acknowledge`], the server is still processing the request,
+    /// [`Exception::SlaveDeviceBusy`], the server is processing another one, and
+    /// [`Exception::GatewayTargetDeviceFailedToRespond`], the device behind a gateway missed
+    /// the request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Exception;
+    /// assert!(Exception::Acknowledge.is_retriable());
+    /// assert!(!Exception::IllegalFunction.is_retriable());
+    /// ```
+    pub fn is_retriable(&self) -> bool {
+        matches!(
+            self,
+            Exception::Acknowledge
+                | Exception::SlaveDeviceBusy
+                | Exception::GatewayTargetDeviceFailedToRespond
+        )
+    }
+
+    /// The exception code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Exception;
+    /// const BUSY: u8 = Exception::SlaveDeviceBusy.to_code();
+    /// assert_eq!(BUSY, 0x06);
+    /// ```
+    pub const fn to_code(self) -> u8 {
         use Exception::*;
         match self {
             IllegalFunction => 0x01,
@@ -541,22 +1374,41 @@ impl Exception {
             IllegalDataValue => 0x03,
             SlaveDeviceFailure => 0x04,
             Acknowledge => 0x05,
+            SlaveDeviceBusy => 0x06,
+            MemoryParityError => 0x08,
+            GatewayPathUnavailable => 0x0A,
+            GatewayTargetDeviceFailedToRespond => 0x0B,
         }
     }
-    pub(crate) fn from_code(code: u8) -> Option<Exception> {
+
+    /// The exception of an exception code, `None` for an undefined one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Exception;
+    /// assert_eq!(Exception::from_code(0x02), Some(Exception::IllegalDataAddress));
+    /// assert_eq!(Exception::from_code(0x07), None);
+    /// ```
+    pub const fn from_code(code: u8) -> Option<Exception> {
         use Exception::*;
         let exception = match code {
-            0x01 => IllegalDataValue,
+            0x01 => IllegalFunction,
             0x02 => IllegalDataAddress,
             0x03 => IllegalDataValue,
             0x04 => SlaveDeviceFailure,
             0x05 => Acknowledge,
+            0x06 => SlaveDeviceBusy,
+            0x08 => MemoryParityError,
+            0x0A => GatewayPathUnavailable,
+            0x0B => GatewayTargetDeviceFailedToRespond,
             _ => {
                 return None;
             }
         };
         Some(exception)
     }
+
     pub(crate) fn as_error_kind(&self) -> ErrorKind {
         use Exception::*;
         match self {
@@ -564,13 +1416,52 @@ impl Exception {
             IllegalDataAddress => ErrorKind::AddrNotAvailable,
             IllegalDataValue => ErrorKind::InvalidData,
             SlaveDeviceFailure => ErrorKind::Interrupted,
-            Acknowledge => ErrorKind::WouldBlock,
+            Acknowledge | SlaveDeviceBusy => ErrorKind::WouldBlock,
+            MemoryParityError => ErrorKind::Other,
+            GatewayPathUnavailable => ErrorKind::NotConnected,
+            GatewayTargetDeviceFailedToRespond => ErrorKind::TimedOut,
+        }
+    }
+}
+
+/// The PDU of an encoded frame, the function code and data without the MBAP header or the
+/// unit id and CRC
+pub(crate) fn strip_adu(mut frame: BytesMut, version: Version) -> BytesMut {
+    match version {
+        Version::Tcp => frame.split_off(7),
+        Version::Rtu => {
+            frame.truncate(frame.len() - 2);
+            frame.split_off(1)
         }
     }
 }
 
+/// The range read by a request, rejecting quantities outside `1..=max`
+fn checked_range(first_address: u16, number: u16, max: u16) -> std::io::Result<AddressRange> {
+    if number > max {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} exceeds the limit of {} per request", number, max),
+        ));
+    }
+    AddressRange::new(first_address, number)
+}
+
+/// The byte count of `len` value bytes, panicking for more than a byte count holds
+pub(crate) fn byte_count(len: usize) -> u8 {
+    match u8::try_from(len) {
+        Ok(bytes_number) => bytes_number,
+        Err(_) => panic!("{} value bytes exceed the byte count limit of 255", len),
+    }
+}
+
+/// Every register big-endian
+pub(crate) fn pack_registers(registers: &[u16]) -> Vec<u8> {
+    registers.iter().flat_map(|register| register.to_be_bytes()).collect()
+}
+
 /// Modbus functions
-#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Function {
     ReadCoils,
     ReadDiscreteInputs,
@@ -580,14 +1471,31 @@ pub enum Function {
     WriteSingleHoldingRegister,
     WriteMultipleCoils,
     WriteMultipleHoldingRegisters,
+    ReadWriteMultipleRegisters,
+
+    /// A function this crate doesn't model, by its code
+    ///
+    /// Its body is opaque, see [`Frame::custom_request`]. Only decoded by a codec it is
+    /// registered with, see [`BodyLength`](crate::codec::BodyLength).
+    Custom(u8),
 }
 
-trait Length {
+pub(crate) trait Length {
     fn len(&self) -> u16;
 }
 
 impl Function {
-    pub(crate) fn to_code(&self) -> u8 {
+    /// The function code
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Function;
+    /// const READ_COILS: u8 = Function::ReadCoils.to_code();
+    /// assert_eq!(READ_COILS, 0x01);
+    /// assert_eq!(Function::Custom(0x41).to_code(), 0x41);
+    /// ```
+    pub const fn to_code(self) -> u8 {
         use Function::*;
         match self {
             ReadCoils => 0x01,
@@ -598,8 +1506,161 @@ impl Function {
             WriteSingleHoldingRegister => 0x06,
             WriteMultipleCoils => 0x0F,
             WriteMultipleHoldingRegisters => 0x10,
+            ReadWriteMultipleRegisters => 0x17,
+            Custom(code) => code,
         }
     }
+
+    /// Every modeled function, in function code order
+    ///
+    /// [`Function::Custom`] is left out, it stands for the codes of functions this crate
+    /// doesn't model.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Function;
+    /// let codes: Vec<u8> = Function::all().iter().map(|function| function.to_code()).collect();
+    /// assert_eq!(codes, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x0F, 0x10, 0x17]);
+    /// ```
+    pub const fn all() -> &'static [Function] {
+        use Function::*;
+        &[
+            ReadCoils,
+            ReadDiscreteInputs,
+            ReadMultipleHoldingRegisters,
+            ReadInputRegisters,
+            WriteSingleCoil,
+            WriteSingleHoldingRegister,
+            WriteMultipleCoils,
+            WriteMultipleHoldingRegisters,
+            ReadWriteMultipleRegisters,
+        ]
+    }
+
+    /// The modeled function of a function code, `None` for any other code
+    ///
+    /// Never returns [`Function::Custom`], which only a codec it is registered with decodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Function;
+    /// assert_eq!(Function::from_code(0x10), Some(Function::WriteMultipleHoldingRegisters));
+    /// assert_eq!(Function::from_code(0x41), None);
+    /// ```
+    pub const fn from_code(code: u8) -> Option<Function> {
+        use Function::*;
+        let function = match code {
+            0x01 => ReadCoils,
+            0x02 => ReadDiscreteInputs,
+            0x03 => ReadMultipleHoldingRegisters,
+            0x04 => ReadInputRegisters,
+            0x05 => WriteSingleCoil,
+            0x06 => WriteSingleHoldingRegister,
+            0x0F => WriteMultipleCoils,
+            0x10 => WriteMultipleHoldingRegisters,
+            0x17 => ReadWriteMultipleRegisters,
+            _ => return None,
+        };
+        Some(function)
+    }
+}
+
+/// Function code of Mask Write Register, built as a custom function
+const MASK_WRITE_REGISTER: u8 = 0x16;
+
+/// The custom function of `code`, panicking for a modeled function or an exception
+pub(crate) fn custom_function(code: u8) -> Function {
+    assert!(code < 0x80, "0x{:0>2X} is an exception code", code);
+    assert!(
+        Function::from_code(code).is_none(),
+        "0x{:0>2X} is the code of a modeled function",
+        code
+    );
+    Function::Custom(code)
+}
+
+/// Any function code byte, supported by [`Function`] or not
+///
+/// Preserves the raw value, for logging traffic which may carry functions this crate doesn't
+/// implement. The functions of the Modbus application protocol specification are named.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::{Function, FunctionCode};
+/// let code = FunctionCode::new(0x2B);
+/// assert_eq!(code.name(), Some("Encapsulated Interface Transport"));
+/// assert_eq!(code.to_function(), None);
+/// assert_eq!(code.to_string(), "function 0x2B (Encapsulated Interface Transport)");
+/// assert_eq!(FunctionCode::new(0x03).to_function(), Some(Function::ReadMultipleHoldingRegisters));
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct FunctionCode(u8);
+
+impl FunctionCode {
+    pub fn new(code: u8) -> FunctionCode {
+        FunctionCode(code)
+    }
+
+    pub fn get_code(&self) -> u8 {
+        self.0
+    }
+
+    /// The name of the function in the specification, `None` for an undefined code
+    pub fn name(&self) -> Option<&'static str> {
+        let name = match self.0 {
+            0x01 => "Read Coils",
+            0x02 => "Read Discrete Inputs",
+            0x03 => "Read Holding Registers",
+            0x04 => "Read Input Registers",
+            0x05 => "Write Single Coil",
+            0x06 => "Write Single Register",
+            0x07 => "Read Exception Status",
+            0x08 => "Diagnostics",
+            0x0B => "Get Comm Event Counter",
+            0x0C => "Get Comm Event Log",
+            0x0F => "Write Multiple Coils",
+            0x10 => "Write Multiple Registers",
+            0x11 => "Report Server ID",
+            0x14 => "Read File Record",
+            0x15 => "Write File Record",
+            0x16 => "Mask Write Register",
+            0x17 => "Read/Write Multiple Registers",
+            0x18 => "Read FIFO Queue",
+            0x2B => "Encapsulated Interface Transport",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    /// The supported function of this code, `None` if it isn't implemented
+    pub fn to_function(&self) -> Option<Function> {
+        Function::from_code(self.0)
+    }
+}
+
+impl From<u8> for FunctionCode {
+    fn from(code: u8) -> Self {
+        FunctionCode(code)
+    }
+}
+
+impl From<Function> for FunctionCode {
+    fn from(function: Function) -> Self {
+        FunctionCode(function.to_code())
+    }
+}
+
+impl fmt::Display for FunctionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "function 0x{:0>2X}", self.0)?;
+        if let Some(name) = self.name() {
+            write!(f, " ({})", name)?;
+        }
+        Ok(())
+    }
 }
 
 impl Head {
@@ -625,6 +1686,89 @@ impl Head {
     pub fn body_length(&mut self, body_length: u16) {
         self.length = body_length + 2;
     }
+
+    /// Convert to the head of a TCP frame with the given transaction and protocol identifiers
+    ///
+    /// The unit id, function and length are kept, the frame is encoded with a MBAP header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Function, Head, Version};
+    /// let head = Head::new(0x0000, 0x0B, Function::ReadCoils, 4, Version::Rtu, false);
+    /// let head = head.to_tcp(0x0001, 0x0000);
+    /// assert_eq!(head, Head::new(0x0001, 0x0B, Function::ReadCoils, 4, Version::Tcp, false));
+    /// ```
+    pub fn to_tcp(mut self, tid: u16, pid: u16) -> Head {
+        self.version = Version::Tcp;
+        self.tid = tid;
+        self.pid = pid;
+        self
+    }
+
+    /// Convert to the head of a RTU frame, clearing the transaction and protocol identifiers
+    ///
+    /// The unit id, function and length are kept, the frame is encoded with a CRC.
+    pub fn to_rtu(mut self) -> Head {
+        self.version = Version::Rtu;
+        self.tid = 0;
+        self.pid = 0;
+        self
+    }
+
+    pub fn get_tid(&self) -> u16 {
+        self.tid
+    }
+
+    pub fn get_pid(&self) -> u16 {
+        self.pid
+    }
+
+    pub fn get_length(&self) -> u16 {
+        self.length
+    }
+
+    pub fn get_uid(&self) -> u8 {
+        self.uid
+    }
+
+    pub fn get_function(&self) -> Function {
+        self.function
+    }
+
+    pub fn get_version(&self) -> Version {
+        self.version
+    }
+
+    pub fn is_exception(&self) -> bool {
+        self.is_exception
+    }
+}
+
+#[test]
+fn test_encode_batch() {
+    use tokio_util::codec::Decoder;
+
+    use crate::codec::TcpServerCodec;
+
+    let builder = Frame::tcp();
+    let mut requests = vec![
+        builder.read_coils_request(0x01, 0x0002, 0x0008),
+        builder.read_discrete_request(0x01, 0x007A, 0x001C),
+        builder.read_multiple_holding_registers_request(0x01, 0x006F, 0x0003),
+    ];
+    let frame = Frame::tcp();
+    let mut buf = BytesMut::new();
+    frame.encode_batch(&mut requests, &mut buf);
+    let tids: Vec<u16> = requests.iter().map(|r| r.head().get_tid()).collect();
+    assert_eq!(tids, vec![1, 2, 3]);
+
+    let mut codec = TcpServerCodec::default();
+    for expected in &requests {
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(&decoded, expected);
+    }
+    assert!(buf.is_empty());
 }
 
 #[test]
@@ -641,3 +1785,326 @@ fn test_head() {
     };
     assert_eq!(head_l, head_r);
 }
+
+#[test]
+fn test_head_conversion() {
+    use crate::codec::decode_rtu_request;
+
+    let v = [0x0B, 0x01, 0x00, 0x13, 0x00, 0x25, 0x0C, 0xBE];
+    let request = decode_rtu_request(&mut BytesMut::from(&v[..])).unwrap().unwrap();
+    let Request::ReadCoils(head, body) = request else {
+        panic!("not a read coils request");
+    };
+
+    let request = Request::ReadCoils(head.clone().to_tcp(0x0102, 0x0000), body.clone());
+    let mut buf = BytesMut::new();
+    request_to_bytesmut(&request, &mut buf);
+    let v_r = [
+        0x01, 0x02, 0x00, 0x00, 0x00, 0x06, 0x0B, 0x01, 0x00, 0x13, 0x00, 0x25,
+    ];
+    assert_eq!(&buf[..], &v_r[..]);
+
+    let request = Request::ReadCoils(request.head().clone().to_rtu(), body);
+    let mut buf = BytesMut::new();
+    request_to_bytesmut(&request, &mut buf);
+    assert_eq!(&buf[..], &v[..]);
+    assert_eq!(request.head(), &head);
+}
+
+#[test]
+fn test_read_in_range() {
+    let range = AddressRange::new(0x0002, 0x0008).unwrap();
+    let request_l = Frame::tcp().read_coils_in(0x01, range);
+    let request_r = Frame::tcp().read_coils_request(0x01, 0x0002, 0x0008);
+    assert_eq!(request_l, request_r);
+
+    let request_l = Frame::rtu().read_input_registers_in(0x0B, range);
+    let request_r = Frame::rtu().read_input_registers_request(0x0B, 0x0002, 0x0008);
+    assert_eq!(request_l, request_r);
+}
+
+#[test]
+fn test_try_read_request() {
+    let frame = Frame::tcp();
+    assert!(frame.try_read_coils_request(0x01, 0x0000, 0).is_err());
+    assert!(frame.try_read_discrete_request(0x01, 0x0000, 0).is_err());
+    assert!(frame.try_read_multiple_holding_registers_request(0x01, 0x0000, 0).is_err());
+    let error = frame.try_read_input_registers_request(0x01, 0x0000, 0).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+    assert!(frame.try_read_coils_request(0x01, 0x0000, 2001).is_err());
+    assert!(frame.try_read_discrete_request(0x01, 0x0000, 2001).is_err());
+    assert!(frame.try_read_multiple_holding_registers_request(0x01, 0x0000, 126).is_err());
+    assert!(frame.try_read_input_registers_request(0x01, 0xFFFF, 2).is_err());
+
+    // Rejected requests consume no transaction identifier
+    let request = frame.try_read_coils_request(0x01, 0x0000, 2000).unwrap();
+    assert_eq!(request, Frame::tcp().read_coils_request(0x01, 0x0000, 2000));
+    let request = frame.try_read_input_registers_request(0x01, 0xFFFF, 1).unwrap();
+    assert_eq!(request.head().get_tid(), 2);
+}
+
+#[test]
+fn test_try_write_request() {
+    let frame = Frame::tcp();
+    let error = frame.try_write_multiple_coils_request(0x01, 0x0000, 2048, vec![0xFF; 256]);
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert!(frame.try_write_multiple_coils_request(0x01, 0x0000, 1969, vec![0xFF; 247]).is_err());
+    assert!(frame.try_write_multiple_coils_request(0x01, 0x0000, 0, vec![]).is_err());
+    assert!(frame.try_write_multiple_coils_request(0x01, 0xFFFF, 2, vec![0x03]).is_err());
+    let request = frame.try_write_multiple_coils_request(0x01, 0x0000, 1968, vec![0xFF; 246]);
+    assert!(request.is_ok());
+
+    let error = frame.try_write_multiple_holding_registers_request(0x01, 0x0000, vec![0; 256]);
+    assert_eq!(error.unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert!(frame.try_write_multiple_holding_registers_request(0x01, 0x0000, vec![0; 3]).is_err());
+    assert!(frame.try_write_multiple_holding_registers_request(0x01, 0x0000, vec![]).is_err());
+    let request = frame.try_write_multiple_holding_registers_request(0x01, 0x0000, vec![0; 246]);
+    assert_eq!(request.unwrap().head().get_tid(), 2);
+}
+
+#[test]
+fn test_try_read_write_request() {
+    let frame = Frame::tcp();
+    for (read_number, values) in [(0, 2), (126, 2), (125, 0), (125, 3), (125, 2 * 122)] {
+        let request =
+            frame.try_read_write_multiple_registers_request(0x01, 0, read_number, 0, vec![0; values]);
+        assert_eq!(request.unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+    assert!(frame
+        .try_read_write_multiple_registers_request(0x01, 0xFFFF, 2, 0, vec![0; 2])
+        .is_err());
+    assert!(frame
+        .try_read_write_multiple_registers_request(0x01, 0, 1, 0xFFFF, vec![0; 4])
+        .is_err());
+    let request = frame.try_read_write_multiple_registers_request(0x01, 0, 125, 0, vec![0; 242]);
+    assert_eq!(request.unwrap().head().get_tid(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_write_request_byte_count_overflow() {
+    Frame::tcp().write_multiple_holding_registers_request(0x01, 0x0000, vec![0; 256]);
+}
+
+#[test]
+fn test_read_response_byte_count() {
+    let frame = Frame::tcp();
+    let response = frame.read_holding_registers_response_from_u16(0x01, &[0; 127]);
+    assert_eq!(response.pdu_bytes()[1], 254);
+    let response = frame.read_coils_response_from_bools(0x01, &[true; 2040]);
+    assert_eq!(response.pdu_bytes()[1], 255);
+}
+
+#[test]
+#[should_panic]
+fn test_read_response_byte_count_overflow() {
+    Frame::tcp().read_input_registers_response_from_u16(0x01, &[0; 128]);
+}
+
+#[test]
+fn test_write_multiple_coils_chunked() {
+    let mut coils = vec![false; 2000];
+    coils[0] = true;
+    coils[1968] = true;
+    coils[1999] = true;
+    let requests = Frame::tcp().write_multiple_coils_chunked(0x0B, 0x0010, &coils);
+    assert_eq!(requests.len(), 2);
+    let expected = [(0x0010, 1968, 246, 0x01), (0x0010 + 1968, 32, 4, 0x01)];
+    for (request, (address, number, bytes, first)) in requests.iter().zip(expected) {
+        match request {
+            Request::WriteMultipleCoils(_, body) => {
+                assert_eq!(*body.first_address(), address);
+                assert_eq!(*body.coils_number(), number);
+                assert_eq!(body.values.len(), bytes);
+                assert_eq!(body.values[0], first);
+            }
+            _ => panic!("unexpected request {:?}", request),
+        }
+    }
+    match &requests[1] {
+        Request::WriteMultipleCoils(_, body) => assert_eq!(body.values[3], 0x80),
+        _ => unreachable!(),
+    }
+
+    let requests = Frame::tcp().write_multiple_coils_chunked(0x0B, 0x0000, &[true; 1968]);
+    assert_eq!(requests.len(), 1);
+    assert!(Frame::tcp().write_multiple_coils_chunked(0x0B, 0x0000, &[]).is_empty());
+    let requests = Frame::tcp().write_multiple_coils_chunked(0x0B, 0xFFFF, &[true]);
+    assert_eq!(requests.len(), 1);
+}
+
+#[test]
+#[should_panic]
+fn test_write_multiple_coils_chunked_past_end() {
+    Frame::tcp().write_multiple_coils_chunked(0x0B, 0xFFFF, &[true, true]);
+}
+
+#[test]
+fn test_response_from_bools() {
+    let coils = [true, false, true, true, false, false, true, true, true, false];
+    let response = Frame::tcp().read_coils_response_from_bools(0x0B, &coils);
+    match &response {
+        Response::ReadCoils(_, body) => {
+            assert_eq!(*body.get_bytes_number(), 2);
+            assert_eq!(&body.get_values()[..], &[0xCD, 0x01]);
+        }
+        _ => panic!("unexpected response {:?}", response),
+    }
+
+    let response = Frame::rtu().read_discrete_response_from_bools(0x0B, &coils);
+    assert_eq!(response, Frame::rtu().read_discrete_response(0x0B, vec![0xCD, 0x01]));
+    let response = Frame::rtu().read_discrete_response_from_bools(0x0B, &[]);
+    assert_eq!(response, Frame::rtu().read_discrete_response(0x0B, vec![]));
+}
+
+#[test]
+fn test_response_from_u16() {
+    let response = Frame::tcp().read_holding_registers_response_from_u16(0x01, &[0x0021, 0x0000]);
+    match &response {
+        Response::ReadMultipleHoldingRegisters(_, body) => {
+            assert_eq!(*body.get_bytes_number(), 4);
+            assert_eq!(&body.get_values()[..], &[0x00, 0x21, 0x00, 0x00]);
+        }
+        _ => panic!("unexpected response {:?}", response),
+    }
+    assert!(response.encodes_to(&[
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x00, 0x21, 0x00, 0x00,
+    ]));
+
+    let response = Frame::rtu().read_input_registers_response_from_u16(0x01, &[0x0021, 0x0000]);
+    match &response {
+        Response::ReadInputRegisters(_, body) => {
+            assert_eq!(*body.get_bytes_number(), 4);
+            assert_eq!(&body.get_values()[..], &[0x00, 0x21, 0x00, 0x00]);
+        }
+        _ => panic!("unexpected response {:?}", response),
+    }
+}
+
+#[test]
+fn test_function_code() {
+    for function in [
+        Function::ReadCoils,
+        Function::WriteMultipleCoils,
+        Function::ReadWriteMultipleRegisters,
+    ] {
+        assert_eq!(FunctionCode::from(function).to_function(), Some(function));
+    }
+
+    let code = FunctionCode::from(0x16);
+    assert_eq!(code.get_code(), 0x16);
+    assert_eq!(code.to_function(), None);
+    assert_eq!(code.name(), Some("Mask Write Register"));
+
+    let code = FunctionCode::new(0x41);
+    assert_eq!(code.get_code(), 0x41);
+    assert_eq!(code.name(), None);
+    assert_eq!(code.to_string(), "function 0x41");
+}
+
+#[test]
+fn test_exception_code() {
+    for code in [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x08, 0x0A, 0x0B] {
+        assert_eq!(Exception::from_code(code).unwrap().to_code(), code);
+    }
+    assert_eq!(
+        Exception::from_code(0x0A),
+        Some(Exception::GatewayPathUnavailable)
+    );
+    assert_eq!(Exception::from_code(0x07), None);
+    assert_eq!(Exception::from_code(0x0C), None);
+}
+
+#[test]
+fn test_exception_retriable() {
+    use Exception::*;
+    assert!(Acknowledge.is_retriable());
+    assert!(SlaveDeviceBusy.is_retriable());
+    assert!(GatewayTargetDeviceFailedToRespond.is_retriable());
+    for exception in [
+        IllegalFunction,
+        IllegalDataAddress,
+        IllegalDataValue,
+        SlaveDeviceFailure,
+        MemoryParityError,
+        GatewayPathUnavailable,
+    ] {
+        assert!(!exception.is_retriable());
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_custom_request_of_modeled_function() {
+    Frame::tcp().custom_request(0x01, 0x03, vec![0x00, 0x00, 0x00, 0x01]);
+}
+
+#[test]
+fn test_const_code() {
+    const READ_COILS: u8 = Function::ReadCoils.to_code();
+    const BUSY: u8 = Exception::SlaveDeviceBusy.to_code();
+    const WRITE: Option<Function> = Function::from_code(0x0F);
+    const GATEWAY: Option<Exception> = Exception::from_code(0x0B);
+    assert_eq!(READ_COILS, 0x01);
+    assert_eq!(BUSY, 0x06);
+    assert_eq!(WRITE, Some(Function::WriteMultipleCoils));
+    assert_eq!(GATEWAY, Some(Exception::GatewayTargetDeviceFailedToRespond));
+    for code in 0..=0xFF {
+        if let Some(function) = Function::from_code(code) {
+            assert_eq!(function.to_code(), code);
+        }
+        if let Some(exception) = Exception::from_code(code) {
+            assert_eq!(exception.to_code(), code);
+        }
+    }
+}
+
+#[test]
+fn test_function_all() {
+    // A new variant fails to compile here, until it is added to `Function::all`
+    let modeled = |function: Function| match function {
+        Function::ReadCoils
+        | Function::ReadDiscreteInputs
+        | Function::ReadMultipleHoldingRegisters
+        | Function::ReadInputRegisters
+        | Function::WriteSingleCoil
+        | Function::WriteSingleHoldingRegister
+        | Function::WriteMultipleCoils
+        | Function::WriteMultipleHoldingRegisters
+        | Function::ReadWriteMultipleRegisters => true,
+        Function::Custom(_) => false,
+    };
+    assert_eq!(Function::all().len(), 9);
+    assert!(Function::all().iter().all(|&function| modeled(function)));
+    assert!(Function::all().windows(2).all(|pair| pair[0].to_code() < pair[1].to_code()));
+    for function in Function::all() {
+        assert_eq!(Function::from_code(function.to_code()), Some(*function));
+    }
+    let defined = (0..=0xFF).filter_map(Function::from_code).count();
+    assert_eq!(defined, Function::all().len());
+}
+
+#[test]
+fn test_set_register_bit_request() {
+    let frame = Frame::rtu();
+    let request = frame.set_register_bit_request(0x0B, 0x0100, 3, true);
+    assert_eq!(&request.pdu_bytes()[..], &[0x16, 0x01, 0x00, 0xFF, 0xF7, 0x00, 0x08]);
+    let request = frame.set_register_bit_request(0x0B, 0x0100, 3, false);
+    assert_eq!(&request.pdu_bytes()[..], &[0x16, 0x01, 0x00, 0xFF, 0xF7, 0x00, 0x00]);
+    let request = frame.set_register_bit_request(0x0B, 0x0100, 15, true);
+    assert_eq!(&request.pdu_bytes()[3..], &[0x7F, 0xFF, 0x80, 0x00]);
+
+    // The register the server computes from the masks
+    let apply = |current: u16, and_mask: u16, or_mask: u16| {
+        (current & and_mask) | (or_mask & !and_mask)
+    };
+    assert_eq!(apply(0x1230, 0xFFF7, 0x0008), 0x1238);
+    assert_eq!(apply(0x123F, 0xFFF7, 0x0000), 0x1237);
+}
+
+#[test]
+#[should_panic]
+fn test_set_register_bit_request_past_end() {
+    Frame::tcp().set_register_bit_request(0x0B, 0x0100, 16, true);
+}
@@ -0,0 +1,70 @@
+use std::io::{Error, ErrorKind, Result};
+
+/// A block of `count` addresses starting at `start`
+///
+/// The range is checked on construction, it is never empty and never runs past `0xFFFF`.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::AddressRange;
+/// let range = AddressRange::new(0x0010, 8).unwrap();
+/// assert_eq!(range.get_start(), 0x0010);
+/// assert_eq!(range.get_count(), 8);
+/// assert!(AddressRange::new(0xFFFF, 2).is_err());
+/// assert!(AddressRange::new(0x0000, 0).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AddressRange {
+    pub(crate) start: u16,
+    pub(crate) count: u16,
+}
+
+impl AddressRange {
+    /// Create a range of `count` addresses starting at `start`
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] for a `count` of zero, or a range past `0xFFFF`.
+    pub fn new(start: u16, count: u16) -> Result<AddressRange> {
+        if count == 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "empty address range"));
+        }
+        if start as u32 + count as u32 > 0x10000 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("address range {:#06X}+{} past 0xFFFF", start, count),
+            ));
+        }
+        Ok(AddressRange { start, count })
+    }
+
+    pub fn get_start(&self) -> u16 {
+        self.start
+    }
+
+    pub fn get_count(&self) -> u16 {
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod range_test {
+    use std::io::ErrorKind;
+
+    use crate::frame::AddressRange;
+
+    #[test]
+    fn new_test() {
+        assert_eq!(
+            AddressRange::new(0xFFFF, 2).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            AddressRange::new(0x0000, 0).unwrap_err().kind(),
+            ErrorKind::InvalidInput
+        );
+        let range = AddressRange::new(0xFFFF, 1).unwrap();
+        assert_eq!((range.get_start(), range.get_count()), (0xFFFF, 1));
+        let range = AddressRange::new(0x0000, u16::MAX).unwrap();
+        assert_eq!((range.get_start(), range.get_count()), (0x0000, u16::MAX));
+    }
+}
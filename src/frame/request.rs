@@ -1,12 +1,13 @@
 use std::fmt;
 use std::fmt::Formatter;
 
-use bytes::{BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
 
 use crate::frame::Version::Rtu;
 use crate::util::crc;
 
-use super::{Head, Length};
+use super::response::WriteMultipleHoldingRegistersResponse;
+use super::{byte_count, strip_adu, Function, Head, Length};
 
 /// Modbus Request
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -19,12 +20,179 @@ pub enum Request {
     WriteSingleHoldingRegister(Head, WriteSingleHoldingRegisterRequest),
     WriteMultipleCoils(Head, WriteMultipleCoilsRequest),
     WriteMultipleHoldingRegisters(Head, WriteMultipleHoldingRegistersRequest),
+    ReadWriteMultipleRegisters(Head, ReadWriteMultipleRegistersRequest),
+    Custom(Head, CustomRequest),
+}
+
+impl Request {
+    pub fn head(&self) -> &Head {
+        match self {
+            Request::ReadCoils(head, _)
+            | Request::ReadDiscreteInputs(head, _)
+            | Request::ReadMultipleHoldingRegisters(head, _)
+            | Request::ReadInputRegisters(head, _)
+            | Request::WriteSingleCoil(head, _)
+            | Request::WriteSingleHoldingRegister(head, _)
+            | Request::WriteMultipleCoils(head, _)
+            | Request::WriteMultipleHoldingRegisters(head, _)
+            | Request::ReadWriteMultipleRegisters(head, _)
+            | Request::Custom(head, _) => head,
+        }
+    }
+
+    /// Whether the request is a RTU broadcast (unit id 0)
+    ///
+    /// Every server on a serial line executes a broadcast request, and none of them answers it.
+    /// Over TCP unit id 0 addresses the server itself, so TCP requests are never broadcasts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// assert!(Frame::rtu().write_single_coil_request(0x00, 0x0001, 0xFF00).is_broadcast());
+    /// assert!(!Frame::rtu().write_single_coil_request(0x0B, 0x0001, 0xFF00).is_broadcast());
+    /// assert!(!Frame::tcp().write_single_coil_request(0x00, 0x0001, 0xFF00).is_broadcast());
+    /// ```
+    pub fn is_broadcast(&self) -> bool {
+        let head = self.head();
+        head.version == Rtu && head.uid == 0
+    }
+
+    /// Convert to a RTU request, for forwarding a TCP request to a serial line
+    ///
+    /// The transaction and protocol identifiers are dropped, the unit id and body are kept. The
+    /// CRC is computed when the request is encoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::tcp().read_coils_request(0x0B, 0x0013, 0x0025);
+    /// assert_eq!(request.into_rtu(), Frame::rtu().read_coils_request(0x0B, 0x0013, 0x0025));
+    /// ```
+    pub fn into_rtu(mut self) -> Request {
+        let head = self.head_mut();
+        *head = head.clone().to_rtu();
+        self
+    }
+
+    /// Whether the request is encoded to exactly `bytes`, with the CRC for a RTU request
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::rtu().read_coils_request(0x0B, 0x001D, 0x001F);
+    /// assert!(request.encodes_to(&[0x0B, 0x01, 0x00, 0x1D, 0x00, 0x1F, 0xED, 0x6E]));
+    /// ```
+    pub fn encodes_to(&self, bytes: &[u8]) -> bool {
+        let mut buf = BytesMut::with_capacity(bytes.len());
+        request_to_bytesmut(self, &mut buf);
+        buf[..] == *bytes
+    }
+
+    /// The PDU of the request: its function code and data, without the unit id, MBAP header
+    /// or CRC
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::rtu().read_coils_request(0x0B, 0x0002, 0x0008);
+    /// assert_eq!(&request.pdu_bytes()[..], &[0x01, 0x00, 0x02, 0x00, 0x08]);
+    /// ```
+    pub fn pdu_bytes(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        request_to_bytesmut(self, &mut buf);
+        strip_adu(buf, self.head().version)
+    }
+
+    /// The function of a response answering the request, unless it is an exception response
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Function};
+    /// let request = Frame::tcp().read_coils_request(0x0B, 0x0013, 0x0025);
+    /// assert_eq!(request.expected_response_kind(), Function::ReadCoils);
+    /// ```
+    pub fn expected_response_kind(&self) -> Function {
+        self.head().function
+    }
+
+    /// The byte count of a well-formed response to a read request, `None` for a write request
+    ///
+    /// A read response carries a variable payload, of as many bytes as the quantity read
+    /// takes. A write response echoes the request and carries no byte count. `None` too for a
+    /// custom request, whose response is opaque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let frame = Frame::tcp();
+    /// assert_eq!(frame.read_coils_request(0x0B, 0x0013, 0x0025).expected_byte_count(), Some(5));
+    /// let request = frame.read_input_registers_request(0x0B, 0x0008, 0x0002);
+    /// assert_eq!(request.expected_byte_count(), Some(4));
+    /// let request = frame.write_single_coil_request(0x0B, 0x00AC, 0xFF00);
+    /// assert_eq!(request.expected_byte_count(), None);
+    /// ```
+    pub fn expected_byte_count(&self) -> Option<usize> {
+        match self {
+            Request::ReadCoils(_, body) => Some((body.coils_number as usize).div_ceil(8)),
+            Request::ReadDiscreteInputs(_, body) => {
+                Some((body.discrete_inputs_number as usize).div_ceil(8))
+            }
+            Request::ReadMultipleHoldingRegisters(_, body) => {
+                Some(body.registers_number as usize * 2)
+            }
+            Request::ReadInputRegisters(_, body) => Some(body.registers_number as usize * 2),
+            Request::ReadWriteMultipleRegisters(_, body) => {
+                Some(body.read_registers_number as usize * 2)
+            }
+            _ => None,
+        }
+    }
+
+    /// Length of the body of a well-formed response, the bytes following its function code
+    ///
+    /// A read response has its byte count and the payload it counts, a write response has a
+    /// fixed body of 4 bytes. `None` for a custom request.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let request = Frame::tcp().read_coils_request(0x0B, 0x0013, 0x0025);
+    /// assert_eq!(request.expected_response_len(), Some(6));
+    /// ```
+    pub fn expected_response_len(&self) -> Option<usize> {
+        match self {
+            Request::Custom(..) => None,
+            _ => Some(self.expected_byte_count().map_or(4, |bytes_number| 1 + bytes_number)),
+        }
+    }
+
+    pub(crate) fn head_mut(&mut self) -> &mut Head {
+        match self {
+            Request::ReadCoils(head, _)
+            | Request::ReadDiscreteInputs(head, _)
+            | Request::ReadMultipleHoldingRegisters(head, _)
+            | Request::ReadInputRegisters(head, _)
+            | Request::WriteSingleCoil(head, _)
+            | Request::WriteSingleHoldingRegister(head, _)
+            | Request::WriteMultipleCoils(head, _)
+            | Request::WriteMultipleHoldingRegisters(head, _)
+            | Request::ReadWriteMultipleRegisters(head, _)
+            | Request::Custom(head, _) => head,
+        }
+    }
 }
 
 impl fmt::Display for Request {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut buf = BytesMut::with_capacity(64);
-        request_to_bytesmut(self.clone(), &mut buf);
+        request_to_bytesmut(self, &mut buf);
         let mut first = true;
         for byte in buf {
             if !first {
@@ -267,7 +435,7 @@ pub struct WriteMultipleCoilsRequest {
     /// Coil values
     ///
     /// Value of each coil is binary (0 for off, 1 for on).
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for WriteMultipleCoilsRequest {
@@ -285,8 +453,8 @@ impl WriteMultipleCoilsRequest {
         WriteMultipleCoilsRequest {
             first_address,
             coils_number,
-            bytes_number: values.len() as u8,
-            values,
+            bytes_number: byte_count(values.len()),
+            values: Bytes::from(values),
         }
     }
 
@@ -315,7 +483,7 @@ pub struct WriteMultipleHoldingRegistersRequest {
     pub(crate) bytes_number: u8,
 
     /// New values of holding registers
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for WriteMultipleHoldingRegistersRequest {
@@ -329,8 +497,8 @@ impl WriteMultipleHoldingRegistersRequest {
         WriteMultipleHoldingRegistersRequest {
             first_address,
             registers_number: values.len() as u16 / 2,
-            bytes_number: values.len() as u8,
-            values,
+            bytes_number: byte_count(values.len()),
+            values: Bytes::from(values),
         }
     }
 
@@ -346,13 +514,133 @@ impl WriteMultipleHoldingRegistersRequest {
         &self.bytes_number
     }
 
-    pub fn get_values(&self) -> &Vec<u8> {
+    pub fn get_values(&self) -> &Bytes {
         &self.values
     }
+
+    /// Whether `response` echoes the first address and number of registers of the request
+    ///
+    /// A response echoing other values is sent by a device which didn't write what was asked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Request, Response};
+    /// let frame = Frame::tcp();
+    /// let request = frame.write_multiple_holding_registers_request(0x0B, 0x0012, vec![0x0B, 0x0A]);
+    /// let response = frame.write_multiple_holding_registers_response(0x0B, 0x0012, 0x0001);
+    /// if let (Request::WriteMultipleHoldingRegisters(_, request),
+    ///         Response::WriteMultipleHoldingRegisters(_, response)) = (request, response) {
+    ///     assert!(request.response_matches(&response));
+    /// }
+    /// ```
+    pub fn response_matches(&self, response: &WriteMultipleHoldingRegistersResponse) -> bool {
+        self.first_address == response.first_address
+            && self.registers_number == response.registers_number
+    }
+}
+
+/// Function Code `0x17`
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReadWriteMultipleRegistersRequest {
+    /// Address of first holding register to read
+    pub(crate) read_first_address: u16,
+
+    /// Number of holding registers to read
+    ///
+    /// Up to 125 registers can be read at once.
+    pub(crate) read_registers_number: u16,
+
+    /// Address of first holding register to write
+    pub(crate) write_first_address: u16,
+
+    /// Number of holding registers to write
+    ///
+    /// Up to 121 registers can be written at once.
+    pub(crate) write_registers_number: u16,
+
+    /// Number of bytes of register value to follow
+    pub(crate) bytes_number: u8,
+
+    /// New values of holding registers
+    pub(crate) values: Bytes,
+}
+
+impl Length for ReadWriteMultipleRegistersRequest {
+    fn len(&self) -> u16 {
+        9 + self.values.len() as u16
+    }
 }
 
-impl From<ReadCoilsRequest> for BytesMut {
-    fn from(request: ReadCoilsRequest) -> Self {
+impl ReadWriteMultipleRegistersRequest {
+    pub(crate) fn new(
+        read_first_address: u16,
+        read_registers_number: u16,
+        write_first_address: u16,
+        values: Vec<u8>,
+    ) -> ReadWriteMultipleRegistersRequest {
+        ReadWriteMultipleRegistersRequest {
+            read_first_address,
+            read_registers_number,
+            write_first_address,
+            write_registers_number: values.len() as u16 / 2,
+            bytes_number: byte_count(values.len()),
+            values: Bytes::from(values),
+        }
+    }
+
+    pub fn get_read_first_address(&self) -> &u16 {
+        &self.read_first_address
+    }
+
+    pub fn get_read_registers_number(&self) -> &u16 {
+        &self.read_registers_number
+    }
+
+    pub fn get_write_first_address(&self) -> &u16 {
+        &self.write_first_address
+    }
+
+    pub fn get_write_registers_number(&self) -> &u16 {
+        &self.write_registers_number
+    }
+
+    pub fn get_bytes_number(&self) -> &u8 {
+        &self.bytes_number
+    }
+
+    pub fn get_values(&self) -> &Bytes {
+        &self.values
+    }
+}
+
+/// A request of a function this crate doesn't model, see [`Function::Custom`](crate::Function)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomRequest {
+    /// Everything following the function code, opaque
+    pub(crate) data: Bytes,
+}
+
+impl Length for CustomRequest {
+    fn len(&self) -> u16 {
+        self.data.len() as u16
+    }
+}
+
+impl CustomRequest {
+    pub(crate) fn new(data: Vec<u8>) -> CustomRequest {
+        CustomRequest {
+            data: Bytes::from(data),
+        }
+    }
+
+    pub fn get_data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
+impl From<&ReadCoilsRequest> for BytesMut {
+    fn from(request: &ReadCoilsRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.coils_number);
@@ -360,8 +648,8 @@ impl From<ReadCoilsRequest> for BytesMut {
     }
 }
 
-impl From<ReadDiscreteInputsRequest> for BytesMut {
-    fn from(request: ReadDiscreteInputsRequest) -> Self {
+impl From<&ReadDiscreteInputsRequest> for BytesMut {
+    fn from(request: &ReadDiscreteInputsRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.discrete_inputs_number);
@@ -369,8 +657,8 @@ impl From<ReadDiscreteInputsRequest> for BytesMut {
     }
 }
 
-impl From<ReadMultipleHoldingRegistersRequest> for BytesMut {
-    fn from(request: ReadMultipleHoldingRegistersRequest) -> Self {
+impl From<&ReadMultipleHoldingRegistersRequest> for BytesMut {
+    fn from(request: &ReadMultipleHoldingRegistersRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.registers_number);
@@ -378,8 +666,8 @@ impl From<ReadMultipleHoldingRegistersRequest> for BytesMut {
     }
 }
 
-impl From<ReadInputRegistersRequest> for BytesMut {
-    fn from(request: ReadInputRegistersRequest) -> Self {
+impl From<&ReadInputRegistersRequest> for BytesMut {
+    fn from(request: &ReadInputRegistersRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.registers_number);
@@ -387,8 +675,8 @@ impl From<ReadInputRegistersRequest> for BytesMut {
     }
 }
 
-impl From<WriteSingleCoilRequest> for BytesMut {
-    fn from(request: WriteSingleCoilRequest) -> Self {
+impl From<&WriteSingleCoilRequest> for BytesMut {
+    fn from(request: &WriteSingleCoilRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.coil_address);
         buf.put_u16(request.value);
@@ -396,8 +684,8 @@ impl From<WriteSingleCoilRequest> for BytesMut {
     }
 }
 
-impl From<WriteSingleHoldingRegisterRequest> for BytesMut {
-    fn from(request: WriteSingleHoldingRegisterRequest) -> Self {
+impl From<&WriteSingleHoldingRegisterRequest> for BytesMut {
+    fn from(request: &WriteSingleHoldingRegisterRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.register_address);
         buf.put_u16(request.value);
@@ -405,74 +693,104 @@ impl From<WriteSingleHoldingRegisterRequest> for BytesMut {
     }
 }
 
-impl From<WriteMultipleCoilsRequest> for BytesMut {
-    fn from(request: WriteMultipleCoilsRequest) -> Self {
+impl From<&WriteMultipleCoilsRequest> for BytesMut {
+    fn from(request: &WriteMultipleCoilsRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.coils_number);
         buf.put_u8(request.bytes_number);
-        buf.put_slice(request.values.as_slice());
+        buf.put_slice(&request.values);
         buf
     }
 }
 
-impl From<WriteMultipleHoldingRegistersRequest> for BytesMut {
-    fn from(request: WriteMultipleHoldingRegistersRequest) -> Self {
+impl From<&WriteMultipleHoldingRegistersRequest> for BytesMut {
+    fn from(request: &WriteMultipleHoldingRegistersRequest) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(request.first_address);
         buf.put_u16(request.registers_number);
         buf.put_u8(request.bytes_number);
-        buf.put_slice(request.values.as_slice());
+        buf.put_slice(&request.values);
+        buf
+    }
+}
+
+impl From<&ReadWriteMultipleRegistersRequest> for BytesMut {
+    fn from(request: &ReadWriteMultipleRegistersRequest) -> Self {
+        let mut buf = BytesMut::new();
+        buf.put_u16(request.read_first_address);
+        buf.put_u16(request.read_registers_number);
+        buf.put_u16(request.write_first_address);
+        buf.put_u16(request.write_registers_number);
+        buf.put_u8(request.bytes_number);
+        buf.put_slice(&request.values);
         buf
     }
 }
 
-pub(crate) fn request_to_bytesmut(item: Request, dst: &mut BytesMut) {
+impl From<&CustomRequest> for BytesMut {
+    fn from(request: &CustomRequest) -> Self {
+        BytesMut::from(&request.data[..])
+    }
+}
+
+pub(crate) fn request_to_bytesmut(item: &Request, dst: &mut BytesMut) {
+    let start = dst.len();
     let version;
     match item {
         Request::ReadCoils(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::ReadDiscreteInputs(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::ReadMultipleHoldingRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::ReadInputRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::WriteSingleCoil(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::WriteSingleHoldingRegister(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::WriteMultipleCoils(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Request::WriteMultipleHoldingRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
+            dst.put(BytesMut::from(head));
+            dst.put(BytesMut::from(body));
+        }
+        Request::ReadWriteMultipleRegisters(head, body) => {
+            version = head.version;
+            dst.put(BytesMut::from(head));
+            dst.put(BytesMut::from(body));
+        }
+        Request::Custom(head, body) => {
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
     };
     if Rtu == version {
-        dst.put_u16(crc::compute(&dst.to_vec()));
+        dst.put_u16(crc::compute(&dst[start..]));
     }
 }
 
@@ -480,6 +798,7 @@ pub(crate) fn request_to_bytesmut(item: Request, dst: &mut BytesMut) {
 mod request_test {
     use crate::frame::Length;
     use crate::frame::request::*;
+    use crate::frame::response::WriteMultipleHoldingRegistersResponse;
 
     #[test]
     fn test_read_coils_request() {
@@ -554,7 +873,7 @@ mod request_test {
             first_address: 0x01,
             coils_number: 0x09,
             bytes_number: 0x02,
-            values: vec![0b0100_1101, 0b0000_0001],
+            values: Bytes::from(vec![0b0100_1101, 0b0000_0001]),
         };
         assert_eq!(request_l, request_r);
         assert_eq!(request_l.len(), 7);
@@ -567,9 +886,102 @@ mod request_test {
             first_address: 0x01,
             registers_number: 0x01,
             bytes_number: 0x02,
-            values: vec![0x00, 0x0f],
+            values: Bytes::from(vec![0x00, 0x0f]),
         };
         assert_eq!(request_l, request_r);
         assert_eq!(request_l.len(), 7);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_response_matches() {
+        let request = WriteMultipleHoldingRegistersRequest::new(0x12, vec![0x00, 0x0F, 0x01, 0x02]);
+        let response = WriteMultipleHoldingRegistersResponse::new(0x12, 0x02);
+        assert!(request.response_matches(&response));
+        let response = WriteMultipleHoldingRegistersResponse::new(0x12, 0x01);
+        assert!(!request.response_matches(&response));
+        let response = WriteMultipleHoldingRegistersResponse::new(0x13, 0x02);
+        assert!(!request.response_matches(&response));
+    }
+
+    #[test]
+    fn test_read_write_multiple_registers_request() {
+        let request_l = ReadWriteMultipleRegistersRequest::new(0x03, 0x06, 0x0E, vec![0x00, 0xFF]);
+        let request_r = ReadWriteMultipleRegistersRequest {
+            read_first_address: 0x03,
+            read_registers_number: 0x06,
+            write_first_address: 0x0E,
+            write_registers_number: 0x01,
+            bytes_number: 0x02,
+            values: Bytes::from(vec![0x00, 0xFF]),
+        };
+        assert_eq!(request_l, request_r);
+        assert_eq!(request_l.len(), 11);
+    }
+
+    #[test]
+    fn test_pdu_bytes() {
+        use crate::Frame;
+
+        let pdu = [0x01, 0x00, 0x02, 0x00, 0x08];
+        let request = Frame::tcp().read_coils_request(0x0B, 0x0002, 0x0008);
+        assert_eq!(&request.pdu_bytes()[..], &pdu[..]);
+        let request = Frame::rtu().read_coils_request(0x0B, 0x0002, 0x0008);
+        assert_eq!(&request.pdu_bytes()[..], &pdu[..]);
+
+        let values = vec![0x4D, 0x01];
+        let request = Frame::rtu().write_multiple_coils_request(0x0B, 0x001B, 0x0009, values);
+        let pdu = [0x0F, 0x00, 0x1B, 0x00, 0x09, 0x02, 0x4D, 0x01];
+        assert_eq!(&request.pdu_bytes()[..], &pdu[..]);
+    }
+
+    #[test]
+    fn test_into_rtu() {
+        use crate::Frame;
+
+        let values = vec![0x0B, 0x0A, 0xC1, 0x02];
+        let frame = Frame::tcp();
+        frame.read_coils_request(0x0B, 0x0000, 0x0001);
+        let request = frame.write_multiple_holding_registers_request(0x0B, 0x0012, values.clone());
+        assert_eq!(request.head().get_tid(), 2);
+        let request = request.into_rtu();
+        let expected = Frame::rtu().write_multiple_holding_registers_request(0x0B, 0x0012, values);
+        assert_eq!(request, expected);
+        assert!(request.encodes_to(&[
+            0x0B, 0x10, 0x00, 0x12, 0x00, 0x02, 0x04, 0x0B, 0x0A, 0xC1, 0x02, 0xA0, 0xD5,
+        ]));
+        assert!(!request.encodes_to(&[0x0B, 0x10, 0x00, 0x12, 0x00, 0x02]));
+    }
+
+    #[test]
+    #[cfg(feature = "runtime")]
+    fn test_expected_response() {
+        use crate::server::{dispatch, DataStore};
+        use crate::{Frame, Function};
+
+        let frame = Frame::tcp();
+        let request = frame.read_coils_request(0x01, 0x0000, 0x000A);
+        assert_eq!(request.expected_response_kind(), Function::ReadCoils);
+        assert_eq!(request.expected_byte_count(), Some(2));
+
+        let store = DataStore::default()
+            .with_coils(0x0000, 16)
+            .with_holding_registers(0x0000, 8);
+        let requests = [
+            request,
+            frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0003),
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 0x0002, 0x0004, vec![0; 4]),
+            frame.write_single_coil_request(0x01, 0x0001, 0xFF00),
+            frame.write_multiple_holding_registers_request(0x01, 0x0000, vec![0; 6]),
+        ];
+        for request in &requests {
+            let response = dispatch(&store, request).unwrap();
+            assert_eq!(response.head().get_function(), request.expected_response_kind());
+            let body_len = response.pdu_bytes().len() - 1;
+            assert_eq!(Some(body_len), request.expected_response_len(), "{}", request);
+        }
+
+        let request = frame.custom_request(0x01, 0x41, vec![]);
+        assert_eq!(request.expected_response_kind(), Function::Custom(0x41));
+        assert_eq!(request.expected_response_len(), None);
+    }
+}
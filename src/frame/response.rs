@@ -1,13 +1,18 @@
 use std::fmt;
 use std::fmt::Formatter;
+use std::io::Result;
 
-use bytes::{BufMut, BytesMut};
+use bytes::{BufMut, Bytes, BytesMut};
 
-use crate::frame::{Exception, Version};
+use crate::data::{bcd_from_registers, scaled_from_register, string_from_registers, CharOrder};
+use crate::data::{Bitfield16, ConversionError, FromRegisters, RegisterBlock, WordOrder};
+use crate::error::WrongVariant;
+use crate::frame::{byte_count, Exception, Version};
 use crate::frame::Version::Rtu;
+use crate::util::bits::{self, Bits};
 use crate::util::crc;
 
-use super::{Head, Length};
+use super::{strip_adu, Head, Length};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Response {
@@ -19,13 +24,229 @@ pub enum Response {
     WriteSingleHoldingRegister(Head, WriteSingleHoldingRegisterResponse),
     WriteMultipleCoils(Head, WriteMultipleCoilsResponse),
     WriteMultipleHoldingRegisters(Head, WriteMultipleHoldingRegistersResponse),
+    ReadWriteMultipleRegisters(Head, ReadWriteMultipleRegistersResponse),
+    Custom(Head, CustomResponse),
     Exception(Head, ExceptionResponse),
 }
 
+impl Response {
+    pub fn head(&self) -> &Head {
+        match self {
+            Response::ReadCoils(head, _)
+            | Response::ReadDiscreteInputs(head, _)
+            | Response::ReadMultipleHoldingRegisters(head, _)
+            | Response::ReadInputRegisters(head, _)
+            | Response::WriteSingleCoil(head, _)
+            | Response::WriteSingleHoldingRegister(head, _)
+            | Response::WriteMultipleCoils(head, _)
+            | Response::WriteMultipleHoldingRegisters(head, _)
+            | Response::ReadWriteMultipleRegisters(head, _)
+            | Response::Custom(head, _)
+            | Response::Exception(head, _) => head,
+        }
+    }
+
+    pub(crate) fn head_mut(&mut self) -> &mut Head {
+        match self {
+            Response::ReadCoils(head, _)
+            | Response::ReadDiscreteInputs(head, _)
+            | Response::ReadMultipleHoldingRegisters(head, _)
+            | Response::ReadInputRegisters(head, _)
+            | Response::WriteSingleCoil(head, _)
+            | Response::WriteSingleHoldingRegister(head, _)
+            | Response::WriteMultipleCoils(head, _)
+            | Response::WriteMultipleHoldingRegisters(head, _)
+            | Response::ReadWriteMultipleRegisters(head, _)
+            | Response::Custom(head, _)
+            | Response::Exception(head, _) => head,
+        }
+    }
+
+    /// Convert to a TCP response, for answering a TCP request forwarded to a serial line
+    ///
+    /// `tid` is the transaction identifier of the TCP request, the unit id and body are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::rtu().read_coils_response(0x0B, vec![0xCD, 0x6B]);
+    /// let expected = Frame::tcp().read_coils_response(0x0B, vec![0xCD, 0x6B]);
+    /// assert_eq!(response.into_tcp(0x0001), expected);
+    /// ```
+    pub fn into_tcp(mut self, tid: u16) -> Response {
+        let head = self.head_mut();
+        *head = head.clone().to_tcp(tid, 0);
+        self
+    }
+
+    /// Whether the response is encoded to exactly `bytes`, with the CRC for a RTU response
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_coils_response(0x0B, vec![0xCD, 0x6B]);
+    /// assert!(response.encodes_to(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x0B, 0x01, 0x02, 0xCD, 0x6B]));
+    /// ```
+    pub fn encodes_to(&self, bytes: &[u8]) -> bool {
+        let mut buf = BytesMut::with_capacity(bytes.len());
+        response_to_bytesmut(self, &mut buf);
+        buf[..] == *bytes
+    }
+
+    /// The PDU of the response: its function code, with the exception bit, and data, without
+    /// the unit id, MBAP header or CRC
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_coils_response(0x0B, vec![0xCD, 0x6B]);
+    /// assert_eq!(&response.pdu_bytes()[..], &[0x01, 0x02, 0xCD, 0x6B]);
+    /// ```
+    pub fn pdu_bytes(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        response_to_bytesmut(self, &mut buf);
+        strip_adu(buf, self.head().version)
+    }
+
+    /// The registers read by a holding or input register read response, big-endian
+    ///
+    /// A trailing odd byte, which a malformed response may carry, is left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x00, 0x0A, 0x01, 0x02]);
+    /// assert_eq!(response.into_registers().unwrap(), vec![0x000A, 0x0102]);
+    /// ```
+    pub fn into_registers(self) -> std::result::Result<Vec<u16>, WrongVariant> {
+        match self {
+            Response::ReadMultipleHoldingRegisters(_, body) => Ok(body.to_vec_u16()),
+            Response::ReadInputRegisters(_, body) => Ok(body.to_vec_u16()),
+            response => Err(response.wrong_variant(&[
+                "ReadMultipleHoldingRegisters",
+                "ReadInputRegisters",
+            ])),
+        }
+    }
+
+    /// States of the first `count` bits read by a [`ReadCoils`](Response::ReadCoils) or
+    /// [`ReadDiscreteInputs`](Response::ReadDiscreteInputs) response
+    ///
+    /// `count` is the quantity of the request, fewer states are returned if the response holds
+    /// fewer bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::Frame;
+    /// let response = Frame::tcp().read_coils_response(0x01, vec![0x05]);
+    /// assert_eq!(response.into_coils(3).unwrap(), vec![true, false, true]);
+    /// ```
+    pub fn into_coils(self, count: usize) -> std::result::Result<Vec<bool>, WrongVariant> {
+        match self {
+            Response::ReadCoils(_, body) => Ok(body.coils(count)),
+            Response::ReadDiscreteInputs(_, body) => Ok(body.inputs(count)),
+            response => Err(response.wrong_variant(&["ReadCoils", "ReadDiscreteInputs"])),
+        }
+    }
+
+    /// The exception of an [`Exception`](Response::Exception) response
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Exception, Frame, Function};
+    /// let response = Frame::tcp().exception_response(
+    ///     0x01,
+    ///     Function::ReadCoils,
+    ///     Exception::IllegalDataAddress,
+    /// );
+    /// assert_eq!(response.into_exception().unwrap(), Exception::IllegalDataAddress);
+    /// ```
+    pub fn into_exception(self) -> std::result::Result<Exception, WrongVariant> {
+        match self {
+            Response::Exception(_, body) => Ok(body.exception),
+            response => Err(response.wrong_variant(&["Exception"])),
+        }
+    }
+
+    /// Name of the variant of the response
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Response::ReadCoils(..) => "ReadCoils",
+            Response::ReadDiscreteInputs(..) => "ReadDiscreteInputs",
+            Response::ReadMultipleHoldingRegisters(..) => "ReadMultipleHoldingRegisters",
+            Response::ReadInputRegisters(..) => "ReadInputRegisters",
+            Response::WriteSingleCoil(..) => "WriteSingleCoil",
+            Response::WriteSingleHoldingRegister(..) => "WriteSingleHoldingRegister",
+            Response::WriteMultipleCoils(..) => "WriteMultipleCoils",
+            Response::WriteMultipleHoldingRegisters(..) => "WriteMultipleHoldingRegisters",
+            Response::ReadWriteMultipleRegisters(..) => "ReadWriteMultipleRegisters",
+            Response::Custom(..) => "Custom",
+            Response::Exception(..) => "Exception",
+        }
+    }
+
+    pub(crate) fn wrong_variant(&self, expected: &'static [&'static str]) -> WrongVariant {
+        WrongVariant {
+            expected,
+            actual: self.variant_name(),
+        }
+    }
+
+    /// Register values of a response reading registers, `None` for any other response
+    pub(crate) fn register_values(&self) -> Option<&Bytes> {
+        match self {
+            Response::ReadMultipleHoldingRegisters(_, body) => Some(&body.values),
+            Response::ReadInputRegisters(_, body) => Some(&body.values),
+            Response::ReadWriteMultipleRegisters(_, body) => Some(&body.values),
+            _ => None,
+        }
+    }
+
+    /// A human readable summary of the response, for logging
+    ///
+    /// An exception response tells the function which faulted and the exception with its
+    /// code. [`Display`](fmt::Display) prints the bytes of the response instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Exception, Frame, Function};
+    /// let response = Frame::tcp().exception_response(
+    ///     0x0A,
+    ///     Function::ReadCoils,
+    ///     Exception::IllegalDataAddress,
+    /// );
+    /// assert_eq!(response.describe(), "Exception on ReadCoils: IllegalDataAddress (0x02)");
+    /// let response = Frame::tcp().read_coils_response(0x0A, vec![0xCD]);
+    /// assert_eq!(response.describe(), "ReadCoils response from unit 0x0A");
+    /// ```
+    pub fn describe(&self) -> String {
+        match self {
+            Response::Exception(head, body) => format!(
+                "Exception on {:?}: {:?} (0x{:0>2X})",
+                head.function,
+                body.exception,
+                body.exception.to_code()
+            ),
+            response => format!(
+                "{:?} response from unit 0x{:0>2X}",
+                response.head().function,
+                response.head().uid
+            ),
+        }
+    }
+}
+
 impl fmt::Display for Response {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let mut buf = BytesMut::with_capacity(64);
-        response_to_bytesmut(self.clone(), &mut buf);
+        response_to_bytesmut(self, &mut buf);
         let mut first = true;
         for byte in buf {
             if !first {
@@ -49,7 +270,7 @@ pub struct ReadCoilsResponse {
     /// Values of each coil input is binary (0 for off, 1 for on). First requested coil input is
     /// as least significant bit of first byte in reply. If number of coils inputs is not a multiple
     /// of 8, most significant bits of last byte will be stuffed zeros.
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for ReadCoilsResponse {
@@ -60,10 +281,10 @@ impl Length for ReadCoilsResponse {
 
 impl ReadCoilsResponse {
     pub(crate) fn new(values: Vec<u8>) -> ReadCoilsResponse {
-        let bytes_number = values.len() as u8;
+        let bytes_number = byte_count(values.len());
         ReadCoilsResponse {
             bytes_number,
-            values,
+            values: Bytes::from(values),
         }
     }
 
@@ -71,9 +292,70 @@ impl ReadCoilsResponse {
         &self.bytes_number
     }
 
-    pub fn get_values(&self) -> &Vec<u8> {
+    pub fn get_values(&self) -> &Bytes {
         &self.values
     }
+
+    /// State of the coil at `index`, counting from the first one read
+    ///
+    /// Returns `None` past the bytes read. The padding bits of the last byte read as `false`,
+    /// the response does not tell how many coils were requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_coils_response(0x01, vec![0xCD]);
+    /// if let Response::ReadCoils(_, body) = response {
+    ///     assert_eq!(body.bit(0), Some(true));
+    ///     assert_eq!(body.bit(1), Some(false));
+    ///     assert_eq!(body.bit(8), None);
+    /// }
+    /// ```
+    pub fn bit(&self, index: usize) -> Option<bool> {
+        bits::get(&self.values, index)
+    }
+
+    /// States of the first `count` coils read
+    ///
+    /// The response does not tell how many coils were requested, `count` is the quantity of
+    /// the request. Fewer states are returned if the response holds fewer bits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_coils_response(0x01, vec![0x05]);
+    /// if let Response::ReadCoils(_, body) = response {
+    ///     assert_eq!(body.coils(3), vec![true, false, true]);
+    /// }
+    /// ```
+    pub fn coils(&self, count: usize) -> Vec<bool> {
+        self.iter_bits_n(count).collect()
+    }
+
+    /// Iterate over every bit read, the padding bits of the last byte included
+    pub fn iter_bits(&self) -> Bits<'_> {
+        bits::iter(&self.values, self.values.len() * 8)
+    }
+
+    /// Iterate over the states of the first `count` coils read, see
+    /// [`ReadCoilsResponse::coils`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_coils_response(0x01, vec![0xFF, 0x01]);
+    /// if let Response::ReadCoils(_, body) = response {
+    ///     assert_eq!(body.iter_bits().len(), 16);
+    ///     assert_eq!(body.iter_bits_n(9).len(), 9);
+    ///     assert!(body.iter_bits_n(9).all(|coil| coil));
+    /// }
+    /// ```
+    pub fn iter_bits_n(&self, count: usize) -> Bits<'_> {
+        bits::iter(&self.values, count)
+    }
 }
 
 /// Function Code `0x02`
@@ -88,7 +370,7 @@ pub struct ReadDiscreteInputsResponse {
     /// input is stored as least significant bit of first byte in reply. If number of discrete
     /// inputs is not a multiple of 8, most significant bits of last byte will be stuffed with
     /// zeros.
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for ReadDiscreteInputsResponse {
@@ -100,8 +382,8 @@ impl Length for ReadDiscreteInputsResponse {
 impl ReadDiscreteInputsResponse {
     pub(crate) fn new(values: Vec<u8>) -> ReadDiscreteInputsResponse {
         ReadDiscreteInputsResponse {
-            bytes_number: values.len() as u8,
-            values,
+            bytes_number: byte_count(values.len()),
+            values: Bytes::from(values),
         }
     }
 
@@ -109,9 +391,46 @@ impl ReadDiscreteInputsResponse {
         &self.bytes_number
     }
 
-    pub fn get_values(&self) -> &Vec<u8> {
+    pub fn get_values(&self) -> &Bytes {
         &self.values
     }
+
+    /// State of the discrete input at `index`, counting from the first one read
+    ///
+    /// Returns `None` past the bytes read. The padding bits of the last byte read as `false`,
+    /// the response does not tell how many discrete inputs were requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_discrete_response(0x01, vec![0xCD]);
+    /// if let Response::ReadDiscreteInputs(_, body) = response {
+    ///     assert_eq!(body.bit(0), Some(true));
+    ///     assert_eq!(body.bit(1), Some(false));
+    ///     assert_eq!(body.bit(8), None);
+    /// }
+    /// ```
+    pub fn bit(&self, index: usize) -> Option<bool> {
+        bits::get(&self.values, index)
+    }
+
+    /// States of the first `count` discrete inputs read
+    ///
+    /// Like [`ReadCoilsResponse::coils`].
+    pub fn inputs(&self, count: usize) -> Vec<bool> {
+        self.iter_bits_n(count).collect()
+    }
+
+    /// Iterate over every bit read, the padding bits of the last byte included
+    pub fn iter_bits(&self) -> Bits<'_> {
+        bits::iter(&self.values, self.values.len() * 8)
+    }
+
+    /// Iterate over the states of the first `count` discrete inputs read
+    pub fn iter_bits_n(&self, count: usize) -> Bits<'_> {
+        bits::iter(&self.values, count)
+    }
 }
 
 /// Function Code `0x03`
@@ -121,7 +440,7 @@ pub struct ReadMultipleHoldingRegistersResponse {
     pub(crate) bytes_number: u8,
 
     /// Register values
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for ReadMultipleHoldingRegistersResponse {
@@ -132,10 +451,10 @@ impl Length for ReadMultipleHoldingRegistersResponse {
 
 impl ReadMultipleHoldingRegistersResponse {
     pub(crate) fn new(values: Vec<u8>) -> ReadMultipleHoldingRegistersResponse {
-        let bytes_number = values.len() as u8;
+        let bytes_number = byte_count(values.len());
         ReadMultipleHoldingRegistersResponse {
             bytes_number,
-            values,
+            values: Bytes::from(values),
         }
     }
 
@@ -143,9 +462,209 @@ impl ReadMultipleHoldingRegistersResponse {
         &self.bytes_number
     }
 
-    pub fn get_values(&self) -> &Vec<u8> {
+    pub fn get_values(&self) -> &Bytes {
         &self.values
     }
+
+    /// The registers read, big-endian
+    ///
+    /// A trailing odd byte, which a malformed response may carry, is left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x0A, 0x01, 0x02]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.registers().collect::<Vec<_>>(), vec![0x000A, 0x0102]);
+    /// }
+    /// ```
+    pub fn registers(&self) -> impl ExactSizeIterator<Item = u16> + '_ {
+        registers(&self.values)
+    }
+
+    /// The registers read, big-endian, see [`registers`](Self::registers)
+    pub fn to_vec_u16(&self) -> Vec<u16> {
+        self.registers().collect()
+    }
+
+    /// The two registers read as one 32-bit value
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// use easy_modbus::data::WordOrder;
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x56, 0x78, 0x12, 0x34]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.as_u32_with(WordOrder::CDAB).unwrap(), 0x1234_5678);
+    /// }
+    /// ```
+    pub fn as_u32_with(&self, order: WordOrder) -> Result<u32> {
+        u32_with(&self.values, order)
+    }
+
+    /// The two registers read as one `f32`
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read, see
+    /// [`Frame::as_f32`](crate::Frame::as_f32) for the word order of the frame.
+    pub fn as_f32_with(&self, order: WordOrder) -> Result<f32> {
+        only_value(&self.values, order)
+    }
+
+    /// The registers read, addressed from `start`, the first address of the request
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x0A, 0x00, 0x0B, 0x00, 0x0C]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.to_block(0x0100).get(0x0102), Some(0x000C));
+    /// }
+    /// ```
+    pub fn to_block(&self, start: u16) -> RegisterBlock {
+        RegisterBlock::new(start, self.to_vec_u16())
+    }
+
+    /// The `f32` held by the two registers from register `offset`
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// unless both registers were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// use easy_modbus::data::WordOrder;
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x52, 0x2B, 0x44, 0x9A]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.get_f32(0, WordOrder::CDAB).unwrap(), 1234.5678);
+    /// }
+    /// ```
+    pub fn get_f32(&self, offset: usize, order: WordOrder) -> Result<f32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The register at `offset` read as a signed 16-bit value
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// past the registers read.
+    pub fn get_i16(&self, offset: usize) -> Result<i16> {
+        Ok(register(&self.values, offset)? as i16)
+    }
+
+    /// Bit `bit` of the register at `register_offset`, bit 0 the least significant
+    ///
+    /// Returns [`ErrorKind::InvalidInput`] past the registers read or for a bit above 15. See
+    /// [`Bitfield16`](crate::data::Bitfield16) for a register of several flags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x80, 0x08]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert!(body.get_bit(0, 3).unwrap());
+    ///     assert!(body.get_bit(0, 15).unwrap());
+    ///     assert!(!body.get_bit(0, 0).unwrap());
+    ///     assert!(body.get_bit(1, 0).is_err());
+    /// }
+    /// ```
+    pub fn get_bit(&self, register_offset: usize, bit: u8) -> Result<bool> {
+        bit_at(&self.values, register_offset, bit)
+    }
+
+    /// The register at `offset` read as a signed count of `scale`
+    ///
+    /// See [`scaled_from_register`](crate::data::scaled_from_register).
+    pub fn get_scaled(&self, offset: usize, scale: f64) -> Result<f64> {
+        Ok(scaled_from_register(register(&self.values, offset)?, scale))
+    }
+
+    /// The ASCII string packed into the `len` registers from register `offset`
+    ///
+    /// See [`string_from_registers`]. Returns [`ErrorKind::InvalidInput`] unless all of them
+    /// were read.
+    pub fn get_string(&self, offset: usize, len: usize, order: CharOrder) -> Result<String> {
+        string_at(&self.values, offset, len, order)
+    }
+
+    /// The value of the `registers` BCD registers from register `offset`, `0x1234` being 1234
+    ///
+    /// The most significant digits come first, see
+    /// [`bcd_from_registers`](crate::data::bcd_from_registers) for other orders. Returns
+    /// [`ErrorKind::InvalidInput`] unless all of them were read, [`ErrorKind::InvalidData`] for
+    /// a nibble above 9.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x12, 0x34, 0x56]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.get_bcd(0, 2).unwrap(), 123456);
+    ///     assert_eq!(body.get_bcd(1, 1).unwrap(), 3456);
+    ///     assert!(body.get_bcd(1, 2).is_err());
+    /// }
+    /// ```
+    pub fn get_bcd(&self, offset: usize, registers: usize) -> Result<u64> {
+        bcd_at(&self.values, offset, registers)
+    }
+
+    /// The `u32` held by the two registers from register `offset`
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// unless both registers were read.
+    pub fn get_u32(&self, offset: usize, order: WordOrder) -> Result<u32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `i32` held by the two registers from register `offset`
+    pub fn get_i32(&self, offset: usize, order: WordOrder) -> Result<i32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `f64` held by the four registers from register `offset`
+    ///
+    /// Like [`get_f32`](Self::get_f32), the word order spanning all four registers.
+    pub fn get_f64(&self, offset: usize, order: WordOrder) -> Result<f64> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `u64` held by the four registers from register `offset`
+    pub fn get_u64(&self, offset: usize, order: WordOrder) -> Result<u64> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `i64` held by the four registers from register `offset`
+    pub fn get_i64(&self, offset: usize, order: WordOrder) -> Result<i64> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// A value scaled by a scale factor register, as in SunSpec register maps
+    ///
+    /// The value is `raw * 10^sf`, where `raw` is the register at `value_index` and `sf` the
+    /// register at `sf_index`, both read as signed 16-bit values. Indices count registers from
+    /// the first one read. Returns [`ErrorKind::InvalidInput`] for an index past the registers
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_holding_register_response(0x01, vec![0x04, 0xD2, 0xFF, 0xFE]);
+    /// if let Response::ReadMultipleHoldingRegisters(_, body) = response {
+    ///     assert_eq!(body.scaled(0, 1).unwrap(), 12.34);
+    /// }
+    /// ```
+    pub fn scaled(&self, value_index: usize, sf_index: usize) -> Result<f64> {
+        let raw = register(&self.values, value_index)? as i16;
+        let sf = register(&self.values, sf_index)? as i16;
+        Ok(raw as f64 * 10f64.powi(sf as i32))
+    }
 }
 
 /// Function Code `0x04`
@@ -155,7 +674,7 @@ pub struct ReadInputRegistersResponse {
     pub(crate) bytes_number: u8,
 
     /// Register values
-    pub(crate) values: Vec<u8>,
+    pub(crate) values: Bytes,
 }
 
 impl Length for ReadInputRegistersResponse {
@@ -166,10 +685,10 @@ impl Length for ReadInputRegistersResponse {
 
 impl ReadInputRegistersResponse {
     pub(crate) fn new(values: Vec<u8>) -> ReadInputRegistersResponse {
-        let bytes_number = values.len() as u8;
+        let bytes_number = byte_count(values.len());
         ReadInputRegistersResponse {
             bytes_number,
-            values,
+            values: Bytes::from(values),
         }
     }
 
@@ -177,9 +696,158 @@ impl ReadInputRegistersResponse {
         &self.bytes_number
     }
 
-    pub fn get_values(&self) -> &Vec<u8> {
+    pub fn get_values(&self) -> &Bytes {
         &self.values
     }
+
+    /// The registers read, big-endian
+    ///
+    /// A trailing odd byte, which a malformed response may carry, is left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x00, 0x0A, 0x01, 0x02]);
+    /// if let Response::ReadInputRegisters(_, body) = response {
+    ///     assert_eq!(body.registers().collect::<Vec<_>>(), vec![0x000A, 0x0102]);
+    /// }
+    /// ```
+    pub fn registers(&self) -> impl ExactSizeIterator<Item = u16> + '_ {
+        registers(&self.values)
+    }
+
+    /// The registers read, big-endian, see [`registers`](Self::registers)
+    pub fn to_vec_u16(&self) -> Vec<u16> {
+        self.registers().collect()
+    }
+
+    /// The two registers read as one 32-bit value
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// use easy_modbus::data::WordOrder;
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x56, 0x78, 0x12, 0x34]);
+    /// if let Response::ReadInputRegisters(_, body) = response {
+    ///     assert_eq!(body.as_u32_with(WordOrder::CDAB).unwrap(), 0x1234_5678);
+    /// }
+    /// ```
+    pub fn as_u32_with(&self, order: WordOrder) -> Result<u32> {
+        u32_with(&self.values, order)
+    }
+
+    /// The two registers read as one `f32`
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read, see
+    /// [`Frame::as_f32`](crate::Frame::as_f32) for the word order of the frame.
+    pub fn as_f32_with(&self, order: WordOrder) -> Result<f32> {
+        only_value(&self.values, order)
+    }
+
+    /// The registers read, addressed from `start`, the first address of the request
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x00, 0x0A, 0x00, 0x0B, 0x00, 0x0C]);
+    /// if let Response::ReadInputRegisters(_, body) = response {
+    ///     assert_eq!(body.to_block(0x0100).get(0x0102), Some(0x000C));
+    /// }
+    /// ```
+    pub fn to_block(&self, start: u16) -> RegisterBlock {
+        RegisterBlock::new(start, self.to_vec_u16())
+    }
+
+    /// The `f32` held by the two registers from register `offset`
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// unless both registers were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// use easy_modbus::data::WordOrder;
+    /// let response = Frame::tcp().read_input_register_response(0x01, vec![0x52, 0x2B, 0x44, 0x9A]);
+    /// if let Response::ReadInputRegisters(_, body) = response {
+    ///     assert_eq!(body.get_f32(0, WordOrder::CDAB).unwrap(), 1234.5678);
+    /// }
+    /// ```
+    pub fn get_f32(&self, offset: usize, order: WordOrder) -> Result<f32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The register at `offset` read as a signed 16-bit value
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// past the registers read.
+    pub fn get_i16(&self, offset: usize) -> Result<i16> {
+        Ok(register(&self.values, offset)? as i16)
+    }
+
+    /// Bit `bit` of the register at `register_offset`, bit 0 the least significant
+    ///
+    /// See [`ReadMultipleHoldingRegistersResponse::get_bit`].
+    pub fn get_bit(&self, register_offset: usize, bit: u8) -> Result<bool> {
+        bit_at(&self.values, register_offset, bit)
+    }
+
+    /// The register at `offset` read as a signed count of `scale`
+    ///
+    /// See [`scaled_from_register`](crate::data::scaled_from_register).
+    pub fn get_scaled(&self, offset: usize, scale: f64) -> Result<f64> {
+        Ok(scaled_from_register(register(&self.values, offset)?, scale))
+    }
+
+    /// The ASCII string packed into the `len` registers from register `offset`
+    ///
+    /// See [`string_from_registers`]. Returns [`ErrorKind::InvalidInput`] unless all of them
+    /// were read.
+    pub fn get_string(&self, offset: usize, len: usize, order: CharOrder) -> Result<String> {
+        string_at(&self.values, offset, len, order)
+    }
+
+    /// The value of the `registers` BCD registers from register `offset`, `0x1234` being 1234
+    ///
+    /// See [`ReadMultipleHoldingRegistersResponse::get_bcd`].
+    pub fn get_bcd(&self, offset: usize, registers: usize) -> Result<u64> {
+        bcd_at(&self.values, offset, registers)
+    }
+
+    /// The `u32` held by the two registers from register `offset`
+    ///
+    /// The offset counts registers from the first one read. Returns [`ErrorKind::InvalidInput`]
+    /// unless both registers were read.
+    pub fn get_u32(&self, offset: usize, order: WordOrder) -> Result<u32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `i32` held by the two registers from register `offset`
+    pub fn get_i32(&self, offset: usize, order: WordOrder) -> Result<i32> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `f64` held by the four registers from register `offset`
+    ///
+    /// Like [`get_f32`](Self::get_f32), the word order spanning all four registers.
+    pub fn get_f64(&self, offset: usize, order: WordOrder) -> Result<f64> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `u64` held by the four registers from register `offset`
+    pub fn get_u64(&self, offset: usize, order: WordOrder) -> Result<u64> {
+        value_at(&self.values, offset, order)
+    }
+
+    /// The `i64` held by the four registers from register `offset`
+    pub fn get_i64(&self, offset: usize, order: WordOrder) -> Result<i64> {
+        value_at(&self.values, offset, order)
+    }
 }
 
 /// Function Code `0x05`
@@ -319,6 +987,100 @@ impl WriteMultipleHoldingRegistersResponse {
     }
 }
 
+/// Function Code `0x17`
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReadWriteMultipleRegistersResponse {
+    /// Number of bytes of register values to follow
+    pub(crate) bytes_number: u8,
+
+    /// Values of the holding registers read
+    pub(crate) values: Bytes,
+}
+
+impl Length for ReadWriteMultipleRegistersResponse {
+    fn len(&self) -> u16 {
+        1 + self.values.len() as u16
+    }
+}
+
+impl ReadWriteMultipleRegistersResponse {
+    pub(crate) fn new(values: Vec<u8>) -> ReadWriteMultipleRegistersResponse {
+        ReadWriteMultipleRegistersResponse {
+            bytes_number: byte_count(values.len()),
+            values: Bytes::from(values),
+        }
+    }
+
+    pub fn get_bytes_number(&self) -> &u8 {
+        &self.bytes_number
+    }
+
+    pub fn get_values(&self) -> &Bytes {
+        &self.values
+    }
+
+    /// The registers read, big-endian, a trailing odd byte left out
+    pub fn registers(&self) -> impl ExactSizeIterator<Item = u16> + '_ {
+        registers(&self.values)
+    }
+
+    /// The registers read, big-endian, see [`registers`](Self::registers)
+    pub fn to_vec_u16(&self) -> Vec<u16> {
+        self.registers().collect()
+    }
+
+    /// The two registers read as one 32-bit value
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{Frame, Response};
+    /// use easy_modbus::data::WordOrder;
+    /// let response = Frame::tcp().read_write_multiple_registers_response(0x01, vec![0x56, 0x78, 0x12, 0x34]);
+    /// if let Response::ReadWriteMultipleRegisters(_, body) = response {
+    ///     assert_eq!(body.as_u32_with(WordOrder::CDAB).unwrap(), 0x1234_5678);
+    /// }
+    /// ```
+    pub fn as_u32_with(&self, order: WordOrder) -> Result<u32> {
+        u32_with(&self.values, order)
+    }
+
+    /// The two registers read as one `f32`
+    ///
+    /// Returns [`ErrorKind::InvalidData`] unless exactly two registers were read, see
+    /// [`Frame::as_f32`](crate::Frame::as_f32) for the word order of the frame.
+    pub fn as_f32_with(&self, order: WordOrder) -> Result<f32> {
+        only_value(&self.values, order)
+    }
+}
+
+/// A response of a function this crate doesn't model, see [`Function::Custom`](crate::Function)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomResponse {
+    /// Everything following the function code, opaque
+    pub(crate) data: Bytes,
+}
+
+impl Length for CustomResponse {
+    fn len(&self) -> u16 {
+        self.data.len() as u16
+    }
+}
+
+impl CustomResponse {
+    pub(crate) fn new(data: Vec<u8>) -> CustomResponse {
+        CustomResponse {
+            data: Bytes::from(data),
+        }
+    }
+
+    pub fn get_data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ExceptionResponse {
     pub(crate) exception: Exception,
@@ -340,44 +1102,113 @@ impl ExceptionResponse {
     }
 }
 
-impl From<ReadCoilsResponse> for BytesMut {
-    fn from(response: ReadCoilsResponse) -> Self {
+/// Register `index` of register values
+fn register(values: &Bytes, index: usize) -> Result<u16> {
+    match values.chunks_exact(2).nth(index) {
+        Some(bytes) => Ok(u16::from_be_bytes([bytes[0], bytes[1]])),
+        None => Err(ConversionError::OffsetOutOfRange {
+            offset: index,
+            len: values.len() / 2,
+        }
+        .into()),
+    }
+}
+
+/// Bit `bit` of the register `index` of register values
+fn bit_at(values: &Bytes, index: usize, bit: u8) -> Result<bool> {
+    if bit > 15 {
+        let (offset, len) = (bit as usize, 16);
+        return Err(ConversionError::OffsetOutOfRange { offset, len }.into());
+    }
+    Ok(Bitfield16::new(register(values, index)?).bit(bit))
+}
+
+/// Every register of register values, a trailing odd byte left out
+fn registers(values: &Bytes) -> impl ExactSizeIterator<Item = u16> + '_ {
+    values
+        .chunks_exact(2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+/// The string packed into the `len` registers from register `offset` of register values
+fn string_at(values: &Bytes, offset: usize, len: usize, order: CharOrder) -> Result<String> {
+    let registers = (0..len)
+        .map(|index| register(values, offset.saturating_add(index)))
+        .collect::<Result<Vec<u16>>>()?;
+    Ok(string_from_registers(&registers, order))
+}
+
+/// The BCD value of the `len` registers from register `offset` of register values
+fn bcd_at(values: &Bytes, offset: usize, len: usize) -> Result<u64> {
+    let registers = (0..len)
+        .map(|index| register(values, offset.saturating_add(index)))
+        .collect::<Result<Vec<u16>>>()?;
+    Ok(bcd_from_registers(&registers, WordOrder::BigEndian)?)
+}
+
+/// The value held by the registers from register `offset` of register values
+fn value_at<T: FromRegisters>(values: &Bytes, offset: usize, order: WordOrder) -> Result<T> {
+    let registers = (0..T::REGISTER_COUNT)
+        .map(|index| register(values, offset.saturating_add(index)))
+        .collect::<Result<Vec<u16>>>()?;
+    T::from_registers(&registers, order)
+}
+
+fn u32_with(values: &Bytes, order: WordOrder) -> Result<u32> {
+    only_value(values, order)
+}
+
+/// The value held by all of register values, exactly as many registers as it occupies
+pub(crate) fn only_value<T: FromRegisters>(values: &Bytes, order: WordOrder) -> Result<T> {
+    match values.len() {
+        actual if actual == 2 * T::REGISTER_COUNT => value_at(values, 0, order),
+        // A trailing odd byte counts as a register
+        actual => Err(ConversionError::LengthMismatch {
+            expected: T::REGISTER_COUNT,
+            actual: actual.div_ceil(2),
+        }
+        .into()),
+    }
+}
+
+impl From<&ReadCoilsResponse> for BytesMut {
+    fn from(response: &ReadCoilsResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u8(response.bytes_number);
-        buf.put_slice(response.values.as_slice());
+        buf.put_slice(&response.values);
         buf
     }
 }
 
-impl From<ReadDiscreteInputsResponse> for BytesMut {
-    fn from(response: ReadDiscreteInputsResponse) -> Self {
+impl From<&ReadDiscreteInputsResponse> for BytesMut {
+    fn from(response: &ReadDiscreteInputsResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u8(response.bytes_number);
-        buf.put_slice(response.values.as_slice());
+        buf.put_slice(&response.values);
         buf
     }
 }
 
-impl From<ReadMultipleHoldingRegistersResponse> for BytesMut {
-    fn from(response: ReadMultipleHoldingRegistersResponse) -> Self {
+impl From<&ReadMultipleHoldingRegistersResponse> for BytesMut {
+    fn from(response: &ReadMultipleHoldingRegistersResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u8(response.bytes_number);
-        buf.put_slice(response.values.as_slice());
+        buf.put_slice(&response.values);
         buf
     }
 }
 
-impl From<ReadInputRegistersResponse> for BytesMut {
-    fn from(response: ReadInputRegistersResponse) -> Self {
+impl From<&ReadInputRegistersResponse> for BytesMut {
+    fn from(response: &ReadInputRegistersResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u8(response.bytes_number);
-        buf.put_slice(response.values.as_slice());
+        buf.put_slice(&response.values);
         buf
     }
 }
 
-impl From<WriteSingleCoilResponse> for BytesMut {
-    fn from(response: WriteSingleCoilResponse) -> Self {
+impl From<&WriteSingleCoilResponse> for BytesMut {
+    fn from(response: &WriteSingleCoilResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(response.coil_address);
         buf.put_u16(response.value);
@@ -385,8 +1216,8 @@ impl From<WriteSingleCoilResponse> for BytesMut {
     }
 }
 
-impl From<WriteSingleHoldingRegisterResponse> for BytesMut {
-    fn from(response: WriteSingleHoldingRegisterResponse) -> Self {
+impl From<&WriteSingleHoldingRegisterResponse> for BytesMut {
+    fn from(response: &WriteSingleHoldingRegisterResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(response.register_address);
         buf.put_u16(response.value);
@@ -394,8 +1225,8 @@ impl From<WriteSingleHoldingRegisterResponse> for BytesMut {
     }
 }
 
-impl From<WriteMultipleCoilsResponse> for BytesMut {
-    fn from(response: WriteMultipleCoilsResponse) -> Self {
+impl From<&WriteMultipleCoilsResponse> for BytesMut {
+    fn from(response: &WriteMultipleCoilsResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(response.first_address);
         buf.put_u16(response.coils_number);
@@ -403,8 +1234,8 @@ impl From<WriteMultipleCoilsResponse> for BytesMut {
     }
 }
 
-impl From<WriteMultipleHoldingRegistersResponse> for BytesMut {
-    fn from(response: WriteMultipleHoldingRegistersResponse) -> Self {
+impl From<&WriteMultipleHoldingRegistersResponse> for BytesMut {
+    fn from(response: &WriteMultipleHoldingRegistersResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u16(response.first_address);
         buf.put_u16(response.registers_number);
@@ -412,16 +1243,31 @@ impl From<WriteMultipleHoldingRegistersResponse> for BytesMut {
     }
 }
 
-impl From<ExceptionResponse> for BytesMut {
-    fn from(response: ExceptionResponse) -> Self {
+impl From<&ReadWriteMultipleRegistersResponse> for BytesMut {
+    fn from(response: &ReadWriteMultipleRegistersResponse) -> Self {
+        let mut buf = BytesMut::new();
+        buf.put_u8(response.bytes_number);
+        buf.put_slice(&response.values);
+        buf
+    }
+}
+
+impl From<&CustomResponse> for BytesMut {
+    fn from(response: &CustomResponse) -> Self {
+        BytesMut::from(&response.data[..])
+    }
+}
+
+impl From<&ExceptionResponse> for BytesMut {
+    fn from(response: &ExceptionResponse) -> Self {
         let mut buf = BytesMut::new();
         buf.put_u8(response.exception.to_code());
         buf
     }
 }
 
-impl From<Head> for BytesMut {
-    fn from(head: Head) -> Self {
+impl From<&Head> for BytesMut {
+    fn from(head: &Head) -> Self {
         let mut buf = BytesMut::new();
 
         let function_code = if head.is_exception {
@@ -441,62 +1287,75 @@ impl From<Head> for BytesMut {
     }
 }
 
-pub(crate) fn response_to_bytesmut(item: Response, dst: &mut BytesMut) {
+pub(crate) fn response_to_bytesmut(item: &Response, dst: &mut BytesMut) {
+    let start = dst.len();
     let version;
     match item {
         Response::ReadCoils(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::ReadDiscreteInputs(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::ReadMultipleHoldingRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::ReadInputRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::WriteSingleCoil(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::WriteSingleHoldingRegister(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::WriteMultipleCoils(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::WriteMultipleHoldingRegisters(head, body) => {
-            version = head.version.clone();
+            version = head.version;
+            dst.put(BytesMut::from(head));
+            dst.put(BytesMut::from(body));
+        }
+        Response::ReadWriteMultipleRegisters(head, body) => {
+            version = head.version;
+            dst.put(BytesMut::from(head));
+            dst.put(BytesMut::from(body));
+        }
+        Response::Custom(head, body) => {
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
         Response::Exception(head, body) => {
-            version = head.version.clone();
+            version = head.version;
             dst.put(BytesMut::from(head));
             dst.put(BytesMut::from(body));
         }
     };
     if Rtu == version {
-        dst.put_u16(crc::compute(&dst.to_vec()));
+        dst.put_u16(crc::compute(&dst[start..]));
     }
 }
 
 #[cfg(test)]
 mod response_test {
+    use std::io::ErrorKind;
+
     use crate::frame::{Exception, Length};
     use crate::frame::response::*;
 
@@ -506,7 +1365,7 @@ mod response_test {
             ReadCoilsResponse::new(vec![0b1100_1101, 0b0110_1011, 0b1011_0010, 0b0111_1111]);
         let response_r = ReadCoilsResponse {
             bytes_number: 0x04,
-            values: vec![0b1100_1101, 0b0110_1011, 0b1011_0010, 0b0111_1111],
+            values: Bytes::from(vec![0b1100_1101, 0b0110_1011, 0b1011_0010, 0b0111_1111]),
         };
         assert_eq!(response_l, response_r);
         assert_eq!(response_l.len(), 5);
@@ -518,7 +1377,7 @@ mod response_test {
             ReadDiscreteInputsResponse::new(vec![0b1010_1100, 0b1101_1011, 0b1111_1011, 0b0000_1101]);
         let response_r = ReadDiscreteInputsResponse {
             bytes_number: 0x04,
-            values: vec![0b1010_1100, 0b1101_1011, 0b1111_1011, 0b0000_1101],
+            values: Bytes::from(vec![0b1010_1100, 0b1101_1011, 0b1111_1011, 0b0000_1101]),
         };
         assert_eq!(response_l, response_r);
         assert_eq!(response_l.len(), 5);
@@ -531,18 +1390,385 @@ mod response_test {
             ReadMultipleHoldingRegistersResponse::new(vec![0xAE, 0x41, 0x56, 0x52, 0x43, 0x40]);
         let response_r = ReadMultipleHoldingRegistersResponse {
             bytes_number: 0x06,
-            values: vec![0xAE, 0x41, 0x56, 0x52, 0x43, 0x40],
+            values: Bytes::from(vec![0xAE, 0x41, 0x56, 0x52, 0x43, 0x40]),
         };
         assert_eq!(response_l, response_r);
         assert_eq!(response_l.len(), 7);
     }
 
+    #[test]
+    fn test_bit() {
+        let response = ReadCoilsResponse::new(vec![0xCD]);
+        let bits: Vec<Option<bool>> = (0..9).map(|index| response.bit(index)).collect();
+        let expected = [true, false, true, true, false, false, true, true];
+        let expected: Vec<Option<bool>> = expected.into_iter().map(Some).chain([None]).collect();
+        assert_eq!(bits, expected);
+
+        let response = ReadDiscreteInputsResponse::new(vec![0x00, 0x80]);
+        assert_eq!(response.bit(0), Some(false));
+        assert_eq!(response.bit(15), Some(true));
+        assert_eq!(response.bit(16), None);
+    }
+
+    #[test]
+    fn test_scaled() {
+        let response = ReadMultipleHoldingRegistersResponse::new(vec![0x04, 0xD2, 0xFF, 0xFE]);
+        assert_eq!(response.scaled(0, 1).unwrap(), 12.34);
+
+        let response =
+            ReadMultipleHoldingRegistersResponse::new(vec![0x00, 0x03, 0xFF, 0x9C, 0x00, 0x02]);
+        assert_eq!(response.scaled(2, 0).unwrap(), 2000.0);
+        assert_eq!(response.scaled(1, 0).unwrap(), -100_000.0);
+        let error = response.scaled(0, 3).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_as_u32_with() {
+        use crate::data::WordOrder;
+
+        let orders = [
+            (WordOrder::ABCD, vec![0x12, 0x34, 0x56, 0x78]),
+            (WordOrder::DCBA, vec![0x78, 0x56, 0x34, 0x12]),
+            (WordOrder::BADC, vec![0x34, 0x12, 0x78, 0x56]),
+            (WordOrder::CDAB, vec![0x56, 0x78, 0x12, 0x34]),
+        ];
+        for (order, values) in orders {
+            let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+            assert_eq!(response.as_u32_with(order).unwrap(), 0x1234_5678);
+            let response = ReadInputRegistersResponse::new(values.clone());
+            assert_eq!(response.as_u32_with(order).unwrap(), 0x1234_5678);
+            let response = ReadWriteMultipleRegistersResponse::new(values);
+            assert_eq!(response.as_u32_with(order).unwrap(), 0x1234_5678);
+        }
+
+        let response = ReadInputRegistersResponse::new(vec![0x12, 0x34]);
+        let error = response.as_u32_with(WordOrder::ABCD).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        let response = ReadInputRegistersResponse::new(vec![0x00; 6]);
+        assert!(response.as_u32_with(WordOrder::ABCD).is_err());
+    }
+
+    #[test]
+    fn test_accessors_short_values() {
+        use crate::data::{CharOrder, ConversionError, WordOrder};
+
+        // Every accessor over random short register values, failing or not but never panicking
+        let mut seed = 0x1234_5678u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            seed >> 24
+        };
+        for _ in 0..2000 {
+            let len = next() as usize % 12;
+            let values: Vec<u8> = (0..len).map(|_| next() as u8).collect();
+            let offset = next() as usize % 8;
+            let count = next() as usize % 6;
+            let bit = next() as u8 % 20;
+            let order = [WordOrder::ABCD, WordOrder::CDAB][next() as usize % 2];
+
+            let holding = ReadMultipleHoldingRegistersResponse::new(values.clone());
+            let input = ReadInputRegistersResponse::new(values.clone());
+            let read_write = ReadWriteMultipleRegistersResponse::new(values);
+            let _ = holding.get_f32(offset, order);
+            let _ = holding.get_u32(offset, order);
+            let _ = holding.get_i32(offset, order);
+            let _ = holding.get_f64(offset, order);
+            let _ = holding.get_u64(offset, order);
+            let _ = holding.get_i64(offset, order);
+            let _ = holding.get_i16(offset);
+            let _ = holding.get_bit(offset, bit);
+            let _ = holding.get_scaled(offset, 0.1);
+            let _ = holding.get_string(offset, count, CharOrder::HighFirst);
+            let _ = holding.get_bcd(offset, count);
+            let _ = holding.scaled(offset, count);
+            let _ = holding.as_u32_with(order);
+            let _ = holding.as_f32_with(order);
+            let _ = input.get_f32(offset, order);
+            let _ = input.get_u64(offset, order);
+            let _ = input.get_bit(offset, bit);
+            let _ = input.get_string(offset, count, CharOrder::LowFirst);
+            let _ = input.get_bcd(offset, count);
+            let _ = input.as_f32_with(order);
+            let _ = read_write.as_u32_with(order);
+            let _ = read_write.as_f32_with(order);
+
+            if let Err(error) = holding.get_i16(offset) {
+                let len = holding.get_values().len() / 2;
+                assert_eq!(
+                    ConversionError::try_from(error).unwrap(),
+                    ConversionError::OffsetOutOfRange { offset, len }
+                );
+            }
+        }
+
+        let response = ReadMultipleHoldingRegistersResponse::new(vec![0x00, 0x01, 0x02]);
+        let error = response.as_f32_with(WordOrder::ABCD).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        let response = ReadMultipleHoldingRegistersResponse::new(vec![0x00; 5]);
+        let error = response.as_u32_with(WordOrder::ABCD).unwrap_err();
+        assert_eq!(
+            ConversionError::try_from(error).unwrap(),
+            ConversionError::LengthMismatch {
+                expected: 2,
+                actual: 3
+            }
+        );
+        let error = response.get_bit(0, 16).unwrap_err();
+        assert_eq!(
+            ConversionError::try_from(error).unwrap(),
+            ConversionError::OffsetOutOfRange { offset: 16, len: 16 }
+        );
+    }
+
+    #[test]
+    fn test_into_payload() {
+        use crate::{Frame, Function};
+
+        let frame = Frame::rtu();
+        let response = frame.read_holding_register_response(0x01, vec![0x12, 0x34, 0x56]);
+        assert_eq!(response.into_registers().unwrap(), vec![0x1234]);
+        let response = frame.read_discrete_response(0x01, vec![0x0D, 0x01]);
+        assert_eq!(
+            response.into_coils(10).unwrap(),
+            vec![true, false, true, true, false, false, false, false, true, false]
+        );
+        let response = frame.read_coils_response(0x01, vec![0x01]);
+        assert_eq!(response.into_coils(16).unwrap().len(), 8);
+
+        let response = frame.read_coils_response(0x01, vec![0x01]);
+        let error = response.into_registers().unwrap_err();
+        assert_eq!(error.get_actual(), "ReadCoils");
+        assert_eq!(
+            error.to_string(),
+            "Expected ReadMultipleHoldingRegisters or ReadInputRegisters response, got ReadCoils"
+        );
+        let response =
+            frame.exception_response(0x01, Function::ReadCoils, Exception::SlaveDeviceBusy);
+        let error = response.clone().into_coils(8).unwrap_err();
+        assert_eq!(error.get_actual(), "Exception");
+        assert_eq!(response.into_exception().unwrap(), Exception::SlaveDeviceBusy);
+        let response = frame.read_input_register_response(0x01, vec![0x00, 0x01]);
+        let error = std::io::Error::from(response.into_exception().unwrap_err());
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(error.to_string(), "Expected Exception response, got ReadInputRegisters");
+    }
+
+    #[test]
+    fn test_frame_word_order() {
+        use crate::data::WordOrder;
+        use crate::Frame;
+
+        // 1234.5678 is 0x449A522B
+        let values = vec![0x44, 0x9A, 0x52, 0x2B];
+        let frame = Frame::tcp().with_word_order(WordOrder::BigEndian);
+        let response = frame.read_holding_register_response(0x01, values.clone());
+        assert_eq!(frame.as_f32(&response).unwrap(), 1234.5678);
+        assert_eq!(frame.as_u32(&response).unwrap(), 0x449A_522B);
+
+        let frame = Frame::tcp().with_word_order(WordOrder::LittleEndian);
+        assert_eq!(frame.as_f32(&response).unwrap(), f32::from_bits(0x522B_449A));
+        assert_eq!(frame.as_u32(&response).unwrap(), 0x522B_449A);
+        let response = frame.read_write_multiple_registers_response(0x01, values);
+        assert_eq!(frame.as_f32(&response).unwrap(), f32::from_bits(0x522B_449A));
+
+        let error = Frame::tcp().as_f32(&response).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let response = frame.read_coils_response(0x01, vec![0x00, 0x00, 0x00, 0x00]);
+        let error = frame.as_u32(&response).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let response = frame.read_input_register_response(0x01, vec![0x00; 8]);
+        let error = frame.as_f32(&response).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_get_f32() {
+        use crate::data::WordOrder;
+
+        // 1234.5678 is 0x449A522B, the last register padding
+        let orders = [
+            (WordOrder::ABCD, vec![0x44, 0x9A, 0x52, 0x2B, 0x00, 0x00]),
+            (WordOrder::CDAB, vec![0x52, 0x2B, 0x44, 0x9A, 0x00, 0x00]),
+            (WordOrder::BADC, vec![0x9A, 0x44, 0x2B, 0x52, 0x00, 0x00]),
+            (WordOrder::DCBA, vec![0x2B, 0x52, 0x9A, 0x44, 0x00, 0x00]),
+        ];
+        for (order, values) in orders {
+            let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+            assert_eq!(response.get_f32(0, order).unwrap(), 1234.5678);
+            let response = ReadInputRegistersResponse::new(values);
+            assert_eq!(response.get_f32(0, order).unwrap(), 1234.5678);
+        }
+
+        let response = ReadInputRegistersResponse::new(vec![0x00, 0x00, 0x44, 0x9A, 0x52, 0x2B]);
+        assert_eq!(response.get_f32(1, WordOrder::ABCD).unwrap(), 1234.5678);
+        let error = response.get_f32(2, WordOrder::ABCD).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_coils() {
+        let response = ReadCoilsResponse::new(vec![0xCD, 0x01]);
+        let coils = [true, false, true, true, false, false, true, true, true, false];
+        assert_eq!(response.coils(10), coils);
+        assert_eq!(response.coils(3), coils[..3]);
+        assert_eq!(response.coils(20).len(), 16);
+        let response = ReadDiscreteInputsResponse::new(vec![0xCD, 0x01]);
+        assert_eq!(response.inputs(10), coils);
+    }
+
+    #[test]
+    fn test_iter_bits() {
+        let response = ReadCoilsResponse::new(vec![0xAA]);
+        assert_eq!(response.iter_bits_n(8).len(), 8);
+        assert_eq!(response.iter_bits_n(8).filter(|&coil| coil).count(), 4);
+        let response = ReadCoilsResponse::new(vec![0xAA, 0x01]);
+        let coils: Vec<bool> = response.iter_bits_n(9).collect();
+        assert_eq!(coils.len(), 9);
+        assert_eq!(coils, response.coils(9));
+        assert!(coils[8]);
+        assert_eq!(response.iter_bits().len(), 16);
+
+        // A full read, 250 bytes for 2000 coils
+        let coils: Vec<bool> = (0..2000).map(|i| i % 3 == 0).collect();
+        let response = ReadDiscreteInputsResponse::new(bits::pack(&coils));
+        let mut iter = response.iter_bits_n(2000);
+        assert_eq!(iter.len(), 2000);
+        assert!(iter.by_ref().eq(coils.iter().copied()));
+        assert_eq!(iter.len(), 0);
+        assert_eq!(response.iter_bits().len(), 2000);
+        assert_eq!(response.iter_bits_n(2008).len(), 2000);
+    }
+
+    #[test]
+    fn test_get_scaled() {
+        // 21.5, -12.5 and the most negative temperature in tenths of a degree
+        let values = vec![0x00, 0xD7, 0xFF, 0x83, 0x80, 0x00];
+        let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+        assert_eq!(response.get_i16(0).unwrap(), 215);
+        assert_eq!(response.get_i16(1).unwrap(), -125);
+        assert_eq!(response.get_i16(2).unwrap(), i16::MIN);
+        assert_eq!(response.get_scaled(1, 0.1).unwrap(), -12.5);
+        assert_eq!(response.get_scaled(2, 0.1).unwrap(), -3276.8);
+        let error = response.get_i16(3).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_scaled(0, 0.1).unwrap(), 21.5);
+        assert!(response.get_scaled(3, 0.1).is_err());
+    }
+
+    #[test]
+    fn test_get_string() {
+        use crate::data::CharOrder;
+
+        let values = b"\x00\x01SN-0042 \x00\x00".to_vec();
+        let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+        assert_eq!(response.get_string(1, 5, CharOrder::HighFirst).unwrap(), "SN-0042");
+        assert_eq!(response.get_string(1, 2, CharOrder::LowFirst).unwrap(), "NS0-");
+        let error = response.get_string(1, 6, CharOrder::HighFirst).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_string(3, 2, CharOrder::HighFirst).unwrap(), "042");
+    }
+
+    #[test]
+    fn test_get_bcd() {
+        let values = vec![0x00, 0x12, 0x34, 0x56, 0x12, 0xA4];
+        let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+        assert_eq!(response.get_bcd(0, 2).unwrap(), 123456);
+        assert_eq!(response.get_bcd(1, 0).unwrap(), 0);
+        let error = response.get_bcd(2, 1).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        let error = response.get_bcd(1, 3).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_bcd(1, 1).unwrap(), 3456);
+        assert!(response.get_bcd(usize::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn test_get_u32() {
+        use crate::data::WordOrder;
+
+        let values = vec![0x00, 0x01, 0x00, 0x02, 0xFF, 0xFF];
+        let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+        assert_eq!(response.get_u32(0, WordOrder::BigEndian).unwrap(), 0x0001_0002);
+        assert_eq!(response.get_u32(0, WordOrder::LittleEndian).unwrap(), 0x0002_0001);
+        assert_eq!(response.get_i32(1, WordOrder::LittleEndian).unwrap(), -65534);
+        // The last register alone, then past the end
+        let error = response.get_u32(2, WordOrder::BigEndian).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(response.get_u32(3, WordOrder::BigEndian).is_err());
+        assert!(response.get_i32(usize::MAX, WordOrder::BigEndian).is_err());
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_u32(1, WordOrder::BigEndian).unwrap(), 0x0002_FFFF);
+        assert!(response.get_i32(2, WordOrder::BigEndian).is_err());
+    }
+
+    #[test]
+    fn test_registers() {
+        let response = ReadMultipleHoldingRegistersResponse::new(vec![0x12, 0x34, 0xAB, 0xCD]);
+        assert_eq!(response.to_vec_u16(), vec![0x1234, 0xABCD]);
+        assert_eq!(response.registers().len(), 2);
+        // A malformed odd byte count
+        let response = ReadInputRegistersResponse::new(vec![0x12, 0x34, 0xAB]);
+        assert_eq!(response.registers().collect::<Vec<_>>(), vec![0x1234]);
+        assert!(ReadInputRegistersResponse::new(vec![]).to_vec_u16().is_empty());
+    }
+
+    #[test]
+    fn test_get_bit() {
+        let response = ReadMultipleHoldingRegistersResponse::new(vec![0x00, 0x00, 0x80, 0x01]);
+        assert!(!response.get_bit(0, 0).unwrap());
+        assert!(!response.get_bit(0, 15).unwrap());
+        assert!(response.get_bit(1, 0).unwrap());
+        assert!(response.get_bit(1, 15).unwrap());
+        assert!(!response.get_bit(1, 14).unwrap());
+        assert_eq!(response.get_bit(1, 16).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(response.get_bit(2, 0).unwrap_err().kind(), ErrorKind::InvalidInput);
+        let response = ReadInputRegistersResponse::new(vec![0x80, 0x01]);
+        assert!(response.get_bit(0, 15).unwrap());
+    }
+
+    #[test]
+    fn test_get_64() {
+        use crate::data::WordOrder;
+
+        // 12345.678 is 0x40C81CD6C8B43958, after a register of padding
+        let values = vec![0x00, 0x00, 0x58, 0x39, 0xB4, 0xC8, 0xD6, 0x1C, 0xC8, 0x40];
+        let response = ReadMultipleHoldingRegistersResponse::new(values);
+        assert_eq!(response.get_f64(1, WordOrder::LittleEndianByteSwap).unwrap(), 12345.678);
+        let values = vec![0x40, 0xC8, 0x1C, 0xD6, 0xC8, 0xB4, 0x39, 0x58];
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_f64(0, WordOrder::BigEndian).unwrap(), 12345.678);
+        assert_eq!(response.get_u64(0, WordOrder::BigEndian).unwrap(), 0x40C8_1CD6_C8B4_3958);
+        let error = response.get_i64(1, WordOrder::BigEndian).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+        let values = vec![0xFF, 0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.get_i64(0, WordOrder::LittleEndian).unwrap(), -2);
+    }
+
+    #[test]
+    fn test_to_block() {
+        let values = vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04];
+        let response = ReadMultipleHoldingRegistersResponse::new(values.clone());
+        let block = response.to_block(0x0100);
+        assert_eq!(block.get_start(), 0x0100);
+        assert_eq!(block.get_values(), &[0x0001, 0x0002, 0x0003, 0x0004]);
+        assert_eq!(block.get(0x0102), Some(0x0003));
+        assert_eq!(block.get(0x0104), None);
+        let response = ReadInputRegistersResponse::new(values);
+        assert_eq!(response.to_block(0x0100).get(0x0102), Some(0x0003));
+    }
+
     #[test]
     fn test_read_input_register_response() {
         let response_l = ReadInputRegistersResponse::new(vec![0x0C, 0x00, 0x00, 0x00]);
         let response_r = ReadInputRegistersResponse {
             bytes_number: 0x04,
-            values: vec![0x0C, 0x00, 0x00, 0x00],
+            values: Bytes::from(vec![0x0C, 0x00, 0x00, 0x00]),
         };
         assert_eq!(response_l, response_r);
         assert_eq!(response_l.len(), 5);
@@ -592,6 +1818,17 @@ mod response_test {
         assert_eq!(response_l.len(), 4);
     }
 
+    #[test]
+    fn test_read_write_multiple_registers_response() {
+        let response_l = ReadWriteMultipleRegistersResponse::new(vec![0x00, 0xFE, 0x0A, 0xCD]);
+        let response_r = ReadWriteMultipleRegistersResponse {
+            bytes_number: 0x04,
+            values: Bytes::from(vec![0x00, 0xFE, 0x0A, 0xCD]),
+        };
+        assert_eq!(response_l, response_r);
+        assert_eq!(response_l.len(), 5);
+    }
+
     #[test]
     fn test_exception_response() {
         let response_l = ExceptionResponse::new(Exception::IllegalDataAddress);
@@ -601,4 +1838,50 @@ mod response_test {
         assert_eq!(response_l, response_r);
         assert_eq!(response_l.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_pdu_bytes() {
+        use crate::frame::Function;
+        use crate::Frame;
+
+        let response = Frame::rtu().read_input_register_response(0x0B, vec![0x10, 0x2F]);
+        assert_eq!(&response.pdu_bytes()[..], &[0x04, 0x02, 0x10, 0x2F]);
+        let response = Frame::tcp().exception_response(
+            0x0B,
+            Function::ReadCoils,
+            Exception::IllegalDataAddress,
+        );
+        assert_eq!(&response.pdu_bytes()[..], &[0x81, 0x02]);
+    }
+
+    #[test]
+    fn test_describe() {
+        use crate::frame::Function;
+        use crate::Frame;
+
+        let response = Frame::rtu().exception_response(
+            0x0B,
+            Function::WriteMultipleHoldingRegisters,
+            Exception::SlaveDeviceFailure,
+        );
+        let description = response.describe();
+        assert!(description.contains("WriteMultipleHoldingRegisters"));
+        assert!(description.contains("SlaveDeviceFailure (0x04)"));
+        let response = Frame::tcp().custom_response(0x0B, 0x41, vec![0x01]);
+        assert_eq!(response.describe(), "Custom(65) response from unit 0x0B");
+    }
+
+    #[test]
+    fn test_into_tcp() {
+        use crate::codec::decode_rtu_response;
+
+        let v = [0x0B, 0x03, 0x04, 0x00, 0x06, 0x00, 0x05, 0x70, 0x31];
+        let response = decode_rtu_response(&mut BytesMut::from(&v[..])).unwrap().unwrap();
+        let response = response.into_tcp(0x1234);
+        assert_eq!(response.head().get_tid(), 0x1234);
+        assert_eq!(response.head().get_uid(), 0x0B);
+        assert!(response.encodes_to(&[
+            0x12, 0x34, 0x00, 0x00, 0x00, 0x07, 0x0B, 0x03, 0x04, 0x00, 0x06, 0x00, 0x05,
+        ]));
+    }
+}
@@ -31,7 +31,7 @@
 //! }
 //!
 //! async fn process(stream: TcpStream) -> Result<(), Box<dyn Error>> {
-//!     let mut transport = Framed::new(stream, TcpServerCodec);
+//!     let mut transport = Framed::new(stream, TcpServerCodec::default());
 //!     let frame = Frame::tcp();
 //!     while let Some(request) = transport.next().await {
 //!         match request {
@@ -67,7 +67,7 @@
 //!     let serial_builder = tokio_serial::new(tty_path, rate);
 //!     let port = SerialStream::open(&serial_builder).unwrap();
 //!
-//!     let mut transport = Framed::new(port, RtuClientCodec);
+//!     let mut transport = Framed::new(port, RtuClientCodec::default());
 //!
 //!     let frame = Frame::rtu();
 //!     let request = frame.read_multiple_holding_registers_request(slave, 0x00, 0x02);
@@ -120,7 +120,7 @@
 //!     let serial_builder = tokio_serial::new(tty_path, rate);
 //!     let port = SerialStream::open(&serial_builder).unwrap();
 //!
-//!     let mut transport = Framed::new(port, RtuClientCodec);
+//!     let mut transport = Framed::new(port, RtuClientCodec::default());
 //!
 //!     let frame = Frame::rtu();
 //!     let request = frame.read_multiple_holding_registers_request(slave, 0x00, 0x02);
@@ -155,14 +155,29 @@
 //! ```
 extern crate core;
 
+pub use error::{ModbusError, WrongVariant};
+pub use frame::AddressRange;
 pub use frame::Frame;
 pub use frame::Function;
+pub use frame::FunctionCode;
 pub use frame::Exception;
+pub use frame::Head;
+pub use frame::Version;
 pub use frame::request::*;
 pub use frame::response::Response;
 
+#[cfg(feature = "runtime")]
+pub mod client;
 pub mod codec;
+pub mod data;
+pub mod limits;
+pub mod prelude;
+#[cfg(feature = "runtime")]
+pub mod server;
+#[cfg(feature = "runtime")]
+pub mod testing;
 pub mod util;
 
+mod error;
 mod frame;
 
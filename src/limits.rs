@@ -0,0 +1,47 @@
+//! Quantity and length limits of the Modbus specification.
+//!
+//! They follow from the largest PDU, 253 bytes: the 256 bytes of a RTU ADU less the unit id
+//! and the CRC. A Modbus TCP ADU carries the same PDU after its 7 bytes MBAP header. The
+//! limits are the same for every transport, a request over TCP may read 125 registers as one
+//! over RTU does.
+//!
+//! [`dispatch`](crate::server::dispatch) answers a request asking for more than these with
+//! [`Exception::IllegalDataValue`](crate::Exception::IllegalDataValue).
+//!
+//! # Examples
+//!
+//! ```
+//! use easy_modbus::limits::{MAX_PDU_LEN, MAX_READ_REGISTERS};
+//! // Function code, byte count and the register values
+//! assert!(2 + 2 * MAX_READ_REGISTERS as usize <= MAX_PDU_LEN);
+//! ```
+
+/// Largest PDU, function code and data, in bytes
+pub const MAX_PDU_LEN: usize = 253;
+
+/// Largest RTU ADU, unit id, PDU and CRC, in bytes
+pub const MAX_RTU_ADU_LEN: usize = 1 + MAX_PDU_LEN + 2;
+
+/// Largest TCP ADU, MBAP header and PDU, in bytes
+pub const MAX_TCP_ADU_LEN: usize = 7 + MAX_PDU_LEN;
+
+/// Most coils a read coils request may ask for
+pub const MAX_READ_COILS: u16 = 2000;
+
+/// Most discrete inputs a read discrete inputs request may ask for
+pub const MAX_READ_DISCRETE_INPUTS: u16 = 2000;
+
+/// Most registers a read holding registers or read input registers request may ask for
+pub const MAX_READ_REGISTERS: u16 = 125;
+
+/// Most coils a write multiple coils request may carry
+pub const MAX_WRITE_COILS: u16 = 1968;
+
+/// Most registers a write multiple registers request may carry
+pub const MAX_WRITE_REGISTERS: u16 = 123;
+
+/// Most registers a read/write multiple registers request may read
+pub const MAX_READ_WRITE_READ_REGISTERS: u16 = 125;
+
+/// Most registers a read/write multiple registers request may write
+pub const MAX_READ_WRITE_WRITE_REGISTERS: u16 = 121;
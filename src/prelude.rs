@@ -0,0 +1,17 @@
+//! Common imports for Easy Modbus.
+//!
+//! # Examples
+//!
+//! ```
+//! use easy_modbus::prelude::*;
+//!
+//! let frame = Frame::tcp();
+//! let request: Request = frame.read_coils_request(0x01, 0x0002, 0x0008);
+//! let _codec = TcpClientCodec::default();
+//! assert_eq!(request.to_string(), "00 01 00 00 00 06 01 01 00 02 00 08");
+//! ```
+
+pub use crate::codec::{RtuClientCodec, RtuServerCodec, TcpClientCodec, TcpServerCodec};
+pub use crate::frame::request::Request;
+pub use crate::frame::response::Response;
+pub use crate::frame::{Exception, Frame, Function, FunctionCode};
@@ -0,0 +1,659 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::io::{Error, Result};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::codec::RtuServerCodec;
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::Exception;
+
+use super::dispatch::{is_dropped, prepare, respond, Call, Reply};
+use super::metrics::record;
+use super::serve::{resync, serve_connections, Dispatch, Hooks};
+use super::{Limits, MetricsSink, TcpLimits};
+
+/// Future of a handler
+type Answer<T> = BoxFuture<'static, std::result::Result<T, Exception>>;
+type ReadHandler<T> = Box<dyn Fn(u8, u16, u16) -> Answer<Vec<T>> + Send + Sync>;
+type WriteHandler<T> = Box<dyn Fn(u8, u16, T) -> Answer<()> + Send + Sync>;
+type ReadWriteHandler = Box<dyn Fn(u8, u16, u16, u16, Vec<u16>) -> Answer<Vec<u16>> + Send + Sync>;
+
+/// A server answering with asynchronous closures, one per function
+///
+/// An alternative to implementing [`Service`](super::Service) for quick tools. Requests are
+/// validated and answered exactly like [`dispatch`](super::dispatch) does for a service, a
+/// function without a handler is answered with [`Exception::IllegalFunction`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tokio::net::TcpListener;
+///
+/// use easy_modbus::server::{Limits, ServerBuilder};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:502").await?;
+///     ServerBuilder::new()
+///         .with_limits(Limits::default().with_holding_registers(0x0000, 10))
+///         .on_read_holding_registers(|_unit, address, count| async move {
+///             Ok((address..address + count).collect())
+///         })
+///         .on_write_single_coil(|_unit, address, value| async move {
+///             println!("coil {} {}", address, value);
+///             Ok(())
+///         })
+///         .serve_tcp(listener)
+///         .await
+/// }
+/// ```
+#[derive(Default)]
+pub struct ServerBuilder {
+    limits: Limits,
+    read_coils: Option<ReadHandler<bool>>,
+    read_discrete_inputs: Option<ReadHandler<bool>>,
+    read_holding_registers: Option<ReadHandler<u16>>,
+    read_input_registers: Option<ReadHandler<u16>>,
+    write_single_coil: Option<WriteHandler<bool>>,
+    write_single_register: Option<WriteHandler<u16>>,
+    write_multiple_coils: Option<WriteHandler<Vec<bool>>>,
+    write_multiple_registers: Option<WriteHandler<Vec<u16>>>,
+    read_write_multiple_registers: Option<ReadWriteHandler>,
+}
+
+impl fmt::Debug for ServerBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerBuilder")
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ServerBuilder {
+    pub fn new() -> ServerBuilder {
+        ServerBuilder::default()
+    }
+
+    /// Check requests against `limits` before a handler is called
+    ///
+    /// See [`Service::limits`](super::Service::limits).
+    pub fn with_limits(mut self, limits: Limits) -> ServerBuilder {
+        self.limits = limits;
+        self
+    }
+
+    /// Answer read coils (Function Code: 0x01) with `handler(unit, address, count)`
+    pub fn on_read_coils<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<bool>, Exception>> + Send + 'static,
+    {
+        self.read_coils = Some(Box::new(move |unit, address, count| {
+            handler(unit, address, count).boxed()
+        }));
+        self
+    }
+
+    /// Answer read discrete inputs (Function Code: 0x02) with `handler(unit, address, count)`
+    pub fn on_read_discrete_inputs<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<bool>, Exception>> + Send + 'static,
+    {
+        self.read_discrete_inputs = Some(Box::new(move |unit, address, count| {
+            handler(unit, address, count).boxed()
+        }));
+        self
+    }
+
+    /// Answer read holding registers (Function Code: 0x03) with `handler(unit, address, count)`
+    pub fn on_read_holding_registers<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<u16>, Exception>> + Send + 'static,
+    {
+        self.read_holding_registers = Some(Box::new(move |unit, address, count| {
+            handler(unit, address, count).boxed()
+        }));
+        self
+    }
+
+    /// Answer read input registers (Function Code: 0x04) with `handler(unit, address, count)`
+    pub fn on_read_input_registers<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<u16>, Exception>> + Send + 'static,
+    {
+        self.read_input_registers = Some(Box::new(move |unit, address, count| {
+            handler(unit, address, count).boxed()
+        }));
+        self
+    }
+
+    /// Answer write a single coil (Function Code: 0x05) with `handler(unit, address, value)`
+    pub fn on_write_single_coil<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, bool) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), Exception>> + Send + 'static,
+    {
+        self.write_single_coil = Some(Box::new(move |unit, address, value| {
+            handler(unit, address, value).boxed()
+        }));
+        self
+    }
+
+    /// Answer write a single holding register (Function Code: 0x06) with
+    /// `handler(unit, address, value)`
+    pub fn on_write_single_register<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), Exception>> + Send + 'static,
+    {
+        self.write_single_register = Some(Box::new(move |unit, address, value| {
+            handler(unit, address, value).boxed()
+        }));
+        self
+    }
+
+    /// Answer write multiple coils (Function Code: 0x0F) with `handler(unit, address, values)`
+    pub fn on_write_multiple_coils<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, Vec<bool>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), Exception>> + Send + 'static,
+    {
+        self.write_multiple_coils = Some(Box::new(move |unit, address, values| {
+            handler(unit, address, values).boxed()
+        }));
+        self
+    }
+
+    /// Answer write multiple holding registers (Function Code: 0x10) with
+    /// `handler(unit, address, values)`
+    pub fn on_write_multiple_registers<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, Vec<u16>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<(), Exception>> + Send + 'static,
+    {
+        self.write_multiple_registers = Some(Box::new(move |unit, address, values| {
+            handler(unit, address, values).boxed()
+        }));
+        self
+    }
+
+    /// Answer write, then read holding registers (Function Code: 0x17) with
+    /// `handler(unit, read_address, read_count, write_address, values)`
+    ///
+    /// Without this handler, the write multiple registers handler is called followed by the
+    /// read holding registers handler.
+    pub fn on_read_write_multiple_registers<F, Fut>(mut self, handler: F) -> ServerBuilder
+    where
+        F: Fn(u8, u16, u16, u16, Vec<u16>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<u16>, Exception>> + Send + 'static,
+    {
+        self.read_write_multiple_registers = Some(Box::new(
+            move |unit, read_address, read_count, write_address, values| {
+                handler(unit, read_address, read_count, write_address, values).boxed()
+            },
+        ));
+        self
+    }
+
+    /// Serve Modbus TCP clients accepted on `listener`
+    ///
+    /// Like [`serve_tcp`](super::serve_tcp), the connections are served the same way.
+    pub async fn serve_tcp(self, listener: TcpListener) -> Result<()> {
+        let active = Arc::new(AtomicUsize::new(0));
+        let (shutdown, drain) = (std::future::pending(), Duration::ZERO);
+        let (limits, hooks) = (TcpLimits::default(), Hooks::default());
+        let (listeners, service) = (vec![listener], Arc::new(self));
+        serve_connections(listeners, service, limits, shutdown, drain, active, hooks).await
+    }
+
+    /// Serve Modbus RTU requests arriving on `transport`
+    ///
+    /// Like [`serve_rtu`](super::serve_rtu).
+    pub async fn serve_rtu<T>(self, transport: Framed<T, RtuServerCodec>) -> Result<()>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        self.serve(transport).await
+    }
+
+    async fn serve<T, C>(&self, mut transport: Framed<T, C>) -> Result<()>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+        C: Decoder<Item = Request, Error = Error> + Encoder<Response, Error = Error>,
+    {
+        while let Some(request) = transport.next().await {
+            let request = match request {
+                Ok(request) => request,
+                Err(error) => {
+                    transport = resync(transport, error)?;
+                    continue;
+                }
+            };
+            if let Some(response) = self.dispatch(&request, None).await {
+                transport.send(response).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer a request with the handlers, like [`dispatch`](super::dispatch), a write with
+    /// `read_only` if set
+    async fn dispatch(&self, request: &Request, read_only: Option<Exception>) -> Option<Response> {
+        if is_dropped(request) {
+            return None;
+        }
+        let reply = match (prepare(request, &self.limits), read_only) {
+            (Ok(call), Some(exception)) if is_write(&call) => Err(exception),
+            (Ok(call), _) => self.execute(request.head().uid, call).await,
+            (Err(exception), _) => Err(exception),
+        };
+        respond(request, reply)
+    }
+
+    async fn execute(&self, unit: u8, call: Call) -> std::result::Result<Reply, Exception> {
+        match call {
+            Call::ReadCoils(address, count) => {
+                call_handler(&self.read_coils, |handler| handler(unit, address, count))
+                    .await
+                    .map(Reply::Bits)
+            }
+            Call::ReadDiscreteInputs(address, count) => {
+                call_handler(&self.read_discrete_inputs, |handler| {
+                    handler(unit, address, count)
+                })
+                .await
+                .map(Reply::Bits)
+            }
+            Call::ReadHoldingRegisters(address, count) => self
+                .read_holding_registers(unit, address, count)
+                .await
+                .map(Reply::Registers),
+            Call::ReadInputRegisters(address, count) => {
+                call_handler(&self.read_input_registers, |handler| {
+                    handler(unit, address, count)
+                })
+                .await
+                .map(Reply::Registers)
+            }
+            Call::WriteSingleCoil(address, value) => {
+                call_handler(&self.write_single_coil, |handler| {
+                    handler(unit, address, value)
+                })
+                .await
+                .map(|_| Reply::Written)
+            }
+            Call::WriteSingleRegister(address, value) => {
+                call_handler(&self.write_single_register, |handler| {
+                    handler(unit, address, value)
+                })
+                .await
+                .map(|_| Reply::Written)
+            }
+            Call::WriteMultipleCoils(address, values) => {
+                call_handler(&self.write_multiple_coils, |handler| {
+                    handler(unit, address, values)
+                })
+                .await
+                .map(|_| Reply::Written)
+            }
+            Call::WriteMultipleRegisters(address, values) => self
+                .write_multiple_registers(unit, address, values)
+                .await
+                .map(|_| Reply::Written),
+            Call::ReadWriteMultipleRegisters(read_address, read_count, write_address, values) => {
+                match &self.read_write_multiple_registers {
+                    Some(handler) => {
+                        handler(unit, read_address, read_count, write_address, values).await
+                    }
+                    None => {
+                        self.write_multiple_registers(unit, write_address, values)
+                            .await?;
+                        self.read_holding_registers(unit, read_address, read_count)
+                            .await
+                    }
+                }
+                .map(Reply::Registers)
+            }
+        }
+    }
+
+    async fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> std::result::Result<Vec<u16>, Exception> {
+        call_handler(&self.read_holding_registers, |handler| {
+            handler(unit, address, count)
+        })
+        .await
+    }
+
+    async fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: Vec<u16>,
+    ) -> std::result::Result<(), Exception> {
+        call_handler(&self.write_multiple_registers, |handler| {
+            handler(unit, address, values)
+        })
+        .await
+    }
+}
+
+impl Dispatch for ServerBuilder {
+    fn answer<'a>(
+        &'a self,
+        request: &'a Request,
+        read_only: Option<Exception>,
+        metrics: Option<&'a dyn MetricsSink>,
+    ) -> BoxFuture<'a, Option<Response>> {
+        async move {
+            let start = Instant::now();
+            let response = self.dispatch(request, read_only).await;
+            if let Some(sink) = metrics {
+                record(sink, request, response.as_ref(), start);
+            }
+            response
+        }
+        .boxed()
+    }
+}
+
+/// Whether `call` writes, which a read-only connection refuses
+fn is_write(call: &Call) -> bool {
+    !matches!(
+        call,
+        Call::ReadCoils(..)
+            | Call::ReadDiscreteInputs(..)
+            | Call::ReadHoldingRegisters(..)
+            | Call::ReadInputRegisters(..)
+    )
+}
+
+/// Run a registered handler, [`Exception::IllegalFunction`] without one
+async fn call_handler<H, T>(
+    handler: &Option<H>,
+    call: impl FnOnce(&H) -> Answer<T>,
+) -> std::result::Result<T, Exception> {
+    match handler {
+        Some(handler) => call(handler).await,
+        None => Err(Exception::IllegalFunction),
+    }
+}
+
+#[cfg(test)]
+mod builder_test {
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::codec::Framed;
+
+    use crate::codec::{RtuClientCodec, RtuServerCodec, TcpClientCodec};
+    use crate::frame::request::Request;
+    use crate::frame::response::Response;
+    use crate::frame::{Exception, Function};
+    use crate::server::{serve_tcp, Limits, ServerBuilder, Service};
+    use crate::Frame;
+
+    /// Coils and holding registers behind both kinds of server
+    #[derive(Default)]
+    struct State {
+        coils: Mutex<[bool; 16]>,
+        registers: Mutex<[u16; 16]>,
+    }
+
+    impl State {
+        fn read_coils(&self, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+            let range = address as usize..address as usize + count as usize;
+            Ok(self.coils.lock().unwrap()[range].to_vec())
+        }
+
+        fn write_coils(&self, address: u16, values: &[bool]) -> Result<(), Exception> {
+            let start = address as usize;
+            self.coils.lock().unwrap()[start..start + values.len()].copy_from_slice(values);
+            Ok(())
+        }
+
+        fn read_registers(&self, address: u16, count: u16) -> Result<Vec<u16>, Exception> {
+            let range = address as usize..address as usize + count as usize;
+            Ok(self.registers.lock().unwrap()[range].to_vec())
+        }
+
+        /// Register 0 is read-only
+        fn write_registers(&self, address: u16, values: &[u16]) -> Result<(), Exception> {
+            if address == 0 {
+                return Err(Exception::SlaveDeviceFailure);
+            }
+            let start = address as usize;
+            self.registers.lock().unwrap()[start..start + values.len()].copy_from_slice(values);
+            Ok(())
+        }
+    }
+
+    fn limits() -> Limits {
+        Limits::default()
+            .with_coils(0x0000, 16)
+            .with_holding_registers(0x0000, 16)
+    }
+
+    struct Fixture(State);
+
+    impl Service for Fixture {
+        fn limits(&self, _unit: u8) -> Limits {
+            limits()
+        }
+
+        fn read_coils(&self, _unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+            self.0.read_coils(address, count)
+        }
+
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            self.0.read_registers(address, count)
+        }
+
+        fn write_single_coil(&self, _unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+            self.0.write_coils(address, &[value])
+        }
+
+        fn write_single_register(
+            &self,
+            _unit: u8,
+            address: u16,
+            value: u16,
+        ) -> Result<(), Exception> {
+            self.0.write_registers(address, &[value])
+        }
+
+        fn write_multiple_coils(
+            &self,
+            _unit: u8,
+            address: u16,
+            values: &[bool],
+        ) -> Result<(), Exception> {
+            self.0.write_coils(address, values)
+        }
+
+        fn write_multiple_registers(
+            &self,
+            _unit: u8,
+            address: u16,
+            values: &[u16],
+        ) -> Result<(), Exception> {
+            self.0.write_registers(address, values)
+        }
+    }
+
+    fn builder() -> ServerBuilder {
+        let state = Arc::new(State::default());
+        let (s1, s2, s3, s4, s5) = (
+            state.clone(),
+            state.clone(),
+            state.clone(),
+            state.clone(),
+            state.clone(),
+        );
+        ServerBuilder::new()
+            .with_limits(limits())
+            .on_read_coils(move |_, address, count| {
+                let state = s1.clone();
+                async move { state.read_coils(address, count) }
+            })
+            .on_read_holding_registers(move |_, address, count| {
+                let state = s2.clone();
+                async move { state.read_registers(address, count) }
+            })
+            .on_write_single_coil(move |_, address, value| {
+                let state = s3.clone();
+                async move { state.write_coils(address, &[value]) }
+            })
+            .on_write_single_register(move |_, address, value| {
+                let state = s4.clone();
+                async move { state.write_registers(address, &[value]) }
+            })
+            .on_write_multiple_coils(move |_, address, values| {
+                let state = s5.clone();
+                async move { state.write_coils(address, &values) }
+            })
+            .on_write_multiple_registers(move |_, address, values| {
+                let state = state.clone();
+                async move { state.write_registers(address, &values) }
+            })
+    }
+
+    /// Requests covering answers, exceptions and validation of every function
+    fn conformance_suite() -> Vec<Request> {
+        let frame = Frame::tcp();
+        vec![
+            frame.read_coils_request(0x01, 0x0000, 0x0008),
+            frame.write_single_coil_request(0x01, 0x0003, 0xFF00),
+            frame.write_single_coil_request(0x01, 0x0003, 0x1234),
+            frame.read_coils_request(0x01, 0x0000, 0x0010),
+            frame.read_coils_request(0x01, 0x000A, 0x0008),
+            frame.read_coils_request(0x01, 0x0000, 0x0000),
+            frame.write_multiple_coils_request(0x01, 0x0000, 0x000A, vec![0xFF, 0x02]),
+            frame.write_multiple_coils_request(0x01, 0x0000, 0x000A, vec![0xFF]),
+            frame.read_coils_request(0x01, 0x0000, 0x0010),
+            frame.read_discrete_request(0x01, 0x0000, 0x0001),
+            frame.read_input_registers_request(0x01, 0x0000, 0x0001),
+            frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0004),
+            frame.write_single_holding_register_request(0x01, 0x0001, 0xABCD),
+            frame.write_single_holding_register_request(0x01, 0x0000, 0xABCD),
+            frame.write_multiple_holding_registers_request(0x01, 0x0004, vec![0, 1, 0, 2]),
+            frame.write_multiple_holding_registers_request(0x01, 0x000F, vec![0, 1, 0, 2]),
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 0x0006, 0x0008, vec![0, 9]),
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 0x0006, 0x0000, vec![0, 9]),
+            frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0010),
+            frame.read_multiple_holding_registers_request(0x01, 0x0010, 0x0001),
+        ]
+    }
+
+    async fn run_suite(addr: SocketAddr) -> Vec<Response> {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let mut responses = Vec::new();
+        for request in conformance_suite() {
+            transport.send(request).await.unwrap();
+            responses.push(transport.next().await.unwrap().unwrap());
+        }
+        responses
+    }
+
+    #[tokio::test]
+    async fn conformance_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let service_addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tcp(listener, Fixture(State::default())));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let builder_addr = listener.local_addr().unwrap();
+        tokio::spawn(builder().serve_tcp(listener));
+
+        let expected = run_suite(service_addr).await;
+        let responses = run_suite(builder_addr).await;
+        assert_eq!(responses, expected);
+
+        assert!(matches!(
+            &responses[9],
+            Response::Exception(_, body) if body.get_exception() == &Exception::IllegalFunction
+        ));
+        assert!(matches!(
+            &responses[8],
+            Response::ReadCoils(_, body) if body.get_values()[..] == [0xFF, 0x02]
+        ));
+    }
+
+    #[tokio::test]
+    async fn answer_test() {
+        use crate::server::serve::Dispatch;
+        use crate::server::RequestCounters;
+
+        let builder = builder();
+        let counters = RequestCounters::default();
+        let frame = Frame::tcp();
+        let exception = Exception::SlaveDeviceBusy;
+        let request = frame.write_single_coil_request(0x01, 0x0003, 0xFF00);
+        let response = builder
+            .answer(&request, Some(exception), Some(&counters))
+            .await;
+        assert!(matches!(
+            response,
+            Some(Response::Exception(_, body)) if body.get_exception() == &exception
+        ));
+        let request = frame.read_coils_request(0x01, 0x0000, 0x0008);
+        let response = builder
+            .answer(&request, Some(exception), Some(&counters))
+            .await;
+        assert!(matches!(response, Some(Response::ReadCoils(..))));
+        assert_eq!(counters.total_requests(), 2);
+        assert_eq!(counters.exceptions(), 1);
+    }
+
+    #[tokio::test]
+    async fn serve_rtu_test() {
+        let (client, server) = tokio::io::duplex(64);
+        let builder = ServerBuilder::new().on_read_input_registers(|unit, address, _| async move {
+            Ok(vec![unit as u16, address])
+        });
+        tokio::spawn(builder.serve_rtu(Framed::new(server, RtuServerCodec::default())));
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let frame = Frame::rtu();
+        transport
+            .send(frame.read_input_registers_request(0x0B, 0x0007, 0x0002))
+            .await
+            .unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = frame.read_input_register_response(0x0B, vec![0x00, 0x0B, 0x00, 0x07]);
+        assert_eq!(response, expected);
+
+        transport
+            .send(frame.read_coils_request(0x0B, 0x0000, 0x0001))
+            .await
+            .unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected =
+            frame.exception_response(0x0B, Function::ReadCoils, Exception::IllegalFunction);
+        assert_eq!(response, expected);
+    }
+}
@@ -0,0 +1,479 @@
+use std::io::{Error, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::BytesMut;
+use futures::SinkExt;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_util::codec::{Encoder, Framed};
+
+use crate::frame::response::Response;
+use crate::frame::{Exception, Version};
+
+use super::{Limits, RtuServer, Service, TcpServer};
+
+/// Faults a [`ChaosService`] injects
+///
+/// The "every" faults count the responses of the server: with `with_drop_every(3)` the third,
+/// sixth, ninth... responses are dropped. Every fault is off by default.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use easy_modbus::server::Faults;
+///
+/// let faults = Faults::default()
+///     .with_drop_every(10)
+///     .with_delay(Duration::from_millis(50));
+/// assert_eq!(faults.get_drop_every(), Some(10));
+/// assert_eq!(faults.get_wrong_tid_every(), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Faults {
+    pub(crate) drop_every: Option<u64>,
+    pub(crate) delay: Option<Duration>,
+    pub(crate) corrupt_crc_every: Option<u64>,
+    pub(crate) wrong_tid_every: Option<u64>,
+}
+
+impl Faults {
+    /// Never send every `n`th response
+    pub fn with_drop_every(mut self, n: u64) -> Faults {
+        self.drop_every = Some(n.max(1));
+        self
+    }
+
+    /// Send every response `delay` late
+    pub fn with_delay(mut self, delay: Duration) -> Faults {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Send every `n`th RTU response with a wrong CRC
+    pub fn with_corrupt_crc_every(mut self, n: u64) -> Faults {
+        self.corrupt_crc_every = Some(n.max(1));
+        self
+    }
+
+    /// Send every `n`th TCP response with the transaction identifier of another request
+    pub fn with_wrong_tid_every(mut self, n: u64) -> Faults {
+        self.wrong_tid_every = Some(n.max(1));
+        self
+    }
+
+    pub fn get_drop_every(&self) -> Option<u64> {
+        self.drop_every
+    }
+
+    pub fn get_delay(&self) -> Option<Duration> {
+        self.delay
+    }
+
+    pub fn get_corrupt_crc_every(&self) -> Option<u64> {
+        self.corrupt_crc_every
+    }
+
+    pub fn get_wrong_tid_every(&self) -> Option<u64> {
+        self.wrong_tid_every
+    }
+}
+
+/// The faults of one response
+pub(crate) struct Plan {
+    drop: bool,
+    delay: Option<Duration>,
+    corrupt_crc: bool,
+    wrong_tid: bool,
+}
+
+/// A [`Service`] misbehaving on demand, for testing the robustness of clients
+///
+/// As a [`Service`] it answers every request with [`Exception::SlaveDeviceBusy`] while made
+/// busy by [`ChaosService::busy_for`], and forwards the other requests to the decorated
+/// service. The faults on the wire, dropped, late or corrupted responses, are injected into the
+/// responses of the servers [`ChaosService::serve_tcp`] and [`ChaosService::serve_rtu`] run. The
+/// faults can be changed while serving.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use tokio::net::TcpListener;
+/// use easy_modbus::server::{ChaosService, DataStore, Faults};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:502").await?;
+///     let store = DataStore::default().with_holding_registers(0x0000, 10);
+///     let faults = Faults::default().with_drop_every(5).with_wrong_tid_every(7);
+///     let chaos = Arc::new(ChaosService::new(store).with_faults(faults));
+///     chaos.serve_tcp(listener).await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ChaosService<S> {
+    service: S,
+    faults: Mutex<Faults>,
+    busy_until: Mutex<Option<Instant>>,
+    responses: AtomicU64,
+}
+
+impl<S: Service> ChaosService<S> {
+    /// Decorate `service`, without any fault
+    pub fn new(service: S) -> ChaosService<S> {
+        ChaosService {
+            service,
+            faults: Mutex::new(Faults::default()),
+            busy_until: Mutex::new(None),
+            responses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_faults(self, faults: Faults) -> ChaosService<S> {
+        self.set_faults(faults);
+        self
+    }
+
+    /// Replace the faults injected from now on
+    pub fn set_faults(&self, faults: Faults) {
+        *self.faults.lock().unwrap() = faults;
+    }
+
+    pub fn get_faults(&self) -> Faults {
+        *self.faults.lock().unwrap()
+    }
+
+    /// Answer every request with [`Exception::SlaveDeviceBusy`] for `duration` from now
+    pub fn busy_for(&self, duration: Duration) {
+        *self.busy_until.lock().unwrap() = Some(Instant::now() + duration);
+    }
+
+    pub fn is_busy(&self) -> bool {
+        matches!(*self.busy_until.lock().unwrap(), Some(until) if Instant::now() < until)
+    }
+
+    pub fn get_service(&self) -> &S {
+        &self.service
+    }
+
+    fn check_busy(&self) -> std::result::Result<(), Exception> {
+        match self.is_busy() {
+            true => Err(Exception::SlaveDeviceBusy),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<S: Service + 'static> ChaosService<S> {
+    /// Serve Modbus TCP clients accepted on `listener`, injecting the faults
+    ///
+    /// Like [`serve_tcp`](super::serve_tcp), shared to change the faults while serving. A wrong CRC
+    /// is never injected over TCP. [`TcpServer::with_chaos`] injects the faults into the
+    /// responses of a server configured otherwise.
+    pub async fn serve_tcp(self: Arc<Self>, listener: TcpListener) -> Result<()> {
+        let server = TcpServer::from_listener(listener)?.with_chaos(self.clone());
+        server.serve(self).await
+    }
+
+    /// Serve the Modbus RTU requests for `unit_id` arriving on `transport`, injecting the faults
+    ///
+    /// Like [`RtuServer::serve`], shared to change the faults while serving. A wrong transaction
+    /// identifier is never injected over RTU, which has none.
+    pub async fn serve_rtu<T>(self: Arc<Self>, transport: T, unit_id: u8) -> Result<()>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        let server = RtuServer::new(transport, unit_id, self.clone());
+        server.with_chaos(self).serve().await
+    }
+}
+
+impl<S: Service> Service for ChaosService<S> {
+    fn limits(&self, unit: u8) -> Limits {
+        self.service.limits(unit)
+    }
+
+    fn read_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> std::result::Result<Vec<bool>, Exception> {
+        self.check_busy()?;
+        self.service.read_coils(unit, address, count)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> std::result::Result<Vec<bool>, Exception> {
+        self.check_busy()?;
+        self.service.read_discrete_inputs(unit, address, count)
+    }
+
+    fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> std::result::Result<Vec<u16>, Exception> {
+        self.check_busy()?;
+        self.service.read_holding_registers(unit, address, count)
+    }
+
+    fn read_input_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> std::result::Result<Vec<u16>, Exception> {
+        self.check_busy()?;
+        self.service.read_input_registers(unit, address, count)
+    }
+
+    fn write_single_coil(
+        &self,
+        unit: u8,
+        address: u16,
+        value: bool,
+    ) -> std::result::Result<(), Exception> {
+        self.check_busy()?;
+        self.service.write_single_coil(unit, address, value)
+    }
+
+    fn write_single_register(
+        &self,
+        unit: u8,
+        address: u16,
+        value: u16,
+    ) -> std::result::Result<(), Exception> {
+        self.check_busy()?;
+        self.service.write_single_register(unit, address, value)
+    }
+
+    fn write_multiple_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[bool],
+    ) -> std::result::Result<(), Exception> {
+        self.check_busy()?;
+        self.service.write_multiple_coils(unit, address, values)
+    }
+
+    fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> std::result::Result<(), Exception> {
+        self.check_busy()?;
+        self.service.write_multiple_registers(unit, address, values)
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        unit: u8,
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: &[u16],
+    ) -> std::result::Result<Vec<u16>, Exception> {
+        self.check_busy()?;
+        self.service.read_write_multiple_registers(
+            unit,
+            read_address,
+            read_count,
+            write_address,
+            values,
+        )
+    }
+}
+
+/// Faults injected into the responses of a server, as a [`ChaosService`] plans them
+pub(crate) trait Tamper: Send + Sync {
+    /// The faults of the next response
+    fn plan(&self) -> Plan;
+}
+
+impl<S: Service> Tamper for ChaosService<S> {
+    fn plan(&self) -> Plan {
+        let n = self.responses.fetch_add(1, Ordering::SeqCst) + 1;
+        let faults = self.get_faults();
+        let every = |every: Option<u64>| matches!(every, Some(every) if n.is_multiple_of(every));
+        Plan {
+            drop: every(faults.drop_every),
+            delay: faults.delay,
+            corrupt_crc: every(faults.corrupt_crc_every),
+            wrong_tid: every(faults.wrong_tid_every),
+        }
+    }
+}
+
+/// Send `response` on `transport`, with the faults `tamper` plans for it
+///
+/// A wrong CRC is only injected into RTU responses, a wrong transaction identifier only into TCP
+/// responses.
+pub(crate) async fn send_tampered<T, C>(
+    transport: &mut Framed<T, C>,
+    mut response: Response,
+    tamper: Option<&dyn Tamper>,
+) -> Result<()>
+where
+    T: AsyncWrite + Unpin,
+    C: Encoder<Response, Error = Error>,
+{
+    let Some(tamper) = tamper else {
+        return transport.send(response).await;
+    };
+    let plan = tamper.plan();
+    if let Some(delay) = plan.delay {
+        tokio::time::sleep(delay).await;
+    }
+    if plan.drop {
+        return Ok(());
+    }
+    match response.head().version {
+        Version::Rtu if plan.corrupt_crc => {
+            let mut buf = BytesMut::new();
+            transport.codec_mut().encode(response, &mut buf)?;
+            let last = buf.len() - 1;
+            buf[last] ^= 0xFF;
+            let port = transport.get_mut();
+            port.write_all(&buf).await?;
+            port.flush().await
+        }
+        Version::Tcp if plan.wrong_tid => {
+            let head = response.head_mut();
+            head.tid = head.tid.wrapping_add(1);
+            transport.send(response).await
+        }
+        _ => transport.send(response).await,
+    }
+}
+
+#[cfg(test)]
+mod chaos_test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::codec::Framed;
+
+    use crate::codec::{RtuClientCodec, TcpClientCodec};
+    use crate::frame::{Exception, Function};
+    use crate::server::{ChaosService, DataStore, Faults};
+    use crate::util::crc;
+    use crate::Frame;
+
+    fn chaos(faults: Faults) -> ChaosService<DataStore> {
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        ChaosService::new(store).with_faults(faults)
+    }
+
+    async fn tcp(chaos: ChaosService<DataStore>) -> Framed<TcpStream, TcpClientCodec> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(Arc::new(chaos).serve_tcp(listener));
+        let stream = TcpStream::connect(addr).await.unwrap();
+        Framed::new(stream, TcpClientCodec::default())
+    }
+
+    fn read_request(frame: &Frame) -> crate::Request {
+        frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001)
+    }
+
+    #[tokio::test]
+    async fn drop_test() {
+        let mut transport = tcp(chaos(Faults::default().with_drop_every(2))).await;
+        let frame = Frame::tcp();
+        for _ in 0..4 {
+            transport.feed(read_request(&frame)).await.unwrap();
+        }
+        transport.flush().await.unwrap();
+
+        // The 2nd and 4th responses are dropped
+        let mut tids = Vec::new();
+        for _ in 0..2 {
+            let response = transport.next().await.unwrap().unwrap();
+            tids.push(response.head().get_tid());
+        }
+        assert_eq!(tids, vec![1, 3]);
+        let next = tokio::time::timeout(Duration::from_millis(100), transport.next());
+        assert!(next.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn delay_test() {
+        let delay = Duration::from_millis(150);
+        let mut transport = tcp(chaos(Faults::default().with_delay(delay))).await;
+        let start = tokio::time::Instant::now();
+        transport.send(read_request(&Frame::tcp())).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+        assert!(start.elapsed() >= delay);
+    }
+
+    #[tokio::test]
+    async fn wrong_tid_test() {
+        let mut transport = tcp(chaos(Faults::default().with_wrong_tid_every(1))).await;
+        transport.send(read_request(&Frame::tcp())).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        assert_eq!(response.head().get_tid(), 2);
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x00]);
+        assert_ne!(response, expected);
+    }
+
+    #[tokio::test]
+    async fn busy_test() {
+        let chaos = Arc::new(chaos(Faults::default()));
+        let (client, server) = tokio::io::duplex(64);
+        tokio::spawn(chaos.clone().serve_rtu(server, 0x01));
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let frame = Frame::rtu();
+        chaos.busy_for(Duration::from_millis(100));
+        assert!(chaos.is_busy());
+        transport.send(read_request(&frame)).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = frame.exception_response(
+            0x01,
+            Function::ReadMultipleHoldingRegisters,
+            Exception::SlaveDeviceBusy,
+        );
+        assert_eq!(response, expected);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert!(!chaos.is_busy());
+        transport.send(read_request(&frame)).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        assert_eq!(
+            response,
+            frame.read_holding_register_response(0x01, vec![0x00, 0x00])
+        );
+    }
+
+    #[tokio::test]
+    async fn corrupt_crc_test() {
+        let chaos = Arc::new(chaos(Faults::default().with_corrupt_crc_every(1)));
+        let (client, server) = tokio::io::duplex(64);
+        tokio::spawn(chaos.serve_rtu(server, 0x01));
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        transport.send(read_request(&Frame::rtu())).await.unwrap();
+        let mut client = transport.into_inner();
+        let mut buf = [0u8; 7];
+        client.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf[..5], &[0x01, 0x03, 0x02, 0x00, 0x00]);
+        let expected = crc::compute(&buf[..5]).to_be_bytes();
+        assert_eq!(buf[5], expected[0]);
+        assert_ne!(buf[6], expected[1]);
+    }
+}
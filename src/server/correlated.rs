@@ -0,0 +1,200 @@
+use std::io::{Error, Result};
+
+use futures::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::{Exception, Head};
+
+use super::dispatch::{respond, Reply};
+
+/// The values answering a request, the head of the response is taken from its request
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Payload {
+    /// The coils or discrete inputs read
+    Bits(Vec<bool>),
+
+    /// The registers read
+    Registers(Vec<u16>),
+
+    /// The write was applied, its response echoes the request
+    Written,
+
+    /// The request failed
+    Exception(Exception),
+}
+
+/// Identifies a request received by [`Correlated::next_request`], consumed answering it
+#[derive(Debug)]
+pub struct RequestToken {
+    request: Request,
+}
+
+impl RequestToken {
+    /// The head of the request, the response gets its transaction identifier and unit id
+    pub fn head(&self) -> &Head {
+        self.request.head()
+    }
+}
+
+/// A server transport pairing every response with its request
+///
+/// An application serving requests in its own loop gets a [`RequestToken`] with every request
+/// and hands it back with the [`Payload`] answering it. The response is built from the
+/// request, so its transaction identifier, unit id and function always match. Requests may be
+/// answered in any order, for pipelining clients. Broadcast requests are never answered.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tokio::net::TcpListener;
+/// use tokio_util::codec::Framed;
+///
+/// use easy_modbus::codec::TcpServerCodec;
+/// use easy_modbus::server::{Correlated, Payload};
+/// use easy_modbus::{Exception, Request};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:502").await?;
+///     let (stream, _) = listener.accept().await?;
+///     let mut transport = Correlated::new(Framed::new(stream, TcpServerCodec::default()));
+///     while let Some((token, request)) = transport.next_request().await? {
+///         let payload = match request {
+///             Request::ReadInputRegisters(_, body) => {
+///                 Payload::Registers(vec![215; *body.get_registers_number() as usize])
+///             }
+///             _ => Payload::Exception(Exception::IllegalFunction),
+///         };
+///         transport.respond(token, payload).await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Correlated<F> {
+    transport: F,
+}
+
+impl<T, C> Correlated<Framed<T, C>>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    C: Decoder<Item = Request, Error = Error> + Encoder<Response, Error = Error>,
+{
+    pub fn new(transport: Framed<T, C>) -> Correlated<Framed<T, C>> {
+        Correlated { transport }
+    }
+
+    /// The next request, with the token answering it
+    ///
+    /// `Ok(None)` once the transport is closed.
+    pub async fn next_request(&mut self) -> Result<Option<(RequestToken, Request)>> {
+        let request = match self.transport.next().await {
+            Some(request) => request?,
+            None => return Ok(None),
+        };
+        let token = RequestToken {
+            request: request.clone(),
+        };
+        Ok(Some((token, request)))
+    }
+
+    /// Send the response to the request of `token`, built from `payload`
+    ///
+    /// A payload not matching the function of the request, registers for a coils read for
+    /// instance, is answered with [`Exception::SlaveDeviceFailure`].
+    pub async fn respond(&mut self, token: RequestToken, payload: Payload) -> Result<()> {
+        let reply = match payload {
+            Payload::Bits(values) => Ok(Reply::Bits(values)),
+            Payload::Registers(values) => Ok(Reply::Registers(values)),
+            Payload::Written => Ok(Reply::Written),
+            Payload::Exception(exception) => Err(exception),
+        };
+        match respond(&token.request, reply) {
+            Some(response) => self.transport.send(response).await,
+            None => Ok(()),
+        }
+    }
+
+    pub fn get_ref(&self) -> &Framed<T, C> {
+        &self.transport
+    }
+
+    pub fn into_inner(self) -> Framed<T, C> {
+        self.transport
+    }
+}
+
+#[cfg(test)]
+mod correlated_test {
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::Framed;
+
+    use crate::codec::{TcpClientCodec, TcpServerCodec};
+    use crate::frame::{Exception, Function};
+    use crate::server::{Correlated, Payload};
+    use crate::Frame;
+
+    #[tokio::test]
+    async fn out_of_order_test() {
+        let (client, server) = tokio::io::duplex(256);
+        let mut server = Correlated::new(Framed::new(server, TcpServerCodec::default()));
+        let mut client = Framed::new(client, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        client
+            .feed(frame.read_coils_request(0x01, 0x0000, 0x0003))
+            .await
+            .unwrap();
+        client
+            .feed(frame.write_single_holding_register_request(0x01, 0x0010, 0x1234))
+            .await
+            .unwrap();
+        client
+            .feed(frame.read_input_registers_request(0x01, 0x0000, 0x0001))
+            .await
+            .unwrap();
+        client.flush().await.unwrap();
+
+        let (first, _) = server.next_request().await.unwrap().unwrap();
+        let (second, _) = server.next_request().await.unwrap().unwrap();
+        let (third, _) = server.next_request().await.unwrap().unwrap();
+        let tids = [
+            first.head().get_tid(),
+            second.head().get_tid(),
+            third.head().get_tid(),
+        ];
+        assert!(tids[0] != tids[1] && tids[1] != tids[2]);
+        // Bits answering a registers read
+        server
+            .respond(third, Payload::Bits(vec![true]))
+            .await
+            .unwrap();
+        server.respond(second, Payload::Written).await.unwrap();
+        let bits = Payload::Bits(vec![true, false, true]);
+        server.respond(first, bits).await.unwrap();
+
+        // Sent in the order answered, each with the transaction identifier of its request
+        let expected_frame = Frame::tcp();
+        let response = client.next().await.unwrap().unwrap();
+        let expected = expected_frame.exception_response(
+            0x01,
+            Function::ReadInputRegisters,
+            Exception::SlaveDeviceFailure,
+        );
+        assert_eq!(response.head().get_tid(), tids[2]);
+        assert_eq!(response.pdu_bytes(), expected.pdu_bytes());
+        let response = client.next().await.unwrap().unwrap();
+        let expected = expected_frame.write_single_holding_register_response(0x01, 0x0010, 0x1234);
+        assert_eq!(response.head().get_tid(), tids[1]);
+        assert_eq!(response.pdu_bytes(), expected.pdu_bytes());
+        let response = client.next().await.unwrap().unwrap();
+        let expected = expected_frame.read_coils_response(0x01, vec![0x05]);
+        assert_eq!(response.head().get_tid(), tids[0]);
+        assert_eq!(response.pdu_bytes(), expected.pdu_bytes());
+
+        drop(client);
+        assert!(server.next_request().await.unwrap().is_none());
+    }
+}
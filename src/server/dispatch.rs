@@ -0,0 +1,943 @@
+use std::ops::Range;
+
+use crate::frame::request::Request;
+use crate::frame::response::*;
+use crate::frame::{pack_registers, Exception, Head, Length};
+use crate::util::bits;
+use crate::limits::{
+    MAX_READ_COILS, MAX_READ_DISCRETE_INPUTS, MAX_READ_REGISTERS, MAX_READ_WRITE_READ_REGISTERS,
+    MAX_READ_WRITE_WRITE_REGISTERS, MAX_WRITE_COILS, MAX_WRITE_REGISTERS,
+};
+
+use super::{Limits, Service};
+
+/// Answer a request with a [`Service`]
+///
+/// The response echoes the transaction identifier, protocol identifier and unit id of the
+/// request. A handler error becomes an exception response for the requested function.
+///
+/// A RTU broadcast request (unit id 0) is never answered, `None` is returned. Broadcast
+/// writes are passed to the handlers, broadcast reads are dropped without calling one.
+///
+/// The request is validated before a handler is called. A quantity outside the limits of the
+/// specification or a mismatching byte count is answered with
+/// [`Exception::IllegalDataValue`], an address range outside `0x0000..=0xFFFF` or the
+/// [`Service::limits`] with [`Exception::IllegalDataAddress`]. A handler answering a read
+/// with more or fewer values than requested is answered with
+/// [`Exception::SlaveDeviceFailure`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::{Exception, Frame, Response};
+/// use easy_modbus::server::{dispatch, Service};
+///
+/// struct Coils;
+///
+/// impl Service for Coils {
+///     fn read_coils(&self, _unit: u8, _address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+///         Ok(vec![true; count as usize])
+///     }
+/// }
+///
+/// let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0003);
+/// let response = dispatch(&Coils, &request).unwrap();
+/// assert_eq!(response.to_string(), "00 01 00 00 00 04 01 01 01 07");
+///
+/// let request = Frame::tcp().read_input_registers_request(0x01, 0x0000, 0x0001);
+/// assert!(matches!(dispatch(&Coils, &request), Some(Response::Exception(..))));
+///
+/// let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0000);
+/// assert!(matches!(dispatch(&Coils, &request), Some(Response::Exception(..))));
+///
+/// let request = Frame::rtu().read_coils_request(0x00, 0x0000, 0x0003);
+/// assert!(dispatch(&Coils, &request).is_none());
+/// ```
+pub fn dispatch<S: Service + ?Sized>(service: &S, request: &Request) -> Option<Response> {
+    if is_dropped(request) {
+        return None;
+    }
+    let reply = prepare(request, &service.limits(request.head().uid))
+        .and_then(|call| execute(service, request.head().uid, call));
+    respond(request, reply)
+}
+
+/// A validated request, its values unpacked for a handler
+pub(crate) enum Call {
+    ReadCoils(u16, u16),
+    ReadDiscreteInputs(u16, u16),
+    ReadHoldingRegisters(u16, u16),
+    ReadInputRegisters(u16, u16),
+    WriteSingleCoil(u16, bool),
+    WriteSingleRegister(u16, u16),
+    WriteMultipleCoils(u16, Vec<bool>),
+    WriteMultipleRegisters(u16, Vec<u16>),
+    /// Read address, read count, write address and values
+    ReadWriteMultipleRegisters(u16, u16, u16, Vec<u16>),
+}
+
+/// Values a handler answered a [`Call`] with
+pub(crate) enum Reply {
+    Bits(Vec<bool>),
+    Registers(Vec<u16>),
+    Written,
+}
+
+/// Whether a request is dropped without calling a handler, a broadcast read
+pub(crate) fn is_dropped(request: &Request) -> bool {
+    request.is_broadcast() && !is_write(request)
+}
+
+/// Validate a request against `limits`, and unpack its values
+pub(crate) fn prepare(request: &Request, limits: &Limits) -> Result<Call, Exception> {
+    validate(request, limits)?;
+    let call = match request {
+        Request::ReadCoils(_, body) => Call::ReadCoils(body.first_address, body.coils_number),
+        Request::ReadDiscreteInputs(_, body) => {
+            Call::ReadDiscreteInputs(body.first_address, body.discrete_inputs_number)
+        }
+        Request::ReadMultipleHoldingRegisters(_, body) => {
+            Call::ReadHoldingRegisters(body.first_address, body.registers_number)
+        }
+        Request::ReadInputRegisters(_, body) => {
+            Call::ReadInputRegisters(body.first_address, body.registers_number)
+        }
+        Request::WriteSingleCoil(_, body) => {
+            Call::WriteSingleCoil(body.coil_address, coil_value(body.value)?)
+        }
+        Request::WriteSingleHoldingRegister(_, body) => {
+            Call::WriteSingleRegister(body.register_address, body.value)
+        }
+        Request::WriteMultipleCoils(_, body) => Call::WriteMultipleCoils(
+            body.first_address,
+            unpack_coils(&body.values, body.coils_number)?,
+        ),
+        Request::WriteMultipleHoldingRegisters(_, body) => Call::WriteMultipleRegisters(
+            body.first_address,
+            unpack_registers(&body.values, body.registers_number)?,
+        ),
+        Request::ReadWriteMultipleRegisters(_, body) => Call::ReadWriteMultipleRegisters(
+            body.read_first_address,
+            body.read_registers_number,
+            body.write_first_address,
+            unpack_registers(&body.values, body.write_registers_number)?,
+        ),
+        Request::Custom(..) => return Err(Exception::IllegalFunction),
+    };
+    Ok(call)
+}
+
+/// Call the handler of a validated request
+fn execute<S: Service + ?Sized>(service: &S, unit: u8, call: Call) -> Result<Reply, Exception> {
+    match call {
+        Call::ReadCoils(address, count) => {
+            service.read_coils(unit, address, count).map(Reply::Bits)
+        }
+        Call::ReadDiscreteInputs(address, count) => service
+            .read_discrete_inputs(unit, address, count)
+            .map(Reply::Bits),
+        Call::ReadHoldingRegisters(address, count) => service
+            .read_holding_registers(unit, address, count)
+            .map(Reply::Registers),
+        Call::ReadInputRegisters(address, count) => service
+            .read_input_registers(unit, address, count)
+            .map(Reply::Registers),
+        Call::WriteSingleCoil(address, value) => service
+            .write_single_coil(unit, address, value)
+            .map(|_| Reply::Written),
+        Call::WriteSingleRegister(address, value) => service
+            .write_single_register(unit, address, value)
+            .map(|_| Reply::Written),
+        Call::WriteMultipleCoils(address, values) => service
+            .write_multiple_coils(unit, address, &values)
+            .map(|_| Reply::Written),
+        Call::WriteMultipleRegisters(address, values) => service
+            .write_multiple_registers(unit, address, &values)
+            .map(|_| Reply::Written),
+        Call::ReadWriteMultipleRegisters(read_address, read_count, write_address, values) => {
+            service
+                .read_write_multiple_registers(
+                    unit,
+                    read_address,
+                    read_count,
+                    write_address,
+                    &values,
+                )
+                .map(Reply::Registers)
+        }
+    }
+}
+
+/// Build the response to a request from the reply of its handler
+///
+/// A broadcast is never answered, `None` is returned.
+pub(crate) fn respond(request: &Request, reply: Result<Reply, Exception>) -> Option<Response> {
+    if request.is_broadcast() {
+        return None;
+    }
+    let response = reply
+        .and_then(|reply| build_response(request, reply))
+        .unwrap_or_else(|exception| {
+            let body = ExceptionResponse::new(exception);
+            let mut head = response_head(request.head(), body.len());
+            head.is_exception = true;
+            Response::Exception(head, body)
+        });
+    Some(response)
+}
+
+/// Whether a request only writes, the requests a broadcast may carry
+fn is_write(request: &Request) -> bool {
+    matches!(
+        request,
+        Request::WriteSingleCoil(..)
+            | Request::WriteSingleHoldingRegister(..)
+            | Request::WriteMultipleCoils(..)
+            | Request::WriteMultipleHoldingRegisters(..)
+    )
+}
+
+/// Check a request against the specification and the bounds declared by a service
+fn validate(request: &Request, limits: &Limits) -> Result<(), Exception> {
+    match request {
+        Request::ReadCoils(_, body) => {
+            check_quantity(body.coils_number, MAX_READ_COILS)?;
+            check_range(limits.get_coils(), body.first_address, body.coils_number)
+        }
+        Request::ReadDiscreteInputs(_, body) => {
+            check_quantity(body.discrete_inputs_number, MAX_READ_DISCRETE_INPUTS)?;
+            check_range(
+                limits.get_discrete_inputs(),
+                body.first_address,
+                body.discrete_inputs_number,
+            )
+        }
+        Request::ReadMultipleHoldingRegisters(_, body) => {
+            check_quantity(body.registers_number, MAX_READ_REGISTERS)?;
+            check_range(
+                limits.get_holding_registers(),
+                body.first_address,
+                body.registers_number,
+            )
+        }
+        Request::ReadInputRegisters(_, body) => {
+            check_quantity(body.registers_number, MAX_READ_REGISTERS)?;
+            check_range(
+                limits.get_input_registers(),
+                body.first_address,
+                body.registers_number,
+            )
+        }
+        Request::WriteSingleCoil(_, body) => {
+            coil_value(body.value)?;
+            check_range(limits.get_coils(), body.coil_address, 1)
+        }
+        Request::WriteSingleHoldingRegister(_, body) => {
+            check_range(limits.get_holding_registers(), body.register_address, 1)
+        }
+        Request::WriteMultipleCoils(_, body) => {
+            check_quantity(body.coils_number, MAX_WRITE_COILS)?;
+            let bytes_number = (body.coils_number as usize).div_ceil(8);
+            check_byte_count(body.bytes_number, body.values.len(), bytes_number)?;
+            check_range(limits.get_coils(), body.first_address, body.coils_number)
+        }
+        Request::WriteMultipleHoldingRegisters(_, body) => {
+            check_quantity(body.registers_number, MAX_WRITE_REGISTERS)?;
+            let bytes_number = body.registers_number as usize * 2;
+            check_byte_count(body.bytes_number, body.values.len(), bytes_number)?;
+            check_range(
+                limits.get_holding_registers(),
+                body.first_address,
+                body.registers_number,
+            )
+        }
+        Request::ReadWriteMultipleRegisters(_, body) => {
+            check_quantity(body.read_registers_number, MAX_READ_WRITE_READ_REGISTERS)?;
+            check_quantity(body.write_registers_number, MAX_READ_WRITE_WRITE_REGISTERS)?;
+            let bytes_number = body.write_registers_number as usize * 2;
+            check_byte_count(body.bytes_number, body.values.len(), bytes_number)?;
+            check_range(
+                limits.get_holding_registers(),
+                body.write_first_address,
+                body.write_registers_number,
+            )?;
+            check_range(
+                limits.get_holding_registers(),
+                body.read_first_address,
+                body.read_registers_number,
+            )
+        }
+        Request::Custom(..) => Err(Exception::IllegalFunction),
+    }
+}
+
+/// At least one, at most `max`
+fn check_quantity(count: u16, max: u16) -> Result<(), Exception> {
+    if count == 0 || count > max {
+        return Err(Exception::IllegalDataValue);
+    }
+    Ok(())
+}
+
+/// Both the byte count field and the values carried match the quantity
+fn check_byte_count(bytes_number: u8, values: usize, expected: usize) -> Result<(), Exception> {
+    if bytes_number as usize != expected || values != expected {
+        return Err(Exception::IllegalDataValue);
+    }
+    Ok(())
+}
+
+/// `count` addresses from `address` fit in the address space and the bounds, if any
+fn check_range(bounds: Option<&Range<u32>>, address: u16, count: u16) -> Result<(), Exception> {
+    let start = address as u32;
+    let end = start + count as u32;
+    if end > 0x10000 {
+        return Err(Exception::IllegalDataAddress);
+    }
+    match bounds {
+        Some(bounds) if start < bounds.start || end > bounds.end => {
+            Err(Exception::IllegalDataAddress)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pack the values of a reply, a reply not matching the function or the requested quantity is
+/// a device failure
+fn build_response(request: &Request, reply: Reply) -> Result<Response, Exception> {
+    let len = match &reply {
+        Reply::Bits(values) => Some(values.len()),
+        Reply::Registers(values) => Some(values.len()),
+        Reply::Written => None,
+    };
+    if len.is_some() && len != read_quantity(request) {
+        return Err(Exception::SlaveDeviceFailure);
+    }
+    let response = match (request, reply) {
+        (Request::ReadCoils(head, _), Reply::Bits(values)) => {
+            let body = ReadCoilsResponse::new(bits::pack(&values));
+            Response::ReadCoils(response_head(head, body.len()), body)
+        }
+        (Request::ReadDiscreteInputs(head, _), Reply::Bits(values)) => {
+            let body = ReadDiscreteInputsResponse::new(bits::pack(&values));
+            Response::ReadDiscreteInputs(response_head(head, body.len()), body)
+        }
+        (Request::ReadMultipleHoldingRegisters(head, _), Reply::Registers(values)) => {
+            let body = ReadMultipleHoldingRegistersResponse::new(pack_registers(&values));
+            Response::ReadMultipleHoldingRegisters(response_head(head, body.len()), body)
+        }
+        (Request::ReadInputRegisters(head, _), Reply::Registers(values)) => {
+            let body = ReadInputRegistersResponse::new(pack_registers(&values));
+            Response::ReadInputRegisters(response_head(head, body.len()), body)
+        }
+        (Request::WriteSingleCoil(head, body), Reply::Written) => {
+            let body = WriteSingleCoilResponse::new(body.coil_address, body.value);
+            Response::WriteSingleCoil(response_head(head, body.len()), body)
+        }
+        (Request::WriteSingleHoldingRegister(head, body), Reply::Written) => {
+            let body = WriteSingleHoldingRegisterResponse::new(body.register_address, body.value);
+            Response::WriteSingleHoldingRegister(response_head(head, body.len()), body)
+        }
+        (Request::WriteMultipleCoils(head, body), Reply::Written) => {
+            let body = WriteMultipleCoilsResponse::new(body.first_address, body.coils_number);
+            Response::WriteMultipleCoils(response_head(head, body.len()), body)
+        }
+        (Request::WriteMultipleHoldingRegisters(head, body), Reply::Written) => {
+            let body = WriteMultipleHoldingRegistersResponse::new(
+                body.first_address,
+                body.registers_number,
+            );
+            Response::WriteMultipleHoldingRegisters(response_head(head, body.len()), body)
+        }
+        (Request::ReadWriteMultipleRegisters(head, _), Reply::Registers(values)) => {
+            let body = ReadWriteMultipleRegistersResponse::new(pack_registers(&values));
+            Response::ReadWriteMultipleRegisters(response_head(head, body.len()), body)
+        }
+        _ => return Err(Exception::SlaveDeviceFailure),
+    };
+    Ok(response)
+}
+
+/// The number of coils, inputs or registers a request reads, `None` for a write
+fn read_quantity(request: &Request) -> Option<usize> {
+    let count = match request {
+        Request::ReadCoils(_, body) => body.coils_number,
+        Request::ReadDiscreteInputs(_, body) => body.discrete_inputs_number,
+        Request::ReadMultipleHoldingRegisters(_, body) => body.registers_number,
+        Request::ReadInputRegisters(_, body) => body.registers_number,
+        Request::ReadWriteMultipleRegisters(_, body) => body.read_registers_number,
+        _ => return None,
+    };
+    Some(count as usize)
+}
+
+/// Head of the response to a request with the given head
+fn response_head(request: &Head, body_length: u16) -> Head {
+    let mut head = Head::new(
+        request.tid,
+        request.uid,
+        request.function,
+        body_length,
+        request.version,
+        false,
+    );
+    head.pid = request.pid;
+    head
+}
+
+/// 0xFF00 for on, 0x0000 for off
+fn coil_value(value: u16) -> Result<bool, Exception> {
+    match value {
+        0xFF00 => Ok(true),
+        0x0000 => Ok(false),
+        _ => Err(Exception::IllegalDataValue),
+    }
+}
+
+fn unpack_coils(bytes: &[u8], count: u16) -> Result<Vec<bool>, Exception> {
+    let count = count as usize;
+    if bytes.len() * 8 < count {
+        return Err(Exception::IllegalDataValue);
+    }
+    Ok(bits::unpack(bytes, count))
+}
+
+fn unpack_registers(bytes: &[u8], count: u16) -> Result<Vec<u16>, Exception> {
+    let count = count as usize;
+    if bytes.len() < count * 2 {
+        return Err(Exception::IllegalDataValue);
+    }
+    Ok(bytes
+        .chunks_exact(2)
+        .take(count)
+        .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+        .collect())
+}
+
+#[cfg(test)]
+mod dispatch_test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use crate::frame::request::Request;
+    use crate::frame::{Exception, Function};
+    use crate::limits::*;
+    use crate::server::{dispatch, Limits, Service};
+    use crate::Frame;
+
+    #[derive(Default)]
+    struct Registers {
+        values: Mutex<Vec<u16>>,
+        coils: Mutex<Vec<bool>>,
+    }
+
+    impl Service for Registers {
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            let values = self.values.lock().unwrap();
+            let range = address as usize..address as usize + count as usize;
+            values
+                .get(range)
+                .map(|values| values.to_vec())
+                .ok_or(Exception::IllegalDataAddress)
+        }
+
+        fn write_multiple_registers(
+            &self,
+            _unit: u8,
+            address: u16,
+            values: &[u16],
+        ) -> Result<(), Exception> {
+            let mut registers = self.values.lock().unwrap();
+            let range = address as usize..address as usize + values.len();
+            match registers.get_mut(range) {
+                Some(registers) => registers.copy_from_slice(values),
+                None => return Err(Exception::IllegalDataAddress),
+            }
+            Ok(())
+        }
+
+        fn write_single_coil(
+            &self,
+            _unit: u8,
+            _address: u16,
+            value: bool,
+        ) -> Result<(), Exception> {
+            self.coils.lock().unwrap().push(value);
+            Ok(())
+        }
+
+        fn write_multiple_coils(
+            &self,
+            _unit: u8,
+            _address: u16,
+            values: &[bool],
+        ) -> Result<(), Exception> {
+            self.coils.lock().unwrap().extend_from_slice(values);
+            Ok(())
+        }
+    }
+
+    /// Answers every request, counting the handler calls
+    #[derive(Default)]
+    struct Recorder {
+        limits: Limits,
+        calls: AtomicUsize,
+    }
+
+    impl Recorder {
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::Relaxed)
+        }
+    }
+
+    impl Service for Recorder {
+        fn limits(&self, _unit: u8) -> Limits {
+            self.limits.clone()
+        }
+
+        fn read_coils(&self, _unit: u8, _address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(vec![false; count as usize])
+        }
+
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(vec![0; count as usize])
+        }
+
+        fn write_single_register(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _value: u16,
+        ) -> Result<(), Exception> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn write_multiple_coils(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _values: &[bool],
+        ) -> Result<(), Exception> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn write_multiple_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _values: &[u16],
+        ) -> Result<(), Exception> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    /// Exception the request is answered with, if any
+    fn rejected(service: &Recorder, request: &Request) -> Option<Exception> {
+        match dispatch(service, request).unwrap() {
+            crate::Response::Exception(_, body) => Some(*body.get_exception()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn quantity_test() {
+        let service = Recorder::default();
+        let frame = Frame::tcp();
+        let value = Some(Exception::IllegalDataValue);
+
+        assert_eq!(
+            rejected(&service, &frame.read_coils_request(0x01, 0x0000, 0)),
+            value
+        );
+        assert_eq!(
+            rejected(&service, &frame.read_coils_request(0x01, 0x0000, 2001)),
+            value
+        );
+        assert_eq!(
+            rejected(&service, &frame.read_coils_request(0x01, 0x0000, 2000)),
+            None
+        );
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 126);
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 125);
+        assert_eq!(rejected(&service, &request), None);
+
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, 1969, vec![0x00; 247]);
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, 1968, vec![0x00; 246]);
+        assert_eq!(rejected(&service, &request), None);
+
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0000, vec![0x00; 248]);
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0000, vec![]);
+        assert_eq!(rejected(&service, &request), value);
+
+        let request =
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 126, 0x0000, vec![0x00; 2]);
+        assert_eq!(rejected(&service, &request), value);
+        let request =
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 1, 0x0000, vec![0x00; 244]);
+        assert_eq!(rejected(&service, &request), value);
+
+        assert_eq!(service.calls(), 3);
+    }
+
+    #[test]
+    fn spec_limits_test() {
+        let service = Recorder::default();
+        let frame = Frame::tcp();
+        let value = Some(Exception::IllegalDataValue);
+        let coils = |count: u16| vec![0x00; (count as usize).div_ceil(8)];
+        let registers = |count: u16| vec![0x00; count as usize * 2];
+
+        let request = frame.read_coils_request(0x01, 0x0000, MAX_READ_COILS + 1);
+        assert_eq!(rejected(&service, &request), value);
+        let count = MAX_READ_DISCRETE_INPUTS;
+        let request = frame.read_discrete_request(0x01, 0x0000, count + 1);
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.read_coils_request(0x01, 0x0000, MAX_READ_COILS);
+        assert_eq!(rejected(&service, &request), None);
+
+        let count = MAX_READ_REGISTERS;
+        let request = frame.read_input_registers_request(0x01, 0x0000, count + 1);
+        assert_eq!(rejected(&service, &request), value);
+
+        let count = MAX_WRITE_COILS;
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, count, coils(count));
+        assert_eq!(rejected(&service, &request), None);
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, count + 1, coils(count + 1));
+        assert_eq!(rejected(&service, &request), value);
+
+        let count = MAX_WRITE_REGISTERS;
+        let request =
+            frame.write_multiple_holding_registers_request(0x01, 0x0000, registers(count));
+        assert_eq!(rejected(&service, &request), None);
+        let request =
+            frame.write_multiple_holding_registers_request(0x01, 0x0000, registers(count + 1));
+        assert_eq!(rejected(&service, &request), value);
+
+        let (read, write) = (
+            MAX_READ_WRITE_READ_REGISTERS,
+            MAX_READ_WRITE_WRITE_REGISTERS,
+        );
+        let request = frame.read_write_multiple_registers_request(
+            0x01,
+            0x0000,
+            read + 1,
+            0x0000,
+            registers(1),
+        );
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.read_write_multiple_registers_request(
+            0x01,
+            0x0000,
+            1,
+            0x0000,
+            registers(write + 1),
+        );
+        assert_eq!(rejected(&service, &request), value);
+
+        // The largest requests and responses fit in a PDU
+        assert!(6 + registers(MAX_WRITE_REGISTERS).len() <= MAX_PDU_LEN);
+        assert!(2 + coils(MAX_READ_COILS).len() <= MAX_PDU_LEN);
+        assert!(10 + registers(write).len() <= MAX_PDU_LEN);
+    }
+
+    #[test]
+    fn byte_count_test() {
+        let service = Recorder::default();
+        let frame = Frame::tcp();
+        let value = Some(Exception::IllegalDataValue);
+
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, 9, vec![0xFF]);
+        assert_eq!(rejected(&service, &request), value);
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, 8, vec![0xFF, 0x00]);
+        assert_eq!(rejected(&service, &request), value);
+
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0000, vec![0x00; 3]);
+        assert_eq!(rejected(&service, &request), value);
+
+        let request =
+            frame.read_write_multiple_registers_request(0x01, 0x0000, 1, 0x0000, vec![0x00; 3]);
+        assert_eq!(rejected(&service, &request), value);
+
+        assert_eq!(service.calls(), 0);
+    }
+
+    #[test]
+    fn address_overflow_test() {
+        let service = Recorder::default();
+        let frame = Frame::tcp();
+        let address = Some(Exception::IllegalDataAddress);
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0xFFFF, 2);
+        assert_eq!(rejected(&service, &request), address);
+        let request = frame.write_multiple_coils_request(0x01, 0xFFF9, 8, vec![0x00]);
+        assert_eq!(rejected(&service, &request), address);
+        assert_eq!(service.calls(), 0);
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0xFFFF, 1);
+        assert_eq!(rejected(&service, &request), None);
+        let request = frame.write_multiple_coils_request(0x01, 0xFFF8, 8, vec![0x00]);
+        assert_eq!(rejected(&service, &request), None);
+        assert_eq!(service.calls(), 2);
+    }
+
+    #[test]
+    fn limits_test() {
+        let service = Recorder {
+            limits: Limits::default()
+                .with_coils(0x0000, 16)
+                .with_holding_registers(0x0100, 16),
+            ..Recorder::default()
+        };
+        let frame = Frame::tcp();
+        let address = Some(Exception::IllegalDataAddress);
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0100, 16);
+        assert_eq!(rejected(&service, &request), None);
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x010F, 1);
+        assert_eq!(rejected(&service, &request), None);
+        let request = frame.write_single_holding_register_request(0x01, 0x010F, 0x0001);
+        assert_eq!(rejected(&service, &request), None);
+        assert_eq!(
+            rejected(&service, &frame.read_coils_request(0x01, 0x0008, 8)),
+            None
+        );
+        assert_eq!(service.calls(), 4);
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0101, 16);
+        assert_eq!(rejected(&service, &request), address);
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x00FF, 1);
+        assert_eq!(rejected(&service, &request), address);
+        let request = frame.write_single_holding_register_request(0x01, 0x0110, 0x0001);
+        assert_eq!(rejected(&service, &request), address);
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x010F, vec![0x00; 4]);
+        assert_eq!(rejected(&service, &request), address);
+        assert_eq!(
+            rejected(&service, &frame.read_coils_request(0x01, 0x0009, 8)),
+            address
+        );
+        let request = frame.write_multiple_coils_request(0x01, 0x0009, 8, vec![0x00]);
+        assert_eq!(rejected(&service, &request), address);
+        let request =
+            frame.read_write_multiple_registers_request(0x01, 0x0100, 1, 0x0110, vec![0x00; 2]);
+        assert_eq!(rejected(&service, &request), address);
+        let request =
+            frame.read_write_multiple_registers_request(0x01, 0x0110, 1, 0x0100, vec![0x00; 2]);
+        assert_eq!(rejected(&service, &request), address);
+        assert_eq!(service.calls(), 4);
+    }
+
+    #[test]
+    fn broadcast_test() {
+        let service = Recorder::default();
+        let frame = Frame::rtu();
+
+        let request = frame.write_multiple_coils_request(0x00, 0x0000, 0x0008, vec![0xFF]);
+        assert!(dispatch(&service, &request).is_none());
+        let request = frame.write_single_holding_register_request(0x00, 0x0000, 0x0001);
+        assert!(dispatch(&service, &request).is_none());
+        assert_eq!(service.calls(), 2);
+
+        let request = frame.read_coils_request(0x00, 0x0000, 0x0008);
+        assert!(dispatch(&service, &request).is_none());
+        let request =
+            frame.read_write_multiple_registers_request(0x00, 0x0000, 1, 0x0000, vec![0; 2]);
+        assert!(dispatch(&service, &request).is_none());
+        let request = frame.write_multiple_coils_request(0x00, 0x0000, 0x0000, vec![]);
+        assert!(dispatch(&service, &request).is_none());
+        assert_eq!(service.calls(), 2);
+
+        let request = Frame::tcp().read_coils_request(0x00, 0x0000, 0x0008);
+        assert!(dispatch(&service, &request).is_some());
+        assert_eq!(service.calls(), 3);
+    }
+
+    #[test]
+    fn quantity_before_address_test() {
+        let service = Recorder {
+            limits: Limits::default().with_coils(0x0000, 16),
+            ..Recorder::default()
+        };
+        let request = Frame::tcp().read_coils_request(0x01, 0x0100, 2001);
+        assert_eq!(
+            rejected(&service, &request),
+            Some(Exception::IllegalDataValue)
+        );
+    }
+
+    #[test]
+    fn read_test() {
+        let service = Registers::default();
+        *service.values.lock().unwrap() = vec![0x0001, 0x0002, 0x0003];
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x0B, 0x0001, 0x0002);
+        let response = dispatch(&service, &request).unwrap();
+        assert_eq!(
+            response.to_string(),
+            "00 01 00 00 00 07 0B 03 04 00 02 00 03"
+        );
+    }
+
+    #[test]
+    fn write_test() {
+        let service = Registers::default();
+        *service.values.lock().unwrap() = vec![0x0000; 4];
+        let frame = Frame::rtu();
+        let request = frame.write_multiple_holding_registers_request(
+            0x0B,
+            0x0002,
+            vec![0x0B, 0x0A, 0xC1, 0x02],
+        );
+        let response_l = dispatch(&service, &request).unwrap();
+        let response_r = frame.write_multiple_holding_registers_response(0x0B, 0x0002, 0x0002);
+        assert_eq!(response_l, response_r);
+        assert_eq!(
+            *service.values.lock().unwrap(),
+            vec![0x0000, 0x0000, 0x0B0A, 0xC102]
+        );
+
+        let request = frame.write_multiple_coils_request(0x0B, 0x0000, 0x0009, vec![0x4D, 0x01]);
+        dispatch(&service, &request);
+        let coils = service.coils.lock().unwrap().clone();
+        assert_eq!(
+            coils,
+            vec![true, false, true, true, false, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn read_write_test() {
+        let service = Registers::default();
+        *service.values.lock().unwrap() = vec![0x0000; 4];
+        let request = Frame::rtu().read_write_multiple_registers_request(
+            0x01,
+            0x0001,
+            0x0002,
+            0x0002,
+            vec![0x12, 0x34],
+        );
+        let response_l = dispatch(&service, &request).unwrap();
+        let response_r =
+            Frame::rtu().read_write_multiple_registers_response(0x01, vec![0x00, 0x00, 0x12, 0x34]);
+        assert_eq!(response_l, response_r);
+    }
+
+    #[test]
+    fn exception_test() {
+        let service = Registers::default();
+        let frame = Frame::rtu();
+
+        let request = frame.read_input_registers_request(0x0B, 0x0000, 0x0001);
+        let response_l = dispatch(&service, &request).unwrap();
+        let response_r = frame.exception_response(
+            0x0B,
+            Function::ReadInputRegisters,
+            Exception::IllegalFunction,
+        );
+        assert_eq!(response_l, response_r);
+
+        let request = frame.read_multiple_holding_registers_request(0x0B, 0x0000, 0x0001);
+        let response_l = dispatch(&service, &request).unwrap();
+        let response_r = frame.exception_response(
+            0x0B,
+            Function::ReadMultipleHoldingRegisters,
+            Exception::IllegalDataAddress,
+        );
+        assert_eq!(response_l, response_r);
+
+        let request = frame.write_single_coil_request(0x0B, 0x0000, 0x1234);
+        let response_l = dispatch(&service, &request).unwrap();
+        let response_r =
+            frame.exception_response(0x0B, Function::WriteSingleCoil, Exception::IllegalDataValue);
+        assert_eq!(response_l, response_r);
+        assert!(service.coils.lock().unwrap().is_empty());
+    }
+
+    /// Answers every read with `len` values, whatever the quantity requested
+    struct Sloppy {
+        len: usize,
+    }
+
+    impl Service for Sloppy {
+        fn read_coils(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _count: u16,
+        ) -> Result<Vec<bool>, Exception> {
+            Ok(vec![true; self.len])
+        }
+
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            Ok(vec![0x1234; self.len])
+        }
+
+        fn write_multiple_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _values: &[u16],
+        ) -> Result<(), Exception> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reply_length_test() {
+        let frame = Frame::tcp();
+        let values = vec![0x00, 0x01];
+        for len in [0, 1, 3, 200] {
+            let service = Sloppy { len };
+            let requests = [
+                frame.read_coils_request(0x0B, 0x0000, 0x0002),
+                frame.read_multiple_holding_registers_request(0x0B, 0x0000, 0x0002),
+                frame.read_write_multiple_registers_request(0x0B, 0, 2, 0x10, values.clone()),
+            ];
+            for request in requests {
+                let response = dispatch(&service, &request).unwrap();
+                let exception = response.into_exception().unwrap();
+                assert_eq!(exception, Exception::SlaveDeviceFailure, "{} values", len);
+            }
+        }
+
+        let service = Sloppy { len: 2 };
+        let request = frame.read_multiple_holding_registers_request(0x0B, 0x0000, 0x0002);
+        let response = dispatch(&service, &request).unwrap();
+        assert_eq!(response.into_registers().unwrap(), [0x1234, 0x1234]);
+    }
+
+    #[test]
+    fn correlation_test() {
+        let service = Registers::default();
+        let frame = Frame::tcp();
+        for _ in 0..4 {
+            frame.read_coils_request(0x0B, 0x0000, 0x0001);
+        }
+        let request = frame.read_coils_request(0x0B, 0x0000, 0x0001);
+        let response = dispatch(&service, &request).unwrap();
+        assert_eq!(response.head().tid, 5);
+        assert_eq!(response.head().uid, 0x0B);
+    }
+}
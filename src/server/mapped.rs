@@ -0,0 +1,271 @@
+use std::sync::{Mutex, MutexGuard};
+
+use crate::data::{FromRegisters, ToRegisters, WordOrder};
+use crate::frame::Exception;
+
+use super::{Limits, Service};
+
+/// Holding registers backed by a value of the application
+///
+/// The value occupies the registers starting at a base address, in its
+/// [`ToRegisters`] form. A read is answered from the registers of the current value, so it may
+/// cover only part of a field spanning several registers. A write is applied to those
+/// registers, then converted back with [`FromRegisters`] and stored. The value is locked for
+/// the whole write, so a read never sees a write half applied.
+///
+/// Requests for other addresses are answered with [`Exception::IllegalDataAddress`], requests
+/// for coils, discrete inputs or input registers with [`Exception::IllegalFunction`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::Frame;
+/// use easy_modbus::data::{FromRegisters, ToRegisters, WordOrder};
+/// use easy_modbus::server::{dispatch, Mapped};
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Drive {
+///     speed: u16,
+///     current: f32,
+/// }
+///
+/// impl ToRegisters for Drive {
+///     const REGISTER_COUNT: usize = 3;
+///
+///     fn to_registers(&self, order: WordOrder) -> Vec<u16> {
+///         let mut registers = self.speed.to_registers(order);
+///         registers.extend(self.current.to_registers(order));
+///         registers
+///     }
+/// }
+///
+/// impl FromRegisters for Drive {
+///     const REGISTER_COUNT: usize = 3;
+///
+///     fn from_registers(registers: &[u16], order: WordOrder) -> std::io::Result<Drive> {
+///         Ok(Drive {
+///             speed: u16::from_registers(registers.get(..1).unwrap_or(&[]), order)?,
+///             current: f32::from_registers(registers.get(1..).unwrap_or(&[]), order)?,
+///         })
+///     }
+/// }
+///
+/// let drive = Mapped::new(0x0100, WordOrder::BigEndian, Drive { speed: 0, current: 1.5 });
+/// let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0100, 1450);
+/// dispatch(&drive, &request);
+/// assert_eq!(drive.get(), Drive { speed: 1450, current: 1.5 });
+/// ```
+#[derive(Debug)]
+pub struct Mapped<T> {
+    base: u16,
+    order: WordOrder,
+    value: Mutex<T>,
+}
+
+impl<T: ToRegisters + FromRegisters> Mapped<T> {
+    /// Map `value` onto the holding registers starting at `base`, in word order `order`
+    pub fn new(base: u16, order: WordOrder, value: T) -> Mapped<T> {
+        Mapped {
+            base,
+            order,
+            value: Mutex::new(value),
+        }
+    }
+
+    /// A copy of the current value
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.lock().clone()
+    }
+
+    /// Replace the value
+    pub fn set(&self, value: T) {
+        *self.lock() = value;
+    }
+
+    /// Lock the value, for the host application to update some of its fields
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.value.lock().unwrap()
+    }
+
+    pub fn get_base(&self) -> u16 {
+        self.base
+    }
+
+    pub fn get_order(&self) -> WordOrder {
+        self.order
+    }
+
+    /// Registers `address..address + count` of the mapping
+    fn offsets(&self, address: u16, count: usize) -> Result<std::ops::Range<usize>, Exception> {
+        let start = (address as usize)
+            .checked_sub(self.base as usize)
+            .ok_or(Exception::IllegalDataAddress)?;
+        if start + count > <T as ToRegisters>::REGISTER_COUNT {
+            return Err(Exception::IllegalDataAddress);
+        }
+        Ok(start..start + count)
+    }
+
+    fn write(&self, address: u16, values: &[u16]) -> Result<(), Exception> {
+        let offsets = self.offsets(address, values.len())?;
+        let mut value = self.lock();
+        let mut registers = value.to_registers(self.order);
+        registers[offsets].copy_from_slice(values);
+        *value =
+            T::from_registers(&registers, self.order).map_err(|_| Exception::IllegalDataValue)?;
+        Ok(())
+    }
+}
+
+impl<T: ToRegisters + FromRegisters + Send> Service for Mapped<T> {
+    fn limits(&self, _unit: u8) -> Limits {
+        Limits::default().with_holding_registers(self.base, <T as ToRegisters>::REGISTER_COUNT)
+    }
+
+    fn read_holding_registers(
+        &self,
+        _unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        let offsets = self.offsets(address, count as usize)?;
+        Ok(self.lock().to_registers(self.order)[offsets].to_vec())
+    }
+
+    fn write_single_register(&self, _unit: u8, address: u16, value: u16) -> Result<(), Exception> {
+        self.write(address, &[value])
+    }
+
+    fn write_multiple_registers(
+        &self,
+        _unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), Exception> {
+        self.write(address, values)
+    }
+}
+
+#[cfg(test)]
+mod mapped_test {
+    use std::io::{Error, ErrorKind};
+
+    use crate::data::{FromRegisters, ToRegisters, WordOrder};
+    use crate::frame::{Exception, Function};
+    use crate::server::{dispatch, Mapped};
+    use crate::{Frame, Response};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Drive {
+        speed: u16,
+        current: f32,
+        status_bits: u16,
+    }
+
+    impl ToRegisters for Drive {
+        const REGISTER_COUNT: usize = 4;
+
+        fn to_registers(&self, order: WordOrder) -> Vec<u16> {
+            let mut registers = self.speed.to_registers(order);
+            registers.extend(self.current.to_registers(order));
+            registers.extend(self.status_bits.to_registers(order));
+            registers
+        }
+    }
+
+    impl FromRegisters for Drive {
+        const REGISTER_COUNT: usize = 4;
+
+        fn from_registers(registers: &[u16], order: WordOrder) -> std::io::Result<Drive> {
+            if registers.len() != 4 {
+                return Err(Error::new(ErrorKind::InvalidInput, "Expected 4 registers"));
+            }
+            let current = f32::from_registers(&registers[1..3], order)?;
+            if current.is_nan() {
+                return Err(Error::new(ErrorKind::InvalidData, "NaN current"));
+            }
+            Ok(Drive {
+                speed: registers[0],
+                current,
+                status_bits: registers[3],
+            })
+        }
+    }
+
+    fn drive(order: WordOrder) -> Mapped<Drive> {
+        let value = Drive {
+            speed: 1450,
+            current: 1234.5,
+            status_bits: 0x0003,
+        };
+        Mapped::new(0x0100, order, value)
+    }
+
+    fn read(drive: &Mapped<Drive>, address: u16, count: u16) -> Response {
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x01, address, count);
+        dispatch(drive, &request).unwrap()
+    }
+
+    #[test]
+    fn read_test() {
+        let drive = drive(WordOrder::BigEndian);
+        let expected = Frame::tcp().read_holding_register_response(
+            0x01,
+            vec![0x05, 0xAA, 0x44, 0x9A, 0x50, 0x00, 0x00, 0x03],
+        );
+        assert_eq!(read(&drive, 0x0100, 4), expected);
+
+        // Only the low word of the current
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x50, 0x00]);
+        assert_eq!(read(&drive, 0x0102, 1), expected);
+
+        let drive = self::drive(WordOrder::LittleEndian);
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x44, 0x9A]);
+        assert_eq!(read(&drive, 0x0102, 1), expected);
+
+        for (address, count) in [(0x00FF, 1), (0x0103, 2)] {
+            let expected = Frame::tcp().exception_response(
+                0x01,
+                Function::ReadMultipleHoldingRegisters,
+                Exception::IllegalDataAddress,
+            );
+            assert_eq!(read(&drive, address, count), expected);
+        }
+    }
+
+    #[test]
+    fn write_test() {
+        let drive = drive(WordOrder::BigEndian);
+        let frame = Frame::tcp();
+
+        // The high word of the current, then a write across the current and status bits
+        let request = frame.write_single_holding_register_request(0x01, 0x0101, 0x4000);
+        dispatch(&drive, &request).unwrap();
+        assert_eq!(drive.get().current, f32::from_bits(0x4000_5000));
+        let values = vec![0x00, 0x00, 0x00, 0x10];
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0102, values);
+        dispatch(&drive, &request).unwrap();
+        let expected = Drive {
+            speed: 1450,
+            current: 2.0,
+            status_bits: 0x0010,
+        };
+        assert_eq!(drive.get(), expected);
+
+        // A write the value can't be converted back from is rejected as a whole
+        let values = vec![0x00, 0x01, 0x7F, 0xC0, 0x00, 0x00];
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0100, values);
+        assert!(matches!(
+            dispatch(&drive, &request),
+            Some(Response::Exception(..))
+        ));
+        assert_eq!(drive.get(), expected);
+
+        drive.lock().speed = 0;
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0x00]);
+        assert_eq!(read(&drive, 0x0100, 1), expected);
+    }
+}
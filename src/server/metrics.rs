@@ -0,0 +1,256 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::{Exception, Function};
+
+use super::{dispatch, Service};
+
+/// Upper bounds of the latency buckets of [`RequestCounters`], a last bucket holds the slower
+/// requests
+pub const LATENCY_BUCKETS: [Duration; 5] = [
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// How a request was answered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// A normal response
+    Ok,
+
+    /// An exception response
+    Exception(Exception),
+
+    /// No response, for a broadcast request
+    NoResponse,
+}
+
+/// Receives a record of every request a server answers
+///
+/// Set on a server with [`TcpServer::with_metrics`](super::TcpServer::with_metrics) or
+/// [`RtuServer::with_metrics`](super::RtuServer::with_metrics). A server without a sink does
+/// not even read the clock.
+pub trait MetricsSink: Send + Sync {
+    /// A request of `function` for `unit` was answered with `outcome`, `elapsed` after it was
+    /// received
+    fn record(&self, unit: u8, function: &Function, outcome: Outcome, elapsed: Duration);
+}
+
+/// Answer a request with a [`Service`] like [`dispatch`], recording it to `sink`
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::Frame;
+/// use easy_modbus::server::{dispatch_with_metrics, DataStore, RequestCounters};
+/// use easy_modbus::Function;
+///
+/// let store = DataStore::default().with_coils(0x0000, 8);
+/// let counters = RequestCounters::default();
+/// let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0008);
+/// dispatch_with_metrics(&store, &request, &counters);
+/// assert_eq!(counters.requests(Function::ReadCoils), 1);
+/// assert_eq!(counters.exceptions(), 0);
+/// ```
+pub fn dispatch_with_metrics<S: Service + ?Sized>(
+    service: &S,
+    request: &Request,
+    sink: &dyn MetricsSink,
+) -> Option<Response> {
+    let start = Instant::now();
+    let response = dispatch(service, request);
+    record(sink, request, response.as_ref(), start);
+    response
+}
+
+/// Record to `sink` that `request`, received at `start`, was answered with `response`
+pub(crate) fn record(
+    sink: &dyn MetricsSink,
+    request: &Request,
+    response: Option<&Response>,
+    start: Instant,
+) {
+    let outcome = match response {
+        Some(Response::Exception(_, body)) => Outcome::Exception(*body.get_exception()),
+        Some(_) => Outcome::Ok,
+        None => Outcome::NoResponse,
+    };
+    let head = request.head();
+    sink.record(head.uid, &head.function, outcome, start.elapsed());
+}
+
+/// [`dispatch_with_metrics`] if a sink is set, [`dispatch`] otherwise
+pub(crate) fn dispatch_recorded<S: Service + ?Sized>(
+    service: &S,
+    request: &Request,
+    sink: Option<&dyn MetricsSink>,
+) -> Option<Response> {
+    match sink {
+        Some(sink) => dispatch_with_metrics(service, request, sink),
+        None => dispatch(service, request),
+    }
+}
+
+/// A [`MetricsSink`] counting requests per function, exceptions and latencies
+///
+/// The latencies are counted in the buckets of [`LATENCY_BUCKETS`].
+#[derive(Debug, Default)]
+pub struct RequestCounters {
+    requests: [AtomicU64; 10],
+    exceptions: AtomicU64,
+    latencies: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl RequestCounters {
+    /// Requests of `function` recorded
+    ///
+    /// Requests of every custom function are counted together.
+    pub fn requests(&self, function: Function) -> u64 {
+        self.requests[index(function)].load(Ordering::Relaxed)
+    }
+
+    /// Requests of every function recorded
+    pub fn total_requests(&self) -> u64 {
+        self.requests
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Requests answered with an exception
+    pub fn exceptions(&self) -> u64 {
+        self.exceptions.load(Ordering::Relaxed)
+    }
+
+    /// Requests answered within each bucket of [`LATENCY_BUCKETS`], then the slower ones
+    pub fn latencies(&self) -> [u64; LATENCY_BUCKETS.len() + 1] {
+        let mut latencies = [0; LATENCY_BUCKETS.len() + 1];
+        for (latency, count) in latencies.iter_mut().zip(&self.latencies) {
+            *latency = count.load(Ordering::Relaxed);
+        }
+        latencies
+    }
+}
+
+impl MetricsSink for RequestCounters {
+    fn record(&self, _unit: u8, function: &Function, outcome: Outcome, elapsed: Duration) {
+        self.requests[index(*function)].fetch_add(1, Ordering::Relaxed);
+        if let Outcome::Exception(_) = outcome {
+            self.exceptions.fetch_add(1, Ordering::Relaxed);
+        }
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latencies[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn index(function: Function) -> usize {
+    use Function::*;
+    match function {
+        ReadCoils => 0,
+        ReadDiscreteInputs => 1,
+        ReadMultipleHoldingRegisters => 2,
+        ReadInputRegisters => 3,
+        WriteSingleCoil => 4,
+        WriteSingleHoldingRegister => 5,
+        WriteMultipleCoils => 6,
+        WriteMultipleHoldingRegisters => 7,
+        ReadWriteMultipleRegisters => 8,
+        Custom(_) => 9,
+    }
+}
+
+#[cfg(test)]
+mod metrics_test {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpStream;
+    use tokio_util::codec::Framed;
+
+    use crate::codec::{RtuClientCodec, TcpClientCodec};
+    use crate::frame::{Exception, Function};
+    use crate::server::{
+        dispatch_with_metrics, DataStore, MetricsSink, Outcome, RequestCounters, RtuServer,
+        TcpServer,
+    };
+    use crate::Frame;
+
+    #[derive(Default)]
+    struct Records(Mutex<Vec<(u8, Function, Outcome)>>);
+
+    impl MetricsSink for Records {
+        fn record(&self, unit: u8, function: &Function, outcome: Outcome, _elapsed: Duration) {
+            self.0.lock().unwrap().push((unit, *function, outcome));
+        }
+    }
+
+    #[test]
+    fn counters_test() {
+        let store = DataStore::default().with_coils(0x0000, 8);
+        let counters = RequestCounters::default();
+        let frame = Frame::rtu();
+        for request in [
+            frame.read_coils_request(0x01, 0x0000, 0x0008),
+            frame.read_coils_request(0x01, 0x0008, 0x0008),
+            frame.write_single_coil_request(0x00, 0x0000, 0xFF00),
+            frame.read_input_registers_request(0x01, 0x0000, 0x0001),
+        ] {
+            dispatch_with_metrics(&store, &request, &counters);
+        }
+        assert_eq!(counters.requests(Function::ReadCoils), 2);
+        assert_eq!(counters.requests(Function::WriteSingleCoil), 1);
+        assert_eq!(counters.requests(Function::ReadInputRegisters), 1);
+        assert_eq!(counters.total_requests(), 4);
+        assert_eq!(counters.exceptions(), 2);
+        assert_eq!(counters.latencies().iter().sum::<u64>(), 4);
+    }
+
+    #[tokio::test]
+    async fn server_test() {
+        let records = Arc::new(Records::default());
+        let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+        let server = server.with_metrics(records.clone());
+        let addr = server.local_addr();
+        tokio::spawn(server.serve(DataStore::default().with_coils(0x0000, 8)));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        for request in [
+            frame.read_coils_request(0x07, 0x0000, 0x0008),
+            frame.read_coils_request(0x07, 0x0010, 0x0008),
+        ] {
+            transport.send(request).await.unwrap();
+            assert!(transport.next().await.unwrap().is_ok());
+        }
+        let expected = vec![
+            (0x07, Function::ReadCoils, Outcome::Ok),
+            (
+                0x07,
+                Function::ReadCoils,
+                Outcome::Exception(Exception::IllegalDataAddress),
+            ),
+        ];
+        assert_eq!(*records.0.lock().unwrap(), expected);
+
+        let records = Arc::new(Records::default());
+        let (client, port) = tokio::io::duplex(64);
+        let server = RtuServer::new(port, 0x0B, DataStore::default().with_coils(0x0000, 8));
+        tokio::spawn(server.with_metrics(records.clone()).serve());
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let request = Frame::rtu().read_coils_request(0x0B, 0x0000, 0x0008);
+        transport.send(request).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+        let expected = vec![(0x0B, Function::ReadCoils, Outcome::Ok)];
+        assert_eq!(*records.0.lock().unwrap(), expected);
+    }
+}
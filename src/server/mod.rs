@@ -0,0 +1,330 @@
+//! Modbus server built on a [`Service`].
+//!
+//! A server decodes requests, calls the matching [`Service`] method and sends back a response
+//! carrying the transaction identifier and unit id of the request. A handler returning an
+//! [`Exception`] is answered with an exception response.
+//!
+//! Requests are validated before a handler is called. A quantity outside the limits of the
+//! specification, or a byte count not matching it, is answered with
+//! [`Exception::IllegalDataValue`]. An address range past `0xFFFF`, or outside the bounds
+//! declared by [`Service::limits`], is answered with [`Exception::IllegalDataAddress`].
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tokio::net::TcpListener;
+//!
+//! use easy_modbus::Exception;
+//! use easy_modbus::server::{serve_tcp, Service};
+//!
+//! struct Thermometer;
+//!
+//! impl Service for Thermometer {
+//!     fn read_input_registers(
+//!         &self,
+//!         _unit: u8,
+//!         address: u16,
+//!         count: u16,
+//!     ) -> Result<Vec<u16>, Exception> {
+//!         match (address, count) {
+//!             (0x0000, 1) => Ok(vec![215]),
+//!             _ => Err(Exception::IllegalDataAddress),
+//!         }
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let listener = TcpListener::bind("127.0.0.1:502").await?;
+//!     serve_tcp(listener, Thermometer).await
+//! }
+//! ```
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::frame::Exception;
+
+pub use builder::ServerBuilder;
+pub use chaos::{ChaosService, Faults};
+pub use correlated::{Correlated, Payload, RequestToken};
+pub use dispatch::dispatch;
+pub use mapped::Mapped;
+pub use metrics::{dispatch_with_metrics, MetricsSink, Outcome, RequestCounters, LATENCY_BUCKETS};
+pub use multi::MultiUnitService;
+pub use policy::{Access, AccessPolicy, Cidr, CidrPolicy};
+pub use rtu::RtuServer;
+pub use serve::{
+    serve_rtu, serve_rtu_until, serve_tcp, serve_tcp_until, serve_tcp_with, TcpLimits,
+};
+pub use stats::ServerStats;
+pub use store::{DataStore, RegisterChange};
+pub use tcp::{TcpServer, TcpServerHandle};
+
+mod builder;
+mod chaos;
+mod correlated;
+mod dispatch;
+mod mapped;
+mod metrics;
+mod multi;
+mod policy;
+mod rtu;
+mod serve;
+mod stats;
+mod store;
+mod tcp;
+
+/// Request handlers of a Modbus server
+///
+/// Every function defaults to answering [`Exception::IllegalFunction`], so an implementation
+/// only provides the functions its device supports. `unit` is the unit id of the request.
+///
+/// The write handlers are also called for RTU broadcast requests (`unit` 0), whose result is
+/// never sent back. The read handlers are never called for a broadcast, such a request is
+/// dropped unanswered.
+pub trait Service: Send + Sync {
+    /// Address bounds requests for `unit` are checked against before a handler is called
+    ///
+    /// Defaults to no bounds, every address is passed to the handlers.
+    fn limits(&self, unit: u8) -> Limits {
+        let _ = unit;
+        Limits::default()
+    }
+
+    /// Read coils (Function Code: 0x01)
+    fn read_coils(&self, unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        let _ = (unit, address, count);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read discrete inputs (Function Code: 0x02)
+    fn read_discrete_inputs(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, Exception> {
+        let _ = (unit, address, count);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read holding registers (Function Code: 0x03)
+    fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        let _ = (unit, address, count);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Read input registers (Function Code: 0x04)
+    fn read_input_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        let _ = (unit, address, count);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write a single coil (Function Code: 0x05)
+    fn write_single_coil(&self, unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+        let _ = (unit, address, value);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write a single holding register (Function Code: 0x06)
+    fn write_single_register(&self, unit: u8, address: u16, value: u16) -> Result<(), Exception> {
+        let _ = (unit, address, value);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write multiple coils (Function Code: 0x0F)
+    fn write_multiple_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[bool],
+    ) -> Result<(), Exception> {
+        let _ = (unit, address, values);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write multiple holding registers (Function Code: 0x10)
+    fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), Exception> {
+        let _ = (unit, address, values);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Write, then read holding registers (Function Code: 0x17)
+    ///
+    /// Defaults to [`Service::write_multiple_registers`] followed by
+    /// [`Service::read_holding_registers`].
+    fn read_write_multiple_registers(
+        &self,
+        unit: u8,
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Exception> {
+        self.write_multiple_registers(unit, write_address, values)?;
+        self.read_holding_registers(unit, read_address, read_count)
+    }
+}
+
+impl<S: Service + ?Sized> Service for Arc<S> {
+    fn limits(&self, unit: u8) -> Limits {
+        (**self).limits(unit)
+    }
+
+    fn read_coils(&self, unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        (**self).read_coils(unit, address, count)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, Exception> {
+        (**self).read_discrete_inputs(unit, address, count)
+    }
+
+    fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        (**self).read_holding_registers(unit, address, count)
+    }
+
+    fn read_input_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        (**self).read_input_registers(unit, address, count)
+    }
+
+    fn write_single_coil(&self, unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+        (**self).write_single_coil(unit, address, value)
+    }
+
+    fn write_single_register(&self, unit: u8, address: u16, value: u16) -> Result<(), Exception> {
+        (**self).write_single_register(unit, address, value)
+    }
+
+    fn write_multiple_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[bool],
+    ) -> Result<(), Exception> {
+        (**self).write_multiple_coils(unit, address, values)
+    }
+
+    fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), Exception> {
+        (**self).write_multiple_registers(unit, address, values)
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        unit: u8,
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Exception> {
+        (**self).read_write_multiple_registers(
+            unit,
+            read_address,
+            read_count,
+            write_address,
+            values,
+        )
+    }
+}
+
+/// Address bounds of a [`Service`]
+///
+/// A space without bounds accepts every address.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::server::Limits;
+/// let limits = Limits::default().with_holding_registers(0x0100, 16);
+/// assert!(limits.get_coils().is_none());
+/// assert_eq!(limits.get_holding_registers(), Some(&(0x0100..0x0110)));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Limits {
+    pub(crate) coils: Option<Range<u32>>,
+    pub(crate) discrete_inputs: Option<Range<u32>>,
+    pub(crate) holding_registers: Option<Range<u32>>,
+    pub(crate) input_registers: Option<Range<u32>>,
+}
+
+impl Limits {
+    /// Bound the coils to `count` addresses starting at `base`
+    pub fn with_coils(mut self, base: u16, count: usize) -> Limits {
+        self.coils = Some(span(base, count));
+        self
+    }
+
+    /// Bound the discrete inputs to `count` addresses starting at `base`
+    pub fn with_discrete_inputs(mut self, base: u16, count: usize) -> Limits {
+        self.discrete_inputs = Some(span(base, count));
+        self
+    }
+
+    /// Bound the holding registers to `count` addresses starting at `base`
+    pub fn with_holding_registers(mut self, base: u16, count: usize) -> Limits {
+        self.holding_registers = Some(span(base, count));
+        self
+    }
+
+    /// Bound the input registers to `count` addresses starting at `base`
+    pub fn with_input_registers(mut self, base: u16, count: usize) -> Limits {
+        self.input_registers = Some(span(base, count));
+        self
+    }
+
+    pub fn get_coils(&self) -> Option<&Range<u32>> {
+        self.coils.as_ref()
+    }
+
+    pub fn get_discrete_inputs(&self) -> Option<&Range<u32>> {
+        self.discrete_inputs.as_ref()
+    }
+
+    pub fn get_holding_registers(&self) -> Option<&Range<u32>> {
+        self.holding_registers.as_ref()
+    }
+
+    pub fn get_input_registers(&self) -> Option<&Range<u32>> {
+        self.input_registers.as_ref()
+    }
+}
+
+fn span(base: u16, count: usize) -> Range<u32> {
+    let end = (base as usize + count).min(0x10000);
+    base as u32..end as u32
+}
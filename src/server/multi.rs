@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::frame::Exception;
+
+use super::{Limits, Service};
+
+/// Routes requests to a [`Service`] per unit id
+///
+/// A gateway exposing several devices on one listener maps each unit id to the service of
+/// its device. Requests for an unmapped unit id are answered with
+/// [`Exception::GatewayPathUnavailable`].
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::{Exception, Frame, Function, Response};
+/// use easy_modbus::server::{dispatch, DataStore, MultiUnitService};
+///
+/// let meter = DataStore::default().with_input_registers(0x0000, 2);
+/// meter.set_input_registers(0x0000, &[230, 5]).unwrap();
+/// let gateway = MultiUnitService::default().with_unit(0x01, meter);
+///
+/// let request = Frame::tcp().read_input_registers_request(0x01, 0x0000, 0x0002);
+/// let response = dispatch(&gateway, &request);
+/// assert!(matches!(response, Some(Response::ReadInputRegisters(..))));
+///
+/// let request = Frame::tcp().read_input_registers_request(0x02, 0x0000, 0x0002);
+/// let response = Frame::tcp().exception_response(
+///     0x02,
+///     Function::ReadInputRegisters,
+///     Exception::GatewayPathUnavailable,
+/// );
+/// assert_eq!(dispatch(&gateway, &request), Some(response));
+/// ```
+#[derive(Default)]
+pub struct MultiUnitService {
+    units: HashMap<u8, Box<dyn Service>>,
+}
+
+impl fmt::Debug for MultiUnitService {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut units: Vec<&u8> = self.units.keys().collect();
+        units.sort();
+        f.debug_struct("MultiUnitService")
+            .field("units", &units)
+            .finish()
+    }
+}
+
+impl MultiUnitService {
+    /// Route requests for `unit` to `service`, replacing a previous mapping
+    pub fn with_unit(mut self, unit: u8, service: impl Service + 'static) -> MultiUnitService {
+        self.insert(unit, Box::new(service));
+        self
+    }
+
+    /// Route requests for `unit` to `service`, returning the service previously mapped
+    pub fn insert(&mut self, unit: u8, service: Box<dyn Service>) -> Option<Box<dyn Service>> {
+        self.units.insert(unit, service)
+    }
+
+    /// Stop routing requests for `unit`, returning the service it was mapped to
+    pub fn remove(&mut self, unit: u8) -> Option<Box<dyn Service>> {
+        self.units.remove(&unit)
+    }
+
+    pub fn get_unit(&self, unit: u8) -> Option<&dyn Service> {
+        self.units.get(&unit).map(|service| service.as_ref())
+    }
+
+    fn route(&self, unit: u8) -> Result<&dyn Service, Exception> {
+        self.get_unit(unit).ok_or(Exception::GatewayPathUnavailable)
+    }
+}
+
+impl Service for MultiUnitService {
+    fn limits(&self, unit: u8) -> Limits {
+        match self.get_unit(unit) {
+            Some(service) => service.limits(unit),
+            None => Limits::default(),
+        }
+    }
+
+    fn read_coils(&self, unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        self.route(unit)?.read_coils(unit, address, count)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, Exception> {
+        self.route(unit)?.read_discrete_inputs(unit, address, count)
+    }
+
+    fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.route(unit)?
+            .read_holding_registers(unit, address, count)
+    }
+
+    fn read_input_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.route(unit)?.read_input_registers(unit, address, count)
+    }
+
+    fn write_single_coil(&self, unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+        self.route(unit)?.write_single_coil(unit, address, value)
+    }
+
+    fn write_single_register(&self, unit: u8, address: u16, value: u16) -> Result<(), Exception> {
+        self.route(unit)?
+            .write_single_register(unit, address, value)
+    }
+
+    fn write_multiple_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[bool],
+    ) -> Result<(), Exception> {
+        self.route(unit)?
+            .write_multiple_coils(unit, address, values)
+    }
+
+    fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), Exception> {
+        self.route(unit)?
+            .write_multiple_registers(unit, address, values)
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        unit: u8,
+        read_address: u16,
+        read_count: u16,
+        write_address: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Exception> {
+        self.route(unit)?.read_write_multiple_registers(
+            unit,
+            read_address,
+            read_count,
+            write_address,
+            values,
+        )
+    }
+}
+
+#[cfg(test)]
+mod multi_test {
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::codec::Framed;
+
+    use crate::codec::TcpClientCodec;
+    use crate::frame::{Exception, Function};
+    use crate::server::{dispatch, serve_tcp, DataStore, MultiUnitService};
+    use crate::Frame;
+
+    fn meter(value: u16) -> DataStore {
+        let store = DataStore::default().with_holding_registers(0x0000, 1);
+        store.set_holding_registers(0x0000, &[value]).unwrap();
+        store
+    }
+
+    #[tokio::test]
+    async fn routing_test() {
+        let gateway = MultiUnitService::default()
+            .with_unit(0x01, meter(0x0101))
+            .with_unit(0x02, meter(0x0202));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tcp(listener, gateway));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        let expected = Frame::tcp();
+        for (unit, value) in [(0x01, 0x0101u16), (0x02, 0x0202), (0x01, 0x0101)] {
+            let request = frame.read_multiple_holding_registers_request(unit, 0x0000, 0x0001);
+            transport.send(request).await.unwrap();
+            let response = transport.next().await.unwrap().unwrap();
+            let bytes = value.to_be_bytes().to_vec();
+            assert_eq!(
+                response,
+                expected.read_holding_register_response(unit, bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn unmapped_test() {
+        let mut gateway = MultiUnitService::default().with_unit(0x01, meter(0x0101));
+        let frame = Frame::tcp();
+        let request = frame.write_single_holding_register_request(0x03, 0x0000, 0x0001);
+        let response_l = dispatch(&gateway, &request).unwrap();
+        let response_r = Frame::tcp().exception_response(
+            0x03,
+            Function::WriteSingleHoldingRegister,
+            Exception::GatewayPathUnavailable,
+        );
+        assert_eq!(response_l, response_r);
+
+        assert!(gateway.remove(0x01).is_some());
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        let response_l = dispatch(&gateway, &request).unwrap();
+        let response_r = Frame::tcp().exception_response(
+            0x01,
+            Function::ReadMultipleHoldingRegisters,
+            Exception::GatewayPathUnavailable,
+        );
+        assert_eq!(response_l, response_r);
+    }
+
+    #[test]
+    fn limits_test() {
+        let gateway = MultiUnitService::default().with_unit(0x01, meter(0x0101));
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x01, 0x0001, 0x0001);
+        let response_l = dispatch(&gateway, &request).unwrap();
+        let response_r = Frame::tcp().exception_response(
+            0x01,
+            Function::ReadMultipleHoldingRegisters,
+            Exception::IllegalDataAddress,
+        );
+        assert_eq!(response_l, response_r);
+    }
+}
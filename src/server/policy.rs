@@ -0,0 +1,377 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io;
+use std::io::ErrorKind;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::frame::Exception;
+
+use super::{Limits, Service};
+
+/// What a TCP client may do, decided by an [`AccessPolicy`] when its connection is accepted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Access {
+    /// The connection is closed right away
+    Deny,
+
+    /// Write requests are answered with [`AccessPolicy::write_exception`]
+    ReadOnly,
+
+    /// Every request is served
+    ReadWrite,
+}
+
+/// Decides what the clients of a [`TcpServer`](super::TcpServer) may do, from their address
+///
+/// Set on a server with [`TcpServer::with_policy`](super::TcpServer::with_policy). The policy
+/// is evaluated once per connection, when it is accepted. [`CidrPolicy`] decides from lists
+/// of address ranges.
+pub trait AccessPolicy: Send + Sync {
+    /// The access of a client connecting from `peer`
+    fn access(&self, peer: IpAddr) -> Access;
+
+    /// The exception answering the write requests of a [`Access::ReadOnly`] client,
+    /// [`Exception::IllegalFunction`] by default
+    fn write_exception(&self) -> Exception {
+        Exception::IllegalFunction
+    }
+}
+
+/// A range of IP addresses, in CIDR notation
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::server::Cidr;
+///
+/// let range: Cidr = "192.168.1.0/24".parse().unwrap();
+/// assert!(range.contains("192.168.1.17".parse().unwrap()));
+/// assert!(!range.contains("192.168.2.17".parse().unwrap()));
+///
+/// let host: Cidr = "10.0.0.5".parse().unwrap();
+/// assert_eq!(host.get_prefix(), 32);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// The addresses sharing the first `prefix` bits of `addr`
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if `prefix` is longer than the address.
+    pub fn new(addr: IpAddr, prefix: u8) -> io::Result<Cidr> {
+        let bits = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > bits {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid prefix length /{} for {}", prefix, addr),
+            ));
+        }
+        Ok(Cidr { addr, prefix })
+    }
+
+    pub fn get_addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn get_prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// Whether `addr` is in the range
+    ///
+    /// An IPv4-mapped IPv6 address is matched as its IPv4 address.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr.to_canonical()) {
+            (IpAddr::V4(range), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(range) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(range), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(range) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = io::Error;
+
+    /// Parse `addr/prefix`, or a single `addr`
+    fn from_str(s: &str) -> io::Result<Cidr> {
+        let invalid = || io::Error::new(ErrorKind::InvalidInput, format!("Invalid CIDR: {}", s));
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let prefix = match (prefix, addr) {
+            (Some(prefix), _) => prefix.parse().map_err(|_| invalid())?,
+            (None, IpAddr::V4(_)) => 32,
+            (None, IpAddr::V6(_)) => 128,
+        };
+        Cidr::new(addr, prefix)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// An [`AccessPolicy`] deciding from lists of address ranges
+///
+/// The ranges are evaluated in the order they were added, the first containing the address of
+/// a client decides its access. A client in no range gets the default access.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::server::{Access, AccessPolicy, CidrPolicy};
+///
+/// let policy = CidrPolicy::new(Access::Deny)
+///     .read_write("10.0.0.5".parse().unwrap())
+///     .read_write("10.0.0.6".parse().unwrap())
+///     .read_only("10.0.0.0/16".parse().unwrap());
+/// assert_eq!(policy.access("10.0.0.5".parse().unwrap()), Access::ReadWrite);
+/// assert_eq!(policy.access("10.0.3.1".parse().unwrap()), Access::ReadOnly);
+/// assert_eq!(policy.access("172.16.0.1".parse().unwrap()), Access::Deny);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CidrPolicy {
+    rules: Vec<(Cidr, Access)>,
+    default: Access,
+    write_exception: Exception,
+}
+
+impl CidrPolicy {
+    /// A policy giving `default` to the clients in no range
+    pub fn new(default: Access) -> CidrPolicy {
+        CidrPolicy {
+            rules: Vec::new(),
+            default,
+            write_exception: Exception::IllegalFunction,
+        }
+    }
+
+    /// Give `access` to the clients in `range`
+    pub fn with_rule(mut self, range: Cidr, access: Access) -> CidrPolicy {
+        self.rules.push((range, access));
+        self
+    }
+
+    /// Close the connections of the clients in `range`
+    pub fn deny(self, range: Cidr) -> CidrPolicy {
+        self.with_rule(range, Access::Deny)
+    }
+
+    /// Refuse the write requests of the clients in `range`
+    pub fn read_only(self, range: Cidr) -> CidrPolicy {
+        self.with_rule(range, Access::ReadOnly)
+    }
+
+    /// Serve every request of the clients in `range`
+    pub fn read_write(self, range: Cidr) -> CidrPolicy {
+        self.with_rule(range, Access::ReadWrite)
+    }
+
+    /// Answer the write requests of read-only clients with `exception`
+    pub fn with_write_exception(mut self, exception: Exception) -> CidrPolicy {
+        self.write_exception = exception;
+        self
+    }
+
+    pub fn get_rules(&self) -> &[(Cidr, Access)] {
+        &self.rules
+    }
+
+    pub fn get_default(&self) -> Access {
+        self.default
+    }
+}
+
+impl AccessPolicy for CidrPolicy {
+    fn access(&self, peer: IpAddr) -> Access {
+        self.rules
+            .iter()
+            .find(|(range, _)| range.contains(peer))
+            .map_or(self.default, |(_, access)| *access)
+    }
+
+    fn write_exception(&self) -> Exception {
+        self.write_exception
+    }
+}
+
+/// A [`Service`] answering every write with `exception`
+pub(crate) struct ReadOnly<'a, S: ?Sized> {
+    pub(crate) service: &'a S,
+    pub(crate) exception: Exception,
+}
+
+impl<S: Service + ?Sized> Service for ReadOnly<'_, S> {
+    fn limits(&self, unit: u8) -> Limits {
+        self.service.limits(unit)
+    }
+
+    fn read_coils(&self, unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        self.service.read_coils(unit, address, count)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, Exception> {
+        self.service.read_discrete_inputs(unit, address, count)
+    }
+
+    fn read_holding_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.service.read_holding_registers(unit, address, count)
+    }
+
+    fn read_input_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.service.read_input_registers(unit, address, count)
+    }
+
+    fn write_single_coil(&self, _unit: u8, _address: u16, _value: bool) -> Result<(), Exception> {
+        Err(self.exception)
+    }
+
+    fn write_single_register(
+        &self,
+        _unit: u8,
+        _address: u16,
+        _value: u16,
+    ) -> Result<(), Exception> {
+        Err(self.exception)
+    }
+
+    fn write_multiple_coils(
+        &self,
+        _unit: u8,
+        _address: u16,
+        _values: &[bool],
+    ) -> Result<(), Exception> {
+        Err(self.exception)
+    }
+
+    fn write_multiple_registers(
+        &self,
+        _unit: u8,
+        _address: u16,
+        _values: &[u16],
+    ) -> Result<(), Exception> {
+        Err(self.exception)
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        _unit: u8,
+        _read_address: u16,
+        _read_count: u16,
+        _write_address: u16,
+        _values: &[u16],
+    ) -> Result<Vec<u16>, Exception> {
+        Err(self.exception)
+    }
+}
+
+#[cfg(test)]
+mod policy_test {
+    use std::sync::Arc;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpStream;
+    use tokio_util::codec::Framed;
+
+    use crate::codec::TcpClientCodec;
+    use crate::frame::{Exception, Function};
+    use crate::server::{Access, AccessPolicy, Cidr, CidrPolicy, DataStore, TcpServer};
+    use crate::Frame;
+
+    #[test]
+    fn cidr_test() {
+        let range: Cidr = "10.1.0.0/16".parse().unwrap();
+        assert!(range.contains("10.1.255.3".parse().unwrap()));
+        assert!(range.contains("::ffff:10.1.0.9".parse().unwrap()));
+        assert!(!range.contains("10.2.0.1".parse().unwrap()));
+        assert!(!range.contains("::1".parse().unwrap()));
+        let all: Cidr = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains("203.0.113.7".parse().unwrap()));
+        let v6: Cidr = "fd00::/8".parse().unwrap();
+        assert!(v6.contains("fd12::1".parse().unwrap()));
+        assert_eq!(v6.to_string(), "fd00::/8");
+        assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+        assert!("10.0.0/8".parse::<Cidr>().is_err());
+    }
+
+    async fn serve(policy: CidrPolicy) -> Framed<TcpStream, TcpClientCodec> {
+        let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr();
+        let server = server.with_policy(Arc::new(policy));
+        let store = DataStore::default()
+            .with_coils(0x0000, 8)
+            .with_holding_registers(0x0000, 4);
+        tokio::spawn(server.serve(store));
+        let stream = TcpStream::connect(addr).await.unwrap();
+        Framed::new(stream, TcpClientCodec::default())
+    }
+
+    #[tokio::test]
+    async fn denied_test() {
+        let policy = CidrPolicy::new(Access::ReadWrite).deny("127.0.0.0/8".parse().unwrap());
+        let mut transport = serve(policy).await;
+        let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0008);
+        let _ = transport.send(request).await;
+        assert!(matches!(transport.next().await, None | Some(Err(_))));
+    }
+
+    #[tokio::test]
+    async fn read_only_test() {
+        let policy = CidrPolicy::new(Access::Deny)
+            .read_write("10.0.0.5".parse().unwrap())
+            .read_only("127.0.0.1".parse().unwrap())
+            .with_write_exception(Exception::IllegalDataAddress);
+        assert_eq!(policy.write_exception(), Exception::IllegalDataAddress);
+        let mut transport = serve(policy).await;
+        let frame = Frame::tcp();
+        let expected_frame = Frame::tcp();
+
+        let request = frame.write_single_holding_register_request(0x01, 0x0001, 0x1234);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = expected_frame.exception_response(
+            0x01,
+            Function::WriteSingleHoldingRegister,
+            Exception::IllegalDataAddress,
+        );
+        assert_eq!(response, expected);
+
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0000, 0x0002);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = expected_frame.read_holding_register_response(0x01, vec![0; 4]);
+        assert_eq!(response, expected);
+    }
+}
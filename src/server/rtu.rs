@@ -0,0 +1,281 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::io::Result;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::time::Instant;
+use tokio_util::codec::Framed;
+
+use crate::codec::RtuServerCodec;
+
+use super::chaos::{send_tampered, Tamper};
+use super::metrics::dispatch_recorded;
+use super::serve::resync;
+use super::{ChaosService, MetricsSink, ServerStats, Service};
+
+/// A Modbus RTU server answering as one unit on a serial line
+///
+/// Requests for other units are ignored, they are answered by the other servers on the line.
+/// Broadcast requests are executed and never answered. A response is sent no sooner than the
+/// turnaround delay after its request was received, which gives a slow client time to switch
+/// its transceiver from sending to receiving.
+///
+/// `transport` is usually a serial port, such as a `tokio_serial::SerialStream`, but any byte
+/// stream carrying RTU frames is served. The communication counters of the line are kept in
+/// [`RtuServer::stats`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use easy_modbus::server::{DataStore, RtuServer};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let builder = tokio_serial::new("/dev/ttyUSB0", 9600);
+///     let port = tokio_serial::SerialStream::open(&builder)?;
+///     let store = DataStore::default().with_holding_registers(0x0000, 10);
+///     RtuServer::new(port, 0x0B, store).serve().await
+/// }
+/// ```
+pub struct RtuServer<T, S> {
+    transport: Framed<T, RtuServerCodec>,
+    service: S,
+    turnaround: Duration,
+    stats: Arc<ServerStats>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    tamper: Option<Arc<dyn Tamper>>,
+}
+
+impl<T, S> RtuServer<T, S>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    S: Service,
+{
+    /// Serve the requests for `unit_id` arriving on `transport` with `service`
+    ///
+    /// The turnaround delay defaults to 1.75 ms, the inter-frame delay the specification
+    /// recommends above 19200 baud.
+    pub fn new(transport: T, unit_id: u8, service: S) -> RtuServer<T, S> {
+        let stats = Arc::new(ServerStats::default());
+        let codec = RtuServerCodec::for_unit(unit_id).observer(stats.clone());
+        RtuServer {
+            transport: Framed::new(transport, codec),
+            service,
+            turnaround: Duration::from_micros(1750),
+            stats,
+            metrics: None,
+            tamper: None,
+        }
+    }
+
+    /// Send a response no sooner than `turnaround` after its request was received
+    pub fn with_turnaround(mut self, turnaround: Duration) -> RtuServer<T, S> {
+        self.turnaround = turnaround;
+        self
+    }
+
+    /// Record every request answered to `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> RtuServer<T, S> {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send every response with the faults `chaos` injects, `chaos` usually being the service
+    ///
+    /// [`ChaosService::serve_rtu`] serves a `ChaosService` with its own faults.
+    pub fn with_chaos<C>(mut self, chaos: Arc<ChaosService<C>>) -> RtuServer<T, S>
+    where
+        C: Service + 'static,
+    {
+        self.tamper = Some(chaos);
+        self
+    }
+
+    pub fn get_turnaround(&self) -> Duration {
+        self.turnaround
+    }
+
+    pub fn get_service(&self) -> &S {
+        &self.service
+    }
+
+    /// The communication counters, shared with the server while it serves
+    pub fn stats(&self) -> Arc<ServerStats> {
+        self.stats.clone()
+    }
+
+    /// Serve requests until the transport is closed, or with the first I/O error
+    ///
+    /// A frame failing to decode is dropped, the CRC errors being counted in the
+    /// [`RtuServer::stats`], and serving goes on with the next frame.
+    pub async fn serve(self) -> Result<()> {
+        self.serve_until(std::future::pending()).await
+    }
+
+    /// Serve requests until `shutdown` completes
+    ///
+    /// Like [`RtuServer::serve`]. A request already received when `shutdown` completes is still
+    /// answered, then the function returns.
+    pub async fn serve_until<F: Future<Output = ()>>(mut self, shutdown: F) -> Result<()> {
+        let mut shutdown = pin!(shutdown);
+        loop {
+            let request = tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                request = self.transport.next() => request,
+            };
+            let request = match request {
+                Some(Ok(request)) => request,
+                Some(Err(error)) => {
+                    self.transport = resync(self.transport, error)?;
+                    continue;
+                }
+                None => return Ok(()),
+            };
+            let received = Instant::now();
+            let metrics = self.metrics.as_deref();
+            let response = dispatch_recorded(&self.service, &request, metrics);
+            self.stats.server_message(response.is_some());
+            if let Some(response) = response {
+                tokio::time::sleep_until(received + self.turnaround).await;
+                let tamper = self.tamper.as_deref();
+                send_tampered(&mut self.transport, response, tamper).await?;
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug, S: fmt::Debug> fmt::Debug for RtuServer<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RtuServer")
+            .field("transport", &self.transport)
+            .field("service", &self.service)
+            .field("turnaround", &self.turnaround)
+            .field("stats", &self.stats)
+            .field("metrics", &self.metrics.is_some())
+            .field("tamper", &self.tamper.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod rtu_test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use bytes::BytesMut;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio_util::codec::{Encoder, Framed};
+    use tokio_util::sync::CancellationToken;
+
+    use crate::codec::RtuClientCodec;
+    use crate::server::{DataStore, RtuServer};
+    use crate::Frame;
+
+    fn store() -> Arc<DataStore> {
+        let store = DataStore::default()
+            .with_coils(0x0000, 8)
+            .with_holding_registers(0x0000, 4);
+        Arc::new(store)
+    }
+
+    /// Nothing is sent back on `client` for a while
+    async fn silent(client: &mut DuplexStream) -> bool {
+        let mut reply = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_millis(100), client.read(&mut reply));
+        read.await.is_err()
+    }
+
+    #[tokio::test]
+    async fn serve_test() {
+        let store = store();
+        let (mut client, port) = tokio::io::duplex(64);
+        let server = RtuServer::new(port, 0x0B, store.clone());
+        assert_eq!(server.get_turnaround(), Duration::from_micros(1750));
+        tokio::spawn(server.serve());
+
+        // Another unit, then a broadcast
+        let mut buf = BytesMut::new();
+        let mut codec = RtuClientCodec::default();
+        let frame = Frame::rtu();
+        let request = frame.read_multiple_holding_registers_request(0x0C, 0x0000, 0x0001);
+        codec.encode(request, &mut buf).unwrap();
+        client.write_all(&buf.split()).await.unwrap();
+        assert!(silent(&mut client).await);
+        let request = frame.write_single_coil_request(0x00, 0x0002, 0xFF00);
+        codec.encode(request, &mut buf).unwrap();
+        client.write_all(&buf.split()).await.unwrap();
+        assert!(silent(&mut client).await);
+        assert_eq!(store.get_coils(0x0002, 1).unwrap(), vec![true]);
+
+        let mut transport = Framed::new(client, codec);
+        let request = frame.read_coils_request(0x0B, 0x0000, 0x0004);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        assert_eq!(response, frame.read_coils_response(0x0B, vec![0x04]));
+    }
+
+    #[tokio::test]
+    async fn turnaround_test() {
+        let (client, port) = tokio::io::duplex(64);
+        let turnaround = Duration::from_millis(150);
+        tokio::spawn(
+            RtuServer::new(port, 0x0B, store())
+                .with_turnaround(turnaround)
+                .serve(),
+        );
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let request = Frame::rtu().read_multiple_holding_registers_request(0x0B, 0x0000, 0x0001);
+        let start = tokio::time::Instant::now();
+        transport.send(request).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+        assert!(start.elapsed() >= turnaround);
+    }
+
+    #[tokio::test]
+    async fn decode_error_test() {
+        let (mut client, port) = tokio::io::duplex(64);
+        tokio::spawn(RtuServer::new(port, 0x0B, store()).serve());
+
+        // A frame failing its CRC check, then one of an unknown function
+        let mut buf = BytesMut::new();
+        let mut codec = RtuClientCodec::default();
+        let frame = Frame::rtu();
+        let request = frame.read_coils_request(0x0B, 0x0000, 0x0004);
+        codec.encode(request.clone(), &mut buf).unwrap();
+        let last = buf.len() - 1;
+        buf[last] ^= 0xFF;
+        client.write_all(&buf.split()).await.unwrap();
+        assert!(silent(&mut client).await);
+        client.write_all(&[0x0B, 0x64, 0x00, 0x00]).await.unwrap();
+        assert!(silent(&mut client).await);
+
+        let mut transport = Framed::new(client, codec);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        assert_eq!(response, frame.read_coils_response(0x0B, vec![0x00]));
+    }
+
+    #[tokio::test]
+    async fn shutdown_test() {
+        let (client, port) = tokio::io::duplex(64);
+        let shutdown = CancellationToken::new();
+        let server = RtuServer::new(port, 0x0B, store());
+        let running = tokio::spawn(server.serve_until(shutdown.clone().cancelled_owned()));
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let request = Frame::rtu().read_multiple_holding_registers_request(0x0B, 0x0000, 0x0001);
+        transport.send(request).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+
+        shutdown.cancel();
+        running.await.unwrap().unwrap();
+        assert!(transport.next().await.is_none());
+    }
+}
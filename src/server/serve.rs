@@ -0,0 +1,672 @@
+use std::future::Future;
+use std::io::{Error, ErrorKind, Result};
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::{self, BoxFuture};
+use futures::{FutureExt, SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tokio_util::codec::Framed;
+use tokio_util::sync::CancellationToken;
+
+use crate::codec::{RtuServerCodec, TcpServerCodec};
+use crate::frame::request::Request;
+use crate::frame::response::Response;
+use crate::frame::Exception;
+use crate::ModbusError;
+
+use super::chaos::{send_tampered, Tamper};
+use super::metrics::dispatch_recorded;
+use super::policy::ReadOnly;
+use super::{dispatch, Access, AccessPolicy, MetricsSink, Service};
+
+/// Limits of the connections served by [`serve_tcp_with`]
+///
+/// Every limit is off by default.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use easy_modbus::server::TcpLimits;
+///
+/// let limits = TcpLimits::default()
+///     .with_max_connections(16)
+///     .with_idle_timeout(Duration::from_secs(60));
+/// assert_eq!(limits.get_max_connections(), Some(16));
+/// assert_eq!(limits.get_max_request_rate(), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TcpLimits {
+    pub(crate) max_connections: Option<usize>,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) max_request_rate: Option<u32>,
+}
+
+impl TcpLimits {
+    /// Serve at most `max_connections` connections at once
+    ///
+    /// A connection accepted while as many are open is closed right away.
+    pub fn with_max_connections(mut self, max_connections: usize) -> TcpLimits {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Close a connection which sent no complete request for `idle_timeout`
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> TcpLimits {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Answer at most `per_second` requests per second on each connection
+    ///
+    /// Requests arriving faster wait for their turn, so a flooding client only slows itself
+    /// down. At least one request per second is answered.
+    pub fn with_max_request_rate(mut self, per_second: u32) -> TcpLimits {
+        self.max_request_rate = Some(per_second.max(1));
+        self
+    }
+
+    pub fn get_max_connections(&self) -> Option<usize> {
+        self.max_connections
+    }
+
+    pub fn get_idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    pub fn get_max_request_rate(&self) -> Option<u32> {
+        self.max_request_rate
+    }
+}
+
+/// Serve Modbus TCP clients accepted on `listener`
+///
+/// Every connection is handled on its own task and shares `service`. A frame which cannot be
+/// decoded is dropped, the connection goes on. Only returns when accepting fails.
+pub async fn serve_tcp<S: Service + 'static>(listener: TcpListener, service: S) -> Result<()> {
+    serve_tcp_with(listener, service, TcpLimits::default()).await
+}
+
+/// Serve Modbus TCP clients accepted on `listener`, within `limits`
+///
+/// Like [`serve_tcp`], guarding the server against clients opening too many connections,
+/// going silent or flooding it with requests.
+pub async fn serve_tcp_with<S: Service + 'static>(
+    listener: TcpListener,
+    service: S,
+    limits: TcpLimits,
+) -> Result<()> {
+    let active = Arc::new(AtomicUsize::new(0));
+    let service = Arc::new(service);
+    let hooks = Hooks::default();
+    let listeners = vec![listener];
+    let (shutdown, drain) = (std::future::pending(), Duration::ZERO);
+    serve_connections(listeners, service, limits, shutdown, drain, active, hooks).await
+}
+
+/// Serve Modbus TCP clients accepted on `listener` within `limits`, until `shutdown` completes
+///
+/// Like [`serve_tcp_with`], for embedding a server in an application which stops it. Once
+/// `shutdown` completes no connection is accepted anymore, and the open connections get
+/// `drain` to finish: a request already received is still answered, then the connection is
+/// closed. Connections still busy after `drain` are aborted. Returns once every connection is
+/// closed, with the accepting error if accepting failed first.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use tokio::net::TcpListener;
+/// use easy_modbus::server::{serve_tcp_until, DataStore, TcpLimits};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = TcpListener::bind("127.0.0.1:502").await?;
+///     let store = DataStore::default().with_holding_registers(0x0000, 10);
+///     let shutdown = async {
+///         let _ = tokio::signal::ctrl_c().await;
+///     };
+///     serve_tcp_until(listener, store, TcpLimits::default(), shutdown, Duration::from_secs(5))
+///         .await
+/// }
+/// ```
+pub async fn serve_tcp_until<S, F>(
+    listener: TcpListener,
+    service: S,
+    limits: TcpLimits,
+    shutdown: F,
+    drain: Duration,
+) -> Result<()>
+where
+    S: Service + 'static,
+    F: Future<Output = ()>,
+{
+    let active = Arc::new(AtomicUsize::new(0));
+    let service = Arc::new(service);
+    let hooks = Hooks::default();
+    let listeners = vec![listener];
+    serve_connections(listeners, service, limits, shutdown, drain, active, hooks).await
+}
+
+/// Answers the requests of the connections served by [`serve_connections`]
+///
+/// Every [`Service`] does, with [`dispatch`], and so does a
+/// [`ServerBuilder`](super::ServerBuilder), whose handlers are asynchronous.
+pub(crate) trait Dispatch: Send + Sync {
+    /// The response to `request`, a write answered with `read_only` if set, recorded to
+    /// `metrics`
+    fn answer<'a>(
+        &'a self,
+        request: &'a Request,
+        read_only: Option<Exception>,
+        metrics: Option<&'a dyn MetricsSink>,
+    ) -> BoxFuture<'a, Option<Response>>;
+}
+
+impl<S: Service> Dispatch for S {
+    fn answer<'a>(
+        &'a self,
+        request: &'a Request,
+        read_only: Option<Exception>,
+        metrics: Option<&'a dyn MetricsSink>,
+    ) -> BoxFuture<'a, Option<Response>> {
+        let response = match read_only {
+            Some(exception) => {
+                let service = ReadOnly {
+                    service: self,
+                    exception,
+                };
+                dispatch_recorded(&service, request, metrics)
+            }
+            None => dispatch_recorded(self, request, metrics),
+        };
+        future::ready(response).boxed()
+    }
+}
+
+/// What a [`TcpServer`](super::TcpServer) applies to its connections besides its limits
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    /// Records every request answered
+    pub(crate) metrics: Option<Arc<dyn MetricsSink>>,
+
+    /// Decides the access of every client accepted
+    pub(crate) policy: Option<Arc<dyn AccessPolicy>>,
+
+    /// Injects faults into every response sent
+    pub(crate) tamper: Option<Arc<dyn Tamper>>,
+}
+
+/// The accepting loop of [`serve_tcp_with`] and [`serve_tcp_until`] on every listener of
+/// `listeners`, counting the open connections in `active` and applying `hooks`
+pub(crate) async fn serve_connections<S, F>(
+    listeners: Vec<TcpListener>,
+    service: Arc<S>,
+    limits: TcpLimits,
+    shutdown: F,
+    drain: Duration,
+    active: Arc<AtomicUsize>,
+    hooks: Hooks,
+) -> Result<()>
+where
+    S: Dispatch + 'static,
+    F: Future<Output = ()>,
+{
+    let connections = limits
+        .max_connections
+        .map(|max_connections| Arc::new(Semaphore::new(max_connections)));
+    let stop = CancellationToken::new();
+    let mut tasks = JoinSet::new();
+    let mut shutdown = pin!(shutdown);
+    let result = loop {
+        let (stream, peer) = tokio::select! {
+            _ = &mut shutdown => break Ok(()),
+            accepted = accept(&listeners) => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => break Err(e),
+            },
+        };
+        while tasks.try_join_next().is_some() {}
+        let read_only = match &hooks.policy {
+            Some(policy) => match policy.access(peer.ip()) {
+                Access::Deny => {
+                    trace_connection("deny", peer);
+                    continue;
+                }
+                Access::ReadOnly => Some(policy.write_exception()),
+                Access::ReadWrite => None,
+            },
+            None => None,
+        };
+        let permit = match &connections {
+            Some(connections) => match connections.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => continue,
+            },
+            None => None,
+        };
+        let service = service.clone();
+        let stop = stop.clone();
+        let active = Active::new(active.clone());
+        let metrics = hooks.metrics.clone();
+        let tamper = hooks.tamper.clone();
+        tasks.spawn(async move {
+            trace_connection("open", peer);
+            let (metrics, tamper) = (metrics.as_deref(), tamper.as_deref());
+            let service = service.as_ref();
+            let connection =
+                serve_tcp_connection(stream, service, limits, stop, metrics, read_only, tamper);
+            match AssertUnwindSafe(connection).catch_unwind().await {
+                Ok(_) => trace_connection("close", peer),
+                Err(_) => trace_connection("panic", peer),
+            }
+            drop((permit, active));
+        });
+    };
+    drop(listeners);
+
+    stop.cancel();
+    let drained = tokio::time::timeout(drain, async { while tasks.join_next().await.is_some() {} });
+    if drained.await.is_err() {
+        tasks.shutdown().await;
+    }
+    result
+}
+
+/// Accept a connection on whichever of `listeners` gets one first
+async fn accept(listeners: &[TcpListener]) -> Result<(TcpStream, SocketAddr)> {
+    let accepting = listeners.iter().map(|listener| Box::pin(listener.accept()));
+    let (accepted, _, _) = futures::future::select_all(accepting).await;
+    accepted
+}
+
+/// Counts a connection as open while alive
+struct Active(Arc<AtomicUsize>);
+
+impl Active {
+    fn new(active: Arc<AtomicUsize>) -> Active {
+        active.fetch_add(1, Ordering::SeqCst);
+        Active(active)
+    }
+}
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Emit a `debug!` event for a connection, or a `warn!` event for a panicking handler
+#[cfg(feature = "tracing")]
+fn trace_connection(event: &'static str, peer: SocketAddr) {
+    match event {
+        "panic" => tracing::warn!(%peer, "modbus connection handler panicked"),
+        _ => tracing::debug!(event, %peer, "modbus connection"),
+    }
+}
+
+/// No-op without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+fn trace_connection(_event: &'static str, _peer: SocketAddr) {}
+
+/// Answer the requests of a connection until it is closed or `stop` is cancelled, recording
+/// them to `metrics`
+///
+/// A `read_only` connection has its write requests answered with that exception. The responses
+/// are sent with the faults of `tamper`.
+async fn serve_tcp_connection<S: Dispatch + ?Sized>(
+    stream: TcpStream,
+    service: &S,
+    limits: TcpLimits,
+    stop: CancellationToken,
+    metrics: Option<&dyn MetricsSink>,
+    read_only: Option<Exception>,
+    tamper: Option<&dyn Tamper>,
+) -> Result<()> {
+    let mut transport = Framed::new(stream, TcpServerCodec::default());
+    let mut turns = limits.max_request_rate.map(|per_second| {
+        let mut turns = tokio::time::interval(Duration::from_secs(1) / per_second);
+        turns.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        turns
+    });
+    loop {
+        let next = async {
+            match limits.idle_timeout {
+                Some(idle_timeout) => tokio::time::timeout(idle_timeout, transport.next())
+                    .await
+                    .unwrap_or(None),
+                None => transport.next().await,
+            }
+        };
+        let request = tokio::select! {
+            _ = stop.cancelled() => return Ok(()),
+            request = next => request,
+        };
+        let request = match request {
+            Some(Ok(request)) => request,
+            Some(Err(error)) => {
+                transport = resync(transport, error)?;
+                continue;
+            }
+            None => return Ok(()),
+        };
+        if let Some(turns) = &mut turns {
+            turns.tick().await;
+        }
+        let response = service.answer(&request, read_only, metrics).await;
+        if let Some(response) = response {
+            send_tampered(&mut transport, response, tamper).await?;
+        }
+    }
+}
+
+/// Serve Modbus RTU requests arriving on `transport`
+///
+/// Broadcast requests are never answered. A frame failing to decode is dropped and serving goes
+/// on. Returns when the transport is closed, or with the first I/O error.
+pub async fn serve_rtu<T, S>(transport: Framed<T, RtuServerCodec>, service: S) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    S: Service,
+{
+    serve_rtu_until(transport, service, std::future::pending()).await
+}
+
+/// Serve Modbus RTU requests arriving on `transport`, until `shutdown` completes
+///
+/// Like [`serve_rtu`]. A request already received when `shutdown` completes is still
+/// answered, then the function returns.
+pub async fn serve_rtu_until<T, S, F>(
+    mut transport: Framed<T, RtuServerCodec>,
+    service: S,
+    shutdown: F,
+) -> Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    S: Service,
+    F: Future<Output = ()>,
+{
+    let mut shutdown = pin!(shutdown);
+    loop {
+        let request = tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            request = transport.next() => request,
+        };
+        let request = match request {
+            Some(Ok(request)) => request,
+            Some(Err(error)) => {
+                transport = resync(transport, error)?;
+                continue;
+            }
+            None => return Ok(()),
+        };
+        if let Some(response) = dispatch(&service, &request) {
+            transport.send(response).await?;
+        }
+    }
+}
+
+/// `transport` ready for the next frame after failing to decode one with `error`
+///
+/// `error` is returned when it is an I/O error rather than a rejected frame. A frame failing its
+/// CRC check is already consumed, any other rejected frame is dropped with the rest of the
+/// buffered bytes. The transport is rebuilt from its parts, as a `Framed` ends its stream after
+/// a decoding error.
+pub(crate) fn resync<T, C>(transport: Framed<T, C>, error: Error) -> Result<Framed<T, C>> {
+    if !matches!(
+        error.kind(),
+        ErrorKind::InvalidData | ErrorKind::Unsupported
+    ) {
+        return Err(error);
+    }
+    let mut parts = transport.into_parts();
+    if !matches!(ModbusError::from(error), ModbusError::Crc { .. }) {
+        parts.read_buf.clear();
+    }
+    Ok(Framed::from_parts(parts))
+}
+
+#[cfg(test)]
+mod serve_test {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::codec::{Encoder, Framed};
+
+    use crate::codec::{RtuClientCodec, RtuServerCodec, TcpClientCodec};
+    use crate::frame::{Exception, Function};
+    use crate::server::{
+        serve_rtu, serve_tcp, serve_tcp_until, serve_tcp_with, DataStore, Service, TcpLimits,
+    };
+    use crate::Frame;
+
+    struct Counter;
+
+    impl Service for Counter {
+        fn read_input_registers(
+            &self,
+            _unit: u8,
+            address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            Ok((address..address + count).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_tcp_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tcp(listener, Arc::new(Counter)));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        let expected_frame = Frame::tcp();
+        for _ in 0..2 {
+            let request = frame.read_input_registers_request(0x07, 0x0010, 0x0002);
+            transport.send(request).await.unwrap();
+            let response = transport.next().await.unwrap().unwrap();
+            let expected =
+                expected_frame.read_input_register_response(0x07, vec![0x00, 0x10, 0x00, 0x11]);
+            assert_eq!(response, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn serve_rtu_test() {
+        let (client, server) = tokio::io::duplex(64);
+        tokio::spawn(serve_rtu(
+            Framed::new(server, RtuServerCodec::default()),
+            Counter,
+        ));
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let frame = Frame::rtu();
+        transport
+            .send(frame.read_input_registers_request(0x0B, 0x0000, 0x0001))
+            .await
+            .unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        assert_eq!(
+            response,
+            frame.read_input_register_response(0x0B, vec![0x00, 0x00])
+        );
+
+        transport
+            .send(frame.read_coils_request(0x0B, 0x0000, 0x0001))
+            .await
+            .unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected =
+            frame.exception_response(0x0B, Function::ReadCoils, Exception::IllegalFunction);
+        assert_eq!(response, expected);
+    }
+
+    #[tokio::test]
+    async fn broadcast_test() {
+        let store = Arc::new(DataStore::default().with_coils(0x0000, 8));
+        let (mut client, server) = tokio::io::duplex(64);
+        tokio::spawn(serve_rtu(
+            Framed::new(server, RtuServerCodec::default()),
+            store.clone(),
+        ));
+
+        let mut buf = bytes::BytesMut::new();
+        let request = Frame::rtu().write_single_coil_request(0x00, 0x0003, 0xFF00);
+        RtuClientCodec::default().encode(request, &mut buf).unwrap();
+        client.write_all(&buf).await.unwrap();
+
+        let mut reply = [0u8; 1];
+        let read = tokio::time::timeout(Duration::from_millis(100), client.read(&mut reply));
+        assert!(read.await.is_err(), "no bytes are sent for a broadcast");
+        assert_eq!(store.get_coils(0x0003, 1).unwrap(), vec![true]);
+    }
+
+    /// Wait for the server to close `stream`
+    async fn closed(stream: &mut TcpStream) -> bool {
+        let mut buf = [0u8; 16];
+        let read = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut buf));
+        matches!(read.await, Ok(Ok(0)) | Ok(Err(_)))
+    }
+
+    #[tokio::test]
+    async fn idle_timeout_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = TcpLimits::default().with_idle_timeout(Duration::from_millis(100));
+        tokio::spawn(serve_tcp_with(listener, Counter, limits));
+
+        // Silent from the start
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        assert!(closed(&mut stream).await);
+
+        // Silent after a request and half of another one
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_input_registers_request(0x01, 0x0000, 0x0001);
+        transport.send(request).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+        let mut stream = transport.into_inner();
+        stream.write_all(&[0x00, 0x02, 0x00, 0x00]).await.unwrap();
+        assert!(closed(&mut stream).await);
+    }
+
+    #[tokio::test]
+    async fn max_connections_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = TcpLimits::default().with_max_connections(1);
+        tokio::spawn(serve_tcp_with(listener, Counter, limits));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_input_registers_request(0x01, 0x0000, 0x0001);
+        transport.send(request.clone()).await.unwrap();
+        assert!(transport.next().await.unwrap().is_ok());
+
+        let mut excess = TcpStream::connect(addr).await.unwrap();
+        assert!(closed(&mut excess).await);
+
+        // The slot is free again once the first connection is closed
+        drop(transport);
+        let mut served = false;
+        for _ in 0..20 {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let mut transport = Framed::new(stream, TcpClientCodec::default());
+            transport.send(request.clone()).await.unwrap();
+            if let Some(Ok(_)) = transport.next().await {
+                served = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(served);
+    }
+
+    #[tokio::test]
+    async fn max_request_rate_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let limits = TcpLimits::default().with_max_request_rate(20);
+        tokio::spawn(serve_tcp_with(listener, Counter, limits));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        let start = tokio::time::Instant::now();
+        for _ in 0..10 {
+            let request = frame.read_input_registers_request(0x01, 0x0000, 0x0001);
+            transport.feed(request).await.unwrap();
+        }
+        transport.flush().await.unwrap();
+        for _ in 0..10 {
+            assert!(transport.next().await.unwrap().is_ok());
+        }
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    /// Answers holding register reads slowly, telling when a read starts
+    struct Slow(tokio::sync::mpsc::UnboundedSender<()>);
+
+    impl Service for Slow {
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            let _ = self.0.send(());
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(vec![0x1234; count as usize])
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn shutdown_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (started, mut reads) = tokio::sync::mpsc::unbounded_channel();
+        let shutdown = tokio_util::sync::CancellationToken::new();
+        let server = tokio::spawn(serve_tcp_until(
+            listener,
+            Slow(started),
+            TcpLimits::default(),
+            shutdown.clone().cancelled_owned(),
+            Duration::from_secs(2),
+        ));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut idle = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        transport.send(request).await.unwrap();
+        reads.recv().await.unwrap();
+        shutdown.cancel();
+
+        // The request being handled is still answered, then every connection is closed
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x12, 0x34]);
+        assert_eq!(response, expected);
+        assert!(transport.next().await.is_none());
+        assert!(closed(&mut idle).await);
+
+        let served = tokio::time::timeout(Duration::from_secs(1), server).await;
+        assert!(served.unwrap().unwrap().is_ok());
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::codec::FrameObserver;
+use crate::frame::{Exception, Function};
+
+/// Communication counters of a serial line server
+///
+/// The counters of the Diagnostics function (0x08). A [`RtuServer`](super::RtuServer) keeps
+/// them while serving, they are read from [`RtuServer::stats`](super::RtuServer::stats) for
+/// exporting to metrics.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::server::{DataStore, RtuServer};
+///
+/// let (_client, port) = tokio::io::duplex(64);
+/// let server = RtuServer::new(port, 0x0B, DataStore::default());
+/// let stats = server.stats();
+/// assert_eq!(stats.bus_messages(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct ServerStats {
+    bus_messages: AtomicU64,
+    crc_errors: AtomicU64,
+    exceptions: AtomicU64,
+    server_messages: AtomicU64,
+    no_responses: AtomicU64,
+}
+
+impl ServerStats {
+    /// Messages detected on the line, for any unit, since the counters were cleared
+    pub fn bus_messages(&self) -> u64 {
+        self.bus_messages.load(Ordering::Relaxed)
+    }
+
+    /// Messages failing their CRC check
+    pub fn crc_errors(&self) -> u64 {
+        self.crc_errors.load(Ordering::Relaxed)
+    }
+
+    /// Exception responses sent
+    pub fn exceptions(&self) -> u64 {
+        self.exceptions.load(Ordering::Relaxed)
+    }
+
+    /// Messages addressed to the server, broadcasts included
+    pub fn server_messages(&self) -> u64 {
+        self.server_messages.load(Ordering::Relaxed)
+    }
+
+    /// Messages addressed to the server it sent no response for, the broadcasts
+    pub fn no_responses(&self) -> u64 {
+        self.no_responses.load(Ordering::Relaxed)
+    }
+
+    /// Reset every counter to 0
+    pub fn clear(&self) {
+        for counter in [
+            &self.bus_messages,
+            &self.crc_errors,
+            &self.exceptions,
+            &self.server_messages,
+            &self.no_responses,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a message addressed to the server, `answered` or not
+    pub(crate) fn server_message(&self, answered: bool) {
+        self.server_messages.fetch_add(1, Ordering::Relaxed);
+        if !answered {
+            self.no_responses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl FrameObserver for ServerStats {
+    fn on_decode_ok(&self, _function: &Function) {
+        self.bus_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_crc_error(&self) {
+        self.bus_messages.fetch_add(1, Ordering::Relaxed);
+        self.crc_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_exception(&self, _exception: &Exception) {
+        self.exceptions.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod stats_test {
+    use bytes::BytesMut;
+    use futures::{SinkExt, StreamExt};
+    use tokio::io::AsyncWriteExt;
+    use tokio_util::codec::{Encoder, Framed};
+
+    use crate::codec::RtuClientCodec;
+    use crate::server::{DataStore, RtuServer};
+    use crate::Frame;
+
+    #[tokio::test]
+    async fn stats_test() {
+        let (client, port) = tokio::io::duplex(64);
+        let store = DataStore::default().with_coils(0x0000, 8);
+        let server = RtuServer::new(port, 0x0B, store);
+        let stats = server.stats();
+        let serving = tokio::spawn(server.serve());
+
+        let mut transport = Framed::new(client, RtuClientCodec::default());
+        let frame = Frame::rtu();
+        for request in [
+            frame.write_single_coil_request(0x0C, 0x0000, 0xFF00),
+            frame.write_single_coil_request(0x00, 0x0000, 0xFF00),
+            frame.read_coils_request(0x0B, 0x0000, 0x0008),
+            frame.read_coils_request(0x0B, 0x0010, 0x0008),
+        ] {
+            transport.feed(request).await.unwrap();
+        }
+        transport.flush().await.unwrap();
+        for _ in 0..2 {
+            assert!(transport.next().await.unwrap().is_ok());
+        }
+        assert_eq!(stats.bus_messages(), 4);
+        assert_eq!(stats.server_messages(), 3);
+        assert_eq!(stats.no_responses(), 1);
+        assert_eq!(stats.exceptions(), 1);
+        assert_eq!(stats.crc_errors(), 0);
+
+        stats.clear();
+        assert_eq!(stats.bus_messages(), 0);
+        assert_eq!(stats.exceptions(), 0);
+
+        // Every CRC error is counted, the server goes on answering
+        let request = frame.read_coils_request(0x0B, 0x0000, 0x0008);
+        let mut corrupt = BytesMut::new();
+        RtuClientCodec::default()
+            .encode(request.clone(), &mut corrupt)
+            .unwrap();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xFF;
+        for crc_errors in 1..=2 {
+            transport.get_mut().write_all(&corrupt).await.unwrap();
+            transport.send(request.clone()).await.unwrap();
+            let response = transport.next().await.unwrap().unwrap();
+            assert_eq!(response, frame.read_coils_response(0x0B, vec![0x01]));
+            assert_eq!(stats.crc_errors(), crc_errors);
+        }
+        assert_eq!(stats.bus_messages(), 4);
+        assert_eq!(stats.server_messages(), 2);
+        assert!(!serving.is_finished());
+    }
+}
@@ -0,0 +1,1315 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use bytes::{Buf, BufMut, BytesMut};
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::MissedTickBehavior;
+
+use crate::data::{FromRegisters, ToRegisters, WordOrder};
+use crate::frame::{AddressRange, Exception};
+use crate::util::bits;
+
+use super::{Limits, Service};
+
+/// In-memory coils, discrete inputs, holding registers and input registers
+///
+/// Each space covers `count` addresses starting at its base address, and starts out empty.
+/// Requests outside a space are answered with [`Exception::IllegalDataAddress`]. Every space has
+/// its own lock, so the host application can update values while a server is running.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::server::DataStore;
+/// let store = DataStore::default()
+///     .with_coils(0x0000, 16)
+///     .with_input_registers(0x0100, 4);
+/// store.set_input_registers(0x0101, &[215, 216]).unwrap();
+/// assert_eq!(store.get_input_registers(0x0100, 3).unwrap(), vec![0, 215, 216]);
+/// assert!(store.get_input_registers(0x0103, 2).is_err());
+/// ```
+///
+/// # Write hooks
+///
+/// [`DataStore::on_coil_write`] and [`DataStore::on_register_write`] register handlers called
+/// when a master writes coils or holding registers through the [`Service`] implementation.
+/// Writes of the host application through the `set_*` functions call no handler.
+///
+/// * A master write updates the store first, then calls the handlers covering the written
+///   addresses in the order they were registered. Master writes are serialized, one write and
+///   its handlers complete before the next write starts.
+/// * A handler returning an [`Exception`] vetoes the write: the previous values are restored,
+///   the remaining handlers are not called and the exception is sent to the master. Only the
+///   addresses still holding the values of the master are restored, a value set in the meantime,
+///   by a handler or the host application, is kept. A value set equal to the one of the master
+///   cannot be told apart from it and is restored.
+/// * No lock on the store is held while a handler runs, so a handler may read and set values
+///   and register handlers. It must not write through the [`Service`] implementation of the
+///   same store, which waits for the running write to complete.
+///
+/// Handlers are synchronous. A handler feeding an asynchronous task sends the values over a
+/// channel.
+///
+/// # Write protection
+///
+/// [`DataStore::protect`] and [`DataStore::protect_coils`] make addresses read-only for a
+/// master. A master write covering a protected address is answered with
+/// [`Exception::IllegalDataAddress`] and writes none of its values, while the host application
+/// can still update them through the `set_*` functions.
+#[derive(Debug, Default)]
+pub struct DataStore {
+    coils: RwLock<Space<bool>>,
+    discrete_inputs: RwLock<Space<bool>>,
+    holding_registers: RwLock<Space<u16>>,
+    input_registers: RwLock<Space<u16>>,
+    coil_hooks: RwLock<Vec<Hook<bool>>>,
+    register_hooks: RwLock<Vec<Hook<u16>>>,
+    protected_coils: RwLock<Vec<AddressRange>>,
+    protected_registers: RwLock<Vec<AddressRange>>,
+    /// Serializes master writes with their handlers
+    writes: Mutex<()>,
+    register_changes: Changes,
+}
+
+/// Most register changes kept for a watcher which is behind
+const CHANGES_CAPACITY: usize = 1024;
+
+/// Leading bytes of a saved [`DataStore`]
+const MAGIC: &[u8; 4] = b"EMDS";
+
+/// Layout version written by [`DataStore::save`]
+///
+/// Version 1: the magic and version bytes, then the coils, discrete inputs, holding registers
+/// and input registers. Each space is its base address (`u16`) and count (`u32`), followed by
+/// the bits packed low bit first or the registers, all big endian.
+const VERSION: u8 = 1;
+
+/// A holding register modified by a write
+///
+/// See [`DataStore::watch_holding_registers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RegisterChange {
+    pub(crate) address: u16,
+    pub(crate) old_value: u16,
+    pub(crate) new_value: u16,
+}
+
+impl RegisterChange {
+    pub fn get_address(&self) -> u16 {
+        self.address
+    }
+
+    pub fn get_old_value(&self) -> u16 {
+        self.old_value
+    }
+
+    pub fn get_new_value(&self) -> u16 {
+        self.new_value
+    }
+}
+
+/// Sender of the holding register changes
+#[derive(Debug)]
+struct Changes(broadcast::Sender<RegisterChange>);
+
+impl Default for Changes {
+    fn default() -> Self {
+        Changes(broadcast::channel(CHANGES_CAPACITY).0)
+    }
+}
+
+/// Handler called with the unit id, first address and new values of a write
+type Handler<T> = dyn Fn(u8, u16, &[T]) -> Result<(), Exception> + Send + Sync;
+
+/// A write handler and the addresses it covers
+struct Hook<T> {
+    range: AddressRange,
+    handler: Arc<Handler<T>>,
+}
+
+impl<T> Clone for Hook<T> {
+    fn clone(&self) -> Self {
+        Hook {
+            range: self.range,
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Hook<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hook").field("range", &self.range).finish()
+    }
+}
+
+impl<T> Hook<T> {
+    /// The part of a write of `values` at `address` the hook covers
+    fn overlap<'a>(&self, address: u16, values: &'a [T]) -> Option<(u16, &'a [T])> {
+        let range_start = self.range.start as usize;
+        let start = (address as usize).max(range_start);
+        let end = (address as usize + values.len()).min(range_start + self.range.count as usize);
+        if start >= end {
+            return None;
+        }
+        let offset = address as usize;
+        Some((start as u16, &values[start - offset..end - offset]))
+    }
+}
+
+/// A block of addresses starting at `base`
+#[derive(Debug, Default)]
+struct Space<T> {
+    base: u16,
+    values: Vec<T>,
+}
+
+impl<T: Copy + Default> Space<T> {
+    fn new(base: u16, count: usize) -> Space<T> {
+        Space {
+            base,
+            values: vec![T::default(); count],
+        }
+    }
+
+    fn range(&self, address: u16, count: usize) -> Result<Range<usize>, Exception> {
+        let start = (address as usize)
+            .checked_sub(self.base as usize)
+            .ok_or(Exception::IllegalDataAddress)?;
+        if start + count > self.values.len() {
+            return Err(Exception::IllegalDataAddress);
+        }
+        Ok(start..start + count)
+    }
+
+    fn read(&self, address: u16, count: u16) -> Result<Vec<T>, Exception> {
+        let range = self.range(address, count as usize)?;
+        Ok(self.values[range].to_vec())
+    }
+
+    fn write(&mut self, address: u16, values: &[T]) -> Result<(), Exception> {
+        let range = self.range(address, values.len())?;
+        self.values[range].copy_from_slice(values);
+        Ok(())
+    }
+
+    fn put_head(&self, dst: &mut BytesMut) {
+        dst.put_u16(self.base);
+        dst.put_u32(self.values.len() as u32);
+    }
+
+    /// Read the base address and count of a space, checking `size` bytes per value follow
+    fn get_head(src: &mut &[u8], size: impl Fn(usize) -> usize) -> std::io::Result<(u16, usize)> {
+        if src.remaining() < 6 {
+            return Err(truncated());
+        }
+        let base = src.get_u16();
+        let count = src.get_u32() as usize;
+        if base as usize + count > 0x10000 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "saved space exceeds the address range",
+            ));
+        }
+        if src.remaining() < size(count) {
+            return Err(truncated());
+        }
+        Ok((base, count))
+    }
+}
+
+impl Space<bool> {
+    fn save(&self, dst: &mut BytesMut) {
+        self.put_head(dst);
+        dst.put_slice(&bits::pack(&self.values));
+    }
+
+    fn load(src: &mut &[u8]) -> std::io::Result<Space<bool>> {
+        let (base, count) = Space::<bool>::get_head(src, |count| count.div_ceil(8))?;
+        let bytes = src.copy_to_bytes(count.div_ceil(8));
+        let values = bits::unpack(&bytes, count);
+        Ok(Space { base, values })
+    }
+}
+
+impl Space<u16> {
+    fn save(&self, dst: &mut BytesMut) {
+        self.put_head(dst);
+        self.values.iter().for_each(|&value| dst.put_u16(value));
+    }
+
+    fn load(src: &mut &[u8]) -> std::io::Result<Space<u16>> {
+        let (base, count) = Space::<u16>::get_head(src, |count| count * 2)?;
+        let values = (0..count).map(|_| src.get_u16()).collect();
+        Ok(Space { base, values })
+    }
+}
+
+/// The values of `current` still equal to the vetoed `written`, back to their `previous` values
+fn restore<T: Copy + PartialEq>(current: &[T], written: &[T], previous: &[T]) -> Vec<T> {
+    let values = current.iter().zip(written).zip(previous);
+    values
+        .map(|((&now, &written), &before)| if now == written { before } else { now })
+        .collect()
+}
+
+/// Reject a master write of `count` values at `address` covering a protected address
+fn check_protection(
+    protected: &RwLock<Vec<AddressRange>>,
+    address: u16,
+    count: usize,
+) -> Result<(), Exception> {
+    let end = address as usize + count;
+    let covered = protected.read().unwrap().iter().any(|range| {
+        let start = range.start as usize;
+        (address as usize) < start + range.count as usize && start < end
+    });
+    match covered {
+        true => Err(Exception::IllegalDataAddress),
+        false => Ok(()),
+    }
+}
+
+fn truncated() -> Error {
+    Error::new(ErrorKind::UnexpectedEof, "saved DataStore is truncated")
+}
+
+impl DataStore {
+    /// Set the coils to `count` addresses starting at `base`, all off
+    pub fn with_coils(self, base: u16, count: usize) -> DataStore {
+        *self.coils.write().unwrap() = Space::new(base, count);
+        self
+    }
+
+    /// Set the discrete inputs to `count` addresses starting at `base`, all off
+    pub fn with_discrete_inputs(self, base: u16, count: usize) -> DataStore {
+        *self.discrete_inputs.write().unwrap() = Space::new(base, count);
+        self
+    }
+
+    /// Set the holding registers to `count` addresses starting at `base`, all zero
+    pub fn with_holding_registers(self, base: u16, count: usize) -> DataStore {
+        *self.holding_registers.write().unwrap() = Space::new(base, count);
+        self
+    }
+
+    /// Set the input registers to `count` addresses starting at `base`, all zero
+    pub fn with_input_registers(self, base: u16, count: usize) -> DataStore {
+        *self.input_registers.write().unwrap() = Space::new(base, count);
+        self
+    }
+
+    pub fn get_coils(&self, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        self.coils.read().unwrap().read(address, count)
+    }
+
+    pub fn set_coils(&self, address: u16, values: &[bool]) -> Result<(), Exception> {
+        self.coils.write().unwrap().write(address, values)
+    }
+
+    pub fn get_discrete_inputs(&self, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        self.discrete_inputs.read().unwrap().read(address, count)
+    }
+
+    pub fn set_discrete_inputs(&self, address: u16, values: &[bool]) -> Result<(), Exception> {
+        self.discrete_inputs.write().unwrap().write(address, values)
+    }
+
+    pub fn get_holding_registers(&self, address: u16, count: u16) -> Result<Vec<u16>, Exception> {
+        self.holding_registers.read().unwrap().read(address, count)
+    }
+
+    pub fn set_holding_registers(&self, address: u16, values: &[u16]) -> Result<(), Exception> {
+        let previous = {
+            let mut registers = self.holding_registers.write().unwrap();
+            let previous = registers.read(address, values.len() as u16)?;
+            registers.write(address, values)?;
+            previous
+        };
+        self.notify_registers(address, &previous, values);
+        Ok(())
+    }
+
+    pub fn get_input_registers(&self, address: u16, count: u16) -> Result<Vec<u16>, Exception> {
+        self.input_registers.read().unwrap().read(address, count)
+    }
+
+    pub fn set_input_registers(&self, address: u16, values: &[u16]) -> Result<(), Exception> {
+        self.input_registers.write().unwrap().write(address, values)
+    }
+
+    /// Call `handler` after a master writes coils in `range`
+    ///
+    /// The handler gets the unit id of the request and the first address and new values of
+    /// the written coils within `range`. See [Write hooks](DataStore#write-hooks).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Exception, Frame};
+    /// use easy_modbus::server::{dispatch, DataStore};
+    ///
+    /// let store = DataStore::default().with_coils(0x0000, 8);
+    /// store.on_coil_write(AddressRange::new(0x0000, 1).unwrap(), |_unit, _address, values| {
+    ///     println!("pump {}", if values[0] { "on" } else { "off" });
+    ///     Ok(())
+    /// });
+    /// let request = Frame::tcp().write_single_coil_request(0x01, 0x0000, 0xFF00);
+    /// dispatch(&store, &request);
+    /// ```
+    pub fn on_coil_write<F>(&self, range: AddressRange, handler: F)
+    where
+        F: Fn(u8, u16, &[bool]) -> Result<(), Exception> + Send + Sync + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.coil_hooks
+            .write()
+            .unwrap()
+            .push(Hook { range, handler });
+    }
+
+    /// Call `handler` after a master writes holding registers in `range`
+    ///
+    /// The handler gets the unit id of the request and the first address and new values of
+    /// the written registers within `range`. See [Write hooks](DataStore#write-hooks).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Exception, Frame};
+    /// use easy_modbus::server::{dispatch, DataStore};
+    ///
+    /// let store = DataStore::default().with_holding_registers(0x0000, 4);
+    /// store.on_register_write(AddressRange::new(0x0000, 1).unwrap(), |_unit, _address, values| {
+    ///     match values[0] {
+    ///         0..=3000 => Ok(()),
+    ///         _ => Err(Exception::IllegalDataValue),
+    ///     }
+    /// });
+    /// let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0000, 4000);
+    /// dispatch(&store, &request);
+    /// assert_eq!(store.get_holding_registers(0x0000, 1).unwrap(), vec![0]);
+    /// ```
+    pub fn on_register_write<F>(&self, range: AddressRange, handler: F)
+    where
+        F: Fn(u8, u16, &[u16]) -> Result<(), Exception> + Send + Sync + 'static,
+    {
+        let handler = Arc::new(handler);
+        self.register_hooks
+            .write()
+            .unwrap()
+            .push(Hook { range, handler });
+    }
+
+    /// Stream of the changes to the holding registers in `range`
+    ///
+    /// Every write modifying a register in `range` yields a change, whether a master or the
+    /// host application wrote it. Writing a register its current value yields nothing, a
+    /// vetoed write yields the change and the change back. The store keeps up to 1024 changes
+    /// for a stream which is not polled; past that the oldest changes are dropped, the latest
+    /// value always wins. A stream never slows down writes. It ends when the store is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures::StreamExt;
+    /// use easy_modbus::AddressRange;
+    /// use easy_modbus::server::DataStore;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let store = DataStore::default().with_holding_registers(0x0000, 4);
+    /// let mut changes = store.watch_holding_registers(AddressRange::new(0x0000, 2).unwrap());
+    /// store.set_holding_registers(0x0001, &[7, 8]).unwrap();
+    /// let change = changes.next().await.unwrap();
+    /// assert_eq!(change.get_address(), 0x0001);
+    /// assert_eq!((change.get_old_value(), change.get_new_value()), (0, 7));
+    /// # }
+    /// ```
+    pub fn watch_holding_registers(
+        &self,
+        range: AddressRange,
+    ) -> impl Stream<Item = RegisterChange> + Send + Unpin {
+        let receiver = self.register_changes.0.subscribe();
+        let start = range.start as u32;
+        let end = start + range.count as u32;
+        Box::pin(stream::unfold(receiver, move |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) if (start..end).contains(&(change.address as u32)) => {
+                        return Some((change, receiver));
+                    }
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }))
+    }
+
+    fn notify_registers(&self, address: u16, old_values: &[u16], new_values: &[u16]) {
+        let changes = old_values.iter().zip(new_values).enumerate();
+        for (i, (&old_value, &new_value)) in changes.filter(|(_, (old, new))| old != new) {
+            let change = RegisterChange {
+                address: address + i as u16,
+                old_value,
+                new_value,
+            };
+            // No watcher is not an error
+            let _ = self.register_changes.0.send(change);
+        }
+    }
+
+    /// Make the holding registers in `range` read-only for a master
+    ///
+    /// See [Write protection](DataStore#write-protection).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::{AddressRange, Exception, Frame, Function};
+    /// use easy_modbus::server::{dispatch, DataStore};
+    ///
+    /// let store = DataStore::default().with_holding_registers(0x0000, 8);
+    /// store.protect(AddressRange::new(0x0004, 4).unwrap());
+    /// store.set_holding_registers(0x0004, &[1000]).unwrap();
+    ///
+    /// let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0004, 0);
+    /// let response = Frame::tcp().exception_response(
+    ///     0x01,
+    ///     Function::WriteSingleHoldingRegister,
+    ///     Exception::IllegalDataAddress,
+    /// );
+    /// assert_eq!(dispatch(&store, &request), Some(response));
+    /// assert_eq!(store.get_holding_registers(0x0004, 1).unwrap(), vec![1000]);
+    /// ```
+    pub fn protect(&self, range: AddressRange) {
+        self.protected_registers.write().unwrap().push(range);
+    }
+
+    /// Make the coils in `range` read-only for a master
+    ///
+    /// See [Write protection](DataStore#write-protection).
+    pub fn protect_coils(&self, range: AddressRange) {
+        self.protected_coils.write().unwrap().push(range);
+    }
+
+    fn write_coils(&self, unit: u8, address: u16, values: &[bool]) -> Result<(), Exception> {
+        check_protection(&self.protected_coils, address, values.len())?;
+        let notify = |_: u16, _: &[bool], _: &[bool]| {};
+        self.write_hooked(&self.coils, &self.coil_hooks, notify, unit, address, values)
+    }
+
+    fn write_registers(&self, unit: u8, address: u16, values: &[u16]) -> Result<(), Exception> {
+        check_protection(&self.protected_registers, address, values.len())?;
+        let notify = |address: u16, old_values: &[u16], new_values: &[u16]| {
+            self.notify_registers(address, old_values, new_values)
+        };
+        self.write_hooked(
+            &self.holding_registers,
+            &self.register_hooks,
+            notify,
+            unit,
+            address,
+            values,
+        )
+    }
+
+    /// Write values of a master, then call the hooks covering them
+    ///
+    /// `notify` gets the address, old values and new values of every change to the space.
+    fn write_hooked<T: Copy + Default + PartialEq>(
+        &self,
+        space: &RwLock<Space<T>>,
+        hooks: &RwLock<Vec<Hook<T>>>,
+        notify: impl Fn(u16, &[T], &[T]),
+        unit: u8,
+        address: u16,
+        values: &[T],
+    ) -> Result<(), Exception> {
+        let _writes = self.writes.lock().unwrap();
+        let previous = {
+            let mut space = space.write().unwrap();
+            let previous = space.read(address, values.len() as u16)?;
+            space.write(address, values)?;
+            previous
+        };
+        notify(address, &previous, values);
+        let hooks = hooks.read().unwrap().clone();
+        for hook in hooks {
+            if let Some((first, written)) = hook.overlap(address, values) {
+                if let Err(exception) = (hook.handler)(unit, first, written) {
+                    let (current, restored) = {
+                        let mut space = space.write().unwrap();
+                        let current = space.read(address, values.len() as u16)?;
+                        let restored = restore(&current, values, &previous);
+                        space.write(address, &restored)?;
+                        (current, restored)
+                    };
+                    notify(address, &current, &restored);
+                    return Err(exception);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Typed getter and setter of the holding registers for a register type
+macro_rules! impl_typed {
+    ($($ty:ident: $get:ident, $set:ident;)*) => {
+        impl DataStore {$(
+            #[doc = concat!("The `", stringify!($ty), "` at `address` of the holding registers")]
+            ///
+            /// See [`DataStore::get_holding_value`].
+            pub fn $get(&self, address: u16, order: WordOrder) -> Result<$ty, Exception> {
+                self.get_holding_value(address, order)
+            }
+
+            #[doc = concat!("Set a `", stringify!($ty), "` at `address` of the holding registers")]
+            ///
+            /// See [`DataStore::set_holding_value`].
+            pub fn $set(
+                &self,
+                address: u16,
+                value: $ty,
+                order: WordOrder,
+            ) -> Result<(), Exception> {
+                self.set_holding_value(address, &value, order)
+            }
+        )*}
+    };
+}
+
+impl_typed! {
+    u32: get_u32, set_u32;
+    i32: get_i32, set_i32;
+    u64: get_u64, set_u64;
+    f32: get_f32, set_f32;
+    f64: get_f64, set_f64;
+}
+
+/// Typed views of the registers
+///
+/// A value spanning several registers is read from and written to the registers, in the word
+/// order of the register map, so a master reads the same value. A master writing only some of
+/// its registers leaves a mixed value, which the getters return as is.
+///
+/// # Examples
+///
+/// ```
+/// use easy_modbus::data::WordOrder;
+/// use easy_modbus::server::DataStore;
+///
+/// let store = DataStore::default().with_holding_registers(0x0000, 4);
+/// store.set_f32(0x0000, 1234.5, WordOrder::BigEndian).unwrap();
+/// assert_eq!(store.get_holding_registers(0x0000, 2).unwrap(), vec![0x449A, 0x5000]);
+/// assert_eq!(store.get_f32(0x0000, WordOrder::BigEndian).unwrap(), 1234.5);
+/// assert!(store.get_f64(0x0002, WordOrder::BigEndian).is_err());
+/// ```
+impl DataStore {
+    /// The value in the holding registers starting at `address`
+    pub fn get_holding_value<T: FromRegisters>(
+        &self,
+        address: u16,
+        order: WordOrder,
+    ) -> Result<T, Exception> {
+        let registers = self.get_holding_registers(address, register_count::<T>()?)?;
+        T::from_registers(&registers, order).map_err(|_| Exception::SlaveDeviceFailure)
+    }
+
+    /// Store `value` in the holding registers starting at `address`
+    pub fn set_holding_value<T: ToRegisters + ?Sized>(
+        &self,
+        address: u16,
+        value: &T,
+        order: WordOrder,
+    ) -> Result<(), Exception> {
+        self.set_holding_registers(address, &value.to_registers(order))
+    }
+
+    /// The value in the input registers starting at `address`
+    pub fn get_input_value<T: FromRegisters>(
+        &self,
+        address: u16,
+        order: WordOrder,
+    ) -> Result<T, Exception> {
+        let registers = self.get_input_registers(address, register_count::<T>()?)?;
+        T::from_registers(&registers, order).map_err(|_| Exception::SlaveDeviceFailure)
+    }
+
+    /// Store `value` in the input registers starting at `address`
+    pub fn set_input_value<T: ToRegisters + ?Sized>(
+        &self,
+        address: u16,
+        value: &T,
+        order: WordOrder,
+    ) -> Result<(), Exception> {
+        self.set_input_registers(address, &value.to_registers(order))
+    }
+}
+
+/// Registers a value of type `T` occupies, as a read count
+fn register_count<T: FromRegisters>() -> Result<u16, Exception> {
+    u16::try_from(T::REGISTER_COUNT).map_err(|_| Exception::IllegalDataAddress)
+}
+
+impl DataStore {
+    /// Write the bases and values of all four spaces to `writer`
+    ///
+    /// Hooks and watchers are not saved. The layout starts with a version, so files saved by
+    /// an older release can still be loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use easy_modbus::server::DataStore;
+    ///
+    /// let store = DataStore::default().with_holding_registers(0x0100, 4);
+    /// store.set_holding_registers(0x0102, &[0x1234]).unwrap();
+    /// let mut saved = Vec::new();
+    /// store.save(&mut saved).unwrap();
+    ///
+    /// let store = DataStore::load(&saved[..]).unwrap();
+    /// assert_eq!(store.get_holding_registers(0x0102, 1).unwrap(), vec![0x1234]);
+    /// ```
+    pub fn save(&self, mut writer: impl Write) -> std::io::Result<()> {
+        let mut dst = BytesMut::new();
+        dst.put_slice(MAGIC);
+        dst.put_u8(VERSION);
+        self.coils.read().unwrap().save(&mut dst);
+        self.discrete_inputs.read().unwrap().save(&mut dst);
+        self.holding_registers.read().unwrap().save(&mut dst);
+        self.input_registers.read().unwrap().save(&mut dst);
+        writer.write_all(&dst)?;
+        writer.flush()
+    }
+
+    /// Create a store from the spaces written by [`DataStore::save`]
+    pub fn load(mut reader: impl Read) -> std::io::Result<DataStore> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        let mut src = &buf[..];
+        if !src.starts_with(MAGIC) {
+            return Err(Error::new(ErrorKind::InvalidData, "not a saved DataStore"));
+        }
+        src.advance(MAGIC.len());
+        match src.first() {
+            Some(&VERSION) => src.advance(1),
+            Some(version) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unsupported DataStore version {}", version),
+                ));
+            }
+            None => return Err(truncated()),
+        }
+
+        let store = DataStore::default();
+        *store.coils.write().unwrap() = Space::<bool>::load(&mut src)?;
+        *store.discrete_inputs.write().unwrap() = Space::<bool>::load(&mut src)?;
+        *store.holding_registers.write().unwrap() = Space::<u16>::load(&mut src)?;
+        *store.input_registers.write().unwrap() = Space::<u16>::load(&mut src)?;
+        if src.has_remaining() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "trailing bytes after saved DataStore",
+            ));
+        }
+        Ok(store)
+    }
+
+    /// Save the store to `path` every `period`, starting now
+    ///
+    /// The store is written to a temporary file next to `path` first, then renamed over it, so
+    /// a crash never leaves a partial file behind. Returns only when saving fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// use easy_modbus::server::DataStore;
+    ///
+    /// # async fn run() -> std::io::Result<()> {
+    /// let store = match std::fs::File::open("simulator.bin") {
+    ///     Ok(file) => DataStore::load(file)?,
+    ///     Err(_) => DataStore::default().with_holding_registers(0x0000, 100),
+    /// };
+    /// let store = Arc::new(store);
+    /// let saver = store.clone();
+    /// tokio::spawn(async move { saver.autosave("simulator.bin", Duration::from_secs(10)).await });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn autosave(&self, path: impl AsRef<Path>, period: Duration) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let mut buf = Vec::new();
+            self.save(&mut buf)?;
+            tokio::fs::write(&temp, buf).await?;
+            tokio::fs::rename(&temp, path).await?;
+        }
+    }
+}
+
+impl Service for DataStore {
+    fn limits(&self, _unit: u8) -> Limits {
+        fn bounds<T>(space: &RwLock<Space<T>>) -> (u16, usize) {
+            let space = space.read().unwrap();
+            (space.base, space.values.len())
+        }
+        let (coils, discrete_inputs, holding_registers, input_registers) = (
+            bounds(&self.coils),
+            bounds(&self.discrete_inputs),
+            bounds(&self.holding_registers),
+            bounds(&self.input_registers),
+        );
+        Limits::default()
+            .with_coils(coils.0, coils.1)
+            .with_discrete_inputs(discrete_inputs.0, discrete_inputs.1)
+            .with_holding_registers(holding_registers.0, holding_registers.1)
+            .with_input_registers(input_registers.0, input_registers.1)
+    }
+
+    fn read_coils(&self, _unit: u8, address: u16, count: u16) -> Result<Vec<bool>, Exception> {
+        self.get_coils(address, count)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        _unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<bool>, Exception> {
+        self.get_discrete_inputs(address, count)
+    }
+
+    fn read_holding_registers(
+        &self,
+        _unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.get_holding_registers(address, count)
+    }
+
+    fn read_input_registers(
+        &self,
+        _unit: u8,
+        address: u16,
+        count: u16,
+    ) -> Result<Vec<u16>, Exception> {
+        self.get_input_registers(address, count)
+    }
+
+    fn write_single_coil(&self, unit: u8, address: u16, value: bool) -> Result<(), Exception> {
+        self.write_coils(unit, address, &[value])
+    }
+
+    fn write_single_register(&self, unit: u8, address: u16, value: u16) -> Result<(), Exception> {
+        self.write_registers(unit, address, &[value])
+    }
+
+    fn write_multiple_coils(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[bool],
+    ) -> Result<(), Exception> {
+        self.write_coils(unit, address, values)
+    }
+
+    fn write_multiple_registers(
+        &self,
+        unit: u8,
+        address: u16,
+        values: &[u16],
+    ) -> Result<(), Exception> {
+        self.write_registers(unit, address, values)
+    }
+}
+
+#[cfg(test)]
+mod store_test {
+    use std::sync::{Arc, Mutex};
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_util::codec::Framed;
+
+    use crate::codec::TcpClientCodec;
+    use crate::data::WordOrder;
+    use crate::frame::{AddressRange, Exception, Function};
+    use crate::server::{dispatch, serve_tcp, DataStore, Service};
+    use crate::{Frame, Response};
+
+    #[test]
+    fn range_test() {
+        let store = DataStore::default().with_holding_registers(0x0010, 4);
+        assert_eq!(store.get_holding_registers(0x0010, 4).unwrap(), vec![0; 4]);
+        assert_eq!(
+            store.get_holding_registers(0x000F, 1),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            store.get_holding_registers(0x0011, 4),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            store.set_holding_registers(0x0013, &[1, 2]),
+            Err(Exception::IllegalDataAddress)
+        );
+        assert_eq!(
+            store.get_coils(0x0000, 1),
+            Err(Exception::IllegalDataAddress)
+        );
+    }
+
+    #[test]
+    fn typed_test() {
+        let orders = [
+            WordOrder::ABCD,
+            WordOrder::CDAB,
+            WordOrder::BADC,
+            WordOrder::DCBA,
+        ];
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        // Edge values, then a pseudo-random sweep of the bit patterns
+        let mut values = vec![0.0, -0.0, 1.5, f32::MIN, f32::MAX, f32::NAN, f32::INFINITY];
+        let mut seed = 0x1234_5678u32;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            values.push(f32::from_bits(seed));
+        }
+        for order in orders {
+            for &value in &values {
+                store.set_f32(0x0001, value, order).unwrap();
+                let stored = store.get_f32(0x0001, order).unwrap();
+                assert_eq!(stored.to_bits(), value.to_bits());
+            }
+            store.set_u32(0x0000, 0xDEAD_BEEF, order).unwrap();
+            assert_eq!(store.get_u32(0x0000, order).unwrap(), 0xDEAD_BEEF);
+            store.set_i32(0x0002, -2, order).unwrap();
+            assert_eq!(store.get_i32(0x0002, order).unwrap(), -2);
+            store.set_u64(0x0000, 0x0102_0304_0506_0708, order).unwrap();
+            assert_eq!(store.get_u64(0x0000, order).unwrap(), 0x0102_0304_0506_0708);
+            store.set_f64(0x0000, -0.25, order).unwrap();
+            assert_eq!(store.get_f64(0x0000, order).unwrap(), -0.25);
+        }
+        assert_eq!(
+            store.set_f64(0x0001, 1.0, WordOrder::BigEndian),
+            Err(Exception::IllegalDataAddress)
+        );
+
+        // A master writing half of a pair leaves a mixed value
+        store
+            .set_u32(0x0000, 0x1234_5678, WordOrder::BigEndian)
+            .unwrap();
+        let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0001, 0xABCD);
+        dispatch(&store, &request);
+        assert_eq!(
+            store.get_u32(0x0000, WordOrder::BigEndian).unwrap(),
+            0x1234_ABCD
+        );
+        store
+            .set_input_value(0x0000, &[1u16, 2], WordOrder::BigEndian)
+            .unwrap_err();
+        let store = store.with_input_registers(0x0000, 2);
+        store
+            .set_input_value(0x0000, &[1u16, 2], WordOrder::BigEndian)
+            .unwrap();
+        let value: u32 = store
+            .get_input_value(0x0000, WordOrder::LittleEndian)
+            .unwrap();
+        assert_eq!(value, 0x0002_0001);
+    }
+
+    #[test]
+    fn service_test() {
+        let store = DataStore::default()
+            .with_coils(0x0000, 8)
+            .with_holding_registers(0x0000, 4);
+        let frame = Frame::rtu();
+
+        let request = frame.write_single_coil_request(0x01, 0x0002, 0xFF00);
+        dispatch(&store, &request);
+        assert_eq!(
+            store.get_coils(0x0000, 4).unwrap(),
+            vec![false, false, true, false]
+        );
+
+        let request = frame.write_single_holding_register_request(0x01, 0x0003, 0xABCD);
+        dispatch(&store, &request);
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0002, 0x0002);
+        let response_l = dispatch(&store, &request).unwrap();
+        let response_r = frame.read_holding_register_response(0x01, vec![0x00, 0x00, 0xAB, 0xCD]);
+        assert_eq!(response_l, response_r);
+
+        let request = frame.read_input_registers_request(0x01, 0x0000, 0x0001);
+        let response_l = dispatch(&store, &request).unwrap();
+        let response_r = frame.exception_response(
+            0x01,
+            Function::ReadInputRegisters,
+            Exception::IllegalDataAddress,
+        );
+        assert_eq!(response_l, response_r);
+    }
+
+    #[test]
+    fn hook_test() {
+        let store = Arc::new(DataStore::default().with_coils(0x0000, 16));
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (hook_store, hook_calls) = (store.clone(), calls.clone());
+        store.on_coil_write(
+            AddressRange::new(0x0004, 4).unwrap(),
+            move |unit, address, values| {
+                // The store is updated, and may be used, by the time a handler runs
+                let stored = hook_store.get_coils(address, values.len() as u16).unwrap();
+                assert_eq!(stored, values);
+                hook_store.set_coils(0x000F, &[true]).unwrap();
+                hook_calls
+                    .lock()
+                    .unwrap()
+                    .push((unit, address, values.to_vec()));
+                Ok(())
+            },
+        );
+
+        let frame = Frame::rtu();
+        let request = frame.write_multiple_coils_request(0x0B, 0x0002, 0x0004, vec![0x0F]);
+        assert!(dispatch(store.as_ref(), &request).is_some());
+        let request = frame.write_single_coil_request(0x0B, 0x0008, 0xFF00);
+        dispatch(store.as_ref(), &request);
+        store.set_coils(0x0004, &[true]).unwrap();
+
+        let calls = calls.lock().unwrap().clone();
+        assert_eq!(calls, vec![(0x0B, 0x0004, vec![true, true])]);
+        assert!(store.get_coils(0x000F, 1).unwrap()[0]);
+    }
+
+    #[test]
+    fn veto_test() {
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        store.set_holding_registers(0x0000, &[1, 2, 3, 4]).unwrap();
+        store.on_register_write(
+            AddressRange::new(0x0002, 1).unwrap(),
+            |_, _, values| match values[0] {
+                0..=100 => Ok(()),
+                _ => Err(Exception::IllegalDataValue),
+            },
+        );
+
+        let frame = Frame::tcp();
+        let request =
+            frame.write_multiple_holding_registers_request(0x01, 0x0001, vec![0, 9, 0x01, 0x01]);
+        let response_l = dispatch(&store, &request).unwrap();
+        let response_r = Frame::tcp().exception_response(
+            0x01,
+            Function::WriteMultipleHoldingRegisters,
+            Exception::IllegalDataValue,
+        );
+        assert_eq!(response_l, response_r);
+        assert_eq!(
+            store.get_holding_registers(0x0000, 4).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+
+        let request =
+            frame.write_multiple_holding_registers_request(0x01, 0x0001, vec![0, 9, 0, 10]);
+        assert!(matches!(
+            dispatch(&store, &request),
+            Some(crate::Response::WriteMultipleHoldingRegisters(..))
+        ));
+        assert_eq!(
+            store.get_holding_registers(0x0000, 4).unwrap(),
+            vec![1, 9, 10, 4]
+        );
+    }
+
+    #[test]
+    fn veto_set_test() {
+        let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 4));
+        store.set_holding_registers(0x0000, &[1, 2, 3, 4]).unwrap();
+        let hook_store = store.clone();
+        store.on_register_write(AddressRange::new(0x0000, 4).unwrap(), move |_, _, _| {
+            // Updates of the host application while the write is handled
+            hook_store.set_holding_registers(0x0001, &[20]).unwrap();
+            hook_store.set_holding_registers(0x0002, &[7]).unwrap();
+            Err(Exception::SlaveDeviceBusy)
+        });
+
+        let request = Frame::tcp().write_multiple_holding_registers_request(
+            0x01,
+            0x0000,
+            vec![0, 7, 0, 7, 0, 7],
+        );
+        assert!(matches!(
+            dispatch(store.as_ref(), &request),
+            Some(Response::Exception(..))
+        ));
+        // The update to 20 is kept, the one to the value of the master is not told apart
+        assert_eq!(
+            store.get_holding_registers(0x0000, 4).unwrap(),
+            vec![1, 20, 3, 4]
+        );
+    }
+
+    #[test]
+    fn order_test() {
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let range = AddressRange::new(0x0000, 4).unwrap();
+        for (name, result) in [
+            ("first", Ok(())),
+            ("second", Err(Exception::SlaveDeviceBusy)),
+            ("third", Ok(())),
+        ] {
+            let calls = calls.clone();
+            store.on_register_write(range, move |_, _, _| {
+                calls.lock().unwrap().push(name);
+                result
+            });
+        }
+
+        let request = Frame::tcp().write_single_holding_register_request(0x01, 0x0000, 0x0001);
+        let response = dispatch(&store, &request).unwrap();
+        assert!(matches!(response, crate::Response::Exception(..)));
+        assert_eq!(*calls.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(store.get_holding_registers(0x0000, 1).unwrap(), vec![0]);
+    }
+
+    #[tokio::test]
+    async fn watch_test() {
+        let store = Arc::new(DataStore::default().with_holding_registers(0x0000, 8));
+        let mut changes = store.watch_holding_registers(AddressRange::new(0x0002, 2).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tcp(listener, store.clone()));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        let values = vec![0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04];
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0001, values);
+        transport.send(request).await.unwrap();
+        transport.next().await.unwrap().unwrap();
+        store
+            .set_holding_registers(0x0003, &[0x0003, 0x0009])
+            .unwrap();
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let change = changes.next().await.unwrap();
+            received.push((
+                change.get_address(),
+                change.get_old_value(),
+                change.get_new_value(),
+            ));
+        }
+        assert_eq!(received, vec![(0x0002, 0, 2), (0x0003, 0, 3)]);
+        store.set_holding_registers(0x0002, &[0x0005]).unwrap();
+        let change = changes.next().await.unwrap();
+        assert_eq!((change.get_address(), change.get_new_value()), (0x0002, 5));
+    }
+
+    #[tokio::test]
+    async fn watch_lag_test() {
+        let store = DataStore::default().with_holding_registers(0x0000, 1);
+        let mut changes = store.watch_holding_registers(AddressRange::new(0x0000, 1).unwrap());
+        for value in 1..=3000u16 {
+            store.set_holding_registers(0x0000, &[value]).unwrap();
+        }
+        let first = changes.next().await.unwrap();
+        assert!(first.get_new_value() > 1);
+        let mut last = first;
+        while last.get_new_value() != 3000 {
+            last = changes.next().await.unwrap();
+        }
+        drop(store);
+        assert!(changes.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn host_update_test() {
+        let store = Arc::new(DataStore::default().with_input_registers(0x0000, 2));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve_tcp(listener, store.clone()));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let frame = Frame::tcp();
+        let expected = Frame::tcp();
+        for value in [0x0001u16, 0x0002] {
+            store.set_input_registers(0x0001, &[value]).unwrap();
+            let request = frame.read_input_registers_request(0x01, 0x0001, 0x0001);
+            transport.send(request).await.unwrap();
+            let response = transport.next().await.unwrap().unwrap();
+            let bytes = value.to_be_bytes().to_vec();
+            assert_eq!(response, expected.read_input_register_response(0x01, bytes));
+        }
+    }
+
+    fn saved_store() -> DataStore {
+        let store = DataStore::default()
+            .with_coils(0x0010, 11)
+            .with_discrete_inputs(0x0200, 3)
+            .with_holding_registers(0x1000, 4)
+            .with_input_registers(0xFFFE, 2);
+        store.set_coils(0x0010, &[true, false, true]).unwrap();
+        store.set_coils(0x001A, &[true]).unwrap();
+        store.set_discrete_inputs(0x0201, &[true]).unwrap();
+        store
+            .set_holding_registers(0x1001, &[0x1234, 0xABCD])
+            .unwrap();
+        store.set_input_registers(0xFFFF, &[0x0102]).unwrap();
+        store
+    }
+
+    #[test]
+    fn save_load_test() {
+        let mut saved = Vec::new();
+        saved_store().save(&mut saved).unwrap();
+        let store = DataStore::load(&saved[..]).unwrap();
+
+        assert_eq!(store.limits(0x01), saved_store().limits(0x01));
+        let coils = store.get_coils(0x0010, 11).unwrap();
+        assert_eq!(coils, saved_store().get_coils(0x0010, 11).unwrap());
+        assert_eq!(
+            store.get_discrete_inputs(0x0200, 3).unwrap(),
+            vec![false, true, false]
+        );
+        assert_eq!(
+            store.get_holding_registers(0x1000, 4).unwrap(),
+            vec![0x0000, 0x1234, 0xABCD, 0x0000]
+        );
+        assert_eq!(
+            store.get_input_registers(0xFFFE, 2).unwrap(),
+            vec![0, 0x0102]
+        );
+        assert!(store.get_holding_registers(0x0FFF, 1).is_err());
+
+        let mut resaved = Vec::new();
+        store.save(&mut resaved).unwrap();
+        assert_eq!(saved, resaved);
+    }
+
+    #[test]
+    fn load_invalid_test() {
+        let mut saved = Vec::new();
+        saved_store().save(&mut saved).unwrap();
+
+        assert!(DataStore::load(&b"JUNK"[..]).is_err());
+        for len in 0..saved.len() {
+            assert!(DataStore::load(&saved[..len]).is_err());
+        }
+        let mut trailing = saved.clone();
+        trailing.push(0x00);
+        assert!(DataStore::load(&trailing[..]).is_err());
+        let mut future = saved.clone();
+        future[4] = 0xFF;
+        let error = DataStore::load(&future[..]).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn autosave_test() {
+        let path = std::env::temp_dir().join(format!("easy-modbus-{}.bin", std::process::id()));
+        let store = Arc::new(saved_store());
+        let saver = store.clone();
+        let file = path.clone();
+        let task = tokio::spawn(async move {
+            saver
+                .autosave(file, std::time::Duration::from_millis(10))
+                .await
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        store.set_holding_registers(0x1000, &[0x0042]).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        task.abort();
+
+        let loaded = DataStore::load(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(
+            loaded.get_holding_registers(0x1000, 1).unwrap(),
+            vec![0x0042]
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn protect_test() {
+        let store = DataStore::default()
+            .with_coils(0x0000, 16)
+            .with_holding_registers(0x0000, 8);
+        store.protect(AddressRange::new(0x0004, 2).unwrap());
+        store.protect_coils(AddressRange::new(0x0008, 1).unwrap());
+        let frame = Frame::tcp();
+
+        // Straddling the start and the end of the protected registers
+        for (address, values) in [(0x0002, vec![0u8; 6]), (0x0005, vec![0u8; 4])] {
+            store.set_holding_registers(0x0000, &[0xAAAA; 8]).unwrap();
+            let request =
+                Frame::tcp().write_multiple_holding_registers_request(0x01, address, values);
+            let response_l = dispatch(&store, &request).unwrap();
+            let response_r = Frame::tcp().exception_response(
+                0x01,
+                Function::WriteMultipleHoldingRegisters,
+                Exception::IllegalDataAddress,
+            );
+            assert_eq!(response_l, response_r);
+            assert_eq!(
+                store.get_holding_registers(0x0000, 8).unwrap(),
+                vec![0xAAAA; 8]
+            );
+        }
+
+        // Adjacent to the protected registers
+        let request = frame.write_multiple_holding_registers_request(0x01, 0x0002, vec![0u8; 4]);
+        assert!(matches!(
+            dispatch(&store, &request),
+            Some(Response::WriteMultipleHoldingRegisters(..))
+        ));
+        let request = frame.write_single_holding_register_request(0x01, 0x0006, 0x0001);
+        assert!(matches!(
+            dispatch(&store, &request),
+            Some(Response::WriteSingleHoldingRegister(..))
+        ));
+        assert_eq!(
+            store.get_holding_registers(0x0000, 8).unwrap(),
+            vec![0xAAAA, 0xAAAA, 0, 0, 0xAAAA, 0xAAAA, 1, 0xAAAA]
+        );
+
+        let request = frame.write_multiple_coils_request(0x01, 0x0000, 0x0010, vec![0xFF, 0xFF]);
+        assert!(matches!(
+            dispatch(&store, &request),
+            Some(Response::Exception(..))
+        ));
+        let request = frame.write_single_coil_request(0x01, 0x0008, 0xFF00);
+        assert!(matches!(
+            dispatch(&store, &request),
+            Some(Response::Exception(..))
+        ));
+        assert_eq!(store.get_coils(0x0000, 16).unwrap(), vec![false; 16]);
+
+        // The host application still updates protected values
+        store.set_holding_registers(0x0004, &[0x0042]).unwrap();
+        store.set_coils(0x0008, &[true]).unwrap();
+        assert_eq!(
+            store.get_holding_registers(0x0004, 1).unwrap(),
+            vec![0x0042]
+        );
+        assert_eq!(store.get_coils(0x0008, 1).unwrap(), vec![true]);
+    }
+}
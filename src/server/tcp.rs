@@ -0,0 +1,382 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{Error, ErrorKind, Result};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio_util::sync::CancellationToken;
+
+use super::serve::{serve_connections, Hooks};
+use super::{AccessPolicy, ChaosService, MetricsSink, Service, TcpLimits};
+
+/// A Modbus TCP server owning its listeners
+///
+/// Wraps the accepting loop of [`serve_tcp_until`](super::serve_tcp_until): every connection is
+/// served on its own task, a panicking handler only closes its own connection, and the server
+/// runs until [`TcpServerHandle::shutdown`] is called. With the `tracing` feature enabled,
+/// opened and closed connections and panicking handlers emit events.
+///
+/// A server bound with [`TcpServer::bind_all`] accepts on several addresses at once. The
+/// connections of every listener share the service, the limits, the connection count and the
+/// shutdown.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use easy_modbus::server::{DataStore, TcpServer};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let server = TcpServer::bind("127.0.0.1:502").await?;
+///     println!("Listening on: {}", server.local_addr());
+///     let handle = server.handle();
+///     tokio::spawn(async move {
+///         let _ = tokio::signal::ctrl_c().await;
+///         handle.shutdown();
+///     });
+///     server.serve(DataStore::default().with_coils(0x0000, 8)).await
+/// }
+/// ```
+pub struct TcpServer {
+    listeners: Vec<TcpListener>,
+    limits: TcpLimits,
+    drain: Duration,
+    hooks: Hooks,
+    handle: TcpServerHandle,
+}
+
+/// Observes and stops a running [`TcpServer`]
+#[derive(Clone, Debug)]
+pub struct TcpServerHandle {
+    local_addrs: Vec<SocketAddr>,
+    active: Arc<AtomicUsize>,
+    stop: CancellationToken,
+}
+
+impl TcpServer {
+    /// Listen on `addr`, port 0 picks a free port
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> Result<TcpServer> {
+        TcpServer::from_listener(TcpListener::bind(addr).await?)
+    }
+
+    /// Listen on every address of `addrs`, IPv4 and IPv6 alike
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if `addrs` is empty, or with the error of the
+    /// first address which cannot be bound.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use easy_modbus::server::{DataStore, TcpServer};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let addrs = ["10.0.0.2:502".parse().unwrap(), "[fd00::2]:502".parse().unwrap()];
+    ///     let server = TcpServer::bind_all(&addrs).await?;
+    ///     server.serve(DataStore::default().with_coils(0x0000, 8)).await
+    /// }
+    /// ```
+    pub async fn bind_all(addrs: &[SocketAddr]) -> Result<TcpServer> {
+        let mut listeners = Vec::with_capacity(addrs.len());
+        for addr in addrs {
+            listeners.push(TcpListener::bind(addr).await?);
+        }
+        TcpServer::from_listeners(listeners)
+    }
+
+    /// Serve the clients of an already bound `listener`
+    pub fn from_listener(listener: TcpListener) -> Result<TcpServer> {
+        TcpServer::from_listeners(vec![listener])
+    }
+
+    /// Serve the clients of several already bound `listeners`
+    ///
+    /// Fails with [`ErrorKind::InvalidInput`] if `listeners` is empty.
+    pub fn from_listeners(listeners: Vec<TcpListener>) -> Result<TcpServer> {
+        if listeners.is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "No address to listen on",
+            ));
+        }
+        let local_addrs = listeners
+            .iter()
+            .map(TcpListener::local_addr)
+            .collect::<Result<_>>()?;
+        let handle = TcpServerHandle {
+            local_addrs,
+            active: Arc::new(AtomicUsize::new(0)),
+            stop: CancellationToken::new(),
+        };
+        Ok(TcpServer {
+            listeners,
+            limits: TcpLimits::default(),
+            drain: Duration::from_secs(5),
+            hooks: Hooks::default(),
+            handle,
+        })
+    }
+
+    /// Serve the connections within `limits`
+    pub fn with_limits(mut self, limits: TcpLimits) -> TcpServer {
+        self.limits = limits;
+        self
+    }
+
+    /// Give the open connections `drain` to finish once shut down, 5 seconds by default
+    pub fn with_drain(mut self, drain: Duration) -> TcpServer {
+        self.drain = drain;
+        self
+    }
+
+    /// Record every request answered to `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<dyn MetricsSink>) -> TcpServer {
+        self.hooks.metrics = Some(metrics);
+        self
+    }
+
+    /// Decide what every client accepted may do with `policy`
+    ///
+    /// A denied client has its connection closed right away, a read-only client has its write
+    /// requests answered with [`AccessPolicy::write_exception`].
+    pub fn with_policy(mut self, policy: Arc<dyn AccessPolicy>) -> TcpServer {
+        self.hooks.policy = Some(policy);
+        self
+    }
+
+    /// Send every response with the faults `chaos` injects, `chaos` usually being the service
+    ///
+    /// [`ChaosService::serve_tcp`] serves a `ChaosService` with its own faults.
+    pub fn with_chaos<S: Service + 'static>(mut self, chaos: Arc<ChaosService<S>>) -> TcpServer {
+        self.hooks.tamper = Some(chaos);
+        self
+    }
+
+    /// The address of the first listener
+    pub fn local_addr(&self) -> SocketAddr {
+        self.handle.local_addr()
+    }
+
+    /// The addresses of every listener, in the order they were given
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        self.handle.local_addrs()
+    }
+
+    /// Number of connections currently open, on every listener
+    pub fn active_connections(&self) -> usize {
+        self.handle.active_connections()
+    }
+
+    /// A handle to observe and stop the server once [`TcpServer::serve`] took it
+    pub fn handle(&self) -> TcpServerHandle {
+        self.handle.clone()
+    }
+
+    /// Serve clients with `service` until shut down
+    ///
+    /// Returns once every connection is closed, with the accepting error if accepting failed.
+    pub async fn serve<S: Service + 'static>(self, service: S) -> Result<()> {
+        let TcpServer {
+            listeners,
+            limits,
+            drain,
+            hooks,
+            handle,
+        } = self;
+        let shutdown = handle.stop.clone().cancelled_owned();
+        let active = handle.active.clone();
+        let service = Arc::new(service);
+        serve_connections(listeners, service, limits, shutdown, drain, active, hooks).await
+    }
+}
+
+impl fmt::Debug for TcpServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpServer")
+            .field("listeners", &self.listeners)
+            .field("limits", &self.limits)
+            .field("drain", &self.drain)
+            .field("metrics", &self.hooks.metrics.is_some())
+            .field("policy", &self.hooks.policy.is_some())
+            .field("chaos", &self.hooks.tamper.is_some())
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl TcpServerHandle {
+    /// The address of the first listener
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addrs[0]
+    }
+
+    /// The addresses of every listener
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
+
+    /// Number of connections currently open, on every listener
+    pub fn active_connections(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Stop accepting connections and close the open ones, see [`TcpServer::with_drain`]
+    pub fn shutdown(&self) {
+        self.stop.cancel();
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.stop.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod tcp_test {
+    use std::time::Duration;
+
+    use futures::{SinkExt, StreamExt};
+    use tokio::net::TcpStream;
+    use tokio_util::codec::Framed;
+
+    use crate::codec::TcpClientCodec;
+    use crate::frame::request::Request;
+    use crate::frame::response::Response;
+    use crate::frame::Exception;
+    use crate::server::{DataStore, Service, TcpServer};
+    use crate::Frame;
+
+    /// Panics on reading coils, answers holding register reads
+    struct Fragile;
+
+    impl Service for Fragile {
+        fn read_coils(
+            &self,
+            _unit: u8,
+            _address: u16,
+            _count: u16,
+        ) -> Result<Vec<bool>, Exception> {
+            panic!("handler failure");
+        }
+
+        fn read_holding_registers(
+            &self,
+            _unit: u8,
+            _address: u16,
+            count: u16,
+        ) -> Result<Vec<u16>, Exception> {
+            Ok(vec![0x0102; count as usize])
+        }
+    }
+
+    async fn wait_for(condition: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("condition not met");
+    }
+
+    #[tokio::test]
+    async fn serve_test() {
+        let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr();
+        assert_ne!(addr.port(), 0);
+        let handle = server.handle();
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        store.set_holding_registers(0x0001, &[0x00AB]).unwrap();
+        let running = tokio::spawn(server.serve(store));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x01, 0x0001, 0x0001);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x00, 0xAB]);
+        assert_eq!(response, expected);
+        assert_eq!(handle.active_connections(), 1);
+
+        drop(transport);
+        wait_for(|| handle.active_connections() == 0).await;
+
+        handle.shutdown();
+        assert!(handle.is_shutdown());
+        running.await.unwrap().unwrap();
+        assert!(TcpStream::connect(addr).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn bind_all_test() {
+        let addrs = [
+            "127.0.0.1:0".parse().unwrap(),
+            "127.0.0.1:0".parse().unwrap(),
+        ];
+        let server = TcpServer::bind_all(&addrs).await.unwrap();
+        let addrs = server.local_addrs().to_vec();
+        assert_eq!(addrs.len(), 2);
+        assert_ne!(addrs[0], addrs[1]);
+        let handle = server.handle();
+        let store = DataStore::default().with_holding_registers(0x0000, 4);
+        let running = tokio::spawn(server.serve(store));
+
+        let frame = Frame::tcp();
+        let stream = TcpStream::connect(addrs[0]).await.unwrap();
+        let mut first = Framed::new(stream, TcpClientCodec::default());
+        let request = frame.write_single_holding_register_request(0x01, 0x0002, 0x1234);
+        round_trip(&mut first, request).await;
+
+        let stream = TcpStream::connect(addrs[1]).await.unwrap();
+        let mut second = Framed::new(stream, TcpClientCodec::default());
+        let request = frame.read_multiple_holding_registers_request(0x01, 0x0002, 0x0001);
+        let response = round_trip(&mut second, request).await;
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x12, 0x34]);
+        assert_eq!(response.pdu_bytes(), expected.pdu_bytes());
+        assert_eq!(handle.active_connections(), 2);
+
+        handle.shutdown();
+        running.await.unwrap().unwrap();
+        for addr in addrs {
+            assert!(TcpStream::connect(addr).await.is_err());
+        }
+        assert!(TcpServer::bind_all(&[]).await.is_err());
+    }
+
+    async fn round_trip(
+        transport: &mut Framed<TcpStream, TcpClientCodec>,
+        request: Request,
+    ) -> Response {
+        transport.send(request).await.unwrap();
+        transport.next().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn panic_test() {
+        let server = TcpServer::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr();
+        let handle = server.handle();
+        let running = tokio::spawn(server.serve(Fragile));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_coils_request(0x01, 0x0000, 0x0001);
+        transport.send(request).await.unwrap();
+        assert!(matches!(transport.next().await, None | Some(Err(_))));
+        wait_for(|| handle.active_connections() == 0).await;
+
+        // The listener survived the panic
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut transport = Framed::new(stream, TcpClientCodec::default());
+        let request = Frame::tcp().read_multiple_holding_registers_request(0x01, 0x0000, 0x0001);
+        transport.send(request).await.unwrap();
+        let response = transport.next().await.unwrap().unwrap();
+        let expected = Frame::tcp().read_holding_register_response(0x01, vec![0x01, 0x02]);
+        assert_eq!(response, expected);
+
+        handle.shutdown();
+        running.await.unwrap().unwrap();
+    }
+}
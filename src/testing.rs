@@ -0,0 +1,90 @@
+//! In-memory transports for end-to-end tests.
+//!
+//! A client and a server framed over a [`tokio::io::duplex`] pipe exchange frames through the
+//! full encode and decode path, without binding a socket.
+//!
+//! # Examples
+//!
+//! ```
+//! use futures::{SinkExt, StreamExt};
+//! use easy_modbus::server::{dispatch, DataStore};
+//! use easy_modbus::{testing, Frame, Response};
+//!
+//! #[tokio::main(flavor = "current_thread")]
+//! async fn main() {
+//!     let store = DataStore::default().with_holding_registers(0x0000, 2);
+//!     store.set_holding_registers(0x0000, &[0x1234, 0x5678]).unwrap();
+//!     let (mut client, mut server) = testing::pair();
+//!
+//!     let request = Frame::tcp().read_multiple_holding_registers_request(0x01, 0x0000, 2);
+//!     client.send(request).await.unwrap();
+//!     let request = server.next().await.unwrap().unwrap();
+//!     server.send(dispatch(&store, &request).unwrap()).await.unwrap();
+//!     match client.next().await.unwrap().unwrap() {
+//!         Response::ReadMultipleHoldingRegisters(_, body) => {
+//!             assert_eq!(&body.get_values()[..], &[0x12, 0x34, 0x56, 0x78]);
+//!         }
+//!         response => panic!("unexpected response {:?}", response),
+//!     }
+//! }
+//! ```
+
+use tokio::io::DuplexStream;
+use tokio_util::codec::Framed;
+
+use crate::codec::{TcpClientCodec, TcpServerCodec, MAX_FRAME};
+
+/// A Modbus TCP client connected to a Modbus TCP server, in memory
+///
+/// Either end reads what the other writes. The pipe buffers a few frames, a writer waits for
+/// the reader once it is full. Dropping one end closes the other.
+pub fn pair() -> (
+    Framed<DuplexStream, TcpClientCodec>,
+    Framed<DuplexStream, TcpServerCodec>,
+) {
+    let (client, server) = tokio::io::duplex(4 * MAX_FRAME);
+    (
+        Framed::new(client, TcpClientCodec::default()),
+        Framed::new(server, TcpServerCodec::default()),
+    )
+}
+
+#[cfg(test)]
+mod pair_test {
+    use futures::{SinkExt, StreamExt};
+
+    use crate::server::{dispatch, DataStore};
+    use crate::{testing, Frame, Response};
+
+    #[tokio::test]
+    async fn read_coils_test() {
+        let store = DataStore::default().with_coils(0x0010, 10);
+        let coils = [
+            true, false, true, true, false, false, true, false, true, true,
+        ];
+        store.set_coils(0x0010, &coils).unwrap();
+        let (mut client, mut server) = testing::pair();
+
+        let request = Frame::tcp().read_coils_request(0x0B, 0x0010, 10);
+        client.send(request.clone()).await.unwrap();
+        let received = server.next().await.unwrap().unwrap();
+        assert_eq!(received, request);
+        let response = dispatch(&store, &received).unwrap();
+        server.send(response).await.unwrap();
+
+        let response = client.next().await.unwrap().unwrap();
+        assert_eq!(response.head().get_tid(), request.head().get_tid());
+        assert_eq!(response.head().get_uid(), 0x0B);
+        match response {
+            Response::ReadCoils(_, body) => {
+                assert_eq!(&body.get_values()[..], &[0x4D, 0x03]);
+                let read: Vec<bool> = (0..10).map(|i| body.bit(i).unwrap()).collect();
+                assert_eq!(read, coils);
+            }
+            response => panic!("unexpected response {:?}", response),
+        }
+
+        drop(server);
+        assert!(client.next().await.is_none());
+    }
+}
@@ -0,0 +1,163 @@
+//! Utility for packing coils and discrete inputs into bytes.
+//!
+//! Modbus packs bits least significant bit first: the first bit goes into the least significant
+//! bit of the first byte, the ninth into the least significant bit of the second byte. The last
+//! byte is stuffed with zeros.
+//!
+//! # Examples
+//! ```
+//! use easy_modbus::util::bits::{pack, unpack};
+//! let bytes = pack(&[true, false, true, true, false, false, true, true, true, false]);
+//! assert_eq!(bytes, vec![0xCD, 0x01]);
+//!
+//! let bits = unpack(&bytes, 10);
+//! assert_eq!(bits, vec![true, false, true, true, false, false, true, true, true, false]);
+//! ```
+
+use std::iter::FusedIterator;
+
+/// Pack bits into bytes, least significant bit first
+///
+/// # Examples
+/// ```
+/// use easy_modbus::util::bits::pack;
+/// assert_eq!(pack(&[true, false, true, true]), vec![0x0D]);
+/// assert!(pack(&[]).is_empty());
+/// ```
+pub fn pack(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, _) in bits.iter().enumerate().filter(|(_, &bit)| bit) {
+        bytes[i / 8] |= 1 << (i % 8);
+    }
+    bytes
+}
+
+/// Unpack the first `count` bits of bytes, least significant bit first
+///
+/// Stops at the end of the bytes, so fewer than `count` bits are returned for bytes too short
+/// to hold them.
+///
+/// # Examples
+/// ```
+/// use easy_modbus::util::bits::unpack;
+/// assert_eq!(unpack(&[0x0D], 4), vec![true, false, true, true]);
+/// assert_eq!(unpack(&[0x0D], 12).len(), 8);
+/// ```
+pub fn unpack(bytes: &[u8], count: usize) -> Vec<bool> {
+    iter(bytes, count).collect()
+}
+
+/// Iterate over the first `count` bits of bytes, least significant bit first
+///
+/// Like [`unpack`], without collecting the bits.
+///
+/// # Examples
+/// ```
+/// use easy_modbus::util::bits::iter;
+/// let bits = iter(&[0x0D, 0x01], 9);
+/// assert_eq!(bits.len(), 9);
+/// assert_eq!(bits.filter(|&bit| bit).count(), 4);
+/// ```
+pub fn iter(bytes: &[u8], count: usize) -> Bits<'_> {
+    Bits {
+        bytes,
+        index: 0,
+        end: count.min(bytes.len() * 8),
+    }
+}
+
+/// Iterator over packed bits, see [`iter`]
+#[derive(Clone, Debug)]
+pub struct Bits<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    end: usize,
+}
+
+impl Iterator for Bits<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        if self.index == self.end {
+            return None;
+        }
+        self.index += 1;
+        get(self.bytes, self.index - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Bits<'_> {}
+
+impl FusedIterator for Bits<'_> {}
+
+/// Bit `index` of bytes, least significant bit first, `None` past the end of the bytes
+///
+/// # Examples
+/// ```
+/// use easy_modbus::util::bits::get;
+/// assert_eq!(get(&[0x0D], 2), Some(true));
+/// assert_eq!(get(&[0x0D], 8), None);
+/// ```
+pub fn get(bytes: &[u8], index: usize) -> Option<bool> {
+    bytes
+        .get(index / 8)
+        .map(|byte| byte & (1 << (index % 8)) != 0)
+}
+
+#[test]
+fn test_pack() {
+    assert_eq!(pack(&[true]), vec![0x01]);
+    assert_eq!(pack(&[false; 8]), vec![0x00]);
+    assert_eq!(pack(&[true; 9]), vec![0xFF, 0x01]);
+    let mut bits = [false; 16];
+    bits[7] = true;
+    bits[15] = true;
+    assert_eq!(pack(&bits), vec![0x80, 0x80]);
+}
+
+#[test]
+fn test_unpack() {
+    assert_eq!(unpack(&[0x80, 0x01], 9)[7..], [true, true]);
+    assert_eq!(unpack(&[0xFF], 0), Vec::<bool>::new());
+    assert_eq!(unpack(&[], 8), Vec::<bool>::new());
+}
+
+#[test]
+fn test_iter() {
+    let mut bits = iter(&[0x81, 0x01], 16);
+    assert_eq!(bits.len(), 16);
+    assert_eq!(bits.next(), Some(true));
+    assert_eq!(bits.len(), 15);
+    assert_eq!(bits.nth(6), Some(true));
+    assert_eq!(bits.next(), Some(true));
+    assert_eq!(bits.by_ref().filter(|&bit| bit).count(), 0);
+    assert_eq!(bits.len(), 0);
+    assert_eq!(bits.next(), None);
+    assert_eq!(iter(&[0xFF], 3).len(), 3);
+}
+
+#[test]
+fn test_round_trip() {
+    // Every length a write multiple coils request allows, with pseudo-random bits
+    let mut seed = 0x1234_5678u32;
+    for len in 1..=1968usize {
+        let bits: Vec<bool> = (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                seed >> 31 == 1
+            })
+            .collect();
+        let bytes = pack(&bits);
+        assert_eq!(bytes.len(), len.div_ceil(8));
+        assert_eq!(unpack(&bytes, len), bits);
+        if !len.is_multiple_of(8) {
+            // The stuffing bits are zero
+            assert_eq!(bytes[len / 8] >> (len % 8), 0);
+        }
+    }
+}
@@ -61,8 +61,7 @@ pub fn compute(data: &[u8]) -> u16 {
     for datum in data {
         crc = (crc >> 8) ^ CRC_TABLE[(crc ^ *datum as u16) as usize & 0xFF];
     }
-    crc = crc << 8 | crc >> 8;
-    crc
+    crc.rotate_right(8)
 }
 
 #[test]
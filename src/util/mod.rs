@@ -1,4 +1,5 @@
 //! Utilities for Easy Modbus.
 
+pub mod bits;
 pub mod crc;
 